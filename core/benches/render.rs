@@ -0,0 +1,76 @@
+// per-frame cost of the render hot paths at the full 240x240.
+// cargo bench -p girlvoice-ui-core [-- <filter>], add --features trig-lut to compare the LUT trig
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use girlvoice_ui_core::{palette, AnalysisFrame, ChannelCount, Color, ColorPalette, Framebuffer, ModeKind, Visualizer, FRAMEBUFFER_LEN};
+
+const CHANNELS: usize = 12;
+// checked at compile time
+const BANDS: ChannelCount = match ChannelCount::new(CHANNELS) {
+    Ok(bands) => bands,
+    Err(_) => panic!("bench channel count out of range"),
+};
+
+// a vowel-ish spectrum, strong low bands falling off towards the top
+fn frame() -> AnalysisFrame {
+    let mut frame = AnalysisFrame::new(BANDS);
+    let energies: [f32; CHANNELS] = core::array::from_fn(|i| 0.9 / (1.0 + i as f32 * 0.3));
+    frame.set_bands(&energies);
+    frame
+}
+
+fn modes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mode_frame");
+    let frame = frame();
+    let mut fb = Box::new(Framebuffer::new());
+    for mode in ModeKind::ALL {
+        let mut visualizer = Visualizer::new(BANDS);
+        visualizer.set_mode(mode);
+        // let smoothers and trails settle so the frame looks like a real one
+        for _ in 0..60 {
+            visualizer.update(1.0 / 30.0, &frame);
+        }
+        group.bench_function(BenchmarkId::from_parameter(mode.id()), |b| {
+            b.iter(|| {
+                fb.clear(palette::BLACK);
+                visualizer.update(1.0 / 30.0, black_box(&frame));
+                visualizer.render(|x, y, c| fb.add(x, y, c));
+            })
+        });
+    }
+    group.finish();
+}
+
+fn color(c: &mut Criterion) {
+    c.bench_function("color_from_hsv", |b| {
+        let mut h = 0.0f32;
+        b.iter(|| {
+            h = (h + 0.013) % 1.0;
+            Color::from_hsv(black_box(h), 0.8, 0.9)
+        })
+    });
+
+    let pal = ColorPalette::default();
+    c.bench_function("palette_sample", |b| {
+        let mut t = 0.0f32;
+        b.iter(|| {
+            t = (t + 0.013) % 1.0;
+            pal.sample(black_box(t))
+        })
+    });
+}
+
+fn conversion(c: &mut Criterion) {
+    let mut fb = Box::new(Framebuffer::new());
+    for (i, pixel) in fb.pixels_mut().iter_mut().enumerate() {
+        *pixel = Color::from_hsv((i % 240) as f32 / 240.0, 1.0, (i / 240) as f32 / 240.0);
+    }
+    let mut out = vec![0u16; FRAMEBUFFER_LEN];
+    c.bench_function("framebuffer_rgb565", |b| b.iter(|| fb.write_rgb565(black_box(&mut out))));
+}
+
+criterion_group!(benches, modes, color, conversion);
+criterion_main!(benches);
@@ -0,0 +1,22 @@
+// turns core/assets into Rust source: pngs become the Sprite consts in icons, palette files
+// the ColorPalette consts in palette. add or edit a file there and rebuild, no byte arrays by hand
+
+use std::path::{Path, PathBuf};
+
+fn main() {
+    let out_dir = PathBuf::from(std::env::var_os("OUT_DIR").expect("cargo sets OUT_DIR"));
+    let assets = Path::new("assets");
+    println!("cargo:rerun-if-changed=assets");
+
+    match girlvoice_ui_tools::assets::convert_dir(assets, &out_dir) {
+        Ok(converted) => {
+            for input in &converted.inputs {
+                println!("cargo:rerun-if-changed={}", input.display());
+            }
+        }
+        Err(e) => {
+            eprintln!("asset conversion failed: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
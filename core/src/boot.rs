@@ -0,0 +1,120 @@
+// power-on animation: a ring sweeps around clockwise from the top drawing the palette, then
+// the palette blooms outwards from the center and fades into the active mode, the logo fades
+// in at the center during the sweep. plays once, any input skips it. band energies are
+// ignored so it looks the same on every boot.
+
+use core::f32::consts::{FRAC_PI_2, TAU};
+use core::ops::Range;
+
+use crate::icons::LOGO;
+use crate::polar;
+use crate::region::{RenderRegion, DISPLAY_REGION};
+use crate::source::AnalysisFrame;
+use crate::vis::VisualMode;
+use crate::{Color, ColorPalette, DISPLAY_CENTER, DISPLAY_SIZE};
+
+pub struct BootAnimation {
+    time: f32,
+}
+
+impl BootAnimation {
+    pub const DURATION_S: f32 = 2.0;
+    const SWEEP_S: f32 = 0.9;
+    const RING_RADIUS: f32 = 84.0;
+    const RING_WIDTH: f32 = 6.0;
+    const HEAD_GLOW: f32 = 0.08; // fraction of a turn behind the sweep head that glows brighter
+    const LOGO_X: i32 = DISPLAY_CENTER as i32 - LOGO.width as i32 / 2;
+    const LOGO_Y: i32 = DISPLAY_CENTER as i32 - LOGO.height as i32 / 2;
+
+    pub fn new() -> Self {
+        Self { time: 0.0 }
+    }
+
+    pub fn restart(&mut self) {
+        self.time = 0.0;
+    }
+
+    // 0-1 over the whole sequence
+    pub fn progress(&self) -> f32 {
+        (self.time / Self::DURATION_S).min(1.0)
+    }
+
+    fn draw_rows(&self, region: &RenderRegion, rows: Range<usize>, step: usize, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        let sweep = (self.time / Self::SWEEP_S).min(1.0);
+        // bloom: 0 during the sweep, then 0-1 until the end
+        let bloom = ((self.time - Self::SWEEP_S) / (Self::DURATION_S - Self::SWEEP_S)).clamp(0.0, 1.0);
+        let bloom_radius = bloom * DISPLAY_CENTER * 1.2;
+        let fade = 1.0 - bloom * bloom;
+
+        for (y, columns) in region.grid(rows, step) {
+            for x in columns {
+                let (angle, radius) = polar::to_polar(x as f32, y as f32);
+                // turns clockwise from 12 o'clock
+                let turn = polar::wrap_angle(angle + FRAC_PI_2) / TAU;
+                let mut value = 0.0;
+                let mut color = pal.sample(turn);
+
+                let ring = 1.0 - libm::fabsf(radius - Self::RING_RADIUS) / Self::RING_WIDTH;
+                if ring > 0.0 && turn <= sweep {
+                    let head = 1.0 + (1.0 - (sweep - turn) / Self::HEAD_GLOW).max(0.0);
+                    value = ring * head * 0.5 * fade;
+                }
+
+                let (lx, ly) = (x as i32 - Self::LOGO_X, y as i32 - Self::LOGO_Y);
+                if (0..LOGO.width as i32).contains(&lx) && (0..LOGO.height as i32).contains(&ly) {
+                    let logo = LOGO.alpha_at(lx as usize, ly as usize) as f32 / 255.0 * sweep * fade;
+                    if logo > value {
+                        value = logo;
+                        color = pal.primary;
+                    }
+                }
+
+                if radius < bloom_radius {
+                    // brightest just inside the expanding edge
+                    let edge = radius / bloom_radius;
+                    let glow = edge * edge * fade;
+                    if glow > value {
+                        value = glow;
+                        color = pal.sample((radius / DISPLAY_CENTER * 0.8 + 0.1 * polar::sin(angle * 2.0)).rem_euclid(1.0));
+                    }
+                }
+
+                if value > 0.004 {
+                    set_pixel(x, y, color.scale(value.min(1.0)));
+                }
+            }
+        }
+    }
+}
+
+impl VisualMode for BootAnimation {
+    fn update(&mut self, dt: f32, _frame: &AnalysisFrame) {
+        self.time = (self.time + dt).min(Self::DURATION_S);
+    }
+
+    fn render(&self, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.render_rows(0..DISPLAY_SIZE, set_pixel, pal);
+    }
+
+    fn render_rows(&self, rows: Range<usize>, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.draw_rows(&DISPLAY_REGION, rows, 1, set_pixel, pal);
+    }
+
+    fn render_region(&self, region: &RenderRegion, step: usize, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.draw_rows(region, 0..DISPLAY_SIZE, step, set_pixel, pal);
+    }
+
+    fn stays_in_region(&self) -> bool {
+        true
+    }
+
+    fn finished(&self) -> bool {
+        self.time >= Self::DURATION_S
+    }
+}
+
+impl Default for BootAnimation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,215 @@
+// two-step calibration for a new mic or a new room, the same procedure on the badge and in the
+// simulator: a few seconds of silence give every band's noise floor, a few seconds of normal
+// speech give its average level. the result is a floor to take off each band and a trim that
+// levels the bands against each other, kept in Settings so it survives power cycles.
+//
+// while it runs the source has to deliver raw band envelopes: switch it to
+// measuring_settings() on start, and back to the own DspSettings (with the new calibration
+// applied) once update() reports the end.
+
+use core::f32::consts::{FRAC_PI_2, TAU};
+use core::fmt::Write;
+
+use crate::draw::arc;
+use crate::font::{FONT_5X7, FONT_8X16};
+use crate::framebuffer::Framebuffer;
+use crate::region::DISPLAY_REGION;
+use crate::settings::DspSettings;
+use crate::source::{AnalysisFrame, ChannelCount};
+use crate::text::{draw_text_centered, TextBuf};
+use crate::{palette, Color, ColorPalette, CHANNELS, DISPLAY_CENTER};
+
+// what a calibration run learned, in DspSettings terms
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default, deny_unknown_fields))]
+pub struct BandCalibration {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impls::bands"))]
+    pub floor: [f32; CHANNELS], // raw envelope units
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impls::bands"))]
+    pub trim: [f32; CHANNELS], // dB
+}
+
+impl BandCalibration {
+    // replaces the floor and trims, everything else is left alone
+    pub fn apply(&self, dsp: &mut DspSettings) {
+        dsp.floor = self.floor;
+        dsp.trim = self.trim;
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CalibrationPhase {
+    Idle,
+    Silence,
+    Speech,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CalibrationError {
+    NotEnoughSpeech, // the speech step never got clearly above the noise floor
+}
+
+pub struct Calibrator {
+    phase: CalibrationPhase,
+    elapsed: f32, // seconds into the current phase
+    num_channels: usize,
+    sums: [f32; CHANNELS],
+    frames: u32,
+    floor: [f32; CHANNELS],
+    voiced: f32, // seconds of the speech step that were above the floor
+}
+
+impl Calibrator {
+    pub const SILENCE_S: f32 = 3.0;
+    pub const SPEECH_S: f32 = 6.0;
+    const SETTLE_S: f32 = 0.3; // the source needs a moment to pick up measuring_settings()
+    const MIN_VOICED_S: f32 = 1.5;
+    const FLOOR_MARGIN: f32 = 1.5; // the floor sits a little above the measured noise
+    const VOICED_RATIO: f32 = 3.0; // a frame counts as speech this far above the floor
+
+    const RING_RADIUS: f32 = 108.0;
+    const RING_WIDTH: f32 = 6.0;
+    const BACKGROUND_DIM: f32 = 0.35;
+    const TRACK_COLOR: Color = Color::new(40, 40, 48);
+
+    pub fn new(channels: ChannelCount) -> Self {
+        Self {
+            phase: CalibrationPhase::Idle,
+            elapsed: 0.0,
+            num_channels: channels.get(),
+            sums: [0.0; CHANNELS],
+            frames: 0,
+            floor: [0.0; CHANNELS],
+            voiced: 0.0,
+        }
+    }
+
+    // restarts from the silence step if a run is already going
+    pub fn start(&mut self) {
+        self.enter(CalibrationPhase::Silence);
+        self.floor = [0.0; CHANNELS];
+        self.voiced = 0.0;
+    }
+
+    pub fn cancel(&mut self) {
+        self.enter(CalibrationPhase::Idle);
+    }
+
+    pub fn phase(&self) -> CalibrationPhase {
+        self.phase
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.phase != CalibrationPhase::Idle
+    }
+
+    // 0-1 through the current step
+    pub fn progress(&self) -> f32 {
+        match self.phase {
+            CalibrationPhase::Idle => 0.0,
+            CalibrationPhase::Silence => (self.elapsed / Self::SILENCE_S).min(1.0),
+            CalibrationPhase::Speech => (self.elapsed / Self::SPEECH_S).min(1.0),
+        }
+    }
+
+    // what the source runs with while measuring: raw envelopes, no agc, eq or gate
+    pub fn measuring_settings(base: &DspSettings) -> DspSettings {
+        DspSettings { agc: false, gain: 1.0, gate: 0.0, tilt: 0.0, trim: [0.0; CHANNELS], floor: [0.0; CHANNELS], ..*base }
+    }
+
+    // feed every UI frame while running, returns the outcome once the speech step is over
+    pub fn update(&mut self, dt: f32, frame: &AnalysisFrame) -> Option<Result<BandCalibration, CalibrationError>> {
+        let n = self.num_channels.min(frame.num_channels);
+        let bands = &frame.energies[..n];
+        self.elapsed += dt;
+        match self.phase {
+            CalibrationPhase::Idle => None,
+            CalibrationPhase::Silence => {
+                if self.elapsed >= Self::SETTLE_S {
+                    self.accumulate(bands);
+                }
+                if self.elapsed >= Self::SILENCE_S {
+                    for (floor, sum) in self.floor.iter_mut().zip(&self.sums) {
+                        *floor = sum / self.frames.max(1) as f32 * Self::FLOOR_MARGIN;
+                    }
+                    self.enter(CalibrationPhase::Speech);
+                }
+                None
+            }
+            CalibrationPhase::Speech => {
+                let total: f32 = bands.iter().sum();
+                let floor_total: f32 = self.floor[..n].iter().sum();
+                if total > 1e-6 && total > floor_total * Self::VOICED_RATIO {
+                    self.accumulate(bands);
+                    self.voiced += dt;
+                }
+                if self.elapsed < Self::SPEECH_S {
+                    return None;
+                }
+                let result = self.finish();
+                self.enter(CalibrationPhase::Idle);
+                Some(result)
+            }
+        }
+    }
+
+    fn enter(&mut self, phase: CalibrationPhase) {
+        self.phase = phase;
+        self.elapsed = 0.0;
+        self.sums = [0.0; CHANNELS];
+        self.frames = 0;
+    }
+
+    fn accumulate(&mut self, bands: &[f32]) {
+        for (sum, energy) in self.sums.iter_mut().zip(bands) {
+            *sum += energy;
+        }
+        self.frames += 1;
+    }
+
+    // trims level every band to the (log) average of all of them
+    fn finish(&self) -> Result<BandCalibration, CalibrationError> {
+        if self.voiced < Self::MIN_VOICED_S {
+            return Err(CalibrationError::NotEnoughSpeech);
+        }
+        let n = self.num_channels;
+        let mut level_db = [0.0f32; CHANNELS];
+        for ((db, sum), floor) in level_db.iter_mut().zip(&self.sums).zip(&self.floor).take(n) {
+            let level = sum / self.frames as f32 - floor;
+            *db = 20.0 * libm::log10f(level.max(1e-6));
+        }
+        let reference = level_db[..n].iter().sum::<f32>() / n as f32;
+
+        let (lo, hi) = DspSettings::TRIM_RANGE;
+        let mut calibration = BandCalibration { floor: self.floor, ..BandCalibration::default() };
+        for (trim, db) in calibration.trim.iter_mut().zip(&level_db[..n]) {
+            *trim = (reference - db).clamp(lo, hi);
+        }
+        Ok(calibration)
+    }
+
+    // instructions and a progress ring over the dimmed visualization, nothing while idle
+    pub fn render(&self, fb: &mut Framebuffer, pal: &ColorPalette) {
+        let (prompt, seconds) = match self.phase {
+            CalibrationPhase::Idle => return,
+            CalibrationPhase::Silence => ("Stay quiet", Self::SILENCE_S),
+            CalibrationPhase::Speech => ("Speak normally", Self::SPEECH_S),
+        };
+        for (y, x_start, x_end) in DISPLAY_REGION.rows() {
+            for pixel in &mut fb.row_mut(y)[x_start..x_end] {
+                *pixel = pixel.scale(Self::BACKGROUND_DIM);
+            }
+        }
+
+        let center = (DISPLAY_CENTER, DISPLAY_CENTER);
+        let color = if self.phase == CalibrationPhase::Silence { pal.secondary } else { pal.primary };
+        arc(fb, center, Self::RING_RADIUS, Self::RING_WIDTH, -FRAC_PI_2, TAU, Self::TRACK_COLOR);
+        arc(fb, center, Self::RING_RADIUS, Self::RING_WIDTH, -FRAC_PI_2, TAU * self.progress(), color);
+
+        let mut remaining = TextBuf::<12>::new();
+        let _ = write!(remaining, "{:.0} s", libm::ceilf(seconds - self.elapsed).max(0.0));
+        draw_text_centered(fb, &FONT_5X7, 86, "CALIBRATING", pal.accent);
+        draw_text_centered(fb, &FONT_8X16, 104, prompt, palette::WHITE);
+        draw_text_centered(fb, &FONT_5X7, 130, remaining.as_str(), palette::WHITE.scale(0.7));
+    }
+}
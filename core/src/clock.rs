@@ -0,0 +1,263 @@
+// watch face for when nobody is talking: hours, minutes and seconds as arcs around the edge
+// running clockwise from 12, the time in digits in the middle and a faint glow behind them
+// that breathes with the room. the time comes from a TimeSource (std time in the simulator,
+// the RTC on the badge) and goes in through Visualizer::set_time once a frame.
+
+use core::f32::consts::{FRAC_PI_2, TAU};
+use core::ops::Range;
+
+use crate::font::FONT_8X16;
+use crate::param::Param;
+use crate::polar;
+use crate::region::{RenderRegion, DISPLAY_REGION};
+use crate::source::AnalysisFrame;
+use crate::surface::LedRing;
+use crate::vis::VisualMode;
+use crate::{palette, Color, ColorPalette, EnvelopeSmoother, DISPLAY_SIZE};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TimeOfDay {
+    pub hour: u8, // 0-23
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl TimeOfDay {
+    pub const SECONDS_PER_DAY: u32 = 24 * 60 * 60;
+
+    pub fn new(hour: u8, minute: u8, second: u8) -> Option<Self> {
+        (hour < 24 && minute < 60 && second < 60).then_some(Self { hour, minute, second })
+    }
+
+    // seconds since midnight, wrapping past a day
+    pub fn from_seconds(seconds: u32) -> Self {
+        let s = seconds % Self::SECONDS_PER_DAY;
+        Self { hour: (s / 3600) as u8, minute: (s / 60 % 60) as u8, second: (s % 60) as u8 }
+    }
+
+    pub fn seconds_of_day(&self) -> u32 {
+        self.hour as u32 * 3600 + self.minute as u32 * 60 + self.second as u32
+    }
+}
+
+// wall clock time, None while it isn't known (RTC never set, battery swapped)
+pub trait TimeSource {
+    fn now(&mut self) -> Option<TimeOfDay>;
+}
+
+pub struct ClockFace {
+    time: Option<TimeOfDay>,
+    subsecond: f32, // seconds since `time` last ticked over, keeps the second hand smooth
+    level: EnvelopeSmoother,
+    phase: f32, // ambient animation clock
+    params: [Param; 2],
+}
+
+impl ClockFace {
+    // ring center radius and width, hours outermost
+    const HOUR_RING: (f32, f32) = (110.0, 7.0);
+    const MINUTE_RING: (f32, f32) = (99.0, 6.0);
+    const SECOND_RING: (f32, f32) = (90.0, 3.0);
+    const TRACK: f32 = 0.12;   // brightness of the unlit part of a ring
+    const TICK_GAP: f32 = 1.2; // pixels cut out of the hour ring at each hour
+
+    const GLOW: usize = 0; // params
+    const SECONDS: usize = 1;
+
+    const GLOW_RADIUS: f32 = 58.0;
+    const GLOW_MAX: f32 = 0.35;  // never brighter than this, it's a backdrop
+    const SOUND_DEPTH: f32 = 0.5; // how much of the band level reaches the glow
+    const BREATH_HZ: f32 = 0.12;
+
+    // digits are FONT_8X16 drawn at twice the size
+    const DIGIT_SCALE: usize = 2;
+    const TEXT_LEN: usize = 5; // "HH:MM"
+
+    pub fn new() -> Self {
+        Self {
+            time: None,
+            subsecond: 0.0,
+            level: EnvelopeSmoother::new(60.0, 150.0, 1500.0),
+            phase: 0.0,
+            params: [Param::new("glow", "Glow", 0.0, 1.0, 0.1, 1.0), Param::toggle("seconds", "Seconds", true)],
+        }
+    }
+
+    pub fn set_time(&mut self, time: Option<TimeOfDay>) {
+        if time != self.time {
+            self.time = time;
+            self.subsecond = 0.0;
+        }
+    }
+
+    pub fn time(&self) -> Option<TimeOfDay> {
+        self.time
+    }
+
+    pub fn update(&mut self, dt: f32, energies: &[f32]) {
+        let mean = if energies.is_empty() { 0.0 } else { energies.iter().sum::<f32>() / energies.len() as f32 };
+        self.level.process(mean.clamp(0.0, 1.0));
+        // the source is polled every frame, so this only matters between its ticks
+        self.subsecond = (self.subsecond + dt).min(0.999);
+        self.phase = (self.phase + dt * Self::BREATH_HZ * TAU) % TAU;
+    }
+
+    // how far round each ring is lit, 0-1
+    fn fractions(&self) -> (f32, f32, f32) {
+        match self.time {
+            None => (0.0, 0.0, 0.0),
+            Some(t) => {
+                let seconds = t.second as f32 + self.subsecond;
+                let minutes = t.minute as f32 + seconds / 60.0;
+                ((t.hour % 12) as f32 / 12.0 + minutes / 720.0, minutes / 60.0, seconds / 60.0)
+            }
+        }
+    }
+
+    fn text(&self) -> [u8; Self::TEXT_LEN] {
+        match self.time {
+            None => *b"--:--",
+            Some(t) => {
+                let colon = if self.subsecond < 0.5 { b':' } else { b' ' };
+                [b'0' + t.hour / 10, b'0' + t.hour % 10, colon, b'0' + t.minute / 10, b'0' + t.minute % 10]
+            }
+        }
+    }
+
+    pub fn render_with_palette<F>(&self, set_pixel: F, pal: &ColorPalette)
+    where
+        F: FnMut(usize, usize, Color),
+    {
+        self.draw_rows(&DISPLAY_REGION, 0..DISPLAY_SIZE, 1, set_pixel, pal);
+    }
+
+    fn draw_rows<F>(&self, region: &RenderRegion, rows: Range<usize>, step: usize, mut set_pixel: F, pal: &ColorPalette)
+    where
+        F: FnMut(usize, usize, Color),
+    {
+        let (hours, minutes, seconds) = self.fractions();
+        let text = self.text();
+        let text_color = Color::lerp(pal.primary, palette::WHITE, 0.6);
+        let (cell_w, cell_h) = (FONT_8X16.advance * Self::DIGIT_SCALE, FONT_8X16.height * Self::DIGIT_SCALE);
+        let text_x = DISPLAY_SIZE / 2 - cell_w * Self::TEXT_LEN / 2;
+        let text_y = DISPLAY_SIZE / 2 - cell_h / 2;
+
+        let level = self.level.value() * Self::SOUND_DEPTH;
+        let breath = polar::sin(self.phase);
+        let glow_radius = Self::GLOW_RADIUS + 4.0 * breath + 14.0 * level;
+        let glow_brightness = Self::GLOW_MAX * (0.35 + 0.15 * breath + 0.5 * level) * self.params[Self::GLOW].value;
+        let show_seconds = self.params[Self::SECONDS].is_on();
+
+        for (y, columns) in region.grid(rows, step) {
+            for x in columns {
+                if Self::digit_pixel(&text, x.wrapping_sub(text_x), y.wrapping_sub(text_y)) {
+                    set_pixel(x, y, text_color);
+                    continue;
+                }
+
+                let (angle, r) = polar::to_polar(x as f32, y as f32);
+                // 0 at 12 o'clock, clockwise
+                let from_top = polar::wrap_angle(angle + FRAC_PI_2);
+                let around = from_top / TAU;
+
+                let color = if let Some(band) = Self::ring(r, Self::HOUR_RING) {
+                    // notches at the hours, measured in pixels along the ring
+                    let to_hour = libm::fabsf(around * 12.0 - libm::roundf(around * 12.0)) * TAU / 12.0 * r;
+                    let notch = (to_hour - Self::TICK_GAP / 2.0 + 0.5).clamp(0.0, 1.0);
+                    Self::arc(pal.primary, around, hours, r).scale(band * notch)
+                } else if let Some(band) = Self::ring(r, Self::MINUTE_RING) {
+                    Self::arc(pal.secondary, around, minutes, r).scale(band)
+                } else if let Some(band) = Self::ring(r, Self::SECOND_RING).filter(|_| show_seconds) {
+                    // brightest at the hand, fading back towards 12
+                    let tail = if seconds > 0.0 { 0.3 + 0.7 * (around / seconds).min(1.0) } else { 1.0 };
+                    Self::arc(pal.accent.scale(tail), around, seconds, r).scale(band)
+                } else if r < Self::SECOND_RING.0 - Self::SECOND_RING.1 {
+                    let d = (r - glow_radius) / 18.0;
+                    let glow = 1.0 / (1.0 + d * d);
+                    let drift = 0.5 + 0.5 * polar::sin(angle * 3.0 + self.phase);
+                    let hue = 0.5 + 0.3 * polar::sin(angle + self.phase) + 0.2 * (drift - 0.5);
+                    pal.sample(hue).scale(glow * glow_brightness * (0.7 + 0.3 * drift))
+                } else {
+                    continue;
+                };
+                set_pixel(x, y, color);
+            }
+        }
+    }
+
+    // whether a glyph covers (dx, dy) from the top left of the digits
+    fn digit_pixel(text: &[u8; Self::TEXT_LEN], dx: usize, dy: usize) -> bool {
+        let (cell_w, cell_h) = (FONT_8X16.advance * Self::DIGIT_SCALE, FONT_8X16.height * Self::DIGIT_SCALE);
+        if dx >= cell_w * Self::TEXT_LEN || dy >= cell_h {
+            return false;
+        }
+        let glyph = FONT_8X16.glyph(text[dx / cell_w] as char);
+        let (col, row) = (dx % cell_w / Self::DIGIT_SCALE, dy / Self::DIGIT_SCALE);
+        glyph[row] & (0x80 >> col) != 0
+    }
+
+    // antialiased coverage of a ring at radius r, None outside it
+    fn ring(r: f32, (center, width): (f32, f32)) -> Option<f32> {
+        let band = ((r - center + width / 2.0 + 0.5).min(center + width / 2.0 - r + 0.5)).clamp(0.0, 1.0);
+        (band > 0.0).then_some(band)
+    }
+
+    // lit up to `fraction` of the way round, the dim track after it, one pixel of blend between
+    fn arc(color: Color, around: f32, fraction: f32, r: f32) -> Color {
+        let edge = ((fraction - around) * TAU * r + 0.5).clamp(0.0, 1.0);
+        Color::lerp(color.scale(Self::TRACK), color, edge)
+    }
+}
+
+impl VisualMode for ClockFace {
+    fn update(&mut self, dt: f32, frame: &AnalysisFrame) {
+        ClockFace::update(self, dt, frame.bands());
+    }
+
+    fn render(&self, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.render_with_palette(set_pixel, pal);
+    }
+
+    fn render_rows(&self, rows: Range<usize>, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.draw_rows(&DISPLAY_REGION, rows, 1, set_pixel, pal);
+    }
+
+    fn render_region(&self, region: &RenderRegion, step: usize, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.draw_rows(region, 0..DISPLAY_SIZE, step, set_pixel, pal);
+    }
+
+    fn stays_in_region(&self) -> bool {
+        true
+    }
+
+    // one LED each for the hands, on a dim dial
+    fn render_ring(&self, ring: &mut LedRing, pal: &ColorPalette) -> bool {
+        ring.clear(pal.primary.scale(Self::TRACK / 2.0));
+        if self.time.is_none() {
+            return true;
+        }
+        let (hours, minutes, seconds) = self.fractions();
+        let len = ring.len();
+        let led = |fraction: f32| (libm::roundf(fraction * len as f32) as usize) % len;
+        if self.params[Self::SECONDS].is_on() {
+            ring.set(led(seconds), pal.accent);
+        }
+        ring.add(led(minutes), pal.secondary);
+        ring.add(led(hours), pal.primary);
+        true
+    }
+
+    fn params(&self) -> &[Param] {
+        &self.params
+    }
+
+    fn params_mut(&mut self) -> &mut [Param] {
+        &mut self.params
+    }
+}
+
+impl Default for ClockFace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
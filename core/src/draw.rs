@@ -0,0 +1,187 @@
+// anti-aliased 2D primitives on the core framebuffer, or through a mode's set_pixel.
+// coordinates are in pixels (f32, pixel centers on integers), angles in radians with
+// 0 at 3 o'clock increasing clockwise (screen y points down), like Point2D::rotate.
+
+use core::f32::consts::{FRAC_PI_2, TAU};
+
+use libm::{atan2f, ceilf, cosf, fabsf, floorf, sinf, sqrtf};
+
+use crate::framebuffer::Framebuffer;
+use crate::Color;
+
+fn coverage_alpha(coverage: f32) -> u8 {
+    (coverage.clamp(0.0, 1.0) * 255.0) as u8
+}
+
+// where the primitives put their pixels. the framebuffer blends by coverage, a mode's
+// set_pixel gets the color scaled by it, the renderer adds that over what's already there
+pub trait Canvas {
+    fn plot(&mut self, x: usize, y: usize, color: Color, coverage: f32);
+}
+
+impl Canvas for Framebuffer {
+    fn plot(&mut self, x: usize, y: usize, color: Color, coverage: f32) {
+        self.blend(x, y, color, coverage_alpha(coverage));
+    }
+}
+
+impl<F: FnMut(usize, usize, Color) + ?Sized> Canvas for F {
+    fn plot(&mut self, x: usize, y: usize, color: Color, coverage: f32) {
+        self(x, y, color.scale(coverage.min(1.0)));
+    }
+}
+
+fn plot<C: Canvas + ?Sized>(fb: &mut C, x: i32, y: i32, color: Color, coverage: f32) {
+    if x >= 0 && y >= 0 && coverage > 0.0 {
+        fb.plot(x as usize, y as usize, color, coverage);
+    }
+}
+
+// bounding box of a circle as inclusive pixel ranges, clipped to the framebuffer
+fn bounds(cx: f32, cy: f32, r: f32) -> (i32, i32, i32, i32) {
+    let max = Framebuffer::WIDTH as i32 - 1;
+    (
+        (floorf(cx - r) as i32).clamp(0, max),
+        (floorf(cy - r) as i32).clamp(0, max),
+        (ceilf(cx + r) as i32).clamp(0, max),
+        (ceilf(cy + r) as i32).clamp(0, max),
+    )
+}
+
+// bounding box of a partial ring: the corners of the wedge and wherever its outer edge crosses
+// an axis, so a narrow wedge doesn't scan the whole circle
+fn wedge_bounds(cx: f32, cy: f32, inner: f32, outer: f32, start: f32, sweep: f32) -> (i32, i32, i32, i32) {
+    let (mut x0, mut y0, mut x1, mut y1) = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+    let mut include = |a: f32, r: f32| {
+        let (x, y) = (cx + r * cosf(a), cy + r * sinf(a));
+        (x0, y0, x1, y1) = (x0.min(x), y0.min(y), x1.max(x), y1.max(y));
+    };
+    for a in [start, start + sweep] {
+        include(a, inner);
+        include(a, outer);
+    }
+    for quarter in 0..4 {
+        let a = quarter as f32 * FRAC_PI_2;
+        if (a - start).rem_euclid(TAU) <= sweep {
+            include(a, outer);
+        }
+    }
+    let max = Framebuffer::WIDTH as i32 - 1;
+    (
+        (floorf(x0 - 1.0) as i32).clamp(0, max),
+        (floorf(y0 - 1.0) as i32).clamp(0, max),
+        (ceilf(x1 + 1.0) as i32).clamp(0, max),
+        (ceilf(y1 + 1.0) as i32).clamp(0, max),
+    )
+}
+
+// signed angular distance (radians) from `a` to the nearest edge of [start, start + sweep],
+// positive inside the span
+fn angle_inside(a: f32, start: f32, sweep: f32) -> f32 {
+    let rel = (a - start).rem_euclid(TAU);
+    if rel <= sweep {
+        rel.min(sweep - rel)
+    } else {
+        -(rel - sweep).min(TAU - rel)
+    }
+}
+
+// Xiaolin Wu's line
+pub fn line_aa<C: Canvas + ?Sized>(fb: &mut C, x0: f32, y0: f32, x1: f32, y1: f32, color: Color) {
+    let steep = fabsf(y1 - y0) > fabsf(x1 - x0);
+    let (mut x0, mut y0, mut x1, mut y1) = if steep { (y0, x0, y1, x1) } else { (x0, y0, x1, y1) };
+    if x0 > x1 {
+        core::mem::swap(&mut x0, &mut x1);
+        core::mem::swap(&mut y0, &mut y1);
+    }
+
+    let dx = x1 - x0;
+    let gradient = if dx == 0.0 { 1.0 } else { (y1 - y0) / dx };
+    let mut put = |x: i32, y: i32, c: f32| {
+        if steep { plot(fb, y, x, color, c) } else { plot(fb, x, y, color, c) }
+    };
+
+    let xs = floorf(x0 + 0.5) as i32;
+    let xe = floorf(x1 + 0.5) as i32;
+    let mut y = y0 + gradient * (xs as f32 - x0);
+    for x in xs..=xe {
+        let fy = floorf(y);
+        let frac = y - fy;
+        put(x, fy as i32, 1.0 - frac);
+        put(x, fy as i32 + 1, frac);
+        y += gradient;
+    }
+}
+
+// 1px circle outline
+pub fn circle<C: Canvas + ?Sized>(fb: &mut C, center: (f32, f32), radius: f32, color: Color) {
+    ring_segment(fb, center, radius - 0.5, radius + 0.5, 0.0, TAU, color);
+}
+
+pub fn filled_circle<C: Canvas + ?Sized>(fb: &mut C, center: (f32, f32), radius: f32, color: Color) {
+    let (cx, cy) = center;
+    let (x_min, y_min, x_max, y_max) = bounds(cx, cy, radius + 1.0);
+    for y in y_min..=y_max {
+        for x in x_min..=x_max {
+            let (dx, dy) = (x as f32 - cx, y as f32 - cy);
+            let d = sqrtf(dx * dx + dy * dy);
+            plot(fb, x, y, color, radius - d + 0.5);
+        }
+    }
+}
+
+// stroked arc of the given thickness, centered on the radius
+pub fn arc<C: Canvas + ?Sized>(fb: &mut C, center: (f32, f32), radius: f32, thickness: f32, start: f32, sweep: f32, color: Color) {
+    let half = thickness / 2.0;
+    ring_segment(fb, center, radius - half, radius + half, start, sweep, color);
+}
+
+// filled annular wedge between two radii, the building block for gauges around the round edge
+pub fn ring_segment<C: Canvas + ?Sized>(fb: &mut C, center: (f32, f32), inner: f32, outer: f32, start: f32, sweep: f32, color: Color) {
+    if sweep <= 0.0 || outer <= inner {
+        return;
+    }
+    let (cx, cy) = center;
+    let full = sweep >= TAU;
+    let (x_min, y_min, x_max, y_max) =
+        if full { bounds(cx, cy, outer + 1.0) } else { wedge_bounds(cx, cy, inner, outer, start, sweep) };
+    let inner_sq = ((inner - 1.0).max(0.0)) * ((inner - 1.0).max(0.0));
+    let outer_sq = (outer + 1.0) * (outer + 1.0);
+
+    for y in y_min..=y_max {
+        for x in x_min..=x_max {
+            let (dx, dy) = (x as f32 - cx, y as f32 - cy);
+            let d_sq = dx * dx + dy * dy;
+            if d_sq < inner_sq || d_sq > outer_sq {
+                continue;
+            }
+            let d = sqrtf(d_sq);
+            let mut coverage = (d - inner + 0.5).min(outer - d + 0.5);
+            if !full {
+                // convert the angular distance to pixels at this radius for a soft edge
+                let a = atan2f(dy, dx);
+                coverage = coverage.min(angle_inside(a, start, sweep) * d + 0.5);
+            }
+            plot(fb, x, y, color, coverage);
+        }
+    }
+}
+
+// filled rectangle with anti-aliased rounded corners, (x, y) is the top left
+pub fn rounded_rect<C: Canvas + ?Sized>(fb: &mut C, x: f32, y: f32, w: f32, h: f32, radius: f32, color: Color) {
+    let r = radius.clamp(0.0, w.min(h) / 2.0);
+    let max = Framebuffer::WIDTH as i32 - 1;
+    let (x_min, x_max) = ((floorf(x) as i32).clamp(0, max), (ceilf(x + w) as i32).clamp(0, max));
+    let (y_min, y_max) = ((floorf(y) as i32).clamp(0, max), (ceilf(y + h) as i32).clamp(0, max));
+
+    for py in y_min..=y_max {
+        for px in x_min..=x_max {
+            // distance to the rectangle shrunk by r, then rounded back out by r
+            let qx = (fabsf(px as f32 - (x + w / 2.0)) - (w / 2.0 - r)).max(0.0);
+            let qy = (fabsf(py as f32 - (y + h / 2.0)) - (h / 2.0 - r)).max(0.0);
+            let outside = sqrtf(qx * qx + qy * qy) - r;
+            let edge = (x + w - px as f32).min(px as f32 - x).min(y + h - py as f32).min(py as f32 - y);
+            plot(fb, px, py, color, (0.5 - outside).min(edge + 0.5));
+        }
+    }
+}
@@ -0,0 +1,71 @@
+// one error type for callers that handle every ui failure in one place. the subsystems keep
+// their own small error enums (a store only fails like a store), each converts into this, so
+// firmware and simulator code can `?` them all into a single match
+
+use crate::calibrate::CalibrationError;
+use crate::framebuffer::SwapError;
+use crate::source::{ChannelCountError, FrameDecodeError};
+use crate::store::StoreError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UiError {
+    Channels(ChannelCountError),
+    // a frame with a different band count than the visualizer was built for, e.g. one relayed
+    // from a badge configured for another filterbank
+    ChannelMismatch { expected: usize, got: usize },
+    BufferTooSmall { needed: usize, got: usize }, // pixels or bytes a caller's buffer is short of
+    Frame(FrameDecodeError),
+    Store(StoreError),
+    Swap(SwapError),
+    Calibration(CalibrationError),
+}
+
+impl core::fmt::Display for UiError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            UiError::Channels(e) => write!(f, "{}", e),
+            UiError::ChannelMismatch { expected, got } => {
+                write!(f, "frame has {} channels, the visualizer was set up for {}", got, expected)
+            }
+            UiError::BufferTooSmall { needed, got } => write!(f, "buffer holds {}, needs {}", got, needed),
+            UiError::Frame(e) => write!(f, "{}", e),
+            UiError::Store(StoreError::Io) => f.write_str("settings store failed to read or write"),
+            UiError::Store(StoreError::TooLarge) => f.write_str("settings don't fit the settings store"),
+            UiError::Store(StoreError::Format) => f.write_str("stored settings don't decode, saved by another version?"),
+            UiError::Swap(SwapError::Busy) => f.write_str("previous frame still streaming to the panel"),
+            UiError::Calibration(CalibrationError::NotEnoughSpeech) => f.write_str("calibration heard too little speech"),
+        }
+    }
+}
+
+impl core::error::Error for UiError {}
+
+impl From<ChannelCountError> for UiError {
+    fn from(e: ChannelCountError) -> Self {
+        UiError::Channels(e)
+    }
+}
+
+impl From<FrameDecodeError> for UiError {
+    fn from(e: FrameDecodeError) -> Self {
+        UiError::Frame(e)
+    }
+}
+
+impl From<StoreError> for UiError {
+    fn from(e: StoreError) -> Self {
+        UiError::Store(e)
+    }
+}
+
+impl From<SwapError> for UiError {
+    fn from(e: SwapError) -> Self {
+        UiError::Swap(e)
+    }
+}
+
+impl From<CalibrationError> for UiError {
+    fn from(e: CalibrationError) -> Self {
+        UiError::Calibration(e)
+    }
+}
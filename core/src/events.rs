@@ -0,0 +1,131 @@
+// user input as events, and the mapping from events to ui actions.
+// firmware side:
+// - poll each button's level every few ms into its PressDetector, which reports presses
+// - pass raw quadrature counts through Encoder::feed, non-zero steps become InputEvent::Encoder
+// - hand every event to InputHandler::handle and carry out the returned UiAction
+// the simulator does the same with keyboard keys, so press timing behaves like the badge.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Button {
+    Select, // encoder push
+    Back,   // side button
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputEvent {
+    ShortPress(Button),
+    LongPress(Button),
+    Encoder(i32), // detent steps, positive clockwise
+}
+
+// level -> press events: short on release, long as soon as the hold time is reached
+pub struct PressDetector {
+    pressed_at: Option<u32>,
+    long_sent: bool,
+    last_change_ms: Option<u32>,
+}
+
+impl PressDetector {
+    pub const LONG_PRESS_MS: u32 = 600;
+    pub const DEBOUNCE_MS: u32 = 20;
+
+    pub fn new() -> Self {
+        Self { pressed_at: None, long_sent: false, last_change_ms: None }
+    }
+
+    // call with the current button level, returns at most one press per call
+    pub fn update(&mut self, button: Button, pressed: bool, now_ms: u32) -> Option<InputEvent> {
+        match (self.pressed_at, pressed) {
+            (None, true) => {
+                let bounced = self.last_change_ms.is_some_and(|t| now_ms.wrapping_sub(t) < Self::DEBOUNCE_MS);
+                if !bounced {
+                    self.pressed_at = Some(now_ms);
+                    self.long_sent = false;
+                    self.last_change_ms = Some(now_ms);
+                }
+                None
+            }
+            (Some(start), true) => {
+                if !self.long_sent && now_ms.wrapping_sub(start) >= Self::LONG_PRESS_MS {
+                    self.long_sent = true;
+                    return Some(InputEvent::LongPress(button));
+                }
+                None
+            }
+            (Some(start), false) => {
+                // contact bounce while held doesn't count as a release
+                if now_ms.wrapping_sub(start) < Self::DEBOUNCE_MS {
+                    return None;
+                }
+                self.pressed_at = None;
+                self.last_change_ms = Some(now_ms);
+                (!self.long_sent).then_some(InputEvent::ShortPress(button))
+            }
+            (None, false) => None,
+        }
+    }
+}
+
+impl Default for PressDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UiAction {
+    None,
+    NextMode,
+    PrevMode,
+    Brightness(i32), // steps, the caller picks the step size
+    OpenMenu,
+    MenuStep(i32),
+    MenuSelect,
+    MenuBack,
+}
+
+// outside the menu the encoder sets brightness, select steps through modes, back goes back
+// one mode and a long select opens the menu. inside the menu the encoder moves the
+// selection, select picks and back (or a long select) leaves.
+pub struct InputHandler {
+    menu_open: bool,
+}
+
+impl InputHandler {
+    pub fn new() -> Self {
+        Self { menu_open: false }
+    }
+
+    // the menu's owner reports opening and closing (also on timeout) here
+    pub fn set_menu_open(&mut self, open: bool) {
+        self.menu_open = open;
+    }
+
+    pub fn menu_open(&self) -> bool {
+        self.menu_open
+    }
+
+    pub fn handle(&self, event: InputEvent) -> UiAction {
+        if self.menu_open {
+            match event {
+                InputEvent::Encoder(steps) => UiAction::MenuStep(steps),
+                InputEvent::ShortPress(Button::Select) => UiAction::MenuSelect,
+                InputEvent::ShortPress(Button::Back) | InputEvent::LongPress(_) => UiAction::MenuBack,
+            }
+        } else {
+            match event {
+                InputEvent::Encoder(steps) => UiAction::Brightness(steps),
+                InputEvent::ShortPress(Button::Select) => UiAction::NextMode,
+                InputEvent::ShortPress(Button::Back) => UiAction::PrevMode,
+                InputEvent::LongPress(Button::Select) => UiAction::OpenMenu,
+                InputEvent::LongPress(Button::Back) => UiAction::None,
+            }
+        }
+    }
+}
+
+impl Default for InputHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
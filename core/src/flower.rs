@@ -0,0 +1,119 @@
+// vocoder flower: a petal per band around a glowing core, low channels first going clockwise.
+// petal length and brightness follow the band, a slower glow lingers on the tips after it
+// drops, and the flower turns slowly, faster for a higher voice and slower for a lower one
+
+use core::f32::consts::TAU;
+
+use crate::draw::{circle, filled_circle, ring_segment};
+use crate::param::Param;
+use crate::polar;
+use crate::source::AnalysisFrame;
+use crate::vis::VisualMode;
+use crate::{Color, ColorPalette, EnvelopeSmoother, CHANNELS, DISPLAY_CENTER, DISPLAY_RADIUS};
+
+const CORE_RADIUS: f32 = 14.0;
+const MIN_PETAL: f32 = 12.0; // length of a silent petal
+const REFERENCE_PITCH: f32 = 200.0; // Hz that turns at the plain spin speed
+
+pub struct Flower {
+    num_channels: usize,
+    energies: [EnvelopeSmoother; CHANNELS],
+    glow: [EnvelopeSmoother; CHANNELS],
+    level: EnvelopeSmoother,
+    speed: EnvelopeSmoother, // pitch factor on the spin, eased so a voice break doesn't jerk it
+    rotation: f32,
+    params: [Param; 4],
+}
+
+impl Flower {
+    const SIZE: usize = 0; // params
+    const WIDTH: usize = 1;
+    const SPIN: usize = 2;
+    const PITCH: usize = 3;
+
+    pub fn new(num_channels: usize) -> Self {
+        let mut speed = EnvelopeSmoother::new(60.0, 400.0, 400.0);
+        speed.process(1.0);
+        Self {
+            num_channels: num_channels.clamp(1, CHANNELS),
+            energies: core::array::from_fn(|_| EnvelopeSmoother::new(60.0, 10.0, 120.0)),
+            glow: core::array::from_fn(|_| EnvelopeSmoother::new(60.0, 30.0, 600.0)),
+            level: EnvelopeSmoother::new(60.0, 20.0, 300.0),
+            speed,
+            rotation: 0.0,
+            params: [
+                Param::new("size", "Size", 0.5, 1.0, 0.05, 1.0),
+                Param::new("width", "Petal width", 0.2, 0.9, 0.05, 0.6),
+                Param::new("spin", "Spin", 0.0, 1.0, 0.05, 0.15),
+                Param::new("pitch", "Pitch pull", 0.0, 2.0, 0.1, 1.0),
+            ],
+        }
+    }
+
+    pub fn update(&mut self, dt: f32, energies: &[f32], pitch: Option<f32>) {
+        let mut total = 0.0;
+        for i in 0..self.num_channels {
+            let e = energies.get(i).copied().unwrap_or(0.0).clamp(0.0, 1.0);
+            total += self.energies[i].process(e);
+            self.glow[i].process(e);
+        }
+        self.level.process(total / self.num_channels as f32);
+
+        // an octave above the reference turns 2^pull times as fast, unvoiced eases back to 1
+        let octaves = pitch.filter(|&p| p > 0.0).map_or(0.0, |p| libm::log2f(p / REFERENCE_PITCH));
+        let factor = libm::exp2f((octaves * self.params[Self::PITCH].value).clamp(-2.0, 2.0));
+        let speed = self.speed.process(factor);
+        self.rotation = polar::wrap_angle(self.rotation + dt * TAU * self.params[Self::SPIN].value * speed);
+    }
+
+    pub fn render_with_palette<F>(&self, mut set_pixel: F, pal: &ColorPalette)
+    where
+        F: FnMut(usize, usize, Color),
+    {
+        let center = (DISPLAY_CENTER, DISPLAY_CENTER);
+        let n = self.num_channels;
+        let slot = TAU / n as f32;
+        let sweep = slot * self.params[Self::WIDTH].value;
+        let core = CORE_RADIUS * (1.0 + 0.5 * self.level.value());
+        let reach = (DISPLAY_RADIUS - core - MIN_PETAL - 8.0) * self.params[Self::SIZE].value;
+
+        for i in 0..n {
+            let energy = self.energies[i].value();
+            let glow = self.glow[i].value();
+            let angle = self.rotation + i as f32 * slot;
+            let outer = core + MIN_PETAL + energy * reach;
+            let color = pal.band(i, n);
+
+            // a dim halo a little wider and longer than the petal, then the petal over it
+            let halo = sweep * 0.25;
+            ring_segment(&mut set_pixel, center, core, outer + 6.0, angle - sweep / 2.0 - halo, sweep + 2.0 * halo, color.scale(0.2 * glow));
+            ring_segment(&mut set_pixel, center, core, outer, angle - sweep / 2.0, sweep, color.scale(0.3 + 0.7 * energy));
+
+            // the rounded tip adds over the petal's end, so loud petals burn brightest there
+            let tip = (sweep / 2.0 * outer).min(10.0);
+            filled_circle(&mut set_pixel, polar::from_polar(angle, outer), tip, color.scale(0.1 + 0.6 * glow));
+        }
+
+        let level = self.level.value();
+        filled_circle(&mut set_pixel, center, core, pal.accent.scale(0.35 + 0.65 * level));
+        circle(&mut set_pixel, center, core + 3.0, pal.accent.scale(0.2 + 0.3 * level));
+    }
+}
+
+impl VisualMode for Flower {
+    fn update(&mut self, dt: f32, frame: &AnalysisFrame) {
+        Flower::update(self, dt, frame.bands(), frame.pitch);
+    }
+
+    fn render(&self, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.render_with_palette(set_pixel, pal);
+    }
+
+    fn params(&self) -> &[Param] {
+        &self.params
+    }
+
+    fn params_mut(&mut self) -> &mut [Param] {
+        &mut self.params
+    }
+}
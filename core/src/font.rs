@@ -0,0 +1,222 @@
+// bitmap fonts for printable ASCII (0x20-0x7E), one byte per row, MSB is the leftmost pixel.
+// glyphs come from the public domain X11 misc-fixed 5x7 and 8x13 fonts, the latter padded
+// to an 8x16 cell so lines get some breathing room.
+
+pub struct Font {
+    pub width: usize,
+    pub height: usize,
+    pub advance: usize,  // horizontal step per character, glyph width plus spacing
+    pub baseline: usize, // rows above the baseline, for mixing fonts on one line
+    data: &'static [u8],
+}
+
+impl Font {
+    const FIRST: u8 = 0x20;
+    const LAST: u8 = 0x7E;
+
+    // rows of a glyph, unknown characters render as '?'
+    pub fn glyph(&self, c: char) -> &'static [u8] {
+        let code = if (Self::FIRST as u32..=Self::LAST as u32).contains(&(c as u32)) { c as u8 } else { b'?' };
+        let start = (code - Self::FIRST) as usize * self.height;
+        &self.data[start..start + self.height]
+    }
+}
+
+pub static FONT_5X7: Font = Font { width: 5, height: 7, advance: 6, baseline: 6, data: &FONT_5X7_DATA };
+pub static FONT_8X16: Font = Font { width: 8, height: 16, advance: 8, baseline: 12, data: &FONT_8X16_DATA };
+
+const FONT_5X7_DATA: [u8; 665] = [
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // space
+    0x20, 0x20, 0x20, 0x20, 0x00, 0x20, 0x00, // !
+    0x50, 0x50, 0x50, 0x00, 0x00, 0x00, 0x00, // "
+    0x00, 0x50, 0xF8, 0x50, 0xF8, 0x50, 0x00, // #
+    0x00, 0x70, 0xA0, 0x70, 0x28, 0x70, 0x00, // $
+    0x80, 0x90, 0x20, 0x40, 0x90, 0x10, 0x00, // %
+    0x00, 0x40, 0xA0, 0x40, 0xA0, 0x50, 0x00, // &
+    0x20, 0x20, 0x20, 0x00, 0x00, 0x00, 0x00, // quote
+    0x20, 0x40, 0x40, 0x40, 0x40, 0x20, 0x00, // (
+    0x40, 0x20, 0x20, 0x20, 0x20, 0x40, 0x00, // )
+    0x00, 0x50, 0x20, 0x70, 0x20, 0x50, 0x00, // *
+    0x00, 0x20, 0x20, 0xF8, 0x20, 0x20, 0x00, // +
+    0x00, 0x00, 0x00, 0x00, 0x30, 0x20, 0x40, // ,
+    0x00, 0x00, 0x00, 0xF0, 0x00, 0x00, 0x00, // -
+    0x00, 0x00, 0x00, 0x00, 0x60, 0x60, 0x00, // .
+    0x00, 0x10, 0x20, 0x40, 0x80, 0x00, 0x00, // /
+    0x20, 0x50, 0x50, 0x50, 0x50, 0x20, 0x00, // 0
+    0x20, 0x60, 0x20, 0x20, 0x20, 0x70, 0x00, // 1
+    0x60, 0x90, 0x10, 0x20, 0x40, 0xF0, 0x00, // 2
+    0xF0, 0x10, 0x60, 0x10, 0x90, 0x60, 0x00, // 3
+    0x20, 0x60, 0xA0, 0xF0, 0x20, 0x20, 0x00, // 4
+    0xF0, 0x80, 0xE0, 0x10, 0x90, 0x60, 0x00, // 5
+    0x60, 0x80, 0xE0, 0x90, 0x90, 0x60, 0x00, // 6
+    0xF0, 0x10, 0x20, 0x20, 0x40, 0x40, 0x00, // 7
+    0x60, 0x90, 0x60, 0x90, 0x90, 0x60, 0x00, // 8
+    0x60, 0x90, 0x90, 0x70, 0x10, 0x60, 0x00, // 9
+    0x00, 0x60, 0x60, 0x00, 0x60, 0x60, 0x00, // :
+    0x00, 0x60, 0x60, 0x00, 0x60, 0x40, 0x80, // ;
+    0x00, 0x10, 0x20, 0x40, 0x20, 0x10, 0x00, // <
+    0x00, 0x00, 0xF0, 0x00, 0xF0, 0x00, 0x00, // =
+    0x00, 0x40, 0x20, 0x10, 0x20, 0x40, 0x00, // >
+    0x20, 0x50, 0x10, 0x20, 0x00, 0x20, 0x00, // ?
+    0x60, 0x90, 0xB0, 0xB0, 0x80, 0x60, 0x00, // @
+    0x60, 0x90, 0x90, 0xF0, 0x90, 0x90, 0x00, // A
+    0xE0, 0x90, 0xE0, 0x90, 0x90, 0xE0, 0x00, // B
+    0x60, 0x90, 0x80, 0x80, 0x90, 0x60, 0x00, // C
+    0xE0, 0x90, 0x90, 0x90, 0x90, 0xE0, 0x00, // D
+    0xF0, 0x80, 0xE0, 0x80, 0x80, 0xF0, 0x00, // E
+    0xF0, 0x80, 0xE0, 0x80, 0x80, 0x80, 0x00, // F
+    0x60, 0x90, 0x80, 0xB0, 0x90, 0x70, 0x00, // G
+    0x90, 0x90, 0xF0, 0x90, 0x90, 0x90, 0x00, // H
+    0x70, 0x20, 0x20, 0x20, 0x20, 0x70, 0x00, // I
+    0x10, 0x10, 0x10, 0x10, 0x90, 0x60, 0x00, // J
+    0x90, 0xA0, 0xC0, 0xC0, 0xA0, 0x90, 0x00, // K
+    0x80, 0x80, 0x80, 0x80, 0x80, 0xF0, 0x00, // L
+    0x90, 0xF0, 0xF0, 0x90, 0x90, 0x90, 0x00, // M
+    0x90, 0xD0, 0xD0, 0xB0, 0xB0, 0x90, 0x00, // N
+    0x60, 0x90, 0x90, 0x90, 0x90, 0x60, 0x00, // O
+    0xE0, 0x90, 0x90, 0xE0, 0x80, 0x80, 0x00, // P
+    0x60, 0x90, 0x90, 0x90, 0xD0, 0x60, 0x10, // Q
+    0xE0, 0x90, 0x90, 0xE0, 0xA0, 0x90, 0x00, // R
+    0x60, 0x90, 0x40, 0x20, 0x90, 0x60, 0x00, // S
+    0x70, 0x20, 0x20, 0x20, 0x20, 0x20, 0x00, // T
+    0x90, 0x90, 0x90, 0x90, 0x90, 0x60, 0x00, // U
+    0x90, 0x90, 0x90, 0x90, 0x60, 0x60, 0x00, // V
+    0x90, 0x90, 0x90, 0xF0, 0xF0, 0x90, 0x00, // W
+    0x90, 0x90, 0x60, 0x60, 0x90, 0x90, 0x00, // X
+    0x50, 0x50, 0x50, 0x20, 0x20, 0x20, 0x00, // Y
+    0xF0, 0x10, 0x20, 0x40, 0x80, 0xF0, 0x00, // Z
+    0x70, 0x40, 0x40, 0x40, 0x40, 0x70, 0x00, // [
+    0x00, 0x80, 0x40, 0x20, 0x10, 0x00, 0x00, // backslash
+    0x70, 0x10, 0x10, 0x10, 0x10, 0x70, 0x00, // ]
+    0x20, 0x50, 0x00, 0x00, 0x00, 0x00, 0x00, // ^
+    0x00, 0x00, 0x00, 0x00, 0x00, 0xF0, 0x00, // _
+    0x40, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, // `
+    0x00, 0x00, 0x70, 0x90, 0xB0, 0x50, 0x00, // a
+    0x80, 0x80, 0xE0, 0x90, 0x90, 0xE0, 0x00, // b
+    0x00, 0x00, 0x60, 0x80, 0x80, 0x60, 0x00, // c
+    0x10, 0x10, 0x70, 0x90, 0x90, 0x70, 0x00, // d
+    0x00, 0x00, 0x60, 0xB0, 0xC0, 0x60, 0x00, // e
+    0x20, 0x50, 0x40, 0xE0, 0x40, 0x40, 0x00, // f
+    0x00, 0x00, 0x70, 0x90, 0x60, 0x80, 0x70, // g
+    0x80, 0x80, 0xE0, 0x90, 0x90, 0x90, 0x00, // h
+    0x20, 0x00, 0x60, 0x20, 0x20, 0x70, 0x00, // i
+    0x10, 0x00, 0x10, 0x10, 0x10, 0x50, 0x20, // j
+    0x80, 0x80, 0xA0, 0xC0, 0xA0, 0x90, 0x00, // k
+    0x60, 0x20, 0x20, 0x20, 0x20, 0x70, 0x00, // l
+    0x00, 0x00, 0xA0, 0xF0, 0x90, 0x90, 0x00, // m
+    0x00, 0x00, 0xE0, 0x90, 0x90, 0x90, 0x00, // n
+    0x00, 0x00, 0x60, 0x90, 0x90, 0x60, 0x00, // o
+    0x00, 0x00, 0xE0, 0x90, 0x90, 0xE0, 0x80, // p
+    0x00, 0x00, 0x70, 0x90, 0x90, 0x70, 0x10, // q
+    0x00, 0x00, 0xE0, 0x90, 0x80, 0x80, 0x00, // r
+    0x00, 0x00, 0x70, 0xC0, 0x30, 0xE0, 0x00, // s
+    0x40, 0x40, 0xE0, 0x40, 0x40, 0x30, 0x00, // t
+    0x00, 0x00, 0x90, 0x90, 0x90, 0x70, 0x00, // u
+    0x00, 0x00, 0x50, 0x50, 0x50, 0x20, 0x00, // v
+    0x00, 0x00, 0x90, 0x90, 0xF0, 0xF0, 0x00, // w
+    0x00, 0x00, 0x90, 0x60, 0x60, 0x90, 0x00, // x
+    0x00, 0x00, 0x90, 0x90, 0x50, 0x20, 0x40, // y
+    0x00, 0x00, 0xF0, 0x20, 0x40, 0xF0, 0x00, // z
+    0x10, 0x20, 0x60, 0x20, 0x20, 0x10, 0x00, // {
+    0x20, 0x20, 0x20, 0x20, 0x20, 0x20, 0x00, // |
+    0x40, 0x20, 0x30, 0x20, 0x20, 0x40, 0x00, // }
+    0x50, 0xA0, 0x00, 0x00, 0x00, 0x00, 0x00, // ~
+];
+
+const FONT_8X16_DATA: [u8; 1520] = [
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // space
+    0x00, 0x00, 0x00, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00, // !
+    0x00, 0x00, 0x00, 0x24, 0x24, 0x24, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // "
+    0x00, 0x00, 0x00, 0x00, 0x24, 0x24, 0x7E, 0x24, 0x7E, 0x24, 0x24, 0x00, 0x00, 0x00, 0x00, 0x00, // #
+    0x00, 0x00, 0x00, 0x10, 0x3C, 0x50, 0x50, 0x38, 0x14, 0x14, 0x78, 0x10, 0x00, 0x00, 0x00, 0x00, // $
+    0x00, 0x00, 0x00, 0x22, 0x52, 0x24, 0x08, 0x08, 0x10, 0x24, 0x2A, 0x44, 0x00, 0x00, 0x00, 0x00, // %
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x30, 0x48, 0x48, 0x30, 0x4A, 0x44, 0x3A, 0x00, 0x00, 0x00, 0x00, // &
+    0x00, 0x00, 0x00, 0x10, 0x10, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // quote
+    0x00, 0x00, 0x00, 0x04, 0x08, 0x08, 0x10, 0x10, 0x10, 0x08, 0x08, 0x04, 0x00, 0x00, 0x00, 0x00, // (
+    0x00, 0x00, 0x00, 0x20, 0x10, 0x10, 0x08, 0x08, 0x08, 0x10, 0x10, 0x20, 0x00, 0x00, 0x00, 0x00, // )
+    0x00, 0x00, 0x00, 0x24, 0x18, 0x7E, 0x18, 0x24, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // *
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x10, 0x7C, 0x10, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // +
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x38, 0x30, 0x40, 0x00, 0x00, 0x00, // ,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x7C, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // -
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x38, 0x10, 0x00, 0x00, 0x00, // .
+    0x00, 0x00, 0x00, 0x02, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x80, 0x00, 0x00, 0x00, 0x00, // /
+    0x00, 0x00, 0x00, 0x18, 0x24, 0x42, 0x42, 0x42, 0x42, 0x42, 0x24, 0x18, 0x00, 0x00, 0x00, 0x00, // 0
+    0x00, 0x00, 0x00, 0x10, 0x30, 0x50, 0x10, 0x10, 0x10, 0x10, 0x10, 0x7C, 0x00, 0x00, 0x00, 0x00, // 1
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x42, 0x02, 0x04, 0x18, 0x20, 0x40, 0x7E, 0x00, 0x00, 0x00, 0x00, // 2
+    0x00, 0x00, 0x00, 0x7E, 0x02, 0x04, 0x08, 0x1C, 0x02, 0x02, 0x42, 0x3C, 0x00, 0x00, 0x00, 0x00, // 3
+    0x00, 0x00, 0x00, 0x04, 0x0C, 0x14, 0x24, 0x44, 0x44, 0x7E, 0x04, 0x04, 0x00, 0x00, 0x00, 0x00, // 4
+    0x00, 0x00, 0x00, 0x7E, 0x40, 0x40, 0x5C, 0x62, 0x02, 0x02, 0x42, 0x3C, 0x00, 0x00, 0x00, 0x00, // 5
+    0x00, 0x00, 0x00, 0x1C, 0x20, 0x40, 0x40, 0x5C, 0x62, 0x42, 0x42, 0x3C, 0x00, 0x00, 0x00, 0x00, // 6
+    0x00, 0x00, 0x00, 0x7E, 0x02, 0x04, 0x08, 0x08, 0x10, 0x10, 0x20, 0x20, 0x00, 0x00, 0x00, 0x00, // 7
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x42, 0x42, 0x3C, 0x42, 0x42, 0x42, 0x3C, 0x00, 0x00, 0x00, 0x00, // 8
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x42, 0x46, 0x3A, 0x02, 0x02, 0x04, 0x38, 0x00, 0x00, 0x00, 0x00, // 9
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x38, 0x10, 0x00, 0x00, 0x10, 0x38, 0x10, 0x00, 0x00, 0x00, // :
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x38, 0x10, 0x00, 0x00, 0x38, 0x30, 0x40, 0x00, 0x00, 0x00, // ;
+    0x00, 0x00, 0x00, 0x02, 0x04, 0x08, 0x10, 0x20, 0x10, 0x08, 0x04, 0x02, 0x00, 0x00, 0x00, 0x00, // <
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x7E, 0x00, 0x00, 0x7E, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // =
+    0x00, 0x00, 0x00, 0x40, 0x20, 0x10, 0x08, 0x04, 0x08, 0x10, 0x20, 0x40, 0x00, 0x00, 0x00, 0x00, // >
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x42, 0x02, 0x04, 0x08, 0x08, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00, // ?
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x42, 0x4E, 0x52, 0x56, 0x4A, 0x40, 0x3C, 0x00, 0x00, 0x00, 0x00, // @
+    0x00, 0x00, 0x00, 0x18, 0x24, 0x42, 0x42, 0x42, 0x7E, 0x42, 0x42, 0x42, 0x00, 0x00, 0x00, 0x00, // A
+    0x00, 0x00, 0x00, 0x78, 0x44, 0x42, 0x44, 0x78, 0x44, 0x42, 0x44, 0x78, 0x00, 0x00, 0x00, 0x00, // B
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x40, 0x40, 0x40, 0x40, 0x40, 0x42, 0x3C, 0x00, 0x00, 0x00, 0x00, // C
+    0x00, 0x00, 0x00, 0x78, 0x44, 0x42, 0x42, 0x42, 0x42, 0x42, 0x44, 0x78, 0x00, 0x00, 0x00, 0x00, // D
+    0x00, 0x00, 0x00, 0x7E, 0x40, 0x40, 0x40, 0x78, 0x40, 0x40, 0x40, 0x7E, 0x00, 0x00, 0x00, 0x00, // E
+    0x00, 0x00, 0x00, 0x7E, 0x40, 0x40, 0x40, 0x78, 0x40, 0x40, 0x40, 0x40, 0x00, 0x00, 0x00, 0x00, // F
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x40, 0x40, 0x40, 0x4E, 0x42, 0x46, 0x3A, 0x00, 0x00, 0x00, 0x00, // G
+    0x00, 0x00, 0x00, 0x42, 0x42, 0x42, 0x42, 0x7E, 0x42, 0x42, 0x42, 0x42, 0x00, 0x00, 0x00, 0x00, // H
+    0x00, 0x00, 0x00, 0x7C, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x7C, 0x00, 0x00, 0x00, 0x00, // I
+    0x00, 0x00, 0x00, 0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x44, 0x38, 0x00, 0x00, 0x00, 0x00, // J
+    0x00, 0x00, 0x00, 0x42, 0x44, 0x48, 0x50, 0x60, 0x50, 0x48, 0x44, 0x42, 0x00, 0x00, 0x00, 0x00, // K
+    0x00, 0x00, 0x00, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x7E, 0x00, 0x00, 0x00, 0x00, // L
+    0x00, 0x00, 0x00, 0x82, 0x82, 0xC6, 0xAA, 0x92, 0x92, 0x82, 0x82, 0x82, 0x00, 0x00, 0x00, 0x00, // M
+    0x00, 0x00, 0x00, 0x42, 0x42, 0x62, 0x52, 0x4A, 0x46, 0x42, 0x42, 0x42, 0x00, 0x00, 0x00, 0x00, // N
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x3C, 0x00, 0x00, 0x00, 0x00, // O
+    0x00, 0x00, 0x00, 0x7C, 0x42, 0x42, 0x42, 0x7C, 0x40, 0x40, 0x40, 0x40, 0x00, 0x00, 0x00, 0x00, // P
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x42, 0x42, 0x42, 0x42, 0x52, 0x4A, 0x3C, 0x02, 0x00, 0x00, 0x00, // Q
+    0x00, 0x00, 0x00, 0x7C, 0x42, 0x42, 0x42, 0x7C, 0x50, 0x48, 0x44, 0x42, 0x00, 0x00, 0x00, 0x00, // R
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x40, 0x40, 0x3C, 0x02, 0x02, 0x42, 0x3C, 0x00, 0x00, 0x00, 0x00, // S
+    0x00, 0x00, 0x00, 0xFE, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x00, 0x00, 0x00, 0x00, // T
+    0x00, 0x00, 0x00, 0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x3C, 0x00, 0x00, 0x00, 0x00, // U
+    0x00, 0x00, 0x00, 0x82, 0x82, 0x44, 0x44, 0x44, 0x28, 0x28, 0x28, 0x10, 0x00, 0x00, 0x00, 0x00, // V
+    0x00, 0x00, 0x00, 0x82, 0x82, 0x82, 0x82, 0x92, 0x92, 0x92, 0xAA, 0x44, 0x00, 0x00, 0x00, 0x00, // W
+    0x00, 0x00, 0x00, 0x82, 0x82, 0x44, 0x28, 0x10, 0x28, 0x44, 0x82, 0x82, 0x00, 0x00, 0x00, 0x00, // X
+    0x00, 0x00, 0x00, 0x82, 0x82, 0x44, 0x28, 0x10, 0x10, 0x10, 0x10, 0x10, 0x00, 0x00, 0x00, 0x00, // Y
+    0x00, 0x00, 0x00, 0x7E, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x40, 0x7E, 0x00, 0x00, 0x00, 0x00, // Z
+    0x00, 0x00, 0x00, 0x3C, 0x20, 0x20, 0x20, 0x20, 0x20, 0x20, 0x20, 0x3C, 0x00, 0x00, 0x00, 0x00, // [
+    0x00, 0x00, 0x00, 0x80, 0x80, 0x40, 0x20, 0x10, 0x08, 0x04, 0x02, 0x02, 0x00, 0x00, 0x00, 0x00, // backslash
+    0x00, 0x00, 0x00, 0x78, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08, 0x78, 0x00, 0x00, 0x00, 0x00, // ]
+    0x00, 0x00, 0x00, 0x10, 0x28, 0x44, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // ^
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFE, 0x00, 0x00, 0x00, // _
+    0x00, 0x00, 0x10, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // `
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3C, 0x02, 0x3E, 0x42, 0x46, 0x3A, 0x00, 0x00, 0x00, 0x00, // a
+    0x00, 0x00, 0x00, 0x40, 0x40, 0x40, 0x5C, 0x62, 0x42, 0x42, 0x62, 0x5C, 0x00, 0x00, 0x00, 0x00, // b
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3C, 0x42, 0x40, 0x40, 0x42, 0x3C, 0x00, 0x00, 0x00, 0x00, // c
+    0x00, 0x00, 0x00, 0x02, 0x02, 0x02, 0x3A, 0x46, 0x42, 0x42, 0x46, 0x3A, 0x00, 0x00, 0x00, 0x00, // d
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3C, 0x42, 0x7E, 0x40, 0x42, 0x3C, 0x00, 0x00, 0x00, 0x00, // e
+    0x00, 0x00, 0x00, 0x1C, 0x22, 0x20, 0x20, 0x7C, 0x20, 0x20, 0x20, 0x20, 0x00, 0x00, 0x00, 0x00, // f
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3A, 0x44, 0x44, 0x38, 0x40, 0x3C, 0x42, 0x3C, 0x00, 0x00, // g
+    0x00, 0x00, 0x00, 0x40, 0x40, 0x40, 0x5C, 0x62, 0x42, 0x42, 0x42, 0x42, 0x00, 0x00, 0x00, 0x00, // h
+    0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x30, 0x10, 0x10, 0x10, 0x10, 0x7C, 0x00, 0x00, 0x00, 0x00, // i
+    0x00, 0x00, 0x00, 0x00, 0x04, 0x00, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x44, 0x44, 0x38, 0x00, 0x00, // j
+    0x00, 0x00, 0x00, 0x40, 0x40, 0x40, 0x44, 0x48, 0x70, 0x48, 0x44, 0x42, 0x00, 0x00, 0x00, 0x00, // k
+    0x00, 0x00, 0x00, 0x30, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x7C, 0x00, 0x00, 0x00, 0x00, // l
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xEC, 0x92, 0x92, 0x92, 0x92, 0x82, 0x00, 0x00, 0x00, 0x00, // m
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x5C, 0x62, 0x42, 0x42, 0x42, 0x42, 0x00, 0x00, 0x00, 0x00, // n
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3C, 0x42, 0x42, 0x42, 0x42, 0x3C, 0x00, 0x00, 0x00, 0x00, // o
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x5C, 0x62, 0x42, 0x62, 0x5C, 0x40, 0x40, 0x40, 0x00, 0x00, // p
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3A, 0x46, 0x42, 0x46, 0x3A, 0x02, 0x02, 0x02, 0x00, 0x00, // q
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x5C, 0x22, 0x20, 0x20, 0x20, 0x20, 0x00, 0x00, 0x00, 0x00, // r
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3C, 0x42, 0x30, 0x0C, 0x42, 0x3C, 0x00, 0x00, 0x00, 0x00, // s
+    0x00, 0x00, 0x00, 0x00, 0x20, 0x20, 0x7C, 0x20, 0x20, 0x20, 0x22, 0x1C, 0x00, 0x00, 0x00, 0x00, // t
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x44, 0x44, 0x44, 0x44, 0x44, 0x3A, 0x00, 0x00, 0x00, 0x00, // u
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x44, 0x44, 0x44, 0x28, 0x28, 0x10, 0x00, 0x00, 0x00, 0x00, // v
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x82, 0x82, 0x92, 0x92, 0xAA, 0x44, 0x00, 0x00, 0x00, 0x00, // w
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x42, 0x24, 0x18, 0x18, 0x24, 0x42, 0x00, 0x00, 0x00, 0x00, // x
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x42, 0x42, 0x42, 0x46, 0x3A, 0x02, 0x42, 0x3C, 0x00, 0x00, // y
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x7E, 0x04, 0x08, 0x10, 0x20, 0x7E, 0x00, 0x00, 0x00, 0x00, // z
+    0x00, 0x00, 0x00, 0x0E, 0x10, 0x10, 0x08, 0x30, 0x08, 0x10, 0x10, 0x0E, 0x00, 0x00, 0x00, 0x00, // {
+    0x00, 0x00, 0x00, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x00, 0x00, 0x00, 0x00, // |
+    0x00, 0x00, 0x00, 0x70, 0x08, 0x08, 0x10, 0x0C, 0x10, 0x08, 0x08, 0x70, 0x00, 0x00, 0x00, 0x00, // }
+    0x00, 0x00, 0x00, 0x24, 0x54, 0x48, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // ~
+];
@@ -0,0 +1,170 @@
+// rough F1/F2 formant tracker. the mono input is decimated to ~12 kHz, pre-emphasized and
+// cut into 256 sample hamming windows (50% overlap), then each window gets a 14th order LPC
+// fit. the two lowest resonances of the LPC envelope in the usual formant ranges are F1 and
+// F2. good enough to tell vowels apart, not a phonetics tool.
+
+use crate::polar;
+
+// formant frequencies in Hz
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Formants {
+    pub f1: f32,
+    pub f2: f32,
+}
+
+impl Formants {
+    // vowel chart corners: close front "ee" is ~(270, 2300), open back "ah" is ~(850, 900)
+    const F1_RANGE: (f32, f32) = (250.0, 900.0);
+    const F2_RANGE: (f32, f32) = (700.0, 2500.0);
+
+    // position on a vowel chart, both 0-1: x runs front (0) to back (1), y close (0) to open (1)
+    pub fn chart_position(&self) -> (f32, f32) {
+        let unit = |v: f32, (lo, hi): (f32, f32)| ((libm::logf(v) - libm::logf(lo)) / (libm::logf(hi) - libm::logf(lo))).clamp(0.0, 1.0);
+        (1.0 - unit(self.f2, Self::F2_RANGE), unit(self.f1, Self::F1_RANGE))
+    }
+}
+
+const WINDOW: usize = 256;
+const HOP: usize = WINDOW / 2;
+const ORDER: usize = 14;
+const SPECTRUM_BINS: usize = 96; // LPC envelope sampled up to MAX_FREQ
+const MAX_FREQ: f32 = 3600.0;
+
+pub struct FormantTracker {
+    decimation: usize,
+    rate: f32, // after decimation
+    sum: f32,  // boxcar for the decimator
+    summed: usize,
+    last_input: f32, // pre-emphasis state
+    ring: [f32; WINDOW],
+    pos: usize,
+    since_hop: usize,
+    hamming: [f32; WINDOW],
+    latest: Option<Formants>,
+}
+
+impl FormantTracker {
+    const TARGET_RATE: f32 = 12000.0;
+    const PRE_EMPHASIS: f32 = 0.7;
+    const SILENCE_RMS: f32 = 0.01;
+    const F1_SEARCH: (f32, f32) = (200.0, 1100.0);
+    const F2_SEARCH: (f32, f32) = (600.0, 3200.0);
+    const MIN_SPACING: f32 = 200.0; // between F1 and F2
+
+    pub fn new(sample_rate: f32) -> Self {
+        let decimation = libm::roundf(sample_rate / Self::TARGET_RATE).max(1.0) as usize;
+        Self {
+            decimation,
+            rate: sample_rate / decimation as f32,
+            sum: 0.0,
+            summed: 0,
+            last_input: 0.0,
+            ring: [0.0; WINDOW],
+            pos: 0,
+            since_hop: 0,
+            hamming: core::array::from_fn(|i| 0.54 - 0.46 * libm::cosf(core::f32::consts::TAU * i as f32 / (WINDOW - 1) as f32)),
+            latest: None,
+        }
+    }
+
+    // latest estimate, None during silence or when no clear resonances were found
+    pub fn formants(&self) -> Option<Formants> {
+        self.latest
+    }
+
+    // feed one input sample, returns true when a new estimate was made
+    pub fn process(&mut self, sample: f32) -> bool {
+        self.sum += sample;
+        self.summed += 1;
+        if self.summed < self.decimation {
+            return false;
+        }
+        let decimated = self.sum / self.decimation as f32;
+        self.sum = 0.0;
+        self.summed = 0;
+
+        self.ring[self.pos] = decimated - Self::PRE_EMPHASIS * self.last_input;
+        self.last_input = decimated;
+        self.pos = (self.pos + 1) % WINDOW;
+        self.since_hop += 1;
+        if self.since_hop < HOP {
+            return false;
+        }
+        self.since_hop = 0;
+        self.latest = self.analyze();
+        true
+    }
+
+    fn analyze(&self) -> Option<Formants> {
+        // oldest sample first
+        let mut frame: [f32; WINDOW] = core::array::from_fn(|i| self.ring[(self.pos + i) % WINDOW]);
+        let mean_square = frame.iter().map(|s| s * s).sum::<f32>() / WINDOW as f32;
+        if mean_square < Self::SILENCE_RMS * Self::SILENCE_RMS {
+            return None;
+        }
+        for (s, w) in frame.iter_mut().zip(&self.hamming) {
+            *s *= w;
+        }
+
+        let mut r = [0.0f32; ORDER + 1];
+        for (lag, r) in r.iter_mut().enumerate() {
+            *r = frame[lag..].iter().zip(&frame).map(|(a, b)| a * b).sum();
+        }
+        r[0] *= 1.0001; // a little white noise keeps the recursion stable
+        let a = levinson(&r)?;
+
+        // LPC envelope 1/|A(e^jw)|^2, sampled on a uniform grid
+        let bin_hz = MAX_FREQ / SPECTRUM_BINS as f32;
+        let envelope: [f32; SPECTRUM_BINS] = core::array::from_fn(|bin| {
+            let w = core::f32::consts::TAU * bin as f32 * bin_hz / self.rate;
+            let (step_re, step_im) = (polar::cos(w), -polar::sin(w));
+            let (mut z_re, mut z_im, mut re, mut im) = (1.0f32, 0.0f32, 0.0f32, 0.0f32);
+            for &coeff in &a {
+                re += coeff * z_re;
+                im += coeff * z_im;
+                (z_re, z_im) = (z_re * step_re - z_im * step_im, z_re * step_im + z_im * step_re);
+            }
+            1.0 / (re * re + im * im).max(1e-12)
+        });
+
+        // local maxima, refined with a parabola through the log envelope
+        let mut peaks = [0.0f32; 8];
+        let mut count = 0;
+        for bin in 1..SPECTRUM_BINS - 1 {
+            let (l, c, r) = (envelope[bin - 1], envelope[bin], envelope[bin + 1]);
+            if c > l && c >= r && count < peaks.len() {
+                let (l, c, r) = (libm::logf(l), libm::logf(c), libm::logf(r));
+                let offset = 0.5 * (l - r) / (l - 2.0 * c + r);
+                peaks[count] = (bin as f32 + offset.clamp(-0.5, 0.5)) * bin_hz;
+                count += 1;
+            }
+        }
+        let peaks = &peaks[..count];
+
+        let in_range = |f: f32, (lo, hi): (f32, f32)| f >= lo && f <= hi;
+        let f1 = *peaks.iter().find(|&&f| in_range(f, Self::F1_SEARCH))?;
+        let f2 = *peaks.iter().find(|&&f| f >= f1 + Self::MIN_SPACING && in_range(f, Self::F2_SEARCH))?;
+        Some(Formants { f1, f2 })
+    }
+}
+
+// levinson-durbin recursion, LPC coefficients with a[0] = 1. None if the input isn't positive definite
+fn levinson(r: &[f32; ORDER + 1]) -> Option<[f32; ORDER + 1]> {
+    let mut a = [0.0f32; ORDER + 1];
+    a[0] = 1.0;
+    let mut error = r[0];
+    for i in 1..=ORDER {
+        if error <= 0.0 {
+            return None;
+        }
+        let acc: f32 = (0..i).map(|j| a[j] * r[i - j]).sum();
+        let k = -acc / error;
+        let previous = a;
+        for j in 1..i {
+            a[j] = previous[j] + k * previous[i - j];
+        }
+        a[i] = k;
+        error *= 1.0 - k * k;
+    }
+    Some(a)
+}
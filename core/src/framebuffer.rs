@@ -0,0 +1,143 @@
+use crate::error::UiError;
+use crate::{Color, DISPLAY_SIZE};
+
+pub const FRAMEBUFFER_LEN: usize = DISPLAY_SIZE * DISPLAY_SIZE;
+
+// full-resolution RGB888 frame, converted to RGB565 (panel) or ARGB32 (simulator) on flush
+pub struct Framebuffer {
+    pixels: [Color; FRAMEBUFFER_LEN],
+}
+
+impl Framebuffer {
+    pub const WIDTH: usize = DISPLAY_SIZE;
+    pub const HEIGHT: usize = DISPLAY_SIZE;
+
+    pub fn new() -> Self {
+        Self { pixels: [Color::default(); FRAMEBUFFER_LEN] }
+    }
+
+    pub fn clear(&mut self, color: Color) {
+        self.pixels.fill(color);
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * Self::WIDTH + x]
+    }
+
+    // out-of-bounds writes are ignored so drawing code doesn't have to clip
+    pub fn set(&mut self, x: usize, y: usize, color: Color) {
+        if x < Self::WIDTH && y < Self::HEIGHT {
+            self.pixels[y * Self::WIDTH + x] = color;
+        }
+    }
+
+    pub fn set_i32(&mut self, x: i32, y: i32, color: Color) {
+        if x >= 0 && y >= 0 {
+            self.set(x as usize, y as usize, color);
+        }
+    }
+
+    // saturating additive blend, what the glowing modes want
+    pub fn add(&mut self, x: usize, y: usize, color: Color) {
+        if x < Self::WIDTH && y < Self::HEIGHT {
+            let p = &mut self.pixels[y * Self::WIDTH + x];
+            *p = Color::new(p.r.saturating_add(color.r), p.g.saturating_add(color.g), p.b.saturating_add(color.b));
+        }
+    }
+
+    // plain alpha blend, alpha 0-255
+    pub fn blend(&mut self, x: usize, y: usize, color: Color, alpha: u8) {
+        if x < Self::WIDTH && y < Self::HEIGHT {
+            let p = &mut self.pixels[y * Self::WIDTH + x];
+            *p = Color::lerp(*p, color, alpha as f32 / 255.0);
+        }
+    }
+
+    pub fn pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [Color] {
+        &mut self.pixels
+    }
+
+    pub fn row(&self, y: usize) -> &[Color] {
+        &self.pixels[y * Self::WIDTH..(y + 1) * Self::WIDTH]
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> &mut [Color] {
+        &mut self.pixels[y * Self::WIDTH..(y + 1) * Self::WIDTH]
+    }
+
+    pub fn write_rgb565(&self, out: &mut [u16]) {
+        for (o, p) in out.iter_mut().zip(self.pixels.iter()) {
+            *o = p.to_rgb565();
+        }
+    }
+
+    pub fn write_argb32(&self, out: &mut [u32]) {
+        for (o, p) in out.iter_mut().zip(self.pixels.iter()) {
+            *o = p.to_argb32();
+        }
+    }
+}
+
+impl Default for Framebuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// two panel-format output buffers in caller memory (DMA-capable on the badge): the post chain
+// flushes the next frame into the back one while the front one streams to the panel. the
+// framebuffer above stays the single drawing surface, trails keep reading the previous frame
+pub struct DoubleBuffer<'a, T> {
+    buffers: [&'a mut [T]; 2],
+    front: usize,
+    streaming: bool, // the front buffer was handed out and its transfer isn't done
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwapError {
+    Busy, // the previous front buffer is still streaming, swapping now would tear
+}
+
+impl<'a, T> DoubleBuffer<'a, T> {
+    // both halves need FRAMEBUFFER_LEN pixels, more is ignored
+    pub fn new(first: &'a mut [T], second: &'a mut [T]) -> Result<Self, UiError> {
+        let got = first.len().min(second.len());
+        if got < FRAMEBUFFER_LEN {
+            return Err(UiError::BufferTooSmall { needed: FRAMEBUFFER_LEN, got });
+        }
+        Ok(Self { buffers: [first, second], front: 0, streaming: false })
+    }
+
+    // where the next frame goes, never the buffer that's streaming
+    pub fn back_mut(&mut self) -> &mut [T] {
+        &mut self.buffers[1 - self.front][..FRAMEBUFFER_LEN]
+    }
+
+    // the latest finished frame
+    pub fn front(&self) -> &[T] {
+        &self.buffers[self.front][..FRAMEBUFFER_LEN]
+    }
+
+    // the back buffer becomes the front one, returned for the panel transfer. fails while the
+    // previous transfer runs, call transfer_done() when the DMA completes
+    pub fn swap(&mut self) -> Result<&[T], SwapError> {
+        if self.streaming {
+            return Err(SwapError::Busy);
+        }
+        self.front = 1 - self.front;
+        self.streaming = true;
+        Ok(self.front())
+    }
+
+    pub fn transfer_done(&mut self) {
+        self.streaming = false;
+    }
+
+    pub fn is_streaming(&self) -> bool {
+        self.streaming
+    }
+}
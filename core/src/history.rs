@@ -0,0 +1,215 @@
+// the last N frames of every band, with the rolling statistics adaptive normalization and the
+// history-based modes need. min and max are O(1): per band a monotonic queue keeps the frames
+// that can still become the extreme of the window, so pushing is amortized O(1) and the answer
+// is always at the front. percentiles sort a copy of the window.
+
+use crate::CHANNELS;
+
+// frame numbers wrap, ages are taken with wrapping_sub so that only matters after 2^32 frames
+#[derive(Clone, Copy)]
+struct MonoQueue<const N: usize> {
+    frames: [u32; N], // ring, front is the oldest frame still in the running
+    front: usize,
+    len: usize,
+}
+
+impl<const N: usize> MonoQueue<N> {
+    const fn new() -> Self {
+        Self { frames: [0; N], front: 0, len: 0 }
+    }
+
+    fn front(&self) -> Option<u32> {
+        (self.len > 0).then(|| self.frames[self.front])
+    }
+
+    fn back(&self) -> Option<u32> {
+        (self.len > 0).then(|| self.frames[(self.front + self.len - 1) % N])
+    }
+
+    fn push_back(&mut self, frame: u32) {
+        self.frames[(self.front + self.len) % N] = frame;
+        self.len += 1;
+    }
+
+    fn pop_back(&mut self) {
+        self.len -= 1;
+    }
+
+    fn pop_front(&mut self) {
+        self.front = (self.front + 1) % N;
+        self.len -= 1;
+    }
+
+    fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+pub struct BandHistory<const N: usize> {
+    frames: [[f32; CHANNELS]; N], // ring indexed by frame number
+    pushed: u32,                  // frame number of the next push
+    len: usize,                   // frames stored, up to N
+    window: usize,                // frames the statistics cover, 1..=N
+    num_channels: usize,
+    max: [MonoQueue<N>; CHANNELS], // values falling front to back
+    min: [MonoQueue<N>; CHANNELS], // values rising front to back
+}
+
+impl<const N: usize> BandHistory<N> {
+    pub const fn new(num_channels: usize) -> Self {
+        const { assert!(N > 0, "BandHistory needs room for a frame") };
+        Self {
+            frames: [[0.0; CHANNELS]; N],
+            pushed: 0,
+            len: 0,
+            window: N,
+            num_channels: if num_channels < CHANNELS { num_channels } else { CHANNELS },
+            max: [MonoQueue::new(); CHANNELS],
+            min: [MonoQueue::new(); CHANNELS],
+        }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn num_channels(&self) -> usize {
+        self.num_channels
+    }
+
+    // frames the statistics look back over, clamped to 1..=N. the frames stay stored either way,
+    // a longer window takes the older ones back in
+    pub fn set_window(&mut self, window: usize) {
+        let window = window.clamp(1, N);
+        if window == self.window {
+            return;
+        }
+        self.window = window;
+        for band in 0..self.num_channels {
+            self.max[band].clear();
+            self.min[band].clear();
+            for age in (0..self.window_len()).rev() {
+                self.enqueue(self.pushed.wrapping_sub(age as u32 + 1), band);
+            }
+        }
+    }
+
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    // frames stored, at most N
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // frames in the window so far
+    pub fn window_len(&self) -> usize {
+        self.len.min(self.window)
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+        for queue in self.max.iter_mut().chain(self.min.iter_mut()) {
+            queue.clear();
+        }
+    }
+
+    // a new frame of band values, missing bands read 0 and extra ones are dropped
+    pub fn push(&mut self, bands: &[f32]) {
+        let frame = self.pushed;
+        let slot = &mut self.frames[frame as usize % N];
+        for (band, value) in slot[..self.num_channels].iter_mut().enumerate() {
+            *value = bands.get(band).copied().unwrap_or(0.0);
+        }
+        self.pushed = frame.wrapping_add(1);
+        self.len = (self.len + 1).min(N);
+
+        for band in 0..self.num_channels {
+            // first, so the queues have room and don't hold the slot just overwritten
+            self.expire(band);
+            self.enqueue(frame, band);
+        }
+    }
+
+    fn enqueue(&mut self, frame: u32, band: usize) {
+        let value = self.value(frame, band);
+        // a newer frame at least as extreme means the older ones can't win anymore
+        while self.max[band].back().is_some_and(|f| self.value(f, band) <= value) {
+            self.max[band].pop_back();
+        }
+        self.max[band].push_back(frame);
+        while self.min[band].back().is_some_and(|f| self.value(f, band) >= value) {
+            self.min[band].pop_back();
+        }
+        self.min[band].push_back(frame);
+    }
+
+    fn value(&self, frame: u32, band: usize) -> f32 {
+        self.frames[frame as usize % N][band]
+    }
+
+    // frames since `frame` was pushed, 0 for the newest
+    fn age(&self, frame: u32) -> usize {
+        self.pushed.wrapping_sub(frame).wrapping_sub(1) as usize
+    }
+
+    fn expire(&mut self, band: usize) {
+        while self.max[band].front().is_some_and(|f| self.age(f) >= self.window) {
+            self.max[band].pop_front();
+        }
+        while self.min[band].front().is_some_and(|f| self.age(f) >= self.window) {
+            self.min[band].pop_front();
+        }
+    }
+
+    // a band `age` frames back, 0 the newest. None past what's stored
+    pub fn get(&self, age: usize, band: usize) -> Option<f32> {
+        if age >= self.len || band >= self.num_channels {
+            return None;
+        }
+        Some(self.value(self.pushed.wrapping_sub(age as u32 + 1), band))
+    }
+
+    // every band of the frame `age` frames back
+    pub fn frame(&self, age: usize) -> Option<&[f32]> {
+        if age >= self.len {
+            return None;
+        }
+        let frame = self.pushed.wrapping_sub(age as u32 + 1);
+        Some(&self.frames[frame as usize % N][..self.num_channels])
+    }
+
+    pub fn latest(&self, band: usize) -> Option<f32> {
+        self.get(0, band)
+    }
+
+    // loudest value of a band in the window
+    pub fn max(&self, band: usize) -> Option<f32> {
+        self.max.get(band)?.front().map(|f| self.value(f, band))
+    }
+
+    // quietest value of a band in the window
+    pub fn min(&self, band: usize) -> Option<f32> {
+        self.min.get(band)?.front().map(|f| self.value(f, band))
+    }
+
+    // value a share `p` (0-1) of the window lies below, nearest rank. 0.5 is the median
+    pub fn percentile(&self, band: usize, p: f32) -> Option<f32> {
+        let len = self.window_len();
+        if len == 0 || band >= self.num_channels {
+            return None;
+        }
+        let mut sorted = [0.0f32; N];
+        for (age, value) in sorted[..len].iter_mut().enumerate() {
+            *value = self.value(self.pushed.wrapping_sub(age as u32 + 1), band);
+        }
+        let rank = libm::roundf(p.clamp(0.0, 1.0) * (len - 1) as f32) as usize;
+        let (_, value, _) = sorted[..len].select_nth_unstable_by(rank, f32::total_cmp);
+        Some(*value)
+    }
+}
@@ -0,0 +1,6 @@
+// sprites that ship with the UI, generated by build.rs from core/assets/*.png (the const is
+// the file name in caps). the sources are white on transparent, draw them with blit_tinted
+
+use crate::sprite::{Sprite, SpriteAlpha};
+
+include!(concat!(env!("OUT_DIR"), "/sprites.rs"));
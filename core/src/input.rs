@@ -0,0 +1,134 @@
+// rotary encoder conditioning: debounce, dead-zone, detent accumulation and acceleration
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
+pub enum AccelCurve {
+    Off,
+    Linear,
+    Quadratic,
+}
+
+// encoder tuning, lives with the rest of the user settings
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default, deny_unknown_fields))]
+pub struct EncoderSettings {
+    pub counts_per_detent: u8,   // raw quadrature counts per physical click (usually 4, cheap ones 2)
+    pub dead_zone: u8,           // counts swallowed after a direction change
+    pub debounce_ms: u16,        // reversals closer together than this are treated as bounce
+    pub accel_curve: AccelCurve,
+    pub accel_window_ms: u16,    // detents closer together than this count as a fast spin
+    pub accel_max: u8,           // step multiplier at full speed
+}
+
+impl Default for EncoderSettings {
+    fn default() -> Self {
+        Self {
+            counts_per_detent: 4,
+            dead_zone: 1,
+            debounce_ms: 3,
+            accel_curve: AccelCurve::Quadratic,
+            accel_window_ms: 120,
+            accel_max: 5,
+        }
+    }
+}
+
+pub struct Encoder {
+    settings: EncoderSettings,
+    accum: i32,
+    direction: i32,
+    dead_remaining: u8,
+    last_count_ms: Option<u32>,
+    last_detent_ms: Option<u32>,
+}
+
+impl Encoder {
+    pub fn new(settings: EncoderSettings) -> Self {
+        Self {
+            settings,
+            accum: 0,
+            direction: 0,
+            dead_remaining: 0,
+            last_count_ms: None,
+            last_detent_ms: None,
+        }
+    }
+
+    pub fn settings(&self) -> &EncoderSettings {
+        &self.settings
+    }
+
+    pub fn set_settings(&mut self, settings: EncoderSettings) {
+        self.settings = settings;
+        self.reset();
+    }
+
+    pub fn reset(&mut self) {
+        self.accum = 0;
+        self.direction = 0;
+        self.dead_remaining = 0;
+        self.last_count_ms = None;
+        self.last_detent_ms = None;
+    }
+
+    // feed raw counts read from the encoder since the last call, returns menu steps (signed)
+    pub fn feed(&mut self, raw_delta: i32, now_ms: u32) -> i32 {
+        if raw_delta == 0 {
+            return 0;
+        }
+        let dir = raw_delta.signum();
+
+        if dir != self.direction {
+            // a reversal right after a count is contact bounce, not the user
+            let bounced = self.last_count_ms
+                .is_some_and(|t| now_ms.wrapping_sub(t) < self.settings.debounce_ms as u32);
+            if bounced && self.direction != 0 {
+                return 0;
+            }
+            self.direction = dir;
+            self.accum = 0;
+            self.dead_remaining = self.settings.dead_zone;
+            self.last_detent_ms = None;
+        }
+        self.last_count_ms = Some(now_ms);
+
+        let mut counts = raw_delta.abs();
+        let swallowed = counts.min(self.dead_remaining as i32);
+        self.dead_remaining -= swallowed as u8;
+        counts -= swallowed;
+
+        self.accum += counts;
+        let per_detent = self.settings.counts_per_detent.max(1) as i32;
+        let detents = self.accum / per_detent;
+        self.accum %= per_detent;
+        if detents == 0 {
+            return 0;
+        }
+
+        let multiplier = self.multiplier(now_ms);
+        self.last_detent_ms = Some(now_ms);
+        dir * detents * multiplier
+    }
+
+    fn multiplier(&self, now_ms: u32) -> i32 {
+        let window = self.settings.accel_window_ms as f32;
+        let max = self.settings.accel_max.max(1) as f32;
+        let Some(last) = self.last_detent_ms else { return 1 };
+        if self.settings.accel_curve == AccelCurve::Off || window <= 0.0 {
+            return 1;
+        }
+
+        let interval = now_ms.wrapping_sub(last) as f32;
+        if interval >= window {
+            return 1;
+        }
+        // 0 at the edge of the window, 1 for back-to-back detents
+        let speed = 1.0 - interval / window;
+        let shaped = match self.settings.accel_curve {
+            AccelCurve::Off => 0.0,
+            AccelCurve::Linear => speed,
+            AccelCurve::Quadratic => speed * speed,
+        };
+        (1.0 + (max - 1.0) * shaped + 0.5) as i32
+    }
+}
@@ -0,0 +1,110 @@
+// kaleidoscope: the mode is rendered into a single wedge of the round panel, which is then
+// copied around it with N-fold rotational symmetry, optionally mirrored so neighbouring
+// wedges meet edge to edge. only 1/N (1/2N mirrored) of the pixels are generated, the copy
+// costs one atan2 and a 2x2 matrix per pixel.
+// angles run clockwise from 12 o'clock, the source wedge starts there.
+
+use core::f32::consts::{FRAC_PI_2, TAU};
+
+use crate::framebuffer::Framebuffer;
+use crate::region::{RenderRegion, DISPLAY_REGION};
+use crate::{polar, DISPLAY_CENTER, DISPLAY_SIZE};
+
+pub const MIN_FOLDS: u8 = 2;
+pub const MAX_FOLDS: u8 = 12;
+
+// the rendered wedge reaches this many pixels past its edges, so copies taken on the
+// PowerProfile::Saver grid never land on unrendered pixels
+const EDGE_PAD: f32 = 3.0;
+
+pub struct Kaleidoscope {
+    folds: u8,
+    mirror: bool,
+    region: RenderRegion,
+    rotations: [[f32; 4]; MAX_FOLDS as usize], // wedge k back onto the source wedge
+    reflection: [f32; 4],                      // across the source wedge's center line
+}
+
+impl Kaleidoscope {
+    // None outside MIN_FOLDS..=MAX_FOLDS
+    pub fn new(folds: u8, mirror: bool) -> Option<Self> {
+        if !(MIN_FOLDS..=MAX_FOLDS).contains(&folds) {
+            return None;
+        }
+        let sweep = TAU / folds as f32;
+        let source_sweep = if mirror { sweep / 2.0 } else { sweep };
+        let region = RenderRegion::covering(|x, y| {
+            let (angle, radius) = turn_of(x, y);
+            let pad = EDGE_PAD / radius.max(1.0);
+            angle <= source_sweep + pad || angle >= TAU - pad
+        });
+        let rotations = core::array::from_fn(|k| rotation(-(k as f32) * sweep));
+        // a reflection across the line at screen angle a is [cos 2a, sin 2a; sin 2a, -cos 2a]
+        let axis = 2.0 * (sweep / 2.0 - FRAC_PI_2);
+        let (s, c) = (polar::sin(axis), polar::cos(axis));
+        Some(Self { folds, mirror, region, rotations, reflection: [c, s, s, -c] })
+    }
+
+    pub fn folds(&self) -> u8 {
+        self.folds
+    }
+
+    pub fn mirror(&self) -> bool {
+        self.mirror
+    }
+
+    // the pixels the mode has to render
+    pub fn region(&self) -> &RenderRegion {
+        &self.region
+    }
+
+    // copies the source wedge over the rest of the panel, on the grid of `step` the frame
+    // was rendered on (PostChain::set_pixel_step)
+    pub fn replicate(&self, fb: &mut Framebuffer, step: usize) {
+        let step = step.max(1);
+        let sweep = TAU / self.folds as f32;
+        let rim = DISPLAY_CENTER - step as f32;
+        for (y, columns) in DISPLAY_REGION.grid(0..DISPLAY_SIZE, step) {
+            for x in columns {
+                let (angle, _) = turn_of(x, y);
+                let k = ((angle / sweep) as usize).min(self.folds as usize - 1);
+                let reflect = self.mirror && angle - k as f32 * sweep > sweep / 2.0;
+                if k == 0 && !reflect {
+                    continue;
+                }
+                let (mut dx, mut dy) = apply(&self.rotations[k], x as f32 - DISPLAY_CENTER, y as f32 - DISPLAY_CENTER);
+                if reflect {
+                    (dx, dy) = apply(&self.reflection, dx, dy);
+                }
+                // rounding can carry a rim pixel's source off the panel
+                let r2 = dx * dx + dy * dy;
+                if r2 > rim * rim {
+                    let pull = rim / libm::sqrtf(r2);
+                    (dx, dy) = (dx * pull, dy * pull);
+                }
+                let snap = |v: f32| {
+                    let v = libm::roundf((DISPLAY_CENTER + v) / step as f32) as usize * step;
+                    v.min(DISPLAY_SIZE - step)
+                };
+                let color = fb.get(snap(dx), snap(dy));
+                fb.set(x, y, color);
+            }
+        }
+    }
+}
+
+// angle clockwise from 12 o'clock (0..TAU) and radius of a pixel
+fn turn_of(x: usize, y: usize) -> (f32, f32) {
+    let (angle, radius) = polar::to_polar(x as f32, y as f32);
+    (polar::wrap_angle(angle + FRAC_PI_2), radius)
+}
+
+// row-major 2x2, clockwise on screen
+fn rotation(angle: f32) -> [f32; 4] {
+    let (s, c) = (polar::sin(angle), polar::cos(angle));
+    [c, -s, s, c]
+}
+
+fn apply(m: &[f32; 4], x: f32, y: f32) -> (f32, f32) {
+    (m[0] * x + m[1] * y, m[2] * x + m[3] * y)
+}
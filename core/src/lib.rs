@@ -1,5 +1,95 @@
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 pub mod vis;
-pub use vis::{Visualizer, ModeKind};
+pub mod boot;
+pub mod source;
+pub mod smoothing;
+pub mod spsc;
+pub mod input;
+pub mod events;
+pub mod menu;
+pub mod overlay;
+pub mod settings;
+pub mod text_input;
+pub mod region;
+pub mod plosive;
+pub mod framebuffer;
+pub mod font;
+pub mod text;
+pub mod draw;
+pub mod polar;
+pub mod procedural;
+pub mod mirror;
+pub mod split;
+pub mod formant;
+pub mod vowel;
+pub mod postfx;
+pub mod preset;
+pub mod param;
+pub mod calibrate;
+pub mod sprite;
+pub mod icons;
+pub mod power;
+pub mod clock;
+pub mod toast;
+pub mod surface;
+pub mod kaleido;
+pub mod history;
+pub mod onset;
+pub mod ripple;
+pub mod flower;
+pub mod particle;
+pub mod starfield;
+pub mod response;
+pub mod registry;
+pub mod error;
+#[cfg(feature = "serde")]
+mod serde_impls;
+#[cfg(feature = "postcard")]
+pub mod persist;
+pub mod store;
+pub use vis::{Visualizer, ModeKind, VisualMode};
+pub use boot::BootAnimation;
+pub use source::{AnalysisFrame, ChannelCount, ChannelCountError, EnergySource, FrameDecodeError, CLIP_LEVEL, WAVEFORM_LEN};
+pub use smoothing::{BandSmoothing, EnergySmoother};
+pub use spsc::{RingConsumer, RingProducer, SpscRing, TripleBuffer, TripleReader, TripleWriter};
+pub use input::{AccelCurve, Encoder, EncoderSettings};
+pub use events::{Button, InputEvent, InputHandler, PressDetector, UiAction};
+pub use menu::{Menu, MenuEffect, MenuItem, MenuTarget};
+pub use overlay::{BatteryGauge, ClipIndicator, LoudnessGauge, LoudnessTarget, LoudnessZone, MicMeter, PitchTarget, PitchTrainer, PitchZone, StatusInputs, StatusOverlay, Widget};
+pub use settings::{Detector, DspSettings, Filterbank, Settings, StartupPolicy, UserTheme, VocoderConfig};
+pub use text_input::{Name, TextInput, TextInputState, WheelEntry};
+pub use region::{RenderRegion, DISPLAY_REGION};
+pub use plosive::PlosiveGuard;
+pub use procedural::{Aurora, Plasma};
+pub use mirror::Mirror;
+pub use split::Split;
+pub use formant::{FormantTracker, Formants};
+pub use vowel::VowelField;
+pub use postfx::{PostChain, PostFx, MAX_POST_STAGES};
+pub use preset::{Preset, PresetBank, PRESET_SLOTS};
+pub use param::{Param, ParamBinding, ParamId, ParamMap, MAX_MODE_PARAMS, MAX_PARAM_BINDINGS};
+pub use calibrate::{BandCalibration, CalibrationError, CalibrationPhase, Calibrator};
+pub use store::{SettingsStore, StoreError, SETTINGS_BLOB_LEN};
+pub use power::PowerProfile;
+pub use clock::{ClockFace, TimeOfDay, TimeSource};
+pub use toast::{Toasts, TOAST_LEN};
+pub use surface::{LedRing, MAX_RING_LEDS};
+pub use kaleido::Kaleidoscope;
+pub use history::BandHistory;
+pub use onset::{Onset, OnsetDetector};
+pub use ripple::{Ripple, RIPPLE_GRID};
+pub use flower::Flower;
+pub use particle::{ParticlePool, Rng};
+pub use starfield::{Starfield, MAX_STARS};
+pub use response::{ResponseCurve, ResponseCurves};
+pub use registry::{ModeInfo, RegisterError, MAX_USER_MODES};
+pub use error::UiError;
+pub use framebuffer::{DoubleBuffer, Framebuffer, SwapError, FRAMEBUFFER_LEN};
+pub use sprite::{blit, blit_tinted, Sprite, SpriteAlpha};
+pub use font::{Font, FONT_5X7, FONT_8X16};
+pub use text::{TextBuf, draw_text, draw_text_with_font, draw_text_centered, text_width, centered_x, fits_centered, max_chars_at};
 
 use libm::{sinf, cosf, sqrtf, fabsf};
 
@@ -11,19 +101,13 @@ pub const DISPLAY_RADIUS: f32 = DISPLAY_CENTER - 10.0;
 // DSP config
 pub const CHANNELS: usize = 16;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct Color {
     pub r: u8,
     pub g: u8,
     pub b: u8,
 }
 
-impl Default for Color {
-    fn default() -> Self {
-        Self { r: 0, g: 0, b: 0 }
-    }
-}
-
 
 impl Color {
     pub const fn new(r: u8, g: u8, b: u8) -> Self {
@@ -38,6 +122,14 @@ impl Color {
         (r << 11) | (g << 5) | b
     }
 
+    // back from RGB565, the low bits repeat the high ones so white stays white
+    pub const fn from_rgb565(rgb: u16) -> Self {
+        let r = ((rgb >> 11) & 0x1F) as u8;
+        let g = ((rgb >> 5) & 0x3F) as u8;
+        let b = (rgb & 0x1F) as u8;
+        Self { r: (r << 3) | (r >> 2), g: (g << 2) | (g >> 4), b: (b << 3) | (b >> 2) }
+    }
+
     // use to 24bit RGB for simulator
     pub fn to_argb32(self) -> u32 {
         0xFF000000 | ((self.r as u32) << 16) | ((self.g as u32) << 8) | (self.b as u32)
@@ -90,6 +182,8 @@ impl Color {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default, deny_unknown_fields))]
 pub struct ColorPalette {
     pub colors: [Color; 16],
     pub primary: Color,
@@ -105,7 +199,7 @@ impl ColorPalette {
 
     // get a color by index
     pub fn get(&self, index: usize) -> Color {
-        self.colors[index % 16].clone()
+        self.colors[index % 16]
     }
 
     // get a color by position
@@ -114,7 +208,40 @@ impl ColorPalette {
         let idx = (t * 16.0) as usize;
         let frac = t * 16.0 - idx as f32;
         let next_idx = (idx + 1) % 16;
-        Color::lerp(self.colors[idx].clone(), self.colors[next_idx].clone(), frac)
+        Color::lerp(self.colors[idx], self.colors[next_idx], frac)
+    }
+
+    // color of one band out of `num_channels`: the lowest and highest bands get the first and
+    // last palette colors whatever the count, sample() would wrap the top bands back to the start
+    pub fn band(&self, band: usize, num_channels: usize) -> Color {
+        if num_channels < 2 {
+            return self.colors[0];
+        }
+        let pos = band.min(num_channels - 1) as f32 / (num_channels - 1) as f32 * 15.0;
+        let idx = (pos as usize).min(14);
+        Color::lerp(self.colors[idx], self.colors[idx + 1], pos - idx as f32)
+    }
+
+    // every color turned `turns` (0-1) around the hue circle, luminance kept
+    pub fn rotate_hue(&self, turns: f32) -> ColorPalette {
+        let angle = turns * core::f32::consts::TAU;
+        let (s, c) = (sinf(angle), cosf(angle));
+        let m = [
+            [0.213 + c * 0.787 - s * 0.213, 0.715 - c * 0.715 - s * 0.715, 0.072 - c * 0.072 + s * 0.928],
+            [0.213 - c * 0.213 + s * 0.143, 0.715 + c * 0.285 + s * 0.140, 0.072 - c * 0.072 - s * 0.283],
+            [0.213 - c * 0.213 - s * 0.787, 0.715 - c * 0.715 + s * 0.715, 0.072 + c * 0.928 + s * 0.072],
+        ];
+        let rotate = |color: Color| {
+            let (r, g, b) = (color.r as f32, color.g as f32, color.b as f32);
+            let channel = |row: &[f32; 3]| (row[0] * r + row[1] * g + row[2] * b + 0.5).clamp(0.0, 255.0) as u8;
+            Color::new(channel(&m[0]), channel(&m[1]), channel(&m[2]))
+        };
+        ColorPalette {
+            colors: self.colors.map(rotate),
+            primary: rotate(self.primary),
+            secondary: rotate(self.secondary),
+            accent: rotate(self.accent),
+        }
     }
 }
 
@@ -132,7 +259,7 @@ impl Default for ColorPalette {
 }
 
 pub mod palette {
-    use super::Color;
+    use super::{Color, ColorPalette};
 
     pub const PINK: Color = Color::new(255, 20, 147);
     pub const CYAN: Color = Color::new(0, 255, 255);
@@ -149,6 +276,39 @@ pub mod palette {
     pub fn rainbow(t: f32) -> Color {
         Color::from_hsv(t * 360.0, 1.0, 1.0)
     }
+
+    // ColorPalette consts generated by build.rs from core/assets/*.gpl and *.pal
+    include!(concat!(env!("OUT_DIR"), "/palettes.rs"));
+
+    // palettes offered by the menu, index 0 is the default
+    pub const BUILTIN: [&str; 6] = ["Rainbow", "Sunset", "Ocean", "Synthwave", "High contrast", "Trans"];
+
+    pub fn builtin(index: usize) -> ColorPalette {
+        match index % BUILTIN.len() {
+            1 => ramp(&[Color::new(40, 0, 80), MAGENTA, ORANGE, YELLOW], ORANGE, MAGENTA, Color::new(120, 20, 60)),
+            2 => ramp(&[Color::new(0, 20, 80), BLUE, CYAN, GREEN], CYAN, BLUE, Color::new(0, 60, 120)),
+            3 => ramp(&[PURPLE, PINK, CYAN, PURPLE], PINK, CYAN, PURPLE),
+            // white and yellow on black, still readable in direct sunlight
+            4 => ramp(&[YELLOW, WHITE, YELLOW], WHITE, YELLOW, YELLOW),
+            5 => TRANS,
+            _ => ColorPalette::default(),
+        }
+    }
+
+    // index into BUILTIN if the palette is one of them
+    pub fn builtin_index(pal: &ColorPalette) -> Option<usize> {
+        (0..BUILTIN.len()).find(|&i| builtin(i) == *pal)
+    }
+
+    // 16 colors interpolated evenly through the stops
+    fn ramp(stops: &[Color], primary: Color, secondary: Color, accent: Color) -> ColorPalette {
+        let colors = core::array::from_fn(|i| {
+            let pos = i as f32 / 15.0 * (stops.len() - 1) as f32;
+            let idx = (pos as usize).min(stops.len() - 2);
+            Color::lerp(stops[idx], stops[idx + 1], pos - idx as f32)
+        });
+        ColorPalette { colors, primary, secondary, accent }
+    }
 }
 
 
@@ -223,7 +383,7 @@ where
     loop {
         if x >= 0 && x < DISPLAY_SIZE as i32 && y >= 0 && y < DISPLAY_SIZE as i32 {
             let (ux, uy) = (x as usize, y as usize);
-            if !circular_mask || is_in_circle(ux, uy) {
+            if !circular_mask || in_display(ux, uy) {
                 set_pixel(ux, uy, color);
             }
         }
@@ -235,6 +395,7 @@ where
 }
 
 // draw a thick line with glow effect
+#[allow(clippy::too_many_arguments)]
 pub fn draw_thick_line<F>(x0: i32, y0: i32, x1: i32, y1: i32, thickness: i32, color: Color, circular_mask: bool, mut set_pixel: F)
 where
     F: FnMut(usize, usize, Color),
@@ -248,11 +409,17 @@ where
     }
 }
 
+// check if a screen point is on the round panel, integer-only so it's cheap per pixel on the MCU
+pub fn in_display(x: usize, y: usize) -> bool {
+    const C: i32 = (DISPLAY_SIZE / 2) as i32;
+    let dx = x as i32 - C;
+    let dy = y as i32 - C;
+    dx * dx + dy * dy <= C * C
+}
+
 // check if a screen point is within the display area
 pub fn is_in_circle(x: usize, y: usize) -> bool {
-    let dx = x as f32 - DISPLAY_CENTER;
-    let dy = y as f32 - DISPLAY_CENTER;
-    (dx * dx + dy * dy) <= (DISPLAY_CENTER * DISPLAY_CENTER)
+    in_display(x, y)
 }
 
 
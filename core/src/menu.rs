@@ -0,0 +1,481 @@
+// ring menu for the round display: one segment per item around the edge, the selected
+// item's name and value in the middle. drawn over the dimmed visualization and closed again
+// after a few seconds without input.
+// navigation comes from InputHandler: turn to pick an item, press to edit it, turn to change
+// the value, press or back to stop editing, back once more (or wait) to leave.
+// saving a theme swaps the value for a character wheel: turn to pick, press to add, back to
+// cancel.
+
+use core::f32::consts::{FRAC_PI_2, TAU};
+use core::fmt::Write;
+
+use crate::draw::ring_segment;
+use crate::events::UiAction;
+use crate::font::{FONT_5X7, FONT_8X16};
+use crate::framebuffer::Framebuffer;
+use crate::param::Param;
+use crate::polar::from_polar;
+use crate::power::PowerProfile;
+use crate::region::DISPLAY_REGION;
+use crate::response::ResponseCurve;
+use crate::settings::{DspSettings, Settings, StartupPolicy, UserTheme};
+use crate::text::{draw_text, draw_text_centered, text_width, TextBuf};
+use crate::text_input::{TextInput, TextInputState, WheelEntry};
+use crate::vis::Visualizer;
+use crate::{palette, ColorPalette, DISPLAY_CENTER};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MenuItem {
+    Mode,
+    Palette,
+    SaveTheme, // names the current palette and keeps it as the user theme
+    Brightness,
+    MicGain,
+    Tilt,
+    Startup,
+    Power,
+    Response, // the global energy-to-visual curve
+    ModeResponse, // the current mode's own curve, or none to follow the global one
+    ModeParams, // the current mode's own knobs, press to step through them
+}
+
+impl MenuItem {
+    pub const ALL: [MenuItem; 11] = [
+        MenuItem::Mode,
+        MenuItem::ModeParams,
+        MenuItem::Palette,
+        MenuItem::SaveTheme,
+        MenuItem::Brightness,
+        MenuItem::MicGain,
+        MenuItem::Tilt,
+        MenuItem::Response,
+        MenuItem::ModeResponse,
+        MenuItem::Startup,
+        MenuItem::Power,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            MenuItem::Mode => "Mode",
+            MenuItem::Palette => "Colors",
+            MenuItem::SaveTheme => "Save theme",
+            MenuItem::Brightness => "Brightness",
+            MenuItem::MicGain => "Mic gain",
+            MenuItem::Tilt => "EQ tilt",
+            MenuItem::Startup => "On boot",
+            MenuItem::Power => "Power",
+            MenuItem::Response => "Response",
+            MenuItem::ModeResponse => "Mode curve",
+            MenuItem::ModeParams => "Tune mode",
+        }
+    }
+
+    // label on the ring segment
+    fn short(&self) -> &'static str {
+        match self {
+            MenuItem::Mode => "MODE",
+            MenuItem::Palette => "COLOR",
+            MenuItem::SaveTheme => "SAVE",
+            MenuItem::Brightness => "LIGHT",
+            MenuItem::MicGain => "GAIN",
+            MenuItem::Tilt => "TILT",
+            MenuItem::Startup => "BOOT",
+            MenuItem::Power => "POWER",
+            MenuItem::Response => "CURVE",
+            MenuItem::ModeResponse => "MCURV",
+            MenuItem::ModeParams => "TUNE",
+        }
+    }
+}
+
+// what the menu edits, borrowed for the duration of one call
+pub struct MenuTarget<'a> {
+    pub visualizer: &'a mut Visualizer,
+    pub settings: &'a mut Settings,
+    pub dsp: &'a mut DspSettings,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MenuEffect {
+    None,
+    DspChanged, // forward the new DspSettings to the EnergySource
+    Closed,
+}
+
+// fixed gains offered after "auto" (agc)
+const GAIN_STEPS: [f32; 6] = [1.0, 2.0, 4.0, 8.0, 16.0, 32.0];
+const BRIGHTNESS_STEP: f32 = 0.1;
+const TILT_STEP: f32 = 1.0; // dB per octave
+
+const RING_INNER: f32 = 100.0;
+const RING_OUTER: f32 = 114.0;
+const LABEL_RADIUS: f32 = 88.0;
+const SEGMENT_GAP: f32 = 0.06; // radians between segments
+const BACKGROUND_DIM: f32 = 0.35;
+
+pub struct Menu {
+    open: bool,
+    selected: usize,
+    editing: bool,
+    param_slot: usize, // knob being edited under MenuItem::ModeParams
+    naming: Option<TextInput>, // while MenuItem::SaveTheme is being edited
+    idle: f32, // seconds since the last input
+}
+
+impl Menu {
+    pub const TIMEOUT_S: f32 = 6.0;
+
+    pub fn new() -> Self {
+        Self { open: false, selected: 0, editing: false, param_slot: 0, naming: None, idle: 0.0 }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.editing
+    }
+
+    pub fn selected(&self) -> MenuItem {
+        MenuItem::ALL[self.selected]
+    }
+
+    pub fn open(&mut self) {
+        self.open = true;
+        self.editing = false;
+        self.idle = 0.0;
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+        self.editing = false;
+        self.naming = None;
+    }
+
+    // closes the menu once it's been left alone for TIMEOUT_S
+    pub fn update(&mut self, dt: f32) -> MenuEffect {
+        if !self.open {
+            return MenuEffect::None;
+        }
+        self.idle += dt;
+        if self.idle >= Self::TIMEOUT_S {
+            self.close();
+            return MenuEffect::Closed;
+        }
+        MenuEffect::None
+    }
+
+    pub fn handle(&mut self, action: UiAction, target: &mut MenuTarget) -> MenuEffect {
+        self.idle = 0.0;
+        if self.naming.is_some() && action != UiAction::OpenMenu {
+            return self.name_theme(action, target);
+        }
+        match action {
+            UiAction::OpenMenu => {
+                self.open();
+                MenuEffect::None
+            }
+            UiAction::MenuStep(steps) if self.editing => self.adjust(steps, target),
+            UiAction::MenuStep(steps) => {
+                let n = MenuItem::ALL.len() as i32;
+                self.selected = (self.selected as i32 + steps).rem_euclid(n) as usize;
+                MenuEffect::None
+            }
+            UiAction::MenuSelect if self.selected() == MenuItem::ModeParams => {
+                // each press moves on to the next knob, past the last one editing ends
+                let count = target.visualizer.params().len();
+                if self.editing {
+                    self.param_slot += 1;
+                } else {
+                    self.param_slot = 0;
+                }
+                self.editing = self.param_slot < count;
+                MenuEffect::None
+            }
+            UiAction::MenuSelect if self.selected() == MenuItem::SaveTheme => {
+                let name = target.settings.theme.map(|theme| theme.name).unwrap_or_default();
+                self.naming = Some(TextInput::new(name.as_str()));
+                self.editing = true;
+                MenuEffect::None
+            }
+            UiAction::MenuSelect => {
+                self.editing = !self.editing;
+                MenuEffect::None
+            }
+            UiAction::MenuBack if self.editing => {
+                self.editing = false;
+                MenuEffect::None
+            }
+            UiAction::MenuBack => {
+                self.close();
+                MenuEffect::Closed
+            }
+            _ => MenuEffect::None,
+        }
+    }
+
+    // the character wheel takes every input until the name is saved or cancelled
+    fn name_theme(&mut self, action: UiAction, target: &mut MenuTarget) -> MenuEffect {
+        let Some(wheel) = &mut self.naming else {
+            return MenuEffect::None;
+        };
+        let state = match action {
+            UiAction::MenuStep(steps) => {
+                wheel.rotate(steps);
+                TextInputState::Editing
+            }
+            UiAction::MenuSelect => wheel.press(),
+            UiAction::MenuBack => wheel.long_press(),
+            _ => TextInputState::Editing,
+        };
+        match state {
+            TextInputState::Editing => return MenuEffect::None,
+            // an empty name keeps the old theme, like cancelling
+            TextInputState::Done(name) if !name.is_empty() => {
+                target.settings.theme = Some(UserTheme { name, palette: *target.visualizer.palette() });
+            }
+            _ => {}
+        }
+        self.naming = None;
+        self.editing = false;
+        MenuEffect::None
+    }
+
+    fn adjust(&mut self, steps: i32, target: &mut MenuTarget) -> MenuEffect {
+        match self.selected() {
+            MenuItem::Mode => {
+                target.visualizer.step_mode(steps);
+            }
+            MenuItem::Palette => {
+                // the built-in palettes, then the user theme if one was saved
+                let theme = target.settings.theme;
+                let current = palette::builtin_index(target.visualizer.palette())
+                    .or_else(|| theme.filter(|theme| theme.palette == *target.visualizer.palette()).map(|_| palette::BUILTIN.len()))
+                    .unwrap_or(0);
+                let n = (palette::BUILTIN.len() + theme.is_some() as usize) as i32;
+                let next = (current as i32 + steps).rem_euclid(n) as usize;
+                match theme {
+                    Some(theme) if next == palette::BUILTIN.len() => target.visualizer.set_palette(theme.palette),
+                    _ => target.visualizer.set_palette(palette::builtin(next)),
+                }
+            }
+            MenuItem::SaveTheme => return MenuEffect::None, // see name_theme
+            MenuItem::Brightness => target.settings.adjust_brightness(steps as f32 * BRIGHTNESS_STEP),
+            MenuItem::MicGain => {
+                // index 0 is auto, the rest are GAIN_STEPS
+                let current = if target.dsp.agc {
+                    0
+                } else {
+                    1 + GAIN_STEPS.iter().position(|&g| g >= target.dsp.gain).unwrap_or(GAIN_STEPS.len() - 1)
+                };
+                let next = (current as i32 + steps).clamp(0, GAIN_STEPS.len() as i32) as usize;
+                target.dsp.agc = next == 0;
+                if next > 0 {
+                    target.dsp.gain = GAIN_STEPS[next - 1];
+                }
+                return MenuEffect::DspChanged;
+            }
+            MenuItem::Tilt => {
+                let (lo, hi) = DspSettings::TILT_RANGE;
+                let tilt = libm::roundf(target.dsp.tilt / TILT_STEP + steps as f32) * TILT_STEP;
+                target.dsp.tilt = tilt.clamp(lo, hi);
+                return MenuEffect::DspChanged;
+            }
+            MenuItem::Startup => target.settings.startup = target.settings.startup.step(steps),
+            MenuItem::Power => target.settings.power = cycle(&PowerProfile::ALL, &target.settings.power, steps),
+            MenuItem::Response => {
+                let response = target.visualizer.response_mut();
+                response.global = cycle(&ResponseCurve::CHOICES, &response.global, steps);
+            }
+            MenuItem::ModeResponse => {
+                // None first, turning past it gives the mode a curve of its own
+                let mode = target.visualizer.current_mode();
+                let response = target.visualizer.response_mut();
+                let curve = cycle(&mode_curve_options(), &response.mode(mode), steps);
+                response.set_mode(mode, curve);
+            }
+            MenuItem::ModeParams => {
+                if let Some(param) = target.visualizer.params_mut().get_mut(self.param_slot) {
+                    param.nudge(steps);
+                }
+                return MenuEffect::None;
+            }
+        }
+        target.settings.record(target.visualizer);
+        MenuEffect::None
+    }
+
+    // overlay on whatever the visualizer drew this frame
+    pub fn render(&self, fb: &mut Framebuffer, visualizer: &Visualizer, settings: &Settings, dsp: &DspSettings) {
+        if !self.open {
+            return;
+        }
+        let pal = visualizer.palette();
+
+        for (y, x_start, x_end) in DISPLAY_REGION.rows() {
+            for pixel in &mut fb.row_mut(y)[x_start..x_end] {
+                *pixel = pixel.scale(BACKGROUND_DIM);
+            }
+        }
+
+        // segments clockwise from the top, the selected one sticks out
+        let n = MenuItem::ALL.len();
+        let sweep = TAU / n as f32;
+        let center = (DISPLAY_CENTER, DISPLAY_CENTER);
+        for (i, item) in MenuItem::ALL.iter().enumerate() {
+            let mid = -FRAC_PI_2 + i as f32 * sweep;
+            let start = mid - sweep / 2.0 + SEGMENT_GAP / 2.0;
+            let selected = i == self.selected;
+            let color = pal.sample(i as f32 / n as f32);
+            if selected {
+                ring_segment(fb, center, RING_INNER - 4.0, RING_OUTER + 3.0, start, sweep - SEGMENT_GAP, color);
+            } else {
+                ring_segment(fb, center, RING_INNER, RING_OUTER, start, sweep - SEGMENT_GAP, color.scale(0.45));
+            }
+
+            let (lx, ly) = from_polar(mid, LABEL_RADIUS);
+            let label = item.short();
+            let label_color = if selected { palette::WHITE } else { palette::WHITE.scale(0.55) };
+            let x = lx as i32 - text_width(&FONT_5X7, label) as i32 / 2;
+            draw_text(fb, x, ly as i32 - FONT_5X7.height as i32 / 2, label, label_color);
+        }
+
+        if let Some(wheel) = &self.naming {
+            render_wheel(fb, wheel, pal);
+            return;
+        }
+
+        let item = self.selected();
+        let mut value = TextBuf::<24>::new();
+        let mut title = item.title();
+        if item == MenuItem::ModeParams {
+            let params = visualizer.params();
+            match params.get(self.param_slot).filter(|_| self.editing) {
+                Some(param) => {
+                    title = param.name;
+                    write_param(&mut value, param);
+                }
+                None if params.is_empty() => {
+                    let _ = write!(value, "Nothing to tune");
+                }
+                None => {
+                    let _ = write!(value, "{} knob{}", params.len(), if params.len() == 1 { "" } else { "s" });
+                }
+            }
+        } else {
+            write_value(&mut value, item, visualizer, settings, dsp);
+        }
+        let mut line = TextBuf::<28>::new();
+        if self.editing {
+            let _ = write!(line, "< {} >", value.as_str());
+        } else {
+            let _ = write!(line, "{}", value.as_str());
+        }
+
+        draw_text_centered(fb, &FONT_8X16, 94, title, pal.primary);
+        draw_text_centered(fb, &FONT_8X16, 114, line.as_str(), palette::WHITE);
+        let more_knobs = item == MenuItem::ModeParams && self.param_slot + 1 < visualizer.params().len();
+        let hint = match (self.editing, more_knobs) {
+            (true, true) => "press for next",
+            (true, false) => "turn to change",
+            (false, _) => "press to edit",
+        };
+        draw_text_centered(fb, &FONT_5X7, 140, hint, pal.accent);
+    }
+}
+
+impl Default for Menu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// step through a list, wrapping around
+fn cycle<T: Copy + PartialEq, const N: usize>(options: &[T; N], current: &T, steps: i32) -> T {
+    let idx = options.iter().position(|o| o == current).unwrap_or(0) as i32;
+    options[(idx + steps).rem_euclid(N as i32) as usize]
+}
+
+fn mode_curve_options() -> [Option<ResponseCurve>; ResponseCurve::CHOICES.len() + 1] {
+    core::array::from_fn(|i| i.checked_sub(1).map(|i| ResponseCurve::CHOICES[i]))
+}
+
+fn write_value(out: &mut TextBuf<24>, item: MenuItem, visualizer: &Visualizer, settings: &Settings, dsp: &DspSettings) {
+    let _ = match item {
+        MenuItem::Mode => write!(out, "{}", visualizer.current_mode().name()),
+        MenuItem::Palette => match (palette::builtin_index(visualizer.palette()), settings.theme) {
+            (Some(i), _) => write!(out, "{}", palette::BUILTIN[i]),
+            (None, Some(theme)) if theme.palette == *visualizer.palette() => write!(out, "{}", theme.name.as_str()),
+            (None, _) => write!(out, "Custom"),
+        },
+        MenuItem::SaveTheme => match settings.theme {
+            Some(theme) => write!(out, "{}", theme.name.as_str()),
+            None => write!(out, "None saved"),
+        },
+        MenuItem::Brightness => write!(out, "{:.0}%", settings.brightness * 100.0),
+        MenuItem::MicGain if dsp.agc => write!(out, "Auto"),
+        MenuItem::MicGain => write!(out, "x{:.0}", dsp.gain),
+        MenuItem::Tilt if dsp.tilt == 0.0 => write!(out, "Flat"),
+        MenuItem::Tilt => write!(out, "{:+.0} dB/oct", dsp.tilt),
+        MenuItem::Startup => match settings.startup {
+            StartupPolicy::Favorite(mode) => write!(out, "{}", mode.name()),
+            policy => write!(out, "{}", policy.name()),
+        },
+        MenuItem::Power => write!(out, "{}", settings.power.name()),
+        MenuItem::Response => write_curve(out, &visualizer.response().global),
+        MenuItem::ModeResponse => match visualizer.response().mode(visualizer.current_mode()) {
+            Some(curve) => write_curve(out, &curve),
+            None => write!(out, "Global"),
+        },
+        MenuItem::ModeParams => Ok(()), // per knob, see write_param
+    };
+}
+
+// the name so far over the wheel, the selected entry bracketed between its neighbours
+fn render_wheel(fb: &mut Framebuffer, wheel: &TextInput, pal: &ColorPalette) {
+    let mut name = TextBuf::<24>::new();
+    let _ = write!(name, "{}_", wheel.text());
+    let mut line = TextBuf::<28>::new();
+    for offset in -2..=2 {
+        let (open, close) = if offset == 0 { ("[", "] ") } else { ("", " ") };
+        let _ = match wheel.neighbour(offset) {
+            WheelEntry::Char(b' ') => write!(line, "{}SP{}", open, close),
+            WheelEntry::Char(c) => write!(line, "{}{}{}", open, c as char, close),
+            WheelEntry::Delete => write!(line, "{}DEL{}", open, close),
+            WheelEntry::Done => write!(line, "{}OK{}", open, close),
+        };
+    }
+    let hint = match wheel.selected() {
+        WheelEntry::Char(_) => "press to add",
+        WheelEntry::Delete => "press to delete",
+        WheelEntry::Done => "press to save",
+    };
+
+    draw_text_centered(fb, &FONT_8X16, 80, "Theme name", pal.primary);
+    draw_text_centered(fb, &FONT_8X16, 100, name.as_str(), palette::WHITE);
+    draw_text_centered(fb, &FONT_8X16, 122, line.as_str().trim_end(), pal.secondary);
+    draw_text_centered(fb, &FONT_5X7, 146, hint, pal.accent);
+}
+
+fn write_curve(out: &mut TextBuf<24>, curve: &ResponseCurve) -> core::fmt::Result {
+    match *curve {
+        ResponseCurve::Log { floor_db } => write!(out, "Log -{:.0} dB", floor_db),
+        ResponseCurve::Power { exponent } => write!(out, "Power {:.1}", exponent),
+        curve => write!(out, "{}", curve.name()),
+    }
+}
+
+// a mode knob's value with as many decimals as its step needs
+fn write_param(out: &mut TextBuf<24>, param: &Param) {
+    if param.is_toggle() {
+        let _ = write!(out, "{}", if param.is_on() { "On" } else { "Off" });
+        return;
+    }
+    let whole = |v: f32| libm::fabsf(v - libm::roundf(v)) < 1e-3;
+    let decimals = match param.step {
+        step if step <= 0.0 => 2,
+        step => [1.0, 10.0, 100.0].iter().position(|&k| whole(step * k)).unwrap_or(2),
+    };
+    let _ = write!(out, "{:.*}", decimals, param.value);
+}
@@ -0,0 +1,167 @@
+// mirror mode, for the person the wearer is talking to: one big pulse in the middle while the
+// wearer is speaking and a plain loudness arc around it. solid shapes on black and nothing
+// else, so it reads at a distance. pair it with the high contrast palette for sunlight.
+
+use core::f32::consts::{FRAC_PI_2, PI, TAU};
+use core::ops::Range;
+
+use libm::sqrtf;
+
+use crate::param::Param;
+use crate::polar;
+use crate::region::{RenderRegion, DISPLAY_REGION};
+use crate::source::AnalysisFrame;
+use crate::surface::LedRing;
+use crate::vis::VisualMode;
+use crate::{Color, ColorPalette, EnvelopeSmoother, DISPLAY_CENTER, DISPLAY_SIZE};
+
+pub struct Mirror {
+    loudness: EnvelopeSmoother,
+    activity: f32, // 0 silent - 1 speaking, eased
+    hangover: f32, // seconds left before a pause counts as silence
+    pulse_phase: f32,
+    params: [Param; 2],
+}
+
+impl Mirror {
+    // params: mean band energy that counts as speech, pulse rate in Hz
+    const THRESHOLD: usize = 0;
+    const PULSE_RATE: usize = 1;
+
+    const HANGOVER_S: f32 = 0.4; // bridges the gaps between words
+    const RISE_PER_S: f32 = 8.0;
+    const FALL_PER_S: f32 = 2.5;
+
+    const PULSE_IDLE_RADIUS: f32 = 26.0;
+    const PULSE_SPEAKING_RADIUS: f32 = 62.0;
+    const ARC_RADIUS: f32 = 100.0;
+    const ARC_WIDTH: f32 = 14.0;
+    const ARC_START: f32 = FRAC_PI_2 + PI / 4.0; // bottom left, running clockwise over the top
+    const ARC_SWEEP: f32 = 1.5 * PI;
+
+    pub fn new() -> Self {
+        Self {
+            loudness: EnvelopeSmoother::new(60.0, 20.0, 300.0),
+            activity: 0.0,
+            hangover: 0.0,
+            pulse_phase: 0.0,
+            params: [
+                Param::new("threshold", "Threshold", 0.05, 0.6, 0.05, 0.25),
+                Param::new("pulse", "Pulse rate", 0.5, 4.0, 0.1, 1.6),
+            ],
+        }
+    }
+
+    pub fn is_speaking(&self) -> bool {
+        self.hangover > 0.0
+    }
+
+    pub fn update(&mut self, dt: f32, energies: &[f32]) {
+        let mean = if energies.is_empty() { 0.0 } else { energies.iter().sum::<f32>() / energies.len() as f32 };
+        self.loudness.process(mean.clamp(0.0, 1.0));
+
+        if mean > self.params[Self::THRESHOLD].value {
+            self.hangover = Self::HANGOVER_S;
+        } else {
+            self.hangover = (self.hangover - dt).max(0.0);
+        }
+        self.activity = if self.is_speaking() {
+            (self.activity + Self::RISE_PER_S * dt).min(1.0)
+        } else {
+            (self.activity - Self::FALL_PER_S * dt).max(0.0)
+        };
+        self.pulse_phase = (self.pulse_phase + dt * self.params[Self::PULSE_RATE].value * TAU * self.activity) % TAU;
+    }
+
+    pub fn render_with_palette<F>(&self, set_pixel: F, pal: &ColorPalette)
+    where
+        F: FnMut(usize, usize, Color),
+    {
+        self.draw_rows(&DISPLAY_REGION, 0..DISPLAY_SIZE, 1, set_pixel, pal);
+    }
+
+    fn draw_rows<F>(&self, region: &RenderRegion, rows: Range<usize>, step: usize, mut set_pixel: F, pal: &ColorPalette)
+    where
+        F: FnMut(usize, usize, Color),
+    {
+        let beat = 0.5 + 0.5 * polar::sin(self.pulse_phase);
+        let pulse_radius = Self::PULSE_IDLE_RADIUS
+            + (Self::PULSE_SPEAKING_RADIUS - Self::PULSE_IDLE_RADIUS) * self.activity * (0.85 + 0.15 * beat);
+        // dim disc while silent so the display never looks switched off
+        let pulse_color = pal.primary.scale(0.25 + 0.75 * self.activity);
+        let level = self.loudness.value().min(1.0);
+        let (inner, outer) = (Self::ARC_RADIUS - Self::ARC_WIDTH / 2.0, Self::ARC_RADIUS + Self::ARC_WIDTH / 2.0);
+
+        for (y, columns) in region.grid(rows, step) {
+            let dy = y as f32 - DISPLAY_CENTER;
+            for x in columns {
+                let dx = x as f32 - DISPLAY_CENTER;
+                let r = sqrtf(dx * dx + dy * dy);
+
+                // antialiased edge, one pixel wide
+                let disc = (pulse_radius - r + 0.5).clamp(0.0, 1.0);
+                if disc > 0.0 {
+                    set_pixel(x, y, pulse_color.scale(disc));
+                    continue;
+                }
+
+                let band = ((r - inner + 0.5).min(outer - r + 0.5)).clamp(0.0, 1.0);
+                if band > 0.0 {
+                    let from_start = polar::wrap_angle(polar::atan2(dy, dx) - Self::ARC_START);
+                    if from_start <= Self::ARC_SWEEP {
+                        let color = if from_start <= Self::ARC_SWEEP * level { pal.secondary } else { pal.secondary.scale(0.18) };
+                        set_pixel(x, y, color.scale(band));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl VisualMode for Mirror {
+    fn update(&mut self, dt: f32, frame: &AnalysisFrame) {
+        Mirror::update(self, dt, frame.bands());
+    }
+
+    fn render(&self, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.render_with_palette(set_pixel, pal);
+    }
+
+    fn render_rows(&self, rows: Range<usize>, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.draw_rows(&DISPLAY_REGION, rows, 1, set_pixel, pal);
+    }
+
+    fn render_region(&self, region: &RenderRegion, step: usize, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.draw_rows(region, 0..DISPLAY_SIZE, step, set_pixel, pal);
+    }
+
+    fn stays_in_region(&self) -> bool {
+        true
+    }
+
+    // the loudness arc again, with the unlit LEDs glowing while the wearer speaks
+    fn render_ring(&self, ring: &mut LedRing, pal: &ColorPalette) -> bool {
+        let level = self.loudness.value().min(1.0);
+        let idle = pal.primary.scale(0.08 + 0.3 * self.activity);
+        for i in 0..ring.len() {
+            let from_start = polar::wrap_angle(ring.angle(i) - Self::ARC_START);
+            let color = if from_start <= Self::ARC_SWEEP * level { pal.secondary } else { idle };
+            ring.set(i, color);
+        }
+        true
+    }
+
+    fn params(&self) -> &[Param] {
+        &self.params
+    }
+
+    fn params_mut(&mut self) -> &mut [Param] {
+        &mut self.params
+    }
+}
+
+impl Default for Mirror {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,60 @@
+// band onsets at the UI frame rate: the positive spectral flux (how much the bands rose since
+// the previous frame) against a threshold that follows the recent flux, so a steady vowel or
+// a noisy room doesn't keep triggering but the start of a syllable does
+
+use crate::history::BandHistory;
+use crate::CHANNELS;
+
+const FLUX_HISTORY: usize = 32; // frames, ~1 s at 30 fps
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Onset {
+    pub band: usize,   // loudest band at the onset
+    pub strength: f32, // flux over the threshold, 0-1
+}
+
+pub struct OnsetDetector {
+    previous: [f32; CHANNELS],
+    flux: BandHistory<FLUX_HISTORY>, // one channel, the recent flux values
+    holdoff: f32, // seconds left before the next onset can fire
+    sensitivity: f32,
+}
+
+impl OnsetDetector {
+    const MIN_FLUX: f32 = 0.08; // summed rise that counts in silence
+    const HOLDOFF_S: f32 = 0.12;
+
+    pub fn new() -> Self {
+        Self { previous: [0.0; CHANNELS], flux: BandHistory::new(1), holdoff: 0.0, sensitivity: 1.0 }
+    }
+
+    // scales the threshold down, 2 fires on rises half as big
+    pub fn set_sensitivity(&mut self, sensitivity: f32) {
+        self.sensitivity = sensitivity.max(0.1);
+    }
+
+    pub fn update(&mut self, dt: f32, bands: &[f32]) -> Option<Onset> {
+        let n = bands.len().min(CHANNELS);
+        let flux: f32 = bands[..n].iter().zip(&self.previous).map(|(&now, &before)| (now - before).max(0.0)).sum();
+        self.previous[..n].copy_from_slice(&bands[..n]);
+
+        // judged against the flux before this frame, a burst doesn't raise its own bar
+        let typical = self.flux.percentile(0, 0.75).unwrap_or(0.0);
+        self.flux.push(&[flux]);
+        self.holdoff = (self.holdoff - dt).max(0.0);
+
+        let threshold = (Self::MIN_FLUX + 1.5 * typical) / self.sensitivity;
+        if self.holdoff > 0.0 || flux <= threshold {
+            return None;
+        }
+        self.holdoff = Self::HOLDOFF_S;
+        let band = (0..n).max_by(|&a, &b| bands[a].total_cmp(&bands[b]))?;
+        Some(Onset { band, strength: ((flux - threshold) / threshold).min(1.0) })
+    }
+}
+
+impl Default for OnsetDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,482 @@
+// status widgets drawn on top of the active mode: battery gauge along the top edge, mic level
+// along the bottom edge, a clip light and, for voice training, a loudness gauge on the left
+// edge and a pitch trainer on the right. each widget stands alone, StatusOverlay bundles the
+// usual set. the firmware fills StatusInputs from its ADC readings, the simulator fakes them.
+
+use core::f32::consts::{FRAC_PI_2, PI};
+
+use crate::draw::{arc, filled_circle, line_aa};
+use crate::framebuffer::Framebuffer;
+use crate::icons::BOLT;
+use crate::polar;
+use crate::sprite::blit_tinted;
+use crate::source::AnalysisFrame;
+use crate::{Color, ColorPalette, DISPLAY_CENTER, DISPLAY_SIZE};
+
+// everything the widgets look at, refreshed once per frame
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StatusInputs {
+    pub battery: Option<f32>, // state of charge 0-1, None hides the gauge (USB powered, no gauge fitted)
+    pub charging: bool,
+    pub mic_level: f32,       // input peak 0-1
+    pub clipping: bool,       // the input hit full scale since the last frame
+    pub loudness: Option<f32>, // LUFS, None while nothing has been said yet
+    pub pitch: Option<f32>,    // fundamental in Hz, None while unvoiced
+}
+
+impl StatusInputs {
+    // mic fields from an analysis frame, battery left as is
+    pub fn set_from_frame(&mut self, frame: &AnalysisFrame) {
+        self.mic_level = frame.peak.clamp(0.0, 1.0);
+        self.clipping = frame.clip;
+        self.loudness = frame.loudness;
+        self.pitch = frame.pitch;
+    }
+}
+
+pub trait Widget {
+    fn update(&mut self, dt: f32, inputs: &StatusInputs);
+    fn render(&self, fb: &mut Framebuffer, pal: &ColorPalette);
+}
+
+const CENTER: (f32, f32) = (DISPLAY_CENTER, DISPLAY_CENTER);
+const EDGE_RADIUS: f32 = 112.0;
+const EDGE_THICKNESS: f32 = 4.0;
+const EDGE_SWEEP: f32 = PI / 3.0;
+const TRACK_COLOR: Color = Color::new(40, 40, 48);
+
+const BATTERY_OK: Color = Color::new(60, 220, 90);
+const BATTERY_LOW: Color = Color::new(240, 190, 30);
+const BATTERY_CRITICAL: Color = Color::new(240, 40, 30);
+const CLIP_COLOR: Color = Color::new(255, 30, 20);
+// training feedback, shared by the loudness and pitch widgets
+const BELOW_TARGET: Color = Color::new(70, 140, 255);
+const ON_TARGET: Color = BATTERY_OK;
+const ABOVE_TARGET: Color = Color::new(255, 120, 30);
+
+pub struct BatteryGauge {
+    level: Option<f32>,
+    charging: bool,
+    time: f32, // drives the blink and charge animation
+}
+
+impl BatteryGauge {
+    pub const LOW: f32 = 0.25;
+    pub const CRITICAL: f32 = 0.1;
+    // just below the middle of the arc
+    const BOLT_POSITION: (i32, i32) = (
+        DISPLAY_CENTER as i32 - BOLT.width as i32 / 2,
+        (DISPLAY_CENTER - EDGE_RADIUS + EDGE_THICKNESS) as i32 + 2,
+    );
+
+    pub fn new() -> Self {
+        Self { level: None, charging: false, time: 0.0 }
+    }
+}
+
+impl Widget for BatteryGauge {
+    fn update(&mut self, dt: f32, inputs: &StatusInputs) {
+        self.level = inputs.battery.map(|b| b.clamp(0.0, 1.0));
+        self.charging = inputs.charging;
+        self.time = (self.time + dt) % 60.0;
+    }
+
+    // fills left to right across the top
+    fn render(&self, fb: &mut Framebuffer, _pal: &ColorPalette) {
+        let Some(level) = self.level else { return };
+        let start = -FRAC_PI_2 - EDGE_SWEEP / 2.0;
+        arc(fb, CENTER, EDGE_RADIUS, EDGE_THICKNESS, start, EDGE_SWEEP, TRACK_COLOR);
+
+        let color = if level <= Self::CRITICAL {
+            BATTERY_CRITICAL
+        } else if level <= Self::LOW {
+            BATTERY_LOW
+        } else {
+            BATTERY_OK
+        };
+        // blink when nearly empty, breathe while charging
+        let brightness = if self.charging {
+            0.6 + 0.4 * libm::sinf(self.time * 4.0)
+        } else if level <= Self::CRITICAL && (self.time * 2.0) as u32 % 2 == 1 {
+            0.25
+        } else {
+            1.0
+        };
+        arc(fb, CENTER, EDGE_RADIUS, EDGE_THICKNESS, start, EDGE_SWEEP * level, color.scale(brightness));
+        if self.charging {
+            let (x, y) = Self::BOLT_POSITION;
+            blit_tinted(fb, &BOLT, x, y, color);
+        }
+    }
+}
+
+impl Default for BatteryGauge {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// VU arc: fast attack, slow release, plus a peak hold tick
+pub struct MicMeter {
+    level: f32,
+    peak: f32,
+    peak_hold: f32, // seconds left before the tick starts falling
+}
+
+impl MicMeter {
+    const RELEASE_PER_S: f32 = 1.5;
+    const PEAK_HOLD_S: f32 = 1.0;
+    const PEAK_FALL_PER_S: f32 = 0.5;
+    const TICK_WIDTH: f32 = 0.03; // radians
+
+    pub fn new() -> Self {
+        Self { level: 0.0, peak: 0.0, peak_hold: 0.0 }
+    }
+}
+
+impl Widget for MicMeter {
+    fn update(&mut self, dt: f32, inputs: &StatusInputs) {
+        let input = inputs.mic_level.clamp(0.0, 1.0);
+        self.level = input.max(self.level - Self::RELEASE_PER_S * dt);
+        if input >= self.peak {
+            self.peak = input;
+            self.peak_hold = Self::PEAK_HOLD_S;
+        } else if self.peak_hold > 0.0 {
+            self.peak_hold -= dt;
+        } else {
+            self.peak = (self.peak - Self::PEAK_FALL_PER_S * dt).max(self.level);
+        }
+    }
+
+    // fills left to right across the bottom, which is counter-clockwise there
+    fn render(&self, fb: &mut Framebuffer, pal: &ColorPalette) {
+        let right_end = FRAC_PI_2 - EDGE_SWEEP / 2.0;
+        let left_end = FRAC_PI_2 + EDGE_SWEEP / 2.0;
+        arc(fb, CENTER, EDGE_RADIUS, EDGE_THICKNESS, right_end, EDGE_SWEEP, TRACK_COLOR);
+
+        let fill = EDGE_SWEEP * self.level;
+        arc(fb, CENTER, EDGE_RADIUS, EDGE_THICKNESS, left_end - fill, fill, pal.secondary);
+        if self.peak > 0.01 {
+            let tick = left_end - EDGE_SWEEP * self.peak;
+            arc(fb, CENTER, EDGE_RADIUS, EDGE_THICKNESS + 2.0, tick - Self::TICK_WIDTH / 2.0, Self::TICK_WIDTH, pal.primary);
+        }
+    }
+}
+
+impl Default for MicMeter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// red light above the mic meter, held on for a moment so single clipped samples are visible
+pub struct ClipIndicator {
+    hold: f32,
+}
+
+impl ClipIndicator {
+    pub const HOLD_S: f32 = 1.0;
+    const POSITION: (f32, f32) = (DISPLAY_CENTER, DISPLAY_SIZE as f32 - 22.0);
+    const RADIUS: f32 = 3.5;
+
+    pub fn new() -> Self {
+        Self { hold: 0.0 }
+    }
+
+    pub fn is_lit(&self) -> bool {
+        self.hold > 0.0
+    }
+}
+
+impl Widget for ClipIndicator {
+    fn update(&mut self, dt: f32, inputs: &StatusInputs) {
+        self.hold = if inputs.clipping { Self::HOLD_S } else { (self.hold - dt).max(0.0) };
+    }
+
+    fn render(&self, fb: &mut Framebuffer, _pal: &ColorPalette) {
+        if self.is_lit() {
+            let fade = (self.hold / Self::HOLD_S).max(0.3);
+            filled_circle(fb, Self::POSITION, Self::RADIUS, CLIP_COLOR.scale(fade));
+        }
+    }
+}
+
+impl Default for ClipIndicator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoudnessZone {
+    Quiet,
+    Good,
+    Loud,
+}
+
+// loudness range the wearer is aiming for, in LUFS
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default, deny_unknown_fields))]
+pub struct LoudnessTarget {
+    pub low: f32,
+    pub high: f32,
+}
+
+impl Default for LoudnessTarget {
+    fn default() -> Self {
+        Self { low: -30.0, high: -16.0 }
+    }
+}
+
+impl LoudnessTarget {
+    pub fn zone(&self, lufs: f32) -> LoudnessZone {
+        if lufs < self.low {
+            LoudnessZone::Quiet
+        } else if lufs > self.high {
+            LoudnessZone::Loud
+        } else {
+            LoudnessZone::Good
+        }
+    }
+}
+
+// vertical arc on the left edge, bottom (quiet) to top (loud), with the target range marked
+// on the track and a fill coloured by the zone the voice is in
+pub struct LoudnessGauge {
+    pub target: LoudnessTarget,
+    level: Option<f32>, // eased LUFS
+}
+
+impl LoudnessGauge {
+    pub const SCALE: (f32, f32) = (-50.0, -6.0); // LUFS at the bottom and top of the arc
+    const FOLLOW_PER_S: f32 = 4.0;
+
+    pub fn new(target: LoudnessTarget) -> Self {
+        Self { target, level: None }
+    }
+
+    pub fn zone(&self) -> Option<LoudnessZone> {
+        self.level.map(|lufs| self.target.zone(lufs))
+    }
+
+    // angle along the arc for a loudness, clamped to the scale
+    fn angle(lufs: f32) -> f32 {
+        let (bottom, top) = Self::SCALE;
+        PI - EDGE_SWEEP / 2.0 + EDGE_SWEEP * ((lufs - bottom) / (top - bottom)).clamp(0.0, 1.0)
+    }
+}
+
+impl Widget for LoudnessGauge {
+    fn update(&mut self, dt: f32, inputs: &StatusInputs) {
+        self.level = match (self.level, inputs.loudness) {
+            (Some(level), Some(input)) => Some(level + (input - level) * (Self::FOLLOW_PER_S * dt).min(1.0)),
+            (_, input) => input,
+        };
+    }
+
+    fn render(&self, fb: &mut Framebuffer, _pal: &ColorPalette) {
+        let bottom = PI - EDGE_SWEEP / 2.0;
+        arc(fb, CENTER, EDGE_RADIUS, EDGE_THICKNESS, bottom, EDGE_SWEEP, TRACK_COLOR);
+        let (low, high) = (Self::angle(self.target.low), Self::angle(self.target.high));
+        arc(fb, CENTER, EDGE_RADIUS, EDGE_THICKNESS, low, high - low, ON_TARGET.scale(0.3));
+
+        let Some(level) = self.level else { return };
+        let color = match self.target.zone(level) {
+            LoudnessZone::Quiet => BELOW_TARGET,
+            LoudnessZone::Good => ON_TARGET,
+            LoudnessZone::Loud => ABOVE_TARGET,
+        };
+        arc(fb, CENTER, EDGE_RADIUS, EDGE_THICKNESS, bottom, Self::angle(level) - bottom, color);
+    }
+}
+
+impl Default for LoudnessGauge {
+    fn default() -> Self {
+        Self::new(LoudnessTarget::default())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PitchZone {
+    Low,
+    InBand,
+    High,
+}
+
+// pitch band the wearer is practising, in Hz
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default, deny_unknown_fields))]
+pub struct PitchTarget {
+    pub low: f32,
+    pub high: f32,
+}
+
+impl Default for PitchTarget {
+    fn default() -> Self {
+        Self { low: 170.0, high: 220.0 }
+    }
+}
+
+impl PitchTarget {
+    pub fn zone(&self, hz: f32) -> PitchZone {
+        if hz < self.low {
+            PitchZone::Low
+        } else if hz > self.high {
+            PitchZone::High
+        } else {
+            PitchZone::InBand
+        }
+    }
+}
+
+// log frequency arc on the right edge, low at the bottom, with the target band marked. the
+// current pitch is a dot on the arc, the last ten seconds trail inward from it (older is
+// further in), so drifting out of the band over a sentence is easy to see
+pub struct PitchTrainer {
+    pub target: PitchTarget,
+    pitch: Option<f32>,
+    history: [Option<f32>; Self::HISTORY_LEN],
+    head: usize, // next history slot to write
+    since_sample: f32,
+}
+
+impl PitchTrainer {
+    pub const SCALE: (f32, f32) = (80.0, 400.0); // Hz at the bottom and top of the arc
+    const SWEEP: f32 = FRAC_PI_2;
+    const HISTORY_S: f32 = 10.0;
+    const HISTORY_LEN: usize = 100;
+    const TRACE_DEPTH: f32 = 60.0; // pixels the oldest point sits inside the arc
+    const DOT_RADIUS: f32 = 4.0;
+
+    pub fn new(target: PitchTarget) -> Self {
+        Self { target, pitch: None, history: [None; Self::HISTORY_LEN], head: 0, since_sample: 0.0 }
+    }
+
+    pub fn zone(&self) -> Option<PitchZone> {
+        self.pitch.map(|hz| self.target.zone(hz))
+    }
+
+    // angle along the arc for a frequency, clamped to the scale
+    fn angle(hz: f32) -> f32 {
+        let (bottom, top) = Self::SCALE;
+        let t = (libm::logf(hz.max(1.0) / bottom) / libm::logf(top / bottom)).clamp(0.0, 1.0);
+        Self::SWEEP / 2.0 - Self::SWEEP * t
+    }
+
+    fn zone_color(&self, hz: f32) -> Color {
+        match self.target.zone(hz) {
+            PitchZone::Low => BELOW_TARGET,
+            PitchZone::InBand => ON_TARGET,
+            PitchZone::High => ABOVE_TARGET,
+        }
+    }
+}
+
+impl Widget for PitchTrainer {
+    fn update(&mut self, dt: f32, inputs: &StatusInputs) {
+        self.pitch = inputs.pitch.filter(|hz| *hz > 0.0);
+        self.since_sample += dt;
+        let period = Self::HISTORY_S / Self::HISTORY_LEN as f32;
+        while self.since_sample >= period {
+            self.since_sample -= period;
+            self.history[self.head] = self.pitch;
+            self.head = (self.head + 1) % Self::HISTORY_LEN;
+        }
+    }
+
+    fn render(&self, fb: &mut Framebuffer, _pal: &ColorPalette) {
+        arc(fb, CENTER, EDGE_RADIUS, EDGE_THICKNESS, -Self::SWEEP / 2.0, Self::SWEEP, TRACK_COLOR);
+        let (low, high) = (Self::angle(self.target.low), Self::angle(self.target.high));
+        arc(fb, CENTER, EDGE_RADIUS, EDGE_THICKNESS, high, low - high, ON_TARGET.scale(0.3));
+
+        // oldest to newest, segments only between voiced neighbours
+        let point = |age: usize, hz: f32| {
+            let radius = EDGE_RADIUS - 2.0 * EDGE_THICKNESS - Self::TRACE_DEPTH * age as f32 / Self::HISTORY_LEN as f32;
+            polar::from_polar(Self::angle(hz), radius)
+        };
+        for age in (1..Self::HISTORY_LEN).rev() {
+            let older = self.history[(self.head + Self::HISTORY_LEN - 1 - age) % Self::HISTORY_LEN];
+            let newer = self.history[(self.head + Self::HISTORY_LEN - age) % Self::HISTORY_LEN];
+            if let (Some(a), Some(b)) = (older, newer) {
+                let ((x0, y0), (x1, y1)) = (point(age, a), point(age - 1, b));
+                let fade = 1.0 - 0.8 * age as f32 / Self::HISTORY_LEN as f32;
+                line_aa(fb, x0, y0, x1, y1, self.zone_color(b).scale(fade));
+            }
+        }
+
+        if let Some(hz) = self.pitch {
+            filled_circle(fb, polar::from_polar(Self::angle(hz), EDGE_RADIUS), Self::DOT_RADIUS, self.zone_color(hz));
+        }
+    }
+}
+
+impl Default for PitchTrainer {
+    fn default() -> Self {
+        Self::new(PitchTarget::default())
+    }
+}
+
+// the standard set, each one switchable
+pub struct StatusOverlay {
+    pub battery: BatteryGauge,
+    pub mic: MicMeter,
+    pub clip: ClipIndicator,
+    pub loudness: LoudnessGauge,
+    pub pitch: PitchTrainer,
+    pub show_battery: bool,
+    pub show_mic: bool,
+    pub show_clip: bool,
+    pub show_loudness: bool, // the training aids are off by default
+    pub show_pitch: bool,
+}
+
+impl StatusOverlay {
+    pub fn new() -> Self {
+        Self {
+            battery: BatteryGauge::new(),
+            mic: MicMeter::new(),
+            clip: ClipIndicator::new(),
+            loudness: LoudnessGauge::default(),
+            pitch: PitchTrainer::default(),
+            show_battery: true,
+            show_mic: true,
+            show_clip: true,
+            show_loudness: false,
+            show_pitch: false,
+        }
+    }
+}
+
+impl Widget for StatusOverlay {
+    fn update(&mut self, dt: f32, inputs: &StatusInputs) {
+        self.battery.update(dt, inputs);
+        self.mic.update(dt, inputs);
+        self.clip.update(dt, inputs);
+        self.loudness.update(dt, inputs);
+        self.pitch.update(dt, inputs);
+    }
+
+    fn render(&self, fb: &mut Framebuffer, pal: &ColorPalette) {
+        if self.show_battery {
+            self.battery.render(fb, pal);
+        }
+        if self.show_mic {
+            self.mic.render(fb, pal);
+        }
+        if self.show_clip {
+            self.clip.render(fb, pal);
+        }
+        if self.show_loudness {
+            self.loudness.render(fb, pal);
+        }
+        if self.show_pitch {
+            self.pitch.render(fb, pal);
+        }
+    }
+}
+
+impl Default for StatusOverlay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,296 @@
+// parameters an external controller can drive: MIDI in the simulator, the serial protocol on
+// the badge. values travel normalized to 0-1 and each parameter maps them onto its own range,
+// so a knob, a fader and a protocol write all take the same path. ParamMap binds whatever
+// address a controller uses (a CC number, a register) to a parameter.
+// besides the fixed ParamIds every VisualMode declares its own knobs as Params, reached through
+// ParamId::ModeParam slots of the current mode or by their Param::id as ParamId::Named.
+
+use crate::menu::{MenuEffect, MenuTarget};
+use crate::power::PowerProfile;
+use crate::settings::{DspSettings, Settings, StartupPolicy};
+use crate::text_input::{Name, NAME_LEN};
+use crate::vis::ModeKind;
+use crate::palette;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParamId {
+    Mode,
+    Palette, // built-in palettes
+    Brightness,
+    Gain, // fixed mic gain, turns agc off
+    Agc,  // on above 0.5
+    Tilt, // eq tilt across DspSettings::TILT_RANGE
+    Power, // PowerProfile::ALL
+    Startup, // StartupPolicy::nth order
+    ModeParam(usize), // slot in the current mode's params(), below MAX_MODE_PARAMS
+    Named(Name), // the current mode's param with this Param::id, ignored by modes without one
+}
+
+// knobs a mode may declare, the slots every control surface has room for
+pub const MAX_MODE_PARAMS: usize = 4;
+
+impl ParamId {
+    pub const ALL: [ParamId; 8 + MAX_MODE_PARAMS] = [
+        ParamId::Mode,
+        ParamId::Palette,
+        ParamId::Brightness,
+        ParamId::Gain,
+        ParamId::Agc,
+        ParamId::Tilt,
+        ParamId::Power,
+        ParamId::Startup,
+        ParamId::ModeParam(0),
+        ParamId::ModeParam(1),
+        ParamId::ModeParam(2),
+        ParamId::ModeParam(3),
+    ];
+    const MODE_PARAM_IDS: [&'static str; MAX_MODE_PARAMS] = ["param1", "param2", "param3", "param4"];
+
+    const GAIN_RANGE: (f32, f32) = (1.0, 32.0); // log scale
+
+    // short stable identifier for config files and the control protocol
+    pub fn id(&self) -> &str {
+        match self {
+            ParamId::Mode => "mode",
+            ParamId::Palette => "palette",
+            ParamId::Brightness => "brightness",
+            ParamId::Gain => "gain",
+            ParamId::Agc => "agc",
+            ParamId::Tilt => "tilt",
+            ParamId::Power => "power",
+            ParamId::Startup => "startup",
+            &ParamId::ModeParam(slot) => Self::MODE_PARAM_IDS.get(slot).copied().unwrap_or("param"),
+            ParamId::Named(name) => name.as_str(),
+        }
+    }
+
+    // anything that isn't a fixed id and looks like a Param::id names a mode param
+    pub fn from_id(id: &str) -> Option<ParamId> {
+        Self::ALL.into_iter().find(|p| p.id() == id).or_else(|| {
+            let valid = |b: u8| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_';
+            (!id.is_empty() && id.len() <= NAME_LEN && id.bytes().all(valid)).then(|| ParamId::Named(Name::new(id)))
+        })
+    }
+
+    // index of the current mode's param this addresses, if it has it
+    fn mode_slot(&self, target: &MenuTarget) -> Option<usize> {
+        match self {
+            &ParamId::ModeParam(slot) => Some(slot),
+            ParamId::Named(name) => target.visualizer.params().iter().position(|p| p.id == name.as_str()),
+            _ => None,
+        }
+    }
+
+    // number of distinct values for stepped parameters, None for continuous ones
+    pub fn steps(&self) -> Option<usize> {
+        match self {
+            ParamId::Mode => Some(ModeKind::count()),
+            ParamId::Palette => Some(palette::BUILTIN.len()),
+            ParamId::Agc => Some(2),
+            ParamId::Power => Some(PowerProfile::ALL.len()),
+            ParamId::Startup => Some(StartupPolicy::count()),
+            // each mode snaps its own values, controllers send them as continuous
+            ParamId::Brightness | ParamId::Gain | ParamId::Tilt | ParamId::ModeParam(_) | ParamId::Named(_) => None,
+        }
+    }
+
+    // normalized value that selects step `index` of a stepped parameter
+    pub fn step_value(&self, index: usize) -> f32 {
+        match self.steps() {
+            Some(n) if n > 1 => index.min(n - 1) as f32 / (n - 1) as f32,
+            _ => 0.0,
+        }
+    }
+
+    // current value, normalized
+    pub fn get(&self, target: &MenuTarget) -> f32 {
+        match self {
+            ParamId::Mode => {
+                self.step_value(target.visualizer.current_mode().position())
+            }
+            ParamId::Palette => self.step_value(palette::builtin_index(target.visualizer.palette()).unwrap_or(0)),
+            ParamId::Brightness => {
+                (target.settings.brightness - Settings::MIN_BRIGHTNESS) / (1.0 - Settings::MIN_BRIGHTNESS)
+            }
+            ParamId::Gain => {
+                let (lo, hi) = Self::GAIN_RANGE;
+                (libm::logf(target.dsp.gain / lo) / libm::logf(hi / lo)).clamp(0.0, 1.0)
+            }
+            ParamId::Agc => self.step_value(target.dsp.agc as usize),
+            ParamId::Tilt => {
+                let (lo, hi) = DspSettings::TILT_RANGE;
+                ((target.dsp.tilt - lo) / (hi - lo)).clamp(0.0, 1.0)
+            }
+            ParamId::Power => {
+                self.step_value(PowerProfile::ALL.iter().position(|&p| p == target.settings.power).unwrap_or(0))
+            }
+            ParamId::Startup => self.step_value(target.settings.startup.position()),
+            ParamId::ModeParam(_) | ParamId::Named(_) => {
+                self.mode_slot(target).and_then(|slot| target.visualizer.params().get(slot)).map_or(0.0, Param::normalized)
+            }
+        }
+    }
+
+    // apply a normalized value, DspChanged means the new DspSettings have to reach the source
+    pub fn set(&self, target: &mut MenuTarget, value: f32) -> MenuEffect {
+        let value = value.clamp(0.0, 1.0);
+        let step = |n: usize| libm::roundf(value * (n - 1) as f32) as usize;
+        match self {
+            ParamId::Mode => {
+                let mode = ModeKind::nth(step(ModeKind::count()));
+                if mode != target.visualizer.current_mode() {
+                    target.visualizer.set_mode(mode);
+                }
+            }
+            ParamId::Palette => target.visualizer.set_palette(palette::builtin(step(palette::BUILTIN.len()))),
+            ParamId::Brightness => {
+                target.settings.brightness = Settings::MIN_BRIGHTNESS + value * (1.0 - Settings::MIN_BRIGHTNESS);
+            }
+            ParamId::Gain => {
+                let (lo, hi) = Self::GAIN_RANGE;
+                target.dsp.gain = lo * libm::powf(hi / lo, value);
+                target.dsp.agc = false;
+                return MenuEffect::DspChanged;
+            }
+            ParamId::Agc => {
+                target.dsp.agc = value >= 0.5;
+                return MenuEffect::DspChanged;
+            }
+            ParamId::Tilt => {
+                let (lo, hi) = DspSettings::TILT_RANGE;
+                target.dsp.tilt = lo + value * (hi - lo);
+                return MenuEffect::DspChanged;
+            }
+            ParamId::Power => target.settings.power = PowerProfile::ALL[step(PowerProfile::ALL.len())],
+            ParamId::Startup => target.settings.startup = StartupPolicy::nth(step(StartupPolicy::count())),
+            ParamId::ModeParam(_) | ParamId::Named(_) => {
+                // params the mode doesn't have are ignored, like an unbound knob
+                if let Some(param) = self.mode_slot(target).and_then(|slot| target.visualizer.params_mut().get_mut(slot)) {
+                    param.set_normalized(value);
+                }
+                return MenuEffect::None;
+            }
+        }
+        target.settings.record(target.visualizer);
+        MenuEffect::None
+    }
+}
+
+// one knob of a visual mode, declared by the mode (VisualMode::params) and read back by it
+// every frame. `step` is the menu increment and the resolution values snap to, 0 for none
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Param {
+    pub id: &'static str,   // short and stable, for the control protocol
+    pub name: &'static str, // menu title
+    pub min: f32,
+    pub max: f32,
+    pub step: f32,
+    pub value: f32,
+}
+
+impl Param {
+    // steps of a continuous param in the menu
+    const MENU_STEPS: f32 = 20.0;
+
+    pub const fn new(id: &'static str, name: &'static str, min: f32, max: f32, step: f32, default: f32) -> Self {
+        Self { id, name, min, max, step, value: default }
+    }
+
+    // 0-1 off / 1 on
+    pub const fn toggle(id: &'static str, name: &'static str, on: bool) -> Self {
+        Self::new(id, name, 0.0, 1.0, 1.0, if on { 1.0 } else { 0.0 })
+    }
+
+    pub fn is_toggle(&self) -> bool {
+        self.min == 0.0 && self.max == 1.0 && self.step == 1.0
+    }
+
+    pub fn is_on(&self) -> bool {
+        self.value >= 0.5
+    }
+
+    // clamped to the range and snapped to the step
+    pub fn set(&mut self, value: f32) {
+        let value = if self.step > 0.0 {
+            self.min + libm::roundf((value - self.min) / self.step) * self.step
+        } else {
+            value
+        };
+        self.value = value.clamp(self.min, self.max);
+    }
+
+    pub fn normalized(&self) -> f32 {
+        if self.max > self.min { (self.value - self.min) / (self.max - self.min) } else { 0.0 }
+    }
+
+    pub fn set_normalized(&mut self, value: f32) {
+        self.set(self.min + value.clamp(0.0, 1.0) * (self.max - self.min));
+    }
+
+    // `steps` menu increments up or down
+    pub fn nudge(&mut self, steps: i32) {
+        let step = if self.step > 0.0 { self.step } else { (self.max - self.min) / Self::MENU_STEPS };
+        self.set(self.value + steps as f32 * step);
+    }
+}
+
+// what an address drives: the parameter, and for buttons/notes the fixed value they send
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParamBinding {
+    pub param: ParamId,
+    pub value: Option<f32>, // None: the controller's own value is used
+}
+
+pub const MAX_PARAM_BINDINGS: usize = 32;
+
+// fixed-capacity address -> parameter table, K is the controller's address type
+pub struct ParamMap<K> {
+    entries: [Option<(K, ParamBinding)>; MAX_PARAM_BINDINGS],
+}
+
+impl<K: Copy + PartialEq> ParamMap<K> {
+    pub fn new() -> Self {
+        Self { entries: [None; MAX_PARAM_BINDINGS] }
+    }
+
+    // replaces an existing binding for `key`, false when the table is full
+    pub fn bind(&mut self, key: K, binding: ParamBinding) -> bool {
+        let slot = match self.entries.iter().position(|e| e.is_some_and(|(k, _)| k == key)) {
+            Some(i) => i,
+            None => match self.entries.iter().position(|e| e.is_none()) {
+                Some(i) => i,
+                None => return false,
+            },
+        };
+        self.entries[slot] = Some((key, binding));
+        true
+    }
+
+    pub fn unbind(&mut self, key: K) {
+        for entry in &mut self.entries {
+            if entry.is_some_and(|(k, _)| k == key) {
+                *entry = None;
+            }
+        }
+    }
+
+    pub fn get(&self, key: K) -> Option<ParamBinding> {
+        self.entries.iter().flatten().find(|(k, _)| *k == key).map(|&(_, b)| b)
+    }
+
+    // route a controller value (0-1) to its parameter, None if `key` isn't bound
+    pub fn apply(&self, key: K, value: f32, target: &mut MenuTarget) -> Option<MenuEffect> {
+        let binding = self.get(key)?;
+        Some(binding.param.set(target, binding.value.unwrap_or(value)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (K, ParamBinding)> + '_ {
+        self.entries.iter().flatten().copied()
+    }
+}
+
+impl<K: Copy + PartialEq> Default for ParamMap<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
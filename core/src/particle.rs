@@ -0,0 +1,97 @@
+// fixed-capacity storage for modes that spawn and retire lots of small things every frame.
+// the live particles are packed at the front, retiring one moves the last into its place, so
+// the order isn't kept but walking them is a plain slice and nothing ever allocates.
+// Rng is the randomness the spawners share
+
+pub struct ParticlePool<T: Copy, const N: usize> {
+    items: [T; N],
+    len: usize,
+}
+
+impl<T: Copy + Default, const N: usize> ParticlePool<T, N> {
+    pub fn new() -> Self {
+        Self { items: [T::default(); N], len: 0 }
+    }
+}
+
+impl<T: Copy, const N: usize> ParticlePool<T, N> {
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    // false when the pool is full, the particle is dropped
+    pub fn spawn(&mut self, particle: T) -> bool {
+        if self.len == N {
+            return false;
+        }
+        self.items[self.len] = particle;
+        self.len += 1;
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.items[..self.len]
+    }
+
+    pub fn iter(&self) -> core::slice::Iter<'_, T> {
+        self.as_slice().iter()
+    }
+
+    // step every particle in place, the ones `f` returns false for are retired
+    pub fn retain(&mut self, mut f: impl FnMut(&mut T) -> bool) {
+        let mut i = 0;
+        while i < self.len {
+            if f(&mut self.items[i]) {
+                i += 1;
+            } else {
+                self.len -= 1;
+                self.items[i] = self.items[self.len];
+            }
+        }
+    }
+}
+
+impl<T: Copy + Default, const N: usize> Default for ParticlePool<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// xorshift32, deterministic so a mode looks the same every run. the seed must not be 0
+pub struct Rng(u32);
+
+impl Rng {
+    pub const fn new(seed: u32) -> Self {
+        Self(if seed == 0 { 0x9e37_79b9 } else { seed })
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.0 = x;
+        x
+    }
+
+    // uniform in 0..1
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+}
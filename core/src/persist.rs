@@ -0,0 +1,32 @@
+// versioned postcard encoding for settings and preset banks kept in flash.
+// the first byte is the format version so old blobs are rejected instead of misread.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+// 2: Settings::calibration, 3: Settings::power, 4: Settings::idle_clock, 5: Settings::response,
+// 6: Settings::theme
+pub const FORMAT_VERSION: u8 = 6;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PersistError {
+    BufferTooSmall,
+    WrongVersion(u8),
+    Corrupt,
+}
+
+// returns the used part of `buf`
+pub fn encode<'a, T: Serialize>(value: &T, buf: &'a mut [u8]) -> Result<&'a mut [u8], PersistError> {
+    let (version, body) = buf.split_first_mut().ok_or(PersistError::BufferTooSmall)?;
+    *version = FORMAT_VERSION;
+    let len = postcard::to_slice(value, body).map_err(|_| PersistError::BufferTooSmall)?.len();
+    Ok(&mut buf[..len + 1])
+}
+
+pub fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, PersistError> {
+    match bytes.split_first() {
+        Some((&FORMAT_VERSION, body)) => postcard::from_bytes(body).map_err(|_| PersistError::Corrupt),
+        Some((&version, _)) => Err(PersistError::WrongVersion(version)),
+        None => Err(PersistError::Corrupt),
+    }
+}
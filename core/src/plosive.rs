@@ -0,0 +1,76 @@
+use crate::{Color, Point2D, EnvelopeSmoother, draw_line};
+
+// softens plosive pops: while the DSP flags one, the low bands are soft-clipped so the
+// display doesn't flash on every "p", and a dim arc at the bottom edge shows what happened
+pub struct PlosiveGuard {
+    enabled: bool,
+    amount: EnvelopeSmoother, // 0 = untouched, 1 = fully clipped, smoothed so it never pops itself
+}
+
+impl PlosiveGuard {
+    const LIMIT: f32 = 0.45;        // soft-clip ceiling for the low bands
+    const LOW_BAND_FRACTION: f32 = 0.34;
+
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            amount: EnvelopeSmoother::new(60.0, 10.0, 150.0),
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn amount(&self) -> f32 {
+        self.amount.value()
+    }
+
+    // call once per frame before the energies reach the mode
+    pub fn apply(&mut self, plosive: bool, energies: &mut [f32]) {
+        let amount = self.amount.process(if plosive && self.enabled { 1.0 } else { 0.0 });
+        if amount < 0.01 {
+            return;
+        }
+
+        let low_bands = ((energies.len() as f32 * Self::LOW_BAND_FRACTION) as usize).max(1);
+        for e in energies.iter_mut().take(low_bands) {
+            // rational soft clip, tanh-like but cheap
+            let x = *e / Self::LIMIT;
+            let clipped = Self::LIMIT * x / (1.0 + x);
+            *e += (clipped - *e) * amount;
+        }
+    }
+
+    // subtle arc along the bottom edge while the guard is working
+    pub fn render<F>(&self, color: Color, mut set_pixel: F)
+    where
+        F: FnMut(usize, usize, Color),
+    {
+        let amount = self.amount.value();
+        if amount < 0.05 {
+            return;
+        }
+
+        let color = color.scale(0.5 * amount);
+        let segments = 12;
+        let (start, sweep) = (core::f32::consts::FRAC_PI_2 - 0.35, 0.7); // centered at the bottom
+        for i in 0..segments {
+            let a0 = start + sweep * i as f32 / segments as f32;
+            let a1 = start + sweep * (i + 1) as f32 / segments as f32;
+            let (x0, y0) = Point2D::new(0.96, 0.0).rotate(a0).to_screen();
+            let (x1, y1) = Point2D::new(0.96, 0.0).rotate(a1).to_screen();
+            draw_line(x0, y0, x1, y1, color, true, &mut set_pixel);
+        }
+    }
+}
+
+impl Default for PlosiveGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,130 @@
+// polar coordinate helpers for round-display effects. angles in radians, 0 at 3 o'clock,
+// clockwise on screen (y points down), radius in pixels from the display center.
+// with the `trig-lut` feature sin/cos/atan2 come from a fixed-point table and a polynomial
+// instead of libm, which is a lot cheaper per pixel on the MCU.
+
+use core::f32::consts::TAU;
+
+use crate::DISPLAY_CENTER;
+
+// angle and radius of a screen point
+pub fn to_polar(x: f32, y: f32) -> (f32, f32) {
+    let (dx, dy) = (x - DISPLAY_CENTER, y - DISPLAY_CENTER);
+    (atan2(dy, dx), libm::sqrtf(dx * dx + dy * dy))
+}
+
+// screen point of an angle and radius
+pub fn from_polar(angle: f32, radius: f32) -> (f32, f32) {
+    (DISPLAY_CENTER + radius * cos(angle), DISPLAY_CENTER + radius * sin(angle))
+}
+
+// angle wrapped to 0..TAU
+pub fn wrap_angle(angle: f32) -> f32 {
+    angle.rem_euclid(TAU)
+}
+
+#[cfg(not(feature = "trig-lut"))]
+pub fn sin(angle: f32) -> f32 {
+    libm::sinf(angle)
+}
+
+#[cfg(not(feature = "trig-lut"))]
+pub fn cos(angle: f32) -> f32 {
+    libm::cosf(angle)
+}
+
+#[cfg(not(feature = "trig-lut"))]
+pub fn atan2(y: f32, x: f32) -> f32 {
+    libm::atan2f(y, x)
+}
+
+#[cfg(feature = "trig-lut")]
+pub fn sin(angle: f32) -> f32 {
+    lut::sin_q15(lut::to_binary_angle(angle)) as f32 / 32767.0
+}
+
+#[cfg(feature = "trig-lut")]
+pub fn cos(angle: f32) -> f32 {
+    lut::cos_q15(lut::to_binary_angle(angle)) as f32 / 32767.0
+}
+
+// polynomial atan2, max error ~0.0015 rad, plenty for pixel work
+#[cfg(feature = "trig-lut")]
+pub fn atan2(y: f32, x: f32) -> f32 {
+    use core::f32::consts::{FRAC_PI_2, PI};
+
+    if x == 0.0 && y == 0.0 {
+        return 0.0;
+    }
+    let (ax, ay) = (libm::fabsf(x), libm::fabsf(y));
+    let t = ax.min(ay) / ax.max(ay);
+    let s = t * t;
+    let mut r = ((-0.046_496_47 * s + 0.159_314_22) * s - 0.327_622_77) * s * t + t;
+    if ay > ax {
+        r = FRAC_PI_2 - r;
+    }
+    if x < 0.0 {
+        r = PI - r;
+    }
+    if y < 0.0 { -r } else { r }
+}
+
+// fixed-point sine table, 256 steps per turn with linear interpolation.
+// binary angles: u16 where 65536 is a full turn, so wrapping is free.
+#[cfg(feature = "trig-lut")]
+pub mod lut {
+    use core::f32::consts::TAU;
+
+    // sin * 32767 (Q15), 257 entries so interpolation never needs to wrap
+    static SIN_TABLE: [i16; 257] = [
+        0, 804, 1608, 2410, 3212, 4011, 4808, 5602,
+        6393, 7179, 7962, 8739, 9512, 10278, 11039, 11793,
+        12539, 13279, 14010, 14732, 15446, 16151, 16846, 17530,
+        18204, 18868, 19519, 20159, 20787, 21403, 22005, 22594,
+        23170, 23731, 24279, 24811, 25329, 25832, 26319, 26790,
+        27245, 27683, 28105, 28510, 28898, 29268, 29621, 29956,
+        30273, 30571, 30852, 31113, 31356, 31580, 31785, 31971,
+        32137, 32285, 32412, 32521, 32609, 32678, 32728, 32757,
+        32767, 32757, 32728, 32678, 32609, 32521, 32412, 32285,
+        32137, 31971, 31785, 31580, 31356, 31113, 30852, 30571,
+        30273, 29956, 29621, 29268, 28898, 28510, 28105, 27683,
+        27245, 26790, 26319, 25832, 25329, 24811, 24279, 23731,
+        23170, 22594, 22005, 21403, 20787, 20159, 19519, 18868,
+        18204, 17530, 16846, 16151, 15446, 14732, 14010, 13279,
+        12539, 11793, 11039, 10278, 9512, 8739, 7962, 7179,
+        6393, 5602, 4808, 4011, 3212, 2410, 1608, 804,
+        0, -804, -1608, -2410, -3212, -4011, -4808, -5602,
+        -6393, -7179, -7962, -8739, -9512, -10278, -11039, -11793,
+        -12539, -13279, -14010, -14732, -15446, -16151, -16846, -17530,
+        -18204, -18868, -19519, -20159, -20787, -21403, -22005, -22594,
+        -23170, -23731, -24279, -24811, -25329, -25832, -26319, -26790,
+        -27245, -27683, -28105, -28510, -28898, -29268, -29621, -29956,
+        -30273, -30571, -30852, -31113, -31356, -31580, -31785, -31971,
+        -32137, -32285, -32412, -32521, -32609, -32678, -32728, -32757,
+        -32767, -32757, -32728, -32678, -32609, -32521, -32412, -32285,
+        -32137, -31971, -31785, -31580, -31356, -31113, -30852, -30571,
+        -30273, -29956, -29621, -29268, -28898, -28510, -28105, -27683,
+        -27245, -26790, -26319, -25832, -25329, -24811, -24279, -23731,
+        -23170, -22594, -22005, -21403, -20787, -20159, -19519, -18868,
+        -18204, -17530, -16846, -16151, -15446, -14732, -14010, -13279,
+        -12539, -11793, -11039, -10278, -9512, -8739, -7962, -7179,
+        -6393, -5602, -4808, -4011, -3212, -2410, -1608, -804,
+        0,
+    ];
+
+    pub fn to_binary_angle(angle: f32) -> u16 {
+        (angle.rem_euclid(TAU) * (65536.0 / TAU)) as u32 as u16
+    }
+
+    pub fn sin_q15(angle: u16) -> i16 {
+        let idx = (angle >> 8) as usize;
+        let frac = (angle & 0xFF) as i32;
+        let a = SIN_TABLE[idx] as i32;
+        let b = SIN_TABLE[idx + 1] as i32;
+        (a + (((b - a) * frac) >> 8)) as i16
+    }
+
+    pub fn cos_q15(angle: u16) -> i16 {
+        sin_q15(angle.wrapping_add(16384))
+    }
+}
@@ -0,0 +1,356 @@
+// post-processing shared by firmware and simulator.
+// trails work on the persistent framebuffer before the visualizer draws into it. the
+// kaleidoscope and the saver's pixel doubling finish the frame in end_frame, before any
+// overlay is drawn. blur and scanlines are applied on the way out (flush to rgb565/argb32) so
+// they never feed back into the trails and need no second full-size buffer. the hue stages
+// turn the palette the modes draw with (Visualizer::set_hue), so the trails keep the hue they
+// were drawn in and overlays keep theirs.
+
+use crate::error::UiError;
+use crate::framebuffer::{Framebuffer, FRAMEBUFFER_LEN};
+use crate::kaleido::{Kaleidoscope, MAX_FOLDS, MIN_FOLDS};
+use crate::region::{RenderRegion, DISPLAY_REGION};
+use crate::source::AnalysisFrame;
+use crate::vis::ModeKind;
+use crate::{Color, DISPLAY_SIZE};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PostFx {
+    Trails { decay: f32 },    // keep this much of the previous frame, 0 clears
+    Blur { amount: f32 },     // mix with a box-filtered quarter resolution copy
+    Scanlines { depth: f32 }, // darken every other row by this much
+    Hue { speed: f32 },       // rotate the hue, faster the louder the voice
+    HueSync,                  // rotate the hue one full turn per phrase of speech
+    Kaleidoscope { folds: u8, mirror: bool }, // render one wedge, copy it around the panel
+}
+
+impl PostFx {
+    // "trails:<decay>", "blur:<amount>", "scanlines:<depth>" or "hue:<speed>", values 0-1,
+    // "hue:sync", or "kaleido:<folds>" with an optional ":mirror", folds 2-12
+    pub fn parse(text: &str) -> Option<PostFx> {
+        if text == "hue:sync" {
+            return Some(PostFx::HueSync);
+        }
+        if let Some(spec) = text.strip_prefix("kaleido:") {
+            let (folds, mirror) = match spec.split_once(':') {
+                Some((folds, "mirror")) => (folds, true),
+                Some(_) => return None,
+                None => (spec, false),
+            };
+            let folds: u8 = folds.parse().ok()?;
+            return (MIN_FOLDS..=MAX_FOLDS).contains(&folds).then_some(PostFx::Kaleidoscope { folds, mirror });
+        }
+        let (name, value) = text.split_once(':')?;
+        let value: f32 = value.parse().ok()?;
+        if !(0.0..=1.0).contains(&value) {
+            return None;
+        }
+        match name {
+            "trails" => Some(PostFx::Trails { decay: value }),
+            "blur" => Some(PostFx::Blur { amount: value }),
+            "scanlines" => Some(PostFx::Scanlines { depth: value }),
+            "hue" => Some(PostFx::Hue { speed: value }),
+            _ => None,
+        }
+    }
+}
+
+pub const MAX_POST_STAGES: usize = 4;
+// same syntax parse() accepts
+impl core::fmt::Display for PostFx {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PostFx::Trails { decay } => write!(f, "trails:{}", decay),
+            PostFx::Blur { amount } => write!(f, "blur:{}", amount),
+            PostFx::Scanlines { depth } => write!(f, "scanlines:{}", depth),
+            PostFx::Hue { speed } => write!(f, "hue:{}", speed),
+            PostFx::HueSync => f.write_str("hue:sync"),
+            PostFx::Kaleidoscope { folds, mirror } => write!(f, "kaleido:{}{}", folds, if *mirror { ":mirror" } else { "" }),
+        }
+    }
+}
+
+const BLUR_FACTOR: usize = 4;
+const SMALL_SIZE: usize = DISPLAY_SIZE / BLUR_FACTOR;
+
+// hue:1 at full energy, in turns per second. silence still drifts at HUE_IDLE of the speed
+const HUE_MAX_TURNS_PER_S: f32 = 0.25;
+const HUE_IDLE: f32 = 0.1;
+// phrase onsets further apart than this are separate conversations, not a cadence
+const CADENCE_RANGE_S: (f32, f32) = (0.5, 8.0);
+const CADENCE_SMOOTHING: f32 = 0.3; // weight of the newest interval
+
+// time between voice onsets, averaged over the last few phrases
+#[derive(Clone, Copy, Debug, Default)]
+struct Cadence {
+    since_onset: Option<f32>, // None before the first onset
+    period: Option<f32>,      // None until two onsets close enough together
+    voiced: bool,
+}
+
+impl Cadence {
+    fn update(&mut self, dt: f32, voiced: bool) {
+        if let Some(t) = &mut self.since_onset {
+            *t += dt;
+        }
+        if voiced && !self.voiced {
+            if let Some(interval) = self.since_onset.filter(|t| (CADENCE_RANGE_S.0..=CADENCE_RANGE_S.1).contains(t)) {
+                self.period = Some(self.period.map_or(interval, |p| p + (interval - p) * CADENCE_SMOOTHING));
+            }
+            self.since_onset = Some(0.0);
+        }
+        self.voiced = voiced;
+    }
+
+    // ignored during a long pause, the next phrases pick it up again
+    fn period(&self) -> Option<f32> {
+        self.period.filter(|_| self.since_onset.is_some_and(|t| t <= CADENCE_RANGE_S.1))
+    }
+}
+
+pub struct PostChain {
+    stages: [Option<PostFx>; MAX_POST_STAGES],
+    small: [Color; SMALL_SIZE * SMALL_SIZE], // downscaled frame for the blur
+    pixel_step: usize, // end_frame repeats every step-th pixel of every step-th row
+    hue: f32,          // current rotation in turns, 0-1
+    cadence: Cadence,
+    kaleidoscope: Option<Kaleidoscope>, // built from the stages whenever they change
+}
+
+impl PostChain {
+    pub const DEFAULT_TRAIL_DECAY: f32 = 0.7;
+
+    // no stages at all, every frame starts from black
+    pub fn empty() -> Self {
+        Self {
+            stages: [None; MAX_POST_STAGES],
+            small: [Color::default(); SMALL_SIZE * SMALL_SIZE],
+            pixel_step: 1,
+            hue: 0.0,
+            cadence: Cadence::default(),
+            kaleidoscope: None,
+        }
+    }
+
+    // false when the chain is full
+    pub fn push(&mut self, fx: PostFx) -> bool {
+        match self.stages.iter_mut().find(|s| s.is_none()) {
+            Some(slot) => {
+                *slot = Some(fx);
+                self.stages_changed();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.stages = [None; MAX_POST_STAGES];
+        self.stages_changed();
+    }
+
+    pub fn stages(&self) -> impl Iterator<Item = &PostFx> + '_ {
+        self.stages.iter().flatten()
+    }
+
+    // raw slots, for presets
+    pub fn slots(&self) -> &[Option<PostFx>; MAX_POST_STAGES] {
+        &self.stages
+    }
+
+    pub fn set_slots(&mut self, slots: &[Option<PostFx>; MAX_POST_STAGES]) {
+        self.stages = *slots;
+        self.stages_changed();
+    }
+
+    // the wedge table is only rebuilt when the kaleidoscope stage itself changes
+    fn stages_changed(&mut self) {
+        let wanted = self.stages().find_map(|s| match *s {
+            PostFx::Kaleidoscope { folds, mirror } => Some((folds, mirror)),
+            _ => None,
+        });
+        let current = self.kaleidoscope.as_ref().map(|k| (k.folds(), k.mirror()));
+        if wanted != current {
+            self.kaleidoscope = wanted.and_then(|(folds, mirror)| Kaleidoscope::new(folds, mirror));
+        }
+    }
+
+    pub fn kaleidoscope(&self) -> Option<&Kaleidoscope> {
+        self.kaleidoscope.as_ref()
+    }
+
+    // what the visualizer has to draw (Visualizer::render_region): the kaleidoscope's wedge,
+    // or the whole panel
+    pub fn render_region(&self) -> &RenderRegion {
+        self.kaleidoscope.as_ref().map_or(&DISPLAY_REGION, |k| k.region())
+    }
+
+    // for frames drawn with Visualizer::render_sparse, end_frame doubles (step 2) the rendered
+    // pixels to full size. 1 keeps every pixel as drawn
+    pub fn set_pixel_step(&mut self, step: usize) {
+        self.pixel_step = step.clamp(1, BLUR_FACTOR);
+    }
+
+    pub fn pixel_step(&self) -> usize {
+        self.pixel_step
+    }
+
+    // configured decay, overridden by modes that repaint everything
+    pub fn trail_decay(&self, mode: ModeKind) -> f32 {
+        mode.trail_decay().unwrap_or_else(|| {
+            self.stages()
+                .find_map(|s| match s {
+                    PostFx::Trails { decay } => Some(*decay),
+                    _ => None,
+                })
+                .unwrap_or(0.0)
+        })
+    }
+
+    // advances the hue rotation, once per analysis frame. without a cadence yet (or after a
+    // long pause) hue:sync turns like hue:1. without a hue stage the rotation is back at 0
+    pub fn update(&mut self, dt: f32, frame: &AnalysisFrame) {
+        if !self.stages().any(|s| matches!(s, PostFx::Hue { .. } | PostFx::HueSync)) {
+            self.hue = 0.0;
+            return;
+        }
+        self.cadence.update(dt, frame.voiced);
+        let bands = frame.bands();
+        let energy = (bands.iter().sum::<f32>() / bands.len().max(1) as f32).clamp(0.0, 1.0);
+        let by_energy = |speed: f32| speed * HUE_MAX_TURNS_PER_S * (HUE_IDLE + (1.0 - HUE_IDLE) * energy);
+        let turns_per_s = self
+            .stages()
+            .map(|s| match *s {
+                PostFx::Hue { speed } => by_energy(speed),
+                PostFx::HueSync => self.cadence.period().map_or(by_energy(1.0), |p| 1.0 / p),
+                _ => 0.0,
+            })
+            .sum::<f32>();
+        self.hue = (self.hue + turns_per_s * dt).rem_euclid(1.0);
+    }
+
+    // current hue rotation in turns (0-1), hand it to Visualizer::set_hue after update
+    pub fn hue(&self) -> f32 {
+        self.hue
+    }
+
+    // call before the visualizer renders; only the visible spans, nothing is drawn outside them
+    pub fn begin_frame(&self, fb: &mut Framebuffer, mode: ModeKind) {
+        let decay = self.trail_decay(mode);
+        for (y, x_start, x_end) in DISPLAY_REGION.rows() {
+            let row = &mut fb.row_mut(y)[x_start..x_end];
+            if decay <= 0.0 {
+                row.fill(Color::default());
+            } else if decay < 1.0 {
+                for pixel in row.iter_mut() {
+                    *pixel = pixel.scale(decay);
+                }
+            }
+        }
+    }
+
+    // call after the visualizer rendered and before any overlays, which shouldn't be copied
+    // or doubled
+    pub fn end_frame(&self, fb: &mut Framebuffer) {
+        if let Some(kaleidoscope) = &self.kaleidoscope {
+            kaleidoscope.replicate(fb, self.pixel_step);
+        }
+        let step = self.pixel_step;
+        if step > 1 {
+            // the grid pixel of a block maps onto itself, so it's read before its copies land
+            for (y, x_start, x_end) in DISPLAY_REGION.rows() {
+                let source = y - y % step;
+                for x in x_start..x_end {
+                    fb.set(x, y, fb.get(x - x % step, source));
+                }
+            }
+        }
+    }
+
+    // `out` needs FRAMEBUFFER_LEN pixels, nothing is written to a shorter one
+    pub fn write_rgb565(&mut self, fb: &Framebuffer, out: &mut [u16]) -> Result<(), UiError> {
+        self.flush(fb, out, Color::to_rgb565)
+    }
+
+    pub fn write_argb32(&mut self, fb: &Framebuffer, out: &mut [u32]) -> Result<(), UiError> {
+        self.flush(fb, out, Color::to_argb32)
+    }
+
+    fn flush<T>(&mut self, fb: &Framebuffer, out: &mut [T], convert: fn(Color) -> T) -> Result<(), UiError> {
+        if out.len() < FRAMEBUFFER_LEN {
+            return Err(UiError::BufferTooSmall { needed: FRAMEBUFFER_LEN, got: out.len() });
+        }
+        let mut blur = 0.0f32;
+        let mut scanlines = 0.0f32;
+        for stage in self.stages() {
+            match *stage {
+                PostFx::Blur { amount } => blur = amount,
+                PostFx::Scanlines { depth } => scanlines = depth,
+                PostFx::Trails { .. } | PostFx::Hue { .. } | PostFx::HueSync | PostFx::Kaleidoscope { .. } => {}
+            }
+        }
+        if blur > 0.0 {
+            self.downscale(fb);
+        }
+
+        for y in 0..Framebuffer::HEIGHT {
+            let dim = if scanlines > 0.0 && y % 2 == 1 { 1.0 - scanlines } else { 1.0 };
+            let out_row = &mut out[y * Framebuffer::WIDTH..(y + 1) * Framebuffer::WIDTH];
+            let row = fb.row(y);
+            for (x, o) in out_row.iter_mut().enumerate() {
+                let mut c = row[x];
+                if blur > 0.0 {
+                    c = Color::lerp(c, self.sample_small(x, y), blur);
+                }
+                if dim < 1.0 {
+                    c = c.scale(dim);
+                }
+                *o = convert(c);
+            }
+        }
+        Ok(())
+    }
+
+    // box filter into the quarter resolution buffer
+    fn downscale(&mut self, fb: &Framebuffer) {
+        let n = (BLUR_FACTOR * BLUR_FACTOR) as u32;
+        for sy in 0..SMALL_SIZE {
+            for sx in 0..SMALL_SIZE {
+                let (mut r, mut g, mut b) = (0u32, 0u32, 0u32);
+                for y in sy * BLUR_FACTOR..(sy + 1) * BLUR_FACTOR {
+                    let row = fb.row(y);
+                    for &p in &row[sx * BLUR_FACTOR..(sx + 1) * BLUR_FACTOR] {
+                        r += p.r as u32;
+                        g += p.g as u32;
+                        b += p.b as u32;
+                    }
+                }
+                self.small[sy * SMALL_SIZE + sx] = Color::new((r / n) as u8, (g / n) as u8, (b / n) as u8);
+            }
+        }
+    }
+
+    // bilinear lookup of the downscaled buffer at full resolution coordinates
+    fn sample_small(&self, x: usize, y: usize) -> Color {
+        let to_small = |v: usize| {
+            let f = ((v as f32 + 0.5) / BLUR_FACTOR as f32 - 0.5).clamp(0.0, (SMALL_SIZE - 1) as f32);
+            let i = (f as usize).min(SMALL_SIZE - 2);
+            (i, f - i as f32)
+        };
+        let (ix, fx) = to_small(x);
+        let (iy, fy) = to_small(y);
+        let at = |x: usize, y: usize| self.small[y * SMALL_SIZE + x];
+        let top = Color::lerp(at(ix, iy), at(ix + 1, iy), fx);
+        let bottom = Color::lerp(at(ix, iy + 1), at(ix + 1, iy + 1), fx);
+        Color::lerp(top, bottom, fy)
+    }
+}
+
+// just the classic trail fade
+impl Default for PostChain {
+    fn default() -> Self {
+        let mut chain = Self::empty();
+        chain.push(PostFx::Trails { decay: Self::DEFAULT_TRAIL_DECAY });
+        chain
+    }
+}
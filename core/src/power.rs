@@ -0,0 +1,72 @@
+// battery saving. Saver draws half as many frames, renders a 120x120 grid that the flush
+// doubles back to the panel's 240x240, and caps the brightness (the backlight is the biggest
+// load on the badge). the UI loop asks the profile for all three, switching takes effect on
+// the next frame.
+
+use crate::postfx::PostChain;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PowerProfile {
+    #[default]
+    Full,
+    Saver,
+}
+
+impl PowerProfile {
+    pub const ALL: [PowerProfile; 2] = [PowerProfile::Full, PowerProfile::Saver];
+    pub const SAVER_MAX_BRIGHTNESS: f32 = 0.5;
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PowerProfile::Full => "Full",
+            PowerProfile::Saver => "Saver",
+        }
+    }
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            PowerProfile::Full => "full",
+            PowerProfile::Saver => "saver",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<PowerProfile> {
+        Self::ALL.into_iter().find(|p| p.id() == id)
+    }
+
+    pub fn next(&self) -> PowerProfile {
+        match self {
+            PowerProfile::Full => PowerProfile::Saver,
+            PowerProfile::Saver => PowerProfile::Full,
+        }
+    }
+
+    // display frames per second for a loop designed for `full_fps`
+    pub fn frame_rate(&self, full_fps: u32) -> u32 {
+        match self {
+            PowerProfile::Full => full_fps,
+            PowerProfile::Saver => (full_fps / 2).max(1),
+        }
+    }
+
+    // render grid: 1 is every pixel, 2 every other pixel of every other row
+    pub fn pixel_step(&self) -> usize {
+        match self {
+            PowerProfile::Full => 1,
+            PowerProfile::Saver => 2,
+        }
+    }
+
+    // the brightness actually drawn with, the setting itself is left alone
+    pub fn brightness(&self, requested: f32) -> f32 {
+        match self {
+            PowerProfile::Full => requested,
+            PowerProfile::Saver => requested.min(Self::SAVER_MAX_BRIGHTNESS),
+        }
+    }
+
+    // post side of the profile, call whenever it changes
+    pub fn configure(&self, post: &mut PostChain) {
+        post.set_pixel_step(self.pixel_step());
+    }
+}
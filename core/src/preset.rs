@@ -0,0 +1,110 @@
+// presets bundle everything that makes up a "look": mode, palette, post effects and the
+// DSP front end. plain Copy data so the firmware can keep a bank in flash as is.
+
+use crate::postfx::{PostChain, PostFx, MAX_POST_STAGES};
+use crate::settings::DspSettings;
+use crate::text_input::Name;
+use crate::vis::{ModeKind, Visualizer};
+use crate::ColorPalette;
+
+pub const PRESET_SLOTS: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default, deny_unknown_fields))]
+pub struct Preset {
+    pub name: Name,
+    pub mode: ModeKind,
+    pub palette: ColorPalette,
+    pub post: [Option<PostFx>; MAX_POST_STAGES],
+    pub dsp: DspSettings,
+}
+
+impl Preset {
+    // snapshot of what is running right now
+    pub fn capture(name: &str, visualizer: &Visualizer, post: &PostChain, dsp: &DspSettings) -> Self {
+        Self {
+            name: Name::new(name),
+            mode: visualizer.current_mode(),
+            palette: *visualizer.palette(),
+            post: *post.slots(),
+            dsp: *dsp,
+        }
+    }
+
+    // the DSP lives outside the UI, so its settings are returned for the caller to forward
+    pub fn apply(&self, visualizer: &mut Visualizer, post: &mut PostChain) -> DspSettings {
+        visualizer.set_mode(self.mode);
+        visualizer.set_palette(self.palette);
+        post.set_slots(&self.post);
+        self.dsp
+    }
+}
+
+impl Default for Preset {
+    fn default() -> Self {
+        let post = PostChain::default();
+        Self {
+            name: Name::default(),
+            mode: ModeKind::HarmonicLoop,
+            palette: ColorPalette::default(),
+            post: *post.slots(),
+            dsp: DspSettings::default(),
+        }
+    }
+}
+
+// fixed number of slots, empty ones are None
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PresetBank {
+    slots: [Option<Preset>; PRESET_SLOTS],
+    active: Option<usize>,
+}
+
+impl PresetBank {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, slot: usize) -> Option<&Preset> {
+        self.slots.get(slot)?.as_ref()
+    }
+
+    // false if the slot is out of range
+    pub fn set(&mut self, slot: usize, preset: Preset) -> bool {
+        match self.slots.get_mut(slot) {
+            Some(s) => {
+                *s = Some(preset);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn remove(&mut self, slot: usize) -> Option<Preset> {
+        if self.active == Some(slot) {
+            self.active = None;
+        }
+        self.slots.get_mut(slot)?.take()
+    }
+
+    // mark a slot as the running one, None if it is empty
+    pub fn select(&mut self, slot: usize) -> Option<&Preset> {
+        let preset = self.slots.get(slot)?.as_ref()?;
+        self.active = Some(slot);
+        Some(preset)
+    }
+
+    pub fn active(&self) -> Option<usize> {
+        self.active
+    }
+
+    // occupied slots with their index
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &Preset)> + '_ {
+        self.slots.iter().enumerate().filter_map(|(i, p)| p.as_ref().map(|p| (i, p)))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.iter().all(Option::is_none)
+    }
+}
@@ -0,0 +1,272 @@
+// full-screen procedural backdrops. every visible pixel is computed each frame, so these
+// double as the worst case for the per-pixel render path.
+// voice energy speeds up the animation and brightens the colors.
+
+use core::f32::consts::TAU;
+use core::ops::Range;
+
+use libm::sqrtf;
+
+use crate::param::Param;
+use crate::polar::{self, sin};
+use crate::region::{RenderRegion, DISPLAY_REGION};
+use crate::source::AnalysisFrame;
+use crate::vis::VisualMode;
+use crate::{Color, ColorPalette, EnvelopeSmoother, DISPLAY_CENTER, DISPLAY_SIZE};
+
+const MAX_CHANNELS: usize = crate::CHANNELS;
+
+// overall loudness -> animation clock, shared by the procedural modes
+struct EnergyDrive {
+    level: EnvelopeSmoother,
+    time: f32,
+    idle_speed: f32,
+    loud_speed: f32,
+}
+
+impl EnergyDrive {
+    fn new(idle_speed: f32, loud_speed: f32) -> Self {
+        Self {
+            level: EnvelopeSmoother::new(60.0, 40.0, 600.0),
+            time: 0.0,
+            idle_speed,
+            loud_speed,
+        }
+    }
+
+    fn update(&mut self, dt: f32, energies: &[f32]) {
+        let mean = if energies.is_empty() { 0.0 } else { energies.iter().sum::<f32>() / energies.len() as f32 };
+        let level = self.level.process(mean.clamp(0.0, 1.0));
+        self.time += dt * (self.idle_speed + (self.loud_speed - self.idle_speed) * level);
+        // keep the clock small so f32 phases stay precise over long runs
+        if self.time > 1000.0 * TAU {
+            self.time -= 1000.0 * TAU;
+        }
+    }
+
+    fn level(&self) -> f32 {
+        self.level.value()
+    }
+}
+
+// classic demoscene plasma, a sum of four sine fields
+pub struct Plasma {
+    drive: EnergyDrive,
+    params: [Param; 2],
+}
+
+impl Plasma {
+    const SPEED: usize = 0; // params
+    const SCALE: usize = 1;
+
+    pub fn new() -> Self {
+        Self {
+            drive: EnergyDrive::new(0.6, 3.0),
+            params: [Param::new("speed", "Speed", 0.25, 3.0, 0.25, 1.0), Param::new("scale", "Zoom", 0.5, 2.0, 0.1, 1.0)],
+        }
+    }
+
+    pub fn update(&mut self, dt: f32, energies: &[f32]) {
+        self.drive.update(dt * self.params[Self::SPEED].value, energies);
+    }
+
+    pub fn render_with_palette<F>(&self, set_pixel: F, pal: &ColorPalette)
+    where
+        F: FnMut(usize, usize, Color),
+    {
+        self.draw_rows(&DISPLAY_REGION, 0..DISPLAY_SIZE, 1, set_pixel, pal);
+    }
+
+    fn draw_rows<F>(&self, region: &RenderRegion, band: Range<usize>, step: usize, mut set_pixel: F, pal: &ColorPalette)
+    where
+        F: FnMut(usize, usize, Color),
+    {
+        let t = self.drive.time;
+        let level = self.drive.level();
+        // spatial frequencies shrink as the field is zoomed in
+        let k = 1.0 / self.params[Self::SCALE].value;
+
+        // the row, column and diagonal terms are separable, so only the radial one is per pixel
+        let mut cols = [0.0f32; DISPLAY_SIZE];
+        let mut rows = [0.0f32; DISPLAY_SIZE];
+        let mut diag = [0.0f32; 2 * DISPLAY_SIZE];
+        for (i, (c, r)) in cols.iter_mut().zip(rows.iter_mut()).enumerate() {
+            *c = sin(i as f32 * 0.045 * k + t);
+            *r = sin(i as f32 * 0.031 * k - t * 0.7);
+        }
+        for (i, d) in diag.iter_mut().enumerate() {
+            *d = sin(i as f32 * 0.022 * k + t * 1.3);
+        }
+
+        // the ripple center wanders around the display
+        let (rx, ry) = (DISPLAY_CENTER + 50.0 * sin(t * 0.31), DISPLAY_CENTER + 50.0 * sin(t * 0.23 + 1.0));
+        let brightness = 0.35 + 0.65 * level;
+        let hue_shift = t * 0.03;
+
+        for (y, columns) in region.grid(band, step) {
+            let dy = y as f32 - ry;
+            for x in columns {
+                let dx = x as f32 - rx;
+                let radial = sin(sqrtf(dx * dx + dy * dy) * 0.06 * k - t * 1.1);
+                let v = cols[x] + rows[y] + diag[x + y] + radial; // -4..4
+                let color = pal.sample((v * 0.125 + 0.5 + hue_shift).rem_euclid(1.0));
+                set_pixel(x, y, color.scale(brightness));
+            }
+        }
+    }
+}
+
+impl VisualMode for Plasma {
+    fn update(&mut self, dt: f32, frame: &AnalysisFrame) {
+        Plasma::update(self, dt, frame.bands());
+    }
+
+    fn render(&self, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.render_with_palette(set_pixel, pal);
+    }
+
+    fn render_rows(&self, rows: Range<usize>, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.draw_rows(&DISPLAY_REGION, rows, 1, set_pixel, pal);
+    }
+
+    fn render_region(&self, region: &RenderRegion, step: usize, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.draw_rows(region, 0..DISPLAY_SIZE, step, set_pixel, pal);
+    }
+
+    fn stays_in_region(&self) -> bool {
+        true
+    }
+
+    fn params(&self) -> &[Param] {
+        &self.params
+    }
+
+    fn params_mut(&mut self) -> &mut [Param] {
+        &mut self.params
+    }
+}
+
+impl Default for Plasma {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+const AURORA_SECTORS: usize = 128;
+
+// radial aurora: a glowing curtain around the center whose height follows the bands,
+// low channels along the top going clockwise
+pub struct Aurora {
+    num_channels: usize,
+    drive: EnergyDrive,
+    smoothers: [EnvelopeSmoother; MAX_CHANNELS],
+    energies: [f32; MAX_CHANNELS],
+    params: [Param; 2],
+}
+
+impl Aurora {
+    const SPEED: usize = 0; // params
+    const REACH: usize = 1; // how far a loud band pulls the curtain in, share of the radius
+
+    pub fn new(num_channels: usize) -> Self {
+        Self {
+            num_channels: num_channels.min(MAX_CHANNELS),
+            drive: EnergyDrive::new(0.3, 1.5),
+            smoothers: core::array::from_fn(|_| EnvelopeSmoother::new(60.0, 30.0, 250.0)),
+            energies: [0.0; MAX_CHANNELS],
+            params: [Param::new("speed", "Speed", 0.25, 3.0, 0.25, 1.0), Param::new("reach", "Reach", 0.0, 0.6, 0.05, 0.35)],
+        }
+    }
+
+    pub fn update(&mut self, dt: f32, energies: &[f32]) {
+        self.drive.update(dt * self.params[Self::SPEED].value, energies);
+        for i in 0..self.num_channels {
+            let e = energies.get(i).copied().unwrap_or(0.0);
+            self.energies[i] = self.smoothers[i].process(e);
+        }
+    }
+
+    // band energy at an angle, linearly interpolated between neighbouring channels
+    fn energy_at(&self, angle: f32) -> f32 {
+        if self.num_channels == 0 {
+            return 0.0;
+        }
+        let pos = polar::wrap_angle(angle + core::f32::consts::FRAC_PI_2) / TAU * self.num_channels as f32;
+        let i = pos as usize % self.num_channels;
+        let frac = pos - libm::floorf(pos);
+        let next = (i + 1) % self.num_channels;
+        self.energies[i] + (self.energies[next] - self.energies[i]) * frac
+    }
+
+    pub fn render_with_palette<F>(&self, set_pixel: F, pal: &ColorPalette)
+    where
+        F: FnMut(usize, usize, Color),
+    {
+        self.draw_rows(&DISPLAY_REGION, 0..DISPLAY_SIZE, 1, set_pixel, pal);
+    }
+
+    fn draw_rows<F>(&self, region: &RenderRegion, rows: Range<usize>, step: usize, mut set_pixel: F, pal: &ColorPalette)
+    where
+        F: FnMut(usize, usize, Color),
+    {
+        let t = self.drive.time;
+        let level = self.drive.level();
+
+        // curtain radius (0-1 of the display radius) per angular sector for this frame
+        let mut curtain = [0.0f32; AURORA_SECTORS];
+        for (k, c) in curtain.iter_mut().enumerate() {
+            let a = k as f32 / AURORA_SECTORS as f32 * TAU;
+            let wave = 0.06 * sin(a * 3.0 + t) + 0.04 * sin(a * 5.0 - t * 1.7);
+            *c = 0.75 + wave - self.params[Self::REACH].value * self.energy_at(a);
+        }
+
+        let brightness = 0.3 + 0.7 * level;
+        for (y, columns) in region.grid(rows, step) {
+            for x in columns {
+                let (angle, radius) = polar::to_polar(x as f32, y as f32);
+                let r = radius / DISPLAY_CENTER;
+                let pos = polar::wrap_angle(angle) / TAU * AURORA_SECTORS as f32;
+                let k = pos as usize % AURORA_SECTORS;
+                let frac = pos - libm::floorf(pos);
+                let edge = curtain[k] + (curtain[(k + 1) % AURORA_SECTORS] - curtain[k]) * frac;
+                let d = (r - edge) * 9.0;
+                // sharp edge towards the center, long fade outwards like a real curtain
+                let glow = if d < 0.0 { 1.0 / (1.0 + d * d * 4.0) } else { 1.0 / (1.0 + d * d * 0.3) };
+                // faint sky gradient so the center never goes fully black
+                let sky = 0.08 * (1.0 - r);
+                let color = pal.sample((r * 0.6 + 0.08 * sin(angle * 2.0 + t * 0.5) + t * 0.02).rem_euclid(1.0));
+                set_pixel(x, y, color.scale(((glow + sky) * brightness).min(1.0)));
+            }
+        }
+    }
+}
+
+impl VisualMode for Aurora {
+    fn update(&mut self, dt: f32, frame: &AnalysisFrame) {
+        Aurora::update(self, dt, frame.bands());
+    }
+
+    fn render(&self, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.render_with_palette(set_pixel, pal);
+    }
+
+    fn render_rows(&self, rows: Range<usize>, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.draw_rows(&DISPLAY_REGION, rows, 1, set_pixel, pal);
+    }
+
+    fn render_region(&self, region: &RenderRegion, step: usize, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.draw_rows(region, 0..DISPLAY_SIZE, step, set_pixel, pal);
+    }
+
+    fn stays_in_region(&self) -> bool {
+        true
+    }
+
+    fn params(&self) -> &[Param] {
+        &self.params
+    }
+
+    fn params_mut(&mut self) -> &mut [Param] {
+        &mut self.params
+    }
+}
@@ -0,0 +1,93 @@
+use core::iter::StepBy;
+use core::ops::Range;
+
+use crate::DISPLAY_SIZE;
+
+// per-row horizontal spans of the visible part of the panel, so per-pixel passes can skip
+// the corners of the round display (~21% of a 240x240 buffer) without testing every pixel
+pub struct RenderRegion {
+    spans: [(u16, u16); DISPLAY_SIZE], // (x_start, x_end), end exclusive
+}
+
+// the round GC9A01 panel, agrees pixel for pixel with in_display()
+pub static DISPLAY_REGION: RenderRegion = RenderRegion::round();
+
+const fn isqrt(n: u32) -> u32 {
+    let mut x = 0;
+    while (x + 1) * (x + 1) <= n {
+        x += 1;
+    }
+    x
+}
+
+impl RenderRegion {
+    pub const fn round() -> Self {
+        let c = (DISPLAY_SIZE / 2) as i32;
+        let mut spans = [(0u16, 0u16); DISPLAY_SIZE];
+        let mut y = 0;
+        while y < DISPLAY_SIZE {
+            let dy = y as i32 - c;
+            let half = isqrt((c * c - dy * dy) as u32) as i32;
+            let start = c - half;
+            let end = if c + half + 1 > DISPLAY_SIZE as i32 { DISPLAY_SIZE as i32 } else { c + half + 1 };
+            spans[y] = (start as u16, end as u16);
+            y += 1;
+        }
+        Self { spans }
+    }
+
+    pub const fn full() -> Self {
+        Self { spans: [(0, DISPLAY_SIZE as u16); DISPLAY_SIZE] }
+    }
+
+    // the visible pixels `inside` accepts, one span from the first to the last of them per
+    // row, so exact for convex shapes (a wedge of the round panel) and a superset otherwise
+    pub fn covering<F>(inside: F) -> Self
+    where
+        F: Fn(usize, usize) -> bool,
+    {
+        let mut spans = [(0u16, 0u16); DISPLAY_SIZE];
+        for (y, x_start, x_end) in DISPLAY_REGION.rows() {
+            let mut columns = (x_start..x_end).filter(|&x| inside(x, y));
+            if let Some(first) = columns.next() {
+                let last = columns.last().unwrap_or(first);
+                spans[y] = (first as u16, last as u16 + 1);
+            }
+        }
+        Self { spans }
+    }
+
+    // visible x range of a row, empty for rows outside the display
+    pub fn span(&self, y: usize) -> (usize, usize) {
+        match self.spans.get(y) {
+            Some(&(start, end)) => (start as usize, end as usize),
+            None => (0, 0),
+        }
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        let (start, end) = self.span(y);
+        x >= start && x < end
+    }
+
+    // (y, x_start, x_end) for every row
+    pub fn rows(&self) -> impl Iterator<Item = (usize, usize, usize)> + '_ {
+        self.spans.iter().enumerate().map(|(y, &(start, end))| (y, start as usize, end as usize))
+    }
+
+    // (y, visible columns) for the rows in `rows` on a grid of `step` pixels, rows and columns
+    // on multiples of step. step 1 is every visible pixel, the reduced resolution of
+    // PowerProfile::Saver passes 2 and PostChain::end_frame doubles the pixels
+    pub fn grid(&self, rows: Range<usize>, step: usize) -> impl Iterator<Item = (usize, StepBy<Range<usize>>)> + '_ {
+        let step = step.max(1);
+        let first = rows.start.next_multiple_of(step);
+        (first..rows.end.min(DISPLAY_SIZE)).step_by(step).map(move |y| {
+            let (start, end) = self.span(y);
+            (y, (start.next_multiple_of(step)..end).step_by(step))
+        })
+    }
+
+    pub fn pixel_count(&self) -> usize {
+        self.spans.iter().map(|&(start, end)| (end - start) as usize).sum()
+    }
+}
@@ -0,0 +1,103 @@
+// visual modes from outside core. a downstream crate declares a ModeInfo for its mode and hands
+// each Visualizer an instance, the mode then joins the built-in ones as ModeKind::User wherever
+// modes are listed: cycling, the menu, the control protocol, config files. the catalog of
+// declared modes is program-wide so ids resolve before any visualizer exists (settings are
+// loaded first), the instances belong to each visualizer.
+//
+// without an allocator the instances are `&'static mut` (e.g. out of a `static` the firmware
+// owns), with the `alloc` feature they can be boxed. either way at most MAX_USER_MODES.
+
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+use crate::vis::{ModeKind, VisualMode};
+
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+
+pub const MAX_USER_MODES: usize = 8;
+
+// what the rest of the ui knows about a registered mode
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ModeInfo {
+    pub id: &'static str, // short and stable, for config files and the control protocol
+    pub name: &'static str,
+    pub full_screen: bool, // like ModeKind::is_full_screen
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RegisterError {
+    Full,
+    IdTaken, // a built-in mode has this id
+}
+
+impl core::fmt::Display for RegisterError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            RegisterError::Full => write!(f, "no room for another mode, at most {} can be registered", MAX_USER_MODES),
+            RegisterError::IdTaken => f.write_str("mode id is taken by a built-in mode"),
+        }
+    }
+}
+
+// only ever holds pointers made from `&'static ModeInfo`, filled front to back and never cleared
+static CATALOG: [AtomicPtr<ModeInfo>; MAX_USER_MODES] = [const { AtomicPtr::new(ptr::null_mut()) }; MAX_USER_MODES];
+
+// adds `info` to the catalog. declaring an id again hands back the mode it already has
+pub fn declare(info: &'static ModeInfo) -> Result<ModeKind, RegisterError> {
+    if ModeKind::ALL.iter().any(|m| m.id() == info.id) {
+        return Err(RegisterError::IdTaken);
+    }
+    let wanted = info as *const ModeInfo as *mut ModeInfo;
+    for (i, slot) in CATALOG.iter().enumerate() {
+        let existing = match slot.compare_exchange(ptr::null_mut(), wanted, Ordering::AcqRel, Ordering::Acquire) {
+            Ok(_) => return Ok(ModeKind::User(i as u8)),
+            Err(existing) => existing,
+        };
+        // SAFETY: non-null entries came from a `&'static ModeInfo`
+        if unsafe { &*existing }.id == info.id {
+            return Ok(ModeKind::User(i as u8));
+        }
+    }
+    Err(RegisterError::Full)
+}
+
+pub fn info(index: usize) -> Option<&'static ModeInfo> {
+    let entry = CATALOG.get(index)?.load(Ordering::Acquire);
+    // SAFETY: as in declare
+    (!entry.is_null()).then(|| unsafe { &*entry })
+}
+
+// declared modes so far
+pub fn count() -> usize {
+    (0..MAX_USER_MODES).take_while(|&i| info(i).is_some()).count()
+}
+
+pub fn find(id: &str) -> Option<ModeKind> {
+    (0..count()).find(|&i| info(i).is_some_and(|info| info.id == id)).map(|i| ModeKind::User(i as u8))
+}
+
+// shared with the render threads, hence Send + Sync
+pub(crate) enum UserMode {
+    Static(&'static mut (dyn VisualMode + Send + Sync)),
+    #[cfg(feature = "alloc")]
+    Boxed(Box<dyn VisualMode + Send + Sync>),
+}
+
+impl UserMode {
+    pub(crate) fn get(&self) -> &dyn VisualMode {
+        match self {
+            UserMode::Static(mode) => &**mode,
+            #[cfg(feature = "alloc")]
+            UserMode::Boxed(mode) => &**mode,
+        }
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut dyn VisualMode {
+        match self {
+            UserMode::Static(mode) => &mut **mode,
+            #[cfg(feature = "alloc")]
+            UserMode::Boxed(mode) => &mut **mode,
+        }
+    }
+}
@@ -0,0 +1,122 @@
+// how band energies (0-1) map onto what the modes draw. straight energies look spiky: most of
+// a voice sits low in the range with the odd peak near the top, so the shapes mostly idle and
+// then jump. sqrt and log lift the quiet part, a power above 1 does the opposite and keeps
+// only the peaks. applied by the Visualizer after smoothing, one curve for every mode unless a
+// mode has its own.
+
+use crate::vis::ModeKind;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ResponseCurve {
+    #[default]
+    Linear,
+    Sqrt,
+    Log { floor_db: f32 },   // this many dB below full scale reads as 0
+    Power { exponent: f32 }, // below 1 lifts quiet bands like sqrt (0.5), above 1 flattens them
+}
+
+impl ResponseCurve {
+    pub const FLOOR_RANGE: (f32, f32) = (6.0, 96.0);
+    pub const EXPONENT_RANGE: (f32, f32) = (0.1, 4.0);
+
+    // what the menu steps through
+    pub const CHOICES: [ResponseCurve; 6] = [
+        ResponseCurve::Linear,
+        ResponseCurve::Sqrt,
+        ResponseCurve::Log { floor_db: 40.0 },
+        ResponseCurve::Log { floor_db: 60.0 },
+        ResponseCurve::Power { exponent: 0.7 },
+        ResponseCurve::Power { exponent: 1.5 },
+    ];
+
+    pub fn apply(&self, energy: f32) -> f32 {
+        let energy = energy.clamp(0.0, 1.0);
+        match *self {
+            ResponseCurve::Linear => energy,
+            ResponseCurve::Sqrt => libm::sqrtf(energy),
+            ResponseCurve::Log { floor_db } if energy > 0.0 => {
+                let floor = floor_db.clamp(Self::FLOOR_RANGE.0, Self::FLOOR_RANGE.1);
+                ((20.0 * libm::log10f(energy) + floor) / floor).max(0.0)
+            }
+            ResponseCurve::Log { .. } => 0.0,
+            ResponseCurve::Power { exponent } => {
+                libm::powf(energy, exponent.clamp(Self::EXPONENT_RANGE.0, Self::EXPONENT_RANGE.1))
+            }
+        }
+    }
+
+    pub fn apply_all(&self, energies: &mut [f32]) {
+        if *self != ResponseCurve::Linear {
+            for e in energies {
+                *e = self.apply(*e);
+            }
+        }
+    }
+
+    // "linear", "sqrt", "log:<floor dB>" or "power:<exponent>"
+    pub fn parse(text: &str) -> Option<ResponseCurve> {
+        match text.split_once(':') {
+            None if text == "linear" => Some(ResponseCurve::Linear),
+            None if text == "sqrt" => Some(ResponseCurve::Sqrt),
+            Some(("log", value)) => {
+                let floor_db: f32 = value.parse().ok()?;
+                (Self::FLOOR_RANGE.0..=Self::FLOOR_RANGE.1).contains(&floor_db).then_some(ResponseCurve::Log { floor_db })
+            }
+            Some(("power", value)) => {
+                let exponent: f32 = value.parse().ok()?;
+                (Self::EXPONENT_RANGE.0..=Self::EXPONENT_RANGE.1).contains(&exponent).then_some(ResponseCurve::Power { exponent })
+            }
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ResponseCurve::Linear => "Linear",
+            ResponseCurve::Sqrt => "Sqrt",
+            ResponseCurve::Log { .. } => "Log",
+            ResponseCurve::Power { .. } => "Power",
+        }
+    }
+}
+
+// same syntax parse() accepts
+impl core::fmt::Display for ResponseCurve {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ResponseCurve::Linear => f.write_str("linear"),
+            ResponseCurve::Sqrt => f.write_str("sqrt"),
+            ResponseCurve::Log { floor_db } => write!(f, "log:{}", floor_db),
+            ResponseCurve::Power { exponent } => write!(f, "power:{}", exponent),
+        }
+    }
+}
+
+// the global curve and the modes that override it
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default, deny_unknown_fields))]
+pub struct ResponseCurves {
+    pub global: ResponseCurve,
+    // by ModeKind::ALL position, in config files a table of mode id to curve
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impls::mode_curves"))]
+    pub modes: [Option<ResponseCurve>; ModeKind::ALL.len()],
+}
+
+impl ResponseCurves {
+    // the curve `mode` draws with
+    pub fn get(&self, mode: ModeKind) -> ResponseCurve {
+        self.mode(mode).unwrap_or(self.global)
+    }
+
+    // registered modes (ModeKind::User) always follow the global curve
+    pub fn mode(&self, mode: ModeKind) -> Option<ResponseCurve> {
+        self.modes[mode.index()?]
+    }
+
+    // None puts the mode back on the global curve. ignored for registered modes
+    pub fn set_mode(&mut self, mode: ModeKind, curve: Option<ResponseCurve>) {
+        if let Some(i) = mode.index() {
+            self.modes[i] = curve;
+        }
+    }
+}
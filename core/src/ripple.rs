@@ -0,0 +1,205 @@
+// water surface in a round bowl: voice onsets drop stones around the rim at the angle of the
+// loudest band (low channels along the top going clockwise, like the aurora), and a 120x120
+// wave-equation grid carries the ripples, reflecting off the edge of the panel. the grid is
+// the demoscene water effect in 16-bit fixed point, upscaled 2x with bilinear filtering.
+
+use core::f32::consts::{FRAC_PI_2, TAU};
+use core::ops::Range;
+
+use libm::{cosf, sinf, sqrtf};
+
+use crate::onset::OnsetDetector;
+use crate::particle::Rng;
+use crate::param::Param;
+use crate::region::{RenderRegion, DISPLAY_REGION};
+use crate::source::AnalysisFrame;
+use crate::vis::VisualMode;
+use crate::{Color, ColorPalette, EnvelopeSmoother, DISPLAY_SIZE};
+
+pub const RIPPLE_GRID: usize = 120;
+const SCALE: usize = DISPLAY_SIZE / RIPPLE_GRID;
+const CELLS: usize = RIPPLE_GRID * RIPPLE_GRID;
+
+// simulation steps per second, ripples travel a cell per step
+const STEP_RATE: f32 = 60.0;
+const MAX_STEPS_PER_FRAME: usize = 4; // after a stall, drop time instead of catching up
+const UNIT: f32 = 8192.0; // grid height of a full-strength stone
+
+pub struct Ripple {
+    heights: [[i16; CELLS]; 2], // current and previous step, swapped every step
+    current: usize,
+    spans: [(u8, u8); RIPPLE_GRID], // wet cells per grid row, end exclusive, the rest is the wall
+    onsets: OnsetDetector,
+    level: EnvelopeSmoother,
+    pending: f32, // simulation time not stepped yet
+    rng: Rng,
+    params: [Param; 3],
+}
+
+impl Ripple {
+    const DAMPING: usize = 0; // params
+    const STONE: usize = 1;
+    const SENSITIVITY: usize = 2;
+
+    pub fn new() -> Self {
+        let c = RIPPLE_GRID as f32 / 2.0;
+        let spans = core::array::from_fn(|y| {
+            let dy = y as f32 + 0.5 - c;
+            let half = sqrtf((c * c - dy * dy).max(0.0));
+            ((c - half).ceil() as u8, ((c + half) as usize).min(RIPPLE_GRID) as u8)
+        });
+        Self {
+            heights: [[0; CELLS]; 2],
+            current: 0,
+            spans,
+            onsets: OnsetDetector::new(),
+            level: EnvelopeSmoother::new(60.0, 40.0, 600.0),
+            pending: 0.0,
+            rng: Rng::new(0x9e37_79b9),
+            params: [
+                Param::new("damping", "Calm", 0.5, 6.0, 0.5, 2.0),
+                Param::new("stone", "Stone size", 1.0, 6.0, 0.5, 3.0),
+                Param::new("sensitivity", "Sensitivity", 0.5, 2.0, 0.1, 1.0),
+            ],
+        }
+    }
+
+    pub fn update(&mut self, dt: f32, energies: &[f32]) {
+        let mean = if energies.is_empty() { 0.0 } else { energies.iter().sum::<f32>() / energies.len() as f32 };
+        self.level.process(mean.clamp(0.0, 1.0));
+
+        self.onsets.set_sensitivity(self.params[Self::SENSITIVITY].value);
+        if let Some(onset) = self.onsets.update(dt, energies) {
+            let n = energies.len().max(1) as f32;
+            let angle = -FRAC_PI_2 + (onset.band as f32 + 0.5) / n * TAU + (self.rng.next_f32() - 0.5) * 0.3;
+            let reach = 0.35 + 0.4 * self.rng.next_f32(); // share of the bowl radius
+            self.drop_stone(angle, reach, 0.4 + 0.6 * onset.strength);
+        }
+
+        self.pending = (self.pending + dt).min(MAX_STEPS_PER_FRAME as f32 / STEP_RATE);
+        while self.pending >= 1.0 / STEP_RATE {
+            self.pending -= 1.0 / STEP_RATE;
+            self.step();
+        }
+    }
+
+    // a dent in the surface at `angle`, `reach` (0-1) of the way out, `strength` 0-1
+    pub fn drop_stone(&mut self, angle: f32, reach: f32, strength: f32) {
+        let c = RIPPLE_GRID as f32 / 2.0;
+        let (cx, cy) = (c + reach * c * cosf(angle), c + reach * c * sinf(angle));
+        let r = self.params[Self::STONE].value;
+        let depth = strength * UNIT;
+        let current = &mut self.heights[self.current];
+        let (y0, y1) = ((cy - r).max(0.0) as usize, ((cy + r) as usize + 1).min(RIPPLE_GRID));
+        let (x0, x1) = ((cx - r).max(0.0) as usize, ((cx + r) as usize + 1).min(RIPPLE_GRID));
+        for y in y0..y1 {
+            let (start, end) = self.spans[y];
+            for x in x0.max(start as usize)..x1.min(end as usize) {
+                let (dx, dy) = (x as f32 - cx, y as f32 - cy);
+                let falloff = 1.0 - (dx * dx + dy * dy) / (r * r);
+                if falloff > 0.0 {
+                    let h = &mut current[y * RIPPLE_GRID + x];
+                    *h = (*h as f32 - depth * falloff).max(i16::MIN as f32) as i16;
+                }
+            }
+        }
+    }
+
+    // one step of the wave equation: the neighbours' mean twice, minus the previous height,
+    // written over the previous step's buffer. the wall cells stay 0 and reflect
+    fn step(&mut self) {
+        // damping as a share of the height lost per step, in 1/256
+        let damping = (self.params[Self::DAMPING].value * 2.56) as i32;
+        let [a, b] = &mut self.heights;
+        let (current, next) = if self.current == 0 { (&*a, b) } else { (&*b, a) };
+        for y in 1..RIPPLE_GRID - 1 {
+            let (start, end) = self.spans[y];
+            let row = y * RIPPLE_GRID;
+            for x in (start as usize).max(1)..(end as usize).min(RIPPLE_GRID - 1) {
+                let i = row + x;
+                let sum = current[i - 1] as i32 + current[i + 1] as i32 + current[i - RIPPLE_GRID] as i32 + current[i + RIPPLE_GRID] as i32;
+                let h = (sum >> 1) - next[i] as i32;
+                next[i] = (h - ((h * damping) >> 8)).clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+            }
+        }
+        self.current ^= 1;
+    }
+
+    // grid height at a cell, 0 off the grid
+    fn cell(&self, x: isize, y: isize) -> f32 {
+        if x < 0 || y < 0 || x >= RIPPLE_GRID as isize || y >= RIPPLE_GRID as isize {
+            return 0.0;
+        }
+        self.heights[self.current][y as usize * RIPPLE_GRID + x as usize] as f32
+    }
+
+    pub fn render_with_palette<F>(&self, set_pixel: F, pal: &ColorPalette)
+    where
+        F: FnMut(usize, usize, Color),
+    {
+        self.draw_rows(&DISPLAY_REGION, 0..DISPLAY_SIZE, 1, set_pixel, pal);
+    }
+
+    fn draw_rows<F>(&self, region: &RenderRegion, rows: Range<usize>, step: usize, mut set_pixel: F, pal: &ColorPalette)
+    where
+        F: FnMut(usize, usize, Color),
+    {
+        let ambient = 0.06 + 0.1 * self.level.value();
+        for (y, columns) in region.grid(rows, step) {
+            // grid position of the pixel center
+            let gy = (y as f32 + 0.5) / SCALE as f32 - 0.5;
+            let (cy, fy) = (libm::floorf(gy) as isize, gy - libm::floorf(gy));
+            for x in columns {
+                let gx = (x as f32 + 0.5) / SCALE as f32 - 0.5;
+                let (cx, fx) = (libm::floorf(gx) as isize, gx - libm::floorf(gx));
+                let (h00, h10) = (self.cell(cx, cy), self.cell(cx + 1, cy));
+                let (h01, h11) = (self.cell(cx, cy + 1), self.cell(cx + 1, cy + 1));
+                let top = h00 + (h10 - h00) * fx;
+                let bottom = h01 + (h11 - h01) * fx;
+                let height = (top + (bottom - top) * fy) / UNIT;
+                // lit from the top left, slopes facing it catch the light
+                let slope = ((h00 - h10) + (h00 - h01)) / UNIT;
+
+                let shade = (ambient + 0.9 * height.abs() + 2.0 * slope.max(0.0)).min(1.0);
+                let color = pal.sample((0.5 + 0.5 * height).clamp(0.0, 1.0));
+                set_pixel(x, y, color.scale(shade));
+            }
+        }
+    }
+}
+
+impl VisualMode for Ripple {
+    fn update(&mut self, dt: f32, frame: &AnalysisFrame) {
+        Ripple::update(self, dt, frame.bands());
+    }
+
+    fn render(&self, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.render_with_palette(set_pixel, pal);
+    }
+
+    fn render_rows(&self, rows: Range<usize>, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.draw_rows(&DISPLAY_REGION, rows, 1, set_pixel, pal);
+    }
+
+    fn render_region(&self, region: &RenderRegion, step: usize, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.draw_rows(region, 0..DISPLAY_SIZE, step, set_pixel, pal);
+    }
+
+    fn stays_in_region(&self) -> bool {
+        true
+    }
+
+    fn params(&self) -> &[Param] {
+        &self.params
+    }
+
+    fn params_mut(&mut self) -> &mut [Param] {
+        &mut self.params
+    }
+}
+
+impl Default for Ripple {
+    fn default() -> Self {
+        Self::new()
+    }
+}
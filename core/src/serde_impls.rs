@@ -0,0 +1,286 @@
+// hand-written serde impls for types that have a natural text form: modes, policies, profiles,
+// parameters and effects use the same strings as the cli and preset files, colors are "#rrggbb" in
+// human-readable formats (toml) and three bytes in binary ones (postcard)
+
+use core::fmt;
+use core::marker::PhantomData;
+
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::param::ParamId;
+use crate::postfx::PostFx;
+use crate::power::PowerProfile;
+use crate::response::ResponseCurve;
+use crate::settings::{Detector, Filterbank, StartupPolicy};
+use crate::text_input::Name;
+use crate::vis::ModeKind;
+use crate::Color;
+
+// visitor for anything parsed from a string
+struct StrVisitor<T> {
+    parse: fn(&str) -> Option<T>,
+    expecting: &'static str,
+    _marker: PhantomData<T>,
+}
+
+impl<T> StrVisitor<T> {
+    fn new(parse: fn(&str) -> Option<T>, expecting: &'static str) -> Self {
+        Self { parse, expecting, _marker: PhantomData }
+    }
+}
+
+impl<T> Visitor<'_> for StrVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.expecting)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+        (self.parse)(v).ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self))
+    }
+}
+
+impl Serialize for ModeKind {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(self.id())
+    }
+}
+
+impl<'de> Deserialize<'de> for ModeKind {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        d.deserialize_str(StrVisitor::new(ModeKind::from_id, "a mode id"))
+    }
+}
+
+impl Serialize for ParamId {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(self.id())
+    }
+}
+
+impl<'de> Deserialize<'de> for ParamId {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        d.deserialize_str(StrVisitor::new(ParamId::from_id, "a parameter id"))
+    }
+}
+
+impl Serialize for StartupPolicy {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for StartupPolicy {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        d.deserialize_str(StrVisitor::new(StartupPolicy::parse, "last, demo or mode:<id>"))
+    }
+}
+
+impl Serialize for PowerProfile {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(self.id())
+    }
+}
+
+impl<'de> Deserialize<'de> for PowerProfile {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        d.deserialize_str(StrVisitor::new(PowerProfile::from_id, "full or saver"))
+    }
+}
+
+impl Serialize for Filterbank {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(self.id())
+    }
+}
+
+impl<'de> Deserialize<'de> for Filterbank {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        d.deserialize_str(StrVisitor::new(Filterbank::from_id, "narrow or mel"))
+    }
+}
+
+impl Serialize for Detector {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(self.id())
+    }
+}
+
+impl<'de> Deserialize<'de> for Detector {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        d.deserialize_str(StrVisitor::new(Detector::from_id, "peak, rms or log"))
+    }
+}
+
+impl Serialize for PostFx {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for PostFx {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        d.deserialize_str(StrVisitor::new(PostFx::parse, "trails:<decay>, blur:<amount>, scanlines:<depth>, hue:<speed>, hue:sync or kaleido:<folds>[:mirror]"))
+    }
+}
+
+impl Serialize for ResponseCurve {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ResponseCurve {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        d.deserialize_str(StrVisitor::new(ResponseCurve::parse, "linear, sqrt, log:<floor dB> or power:<exponent>"))
+    }
+}
+
+impl Serialize for Name {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Name {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        // longer names are cut off like typed input would be
+        d.deserialize_str(StrVisitor::new(|s| Some(Name::new(s)), "a name"))
+    }
+}
+
+fn parse_hex_color(text: &str) -> Option<Color> {
+    let hex = text.strip_prefix('#').unwrap_or(text);
+    if hex.len() != 6 {
+        return None;
+    }
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    Some(Color::new((value >> 16) as u8, (value >> 8) as u8, value as u8))
+}
+
+struct HexColor(Color);
+
+impl fmt::Display for HexColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.0.r, self.0.g, self.0.b)
+    }
+}
+
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        if s.is_human_readable() {
+            s.collect_str(&HexColor(*self))
+        } else {
+            (self.r, self.g, self.b).serialize(s)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        if d.is_human_readable() {
+            d.deserialize_str(StrVisitor::new(parse_hex_color, "a #rrggbb color"))
+        } else {
+            let (r, g, b) = <(u8, u8, u8)>::deserialize(d)?;
+            Ok(Color::new(r, g, b))
+        }
+    }
+}
+
+// per-band lists in DspSettings: up to CHANNELS values, missing bands are the default (0,
+// peak). trailing defaults are left out, so an untrimmed config just says `trim = []`
+pub(crate) mod bands {
+    use super::*;
+    use serde::de::SeqAccess;
+    use serde::ser::SerializeSeq;
+
+    use crate::CHANNELS;
+
+    pub fn serialize<T, S>(values: &[T; CHANNELS], s: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize + Default + PartialEq,
+        S: Serializer,
+    {
+        let len = values.iter().rposition(|v| *v != T::default()).map_or(0, |i| i + 1);
+        let mut seq = s.serialize_seq(Some(len))?;
+        for value in &values[..len] {
+            seq.serialize_element(value)?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, T, D>(d: D) -> Result<[T; CHANNELS], D::Error>
+    where
+        T: Deserialize<'de> + Default + Copy,
+        D: Deserializer<'de>,
+    {
+        d.deserialize_seq(BandsVisitor(PhantomData))
+    }
+
+    struct BandsVisitor<T>(PhantomData<T>);
+
+    impl<'de, T: Deserialize<'de> + Default + Copy> Visitor<'de> for BandsVisitor<T> {
+        type Value = [T; CHANNELS];
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "at most {} per-band values", CHANNELS)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut values = [T::default(); CHANNELS];
+            let mut len = 0;
+            while let Some(value) = seq.next_element::<T>()? {
+                let slot = values.get_mut(len).ok_or_else(|| de::Error::invalid_length(len + 1, &self))?;
+                *slot = value;
+                len += 1;
+            }
+            Ok(values)
+        }
+    }
+}
+
+// per-mode response curves: a table of mode id to curve, modes on the global curve are left out
+pub(crate) mod mode_curves {
+    use super::*;
+    use serde::de::MapAccess;
+    use serde::ser::SerializeMap;
+
+    type Curves = [Option<ResponseCurve>; ModeKind::ALL.len()];
+
+    pub fn serialize<S: Serializer>(curves: &Curves, s: S) -> Result<S::Ok, S::Error> {
+        let mut map = s.serialize_map(Some(curves.iter().flatten().count()))?;
+        for (mode, curve) in ModeKind::ALL.iter().zip(curves) {
+            if let Some(curve) = curve {
+                map.serialize_entry(mode, curve)?;
+            }
+        }
+        map.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Curves, D::Error> {
+        d.deserialize_map(CurvesVisitor)
+    }
+
+    struct CurvesVisitor;
+
+    impl<'de> Visitor<'de> for CurvesVisitor {
+        type Value = Curves;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a table of mode id to response curve")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Curves, A::Error> {
+            let mut curves = [None; ModeKind::ALL.len()];
+            while let Some((mode, curve)) = map.next_entry::<ModeKind, ResponseCurve>()? {
+                let Some(i) = mode.index() else {
+                    return Err(de::Error::custom("registered modes follow the global curve"));
+                };
+                curves[i] = Some(curve);
+            }
+            Ok(curves)
+        }
+    }
+}
@@ -0,0 +1,296 @@
+use crate::calibrate::BandCalibration;
+use crate::input::EncoderSettings;
+use crate::power::PowerProfile;
+use crate::response::ResponseCurves;
+use crate::source::{ChannelCount, ChannelCountError};
+use crate::text_input::Name;
+use crate::vis::{ModeKind, Visualizer};
+use crate::{ColorPalette, CHANNELS};
+
+// what the badge shows after power-on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum StartupPolicy {
+    #[default]
+    LastUsed,           // resume whatever was on screen at power-off
+    Favorite(ModeKind), // always boot into one mode
+    DemoCycle,          // step through every mode
+}
+
+impl StartupPolicy {
+    pub const DEMO_SECONDS_PER_MODE: f32 = 20.0;
+
+    // "last", "demo" or "mode:<id>", shared by the config file, menu labels and control protocol
+    pub fn parse(text: &str) -> Option<StartupPolicy> {
+        match text.split_once(':') {
+            None if text == "last" => Some(StartupPolicy::LastUsed),
+            None if text == "demo" => Some(StartupPolicy::DemoCycle),
+            Some(("mode", id)) => ModeKind::from_id(id).map(StartupPolicy::Favorite),
+            _ => None,
+        }
+    }
+
+    // last, demo, then a favorite per mode in ModeKind::nth order
+    pub fn count() -> usize {
+        2 + ModeKind::count()
+    }
+
+    pub fn nth(n: usize) -> StartupPolicy {
+        match n {
+            0 => StartupPolicy::LastUsed,
+            1 => StartupPolicy::DemoCycle,
+            _ => StartupPolicy::Favorite(ModeKind::nth(n - 2)),
+        }
+    }
+
+    // this policy's n in nth
+    pub fn position(&self) -> usize {
+        match self {
+            StartupPolicy::LastUsed => 0,
+            StartupPolicy::DemoCycle => 1,
+            StartupPolicy::Favorite(mode) => 2 + mode.position(),
+        }
+    }
+
+    // `steps` policies on, wrapping around
+    pub fn step(&self, steps: i32) -> StartupPolicy {
+        let count = Self::count() as i32;
+        Self::nth((self.position() as i32 + steps).rem_euclid(count) as usize)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StartupPolicy::LastUsed => "Last used",
+            StartupPolicy::Favorite(_) => "Favorite",
+            StartupPolicy::DemoCycle => "Demo cycle",
+        }
+    }
+}
+
+// same syntax parse() accepts
+impl core::fmt::Display for StartupPolicy {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            StartupPolicy::LastUsed => f.write_str("last"),
+            StartupPolicy::Favorite(mode) => write!(f, "mode:{}", mode.id()),
+            StartupPolicy::DemoCycle => f.write_str("demo"),
+        }
+    }
+}
+
+// filterbank layout: number of bands and the mel-spaced center frequency range (Hz)
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default, deny_unknown_fields))]
+pub struct VocoderConfig {
+    pub num_channels: usize,
+    pub start_freq: f32,
+    pub end_freq: f32,
+    pub filterbank: Filterbank,
+}
+
+impl Default for VocoderConfig {
+    fn default() -> Self {
+        Self { num_channels: 12, start_freq: 100.0, end_freq: 3000.0, filterbank: Filterbank::Narrow }
+    }
+}
+
+// how the band filters cover the spectrum between the centers
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Filterbank {
+    // one bandpass of +-3.5% around each center, as the gateware has it now: gaps between the
+    // low bands, overlap between the high ones
+    #[default]
+    Narrow,
+    // triangular bands from one neighbouring center to the other, the standard mel response.
+    // the triangle is built from contiguous sub-bands at the centers and the mel midpoints
+    Mel,
+}
+
+impl Filterbank {
+    pub const ALL: [Filterbank; 2] = [Filterbank::Narrow, Filterbank::Mel];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            Filterbank::Narrow => "narrow",
+            Filterbank::Mel => "mel",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Filterbank> {
+        Self::ALL.into_iter().find(|f| f.id() == id)
+    }
+}
+
+impl VocoderConfig {
+    // num_channels once it's known to fit, check before building sources from a loaded config
+    pub fn channels(&self) -> Result<ChannelCount, ChannelCountError> {
+        ChannelCount::new(self.num_channels)
+    }
+}
+
+// analysis front end knobs, applied by whatever owns the DSP
+// (VocoderDSP in the simulator, gateware registers on the badge)
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default, deny_unknown_fields))]
+pub struct DspSettings {
+    pub agc: bool,        // normalize every band to its own recent peak
+    pub agc_release: f32, // seconds a band peak counts for, the agc normalizes to the loudest in that window
+    pub gain: f32,        // fixed gain on the band envelopes when agc is off
+    pub gate: f32,        // normalized energies below this read as silence, 0 disables
+    pub tilt: f32,        // dB per octave around TILT_PIVOT, positive lifts the high bands
+    // per-band lists start at the lowest band, in config files a shorter list leaves the rest at 0
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impls::bands"))]
+    pub trim: [f32; CHANNELS], // dB
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impls::bands"))]
+    pub floor: [f32; CHANNELS], // noise floor subtracted from the raw band envelope
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impls::bands"))]
+    pub detector: [Detector; CHANNELS],
+}
+
+impl Default for DspSettings {
+    fn default() -> Self {
+        Self {
+            agc: true,
+            agc_release: 0.144,
+            gain: 8.0,
+            gate: 0.0,
+            tilt: 0.0,
+            trim: [0.0; CHANNELS],
+            floor: [0.0; CHANNELS],
+            detector: [Detector::Peak; CHANNELS],
+        }
+    }
+}
+
+impl DspSettings {
+    pub const TILT_RANGE: (f32, f32) = (-12.0, 12.0);
+    pub const TILT_PIVOT: f32 = 1000.0; // Hz, bands here keep their level whatever the tilt
+    pub const TRIM_RANGE: (f32, f32) = (-24.0, 24.0);
+
+    // linear gain for one band from its trim and the tilt, applied to the band envelope (after
+    // the floor is taken off) before normalization
+    pub fn band_gain(&self, band: usize, center_freq: f32) -> f32 {
+        let trim = self.trim.get(band).copied().unwrap_or(0.0).clamp(Self::TRIM_RANGE.0, Self::TRIM_RANGE.1);
+        let tilt = self.tilt.clamp(Self::TILT_RANGE.0, Self::TILT_RANGE.1);
+        let db = trim + tilt * libm::log2f(center_freq.max(1.0) / Self::TILT_PIVOT);
+        libm::powf(10.0, db / 20.0)
+    }
+
+    // gate a normalized energy, rescaled so the output still spans 0-1
+    pub fn apply_gate(&self, energy: f32) -> f32 {
+        if self.gate <= 0.0 {
+            energy
+        } else if energy <= self.gate {
+            0.0
+        } else {
+            (energy - self.gate) / (1.0 - self.gate).max(1e-6)
+        }
+    }
+}
+
+// what a band's envelope follower tracks of its filter output
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Detector {
+    // rectified amplitude (girlvoice/dsp/envelope.py), what the gateware does now
+    #[default]
+    Peak,
+    // smoothed square, square root on read. follows perceived loudness more closely
+    Rms,
+    // attack and release in dB, so every level decays at the same rate in dB per second
+    Log,
+}
+
+impl Detector {
+    pub const ALL: [Detector; 3] = [Detector::Peak, Detector::Rms, Detector::Log];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            Detector::Peak => "peak",
+            Detector::Rms => "rms",
+            Detector::Log => "log",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Detector> {
+        Self::ALL.into_iter().find(|d| d.id() == id)
+    }
+}
+
+// a palette saved from the menu under a name typed on the character wheel
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default, deny_unknown_fields))]
+pub struct UserTheme {
+    pub name: Name,
+    pub palette: ColorPalette,
+}
+
+// user-tunable settings, grouped per subsystem
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default, deny_unknown_fields))]
+pub struct Settings {
+    pub encoder: EncoderSettings,
+    pub startup: StartupPolicy,
+    pub last_mode: ModeKind, // kept up to date while running, used by StartupPolicy::LastUsed
+    pub last_preset: Option<usize>, // preset bank slot to restore on boot
+    pub brightness: f32,            // 0-1, scales everything the visualizer draws
+    pub power: PowerProfile,        // may cap the brightness actually drawn with
+    pub idle_clock: f32,            // seconds without voice or input before the clock face shows, 0 never
+    pub palette: ColorPalette,
+    pub theme: Option<UserTheme>, // offered after the built-in palettes
+    pub response: ResponseCurves, // energy-to-visual curves, the global one and per-mode overrides
+    pub calibration: Option<BandCalibration>, // from the last calibration run, applied on top of DspSettings
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            encoder: EncoderSettings::default(),
+            startup: StartupPolicy::default(),
+            last_mode: ModeKind::HarmonicLoop,
+            last_preset: None,
+            brightness: 1.0,
+            power: PowerProfile::Full,
+            idle_clock: 60.0,
+            palette: ColorPalette::default(),
+            theme: None,
+            response: ResponseCurves::default(),
+            calibration: None,
+        }
+    }
+}
+
+impl Settings {
+    // put the visualizer into the state the startup policy asks for
+    pub fn apply_startup(&self, visualizer: &mut Visualizer) {
+        match self.startup {
+            StartupPolicy::LastUsed => {
+                visualizer.set_mode(self.last_mode);
+                visualizer.set_demo_cycle(None);
+            }
+            StartupPolicy::Favorite(mode) => {
+                visualizer.set_mode(mode);
+                visualizer.set_demo_cycle(None);
+            }
+            StartupPolicy::DemoCycle => {
+                visualizer.set_demo_cycle(Some(StartupPolicy::DEMO_SECONDS_PER_MODE));
+            }
+        }
+    }
+
+    pub const MIN_BRIGHTNESS: f32 = 0.1;
+
+    // idle_clock as Visualizer::set_idle_clock takes it
+    pub fn idle_clock_after(&self) -> Option<f32> {
+        (self.idle_clock > 0.0).then_some(self.idle_clock)
+    }
+
+    // remember the running mode, palette and response curves, call before persisting
+    pub fn record(&mut self, visualizer: &Visualizer) {
+        self.last_mode = visualizer.current_mode();
+        self.palette = *visualizer.palette();
+        self.response = *visualizer.response();
+    }
+
+    pub fn adjust_brightness(&mut self, delta: f32) {
+        self.brightness = (self.brightness + delta).clamp(Self::MIN_BRIGHTNESS, 1.0);
+    }
+}
@@ -0,0 +1,104 @@
+// band energies at the render frame rate. the DSP delivers a new block whenever the audio
+// callback runs, which doesn't line up with UI frames, so taking the latest block as-is
+// jitters. the smoother keeps the last two blocks, replays them on a render clock that trails
+// the analysis clock by one block and interpolates between them, then applies per-band
+// attack/release so the result no longer depends on either rate.
+
+use crate::source::AnalysisFrame;
+use crate::CHANNELS;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BandSmoothing {
+    pub attack_ms: f32,
+    pub release_ms: f32,
+}
+
+impl Default for BandSmoothing {
+    fn default() -> Self {
+        Self { attack_ms: 15.0, release_ms: 60.0 }
+    }
+}
+
+pub struct EnergySmoother {
+    bands: [BandSmoothing; CHANNELS],
+    keys: [[f32; CHANNELS]; 2], // previous and latest block
+    key_times: [f64; 2],
+    clock: f64, // render position on the analysis clock
+    values: [f32; CHANNELS],
+    num_channels: usize,
+}
+
+impl EnergySmoother {
+    // render clock never trails the newest block by more than this, e.g. after a stall
+    const MAX_LAG_S: f64 = 0.1;
+
+    pub fn new(num_channels: usize) -> Self {
+        Self {
+            bands: [BandSmoothing::default(); CHANNELS],
+            keys: [[0.0; CHANNELS]; 2],
+            key_times: [0.0; 2],
+            clock: 0.0,
+            values: [0.0; CHANNELS],
+            num_channels: num_channels.min(CHANNELS),
+        }
+    }
+
+    pub fn set_band(&mut self, band: usize, smoothing: BandSmoothing) {
+        if let Some(b) = self.bands.get_mut(band) {
+            *b = smoothing;
+        }
+    }
+
+    pub fn set_all(&mut self, smoothing: BandSmoothing) {
+        self.bands = [smoothing; CHANNELS];
+    }
+
+    pub fn band(&self, band: usize) -> BandSmoothing {
+        self.bands[band]
+    }
+
+    pub fn values(&self) -> &[f32] {
+        &self.values[..self.num_channels]
+    }
+
+    // advance by one render frame of `dt` seconds, returns the smoothed bands
+    pub fn process(&mut self, dt: f32, frame: &AnalysisFrame) -> &[f32] {
+        let n = frame.num_channels.min(self.num_channels);
+        let target = self.interpolate(dt, frame);
+
+        for ((value, &target), band) in self.values[..n].iter_mut().zip(&target).zip(&self.bands) {
+            let ms = if target > *value { band.attack_ms } else { band.release_ms };
+            let coeff = if ms > 0.0 { 1.0 - libm::expf(-dt * 1000.0 / ms) } else { 1.0 };
+            *value += (target - *value) * coeff;
+        }
+        &self.values[..n]
+    }
+
+    fn interpolate(&mut self, dt: f32, frame: &AnalysisFrame) -> [f32; CHANNELS] {
+        // sources without an analysis clock: every frame is a new block, nothing to interpolate
+        if frame.time <= 0.0 {
+            return frame.energies;
+        }
+
+        if frame.time < self.key_times[1] {
+            // the source restarted its clock
+            self.keys = [frame.energies; 2];
+            self.key_times = [frame.time; 2];
+            self.clock = frame.time;
+        } else if frame.time != self.key_times[1] {
+            self.keys[0] = self.keys[1];
+            self.key_times[0] = self.key_times[1];
+            self.keys[1] = frame.energies;
+            self.key_times[1] = frame.time;
+        }
+
+        let [t0, t1] = self.key_times;
+        self.clock = (self.clock + dt as f64).clamp(t0.max(t1 - Self::MAX_LAG_S), t1);
+        if t1 <= t0 {
+            return self.keys[1];
+        }
+
+        let frac = ((self.clock - t0) / (t1 - t0)) as f32;
+        core::array::from_fn(|i| self.keys[0][i] + (self.keys[1][i] - self.keys[0][i]) * frac)
+    }
+}
@@ -0,0 +1,243 @@
+use crate::formant::Formants;
+use crate::settings::{DspSettings, VocoderConfig};
+use crate::CHANNELS;
+
+// number of raw samples carried with each frame (enough for a scope trace)
+pub const WAVEFORM_LEN: usize = 256;
+
+// sample magnitude that counts as clipping
+pub const CLIP_LEVEL: f32 = 0.99;
+
+// number of analysis bands a source delivers, checked once against CHANNELS so the frames,
+// modes and smoothers sized by it all agree
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChannelCount(usize);
+
+impl ChannelCount {
+    pub const MIN: usize = 2; // the filterbank spaces bands between the first and the last
+    pub const MAX: usize = CHANNELS;
+
+    pub const fn new(num_channels: usize) -> Result<Self, ChannelCountError> {
+        if num_channels < Self::MIN || num_channels > Self::MAX {
+            return Err(ChannelCountError(num_channels));
+        }
+        Ok(Self(num_channels))
+    }
+
+    pub const fn get(self) -> usize {
+        self.0
+    }
+}
+
+// a band count outside ChannelCount::MIN..=MAX
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChannelCountError(pub usize);
+
+impl core::fmt::Display for ChannelCountError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} channels, the UI supports {} to {}", self.0, ChannelCount::MIN, ChannelCount::MAX)
+    }
+}
+
+// one snapshot of analysis data, the same type from the DSP through sources and telemetry
+// links to every VisualMode
+#[derive(Clone, Copy)]
+pub struct AnalysisFrame {
+    pub energies: [f32; CHANNELS],
+    pub num_channels: usize,
+    pub right: Option<[f32; CHANNELS]>, // second input's bands for stereo sources, `energies` is the left one
+    pub peak: f32,
+    pub rms: f32, // input level over the newest hop
+    pub waveform: [f32; WAVEFORM_LEN],
+    pub pitch: Option<f32>,
+    pub formants: Option<Formants>, // F1/F2 while voiced, None if the source doesn't track them
+    pub loudness: Option<f32>,      // gated LUFS over the last few seconds, None before any speech
+    pub voiced: bool,  // voice activity, speech or not rather than pitched or not
+    pub plosive: bool, // low-band pop detected by the DSP
+    pub clip: bool,    // a sample reached CLIP_LEVEL since the previous frame
+    pub time: f64,     // analysis clock in seconds at the newest hop, 0 if the source has none
+}
+
+impl AnalysisFrame {
+    pub fn new(channels: ChannelCount) -> Self {
+        Self {
+            energies: [0.0; CHANNELS],
+            num_channels: channels.get(),
+            right: None,
+            peak: 0.0,
+            rms: 0.0,
+            waveform: [0.0; WAVEFORM_LEN],
+            pitch: None,
+            formants: None,
+            loudness: None,
+            voiced: false,
+            plosive: false,
+            clip: false,
+            time: 0.0,
+        }
+    }
+
+    // energies of the active channels only
+    pub fn bands(&self) -> &[f32] {
+        &self.energies[..self.num_channels.min(CHANNELS)]
+    }
+
+    // num_channels, checked. it's a plain field, a frame built by hand or relayed from
+    // elsewhere can hold anything
+    pub fn channels(&self) -> Result<ChannelCount, ChannelCountError> {
+        ChannelCount::new(self.num_channels)
+    }
+
+    // copies a source's bands in, extra ones are dropped and missing ones read 0
+    pub fn set_bands(&mut self, bands: &[f32]) {
+        let n = self.num_channels;
+        for (i, energy) in self.energies[..n].iter_mut().enumerate() {
+            *energy = bands.get(i).copied().unwrap_or(0.0);
+        }
+    }
+
+    // move on to a newer frame, keeping this one's plosive and clip so a consumer that skips
+    // frames doesn't miss them
+    pub fn catch_up(&mut self, newer: &AnalysisFrame) {
+        let (plosive, clip) = (self.plosive, self.clip);
+        *self = *newer;
+        self.plosive |= plosive;
+        self.clip |= clip;
+    }
+
+    // fixed little-endian layout for telemetry links, everything but the waveform:
+    //   0       layout version
+    //   1       num_channels
+    //   2       flags, bit 0 up: voiced, plosive, clip, right, pitch, formants, loudness
+    //   3       reserved, 0
+    //   4..28   peak, rms, pitch, f1, f2, loudness (f32, 0 when absent)
+    //   28..36  time (f64)
+    //   36..    energies, then the right bands (CHANNELS f32 each, unused ones 0)
+    // fields only get added at the end, with a new version
+    pub const LAYOUT_VERSION: u8 = 1;
+    pub const ENCODED_LEN: usize = 36 + 2 * CHANNELS * 4;
+
+    const VOICED: u8 = 1 << 0;
+    const PLOSIVE: u8 = 1 << 1;
+    const CLIP: u8 = 1 << 2;
+    const RIGHT: u8 = 1 << 3;
+    const PITCH: u8 = 1 << 4;
+    const FORMANTS: u8 = 1 << 5;
+    const LOUDNESS: u8 = 1 << 6;
+
+    pub fn to_bytes(&self) -> [u8; Self::ENCODED_LEN] {
+        let flag = |set: bool, bit: u8| if set { bit } else { 0 };
+        let mut out = [0u8; Self::ENCODED_LEN];
+        out[0] = Self::LAYOUT_VERSION;
+        out[1] = self.num_channels as u8;
+        out[2] = flag(self.voiced, Self::VOICED)
+            | flag(self.plosive, Self::PLOSIVE)
+            | flag(self.clip, Self::CLIP)
+            | flag(self.right.is_some(), Self::RIGHT)
+            | flag(self.pitch.is_some(), Self::PITCH)
+            | flag(self.formants.is_some(), Self::FORMANTS)
+            | flag(self.loudness.is_some(), Self::LOUDNESS);
+
+        let (f1, f2) = self.formants.map_or((0.0, 0.0), |f| (f.f1, f.f2));
+        let scalars = [self.peak, self.rms, self.pitch.unwrap_or(0.0), f1, f2, self.loudness.unwrap_or(0.0)];
+        let right = self.right.unwrap_or([0.0; CHANNELS]);
+        let mut put = |at: usize, value: f32| out[at..at + 4].copy_from_slice(&value.to_le_bytes());
+        for (i, &value) in scalars.iter().enumerate() {
+            put(4 + i * 4, value);
+        }
+        for (i, &value) in self.energies.iter().chain(&right).enumerate() {
+            put(36 + i * 4, value);
+        }
+        out[28..36].copy_from_slice(&self.time.to_le_bytes());
+        out
+    }
+
+    // the waveform reads silent, the layout doesn't carry it. band energies are clamped to 0-1,
+    // a NaN anywhere rejects the frame
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FrameDecodeError> {
+        match bytes.first() {
+            None => return Err(FrameDecodeError::Truncated),
+            Some(&Self::LAYOUT_VERSION) => {}
+            Some(&version) => return Err(FrameDecodeError::WrongVersion(version)),
+        }
+        if bytes.len() < Self::ENCODED_LEN {
+            return Err(FrameDecodeError::Truncated);
+        }
+        let channels = ChannelCount::new(bytes[1] as usize).map_err(FrameDecodeError::Channels)?;
+        let flags = bytes[2];
+        let f32_at = |at: usize| f32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]]);
+        let mut time = [0u8; 8];
+        time.copy_from_slice(&bytes[28..36]);
+        let time = f64::from_le_bytes(time);
+        let nan = (0..6).map(|i| 4 + i * 4).chain((0..2 * CHANNELS).map(|i| 36 + i * 4)).any(|at| f32_at(at).is_nan());
+        if nan || time.is_nan() {
+            return Err(FrameDecodeError::NotANumber);
+        }
+        let bands = |start: usize| -> [f32; CHANNELS] { core::array::from_fn(|i| f32_at(start + i * 4).clamp(0.0, 1.0)) };
+        let present = |bit: u8| flags & bit != 0;
+        Ok(Self {
+            energies: bands(36),
+            right: present(Self::RIGHT).then(|| bands(36 + CHANNELS * 4)),
+            peak: f32_at(4),
+            rms: f32_at(8),
+            pitch: present(Self::PITCH).then(|| f32_at(12)),
+            formants: present(Self::FORMANTS).then(|| Formants { f1: f32_at(16), f2: f32_at(20) }),
+            loudness: present(Self::LOUDNESS).then(|| f32_at(24)),
+            voiced: present(Self::VOICED),
+            plosive: present(Self::PLOSIVE),
+            clip: present(Self::CLIP),
+            time,
+            ..Self::new(channels)
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameDecodeError {
+    Truncated,
+    WrongVersion(u8),
+    Channels(ChannelCountError),
+    NotANumber,
+}
+
+impl core::fmt::Display for FrameDecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FrameDecodeError::Truncated => write!(f, "frame shorter than {} bytes", AnalysisFrame::ENCODED_LEN),
+            FrameDecodeError::WrongVersion(version) => {
+                write!(f, "frame layout version {}, expected {}", version, AnalysisFrame::LAYOUT_VERSION)
+            }
+            FrameDecodeError::Channels(e) => write!(f, "{}", e),
+            FrameDecodeError::NotANumber => write!(f, "frame holds a NaN"),
+        }
+    }
+}
+
+// anything that can feed the UI: mic capture, file playback, telemetry, test signals
+pub trait EnergySource {
+    // called once per UI frame, returns the latest analysis data
+    fn poll(&mut self) -> AnalysisFrame;
+
+    // every frame since the last call, oldest first, for consumers that want each one and not
+    // just the latest. sources analyzing at a fixed hop override it, the rest hand over poll's
+    fn poll_frames(&mut self, on_frame: &mut dyn FnMut(&AnalysisFrame)) {
+        on_frame(&self.poll());
+    }
+
+    fn num_channels(&self) -> ChannelCount;
+
+    // sources without a tunable DSP (playback, telemetry) ignore this
+    fn set_dsp_settings(&mut self, _settings: &DspSettings) {}
+
+    // a second DSP on the same samples, built for `vocoder` (rebuilt when it changes) and
+    // tuned with `settings`, so two analyses can be compared side by side. false for sources
+    // without a DSP of their own
+    fn set_comparison(&mut self, _vocoder: &VocoderConfig, _settings: &DspSettings) -> bool {
+        false
+    }
+
+    // the second DSP's latest frame, None until set_comparison built one
+    fn poll_comparison(&mut self) -> Option<AnalysisFrame> {
+        None
+    }
+}
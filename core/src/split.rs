@@ -0,0 +1,146 @@
+// split mode for two-mic badges: the left half of the display shows the left input (the
+// wearer's mic), the right half the right input (ambient), as bars fanned out from the bottom
+// to the top of each half. both halves are mirror images, so the same sound on both mics
+// looks symmetric. sources with one input show it on both sides.
+
+use core::f32::consts::{FRAC_PI_2, PI};
+use core::ops::Range;
+
+use libm::sqrtf;
+
+use crate::param::Param;
+use crate::polar;
+use crate::region::{RenderRegion, DISPLAY_REGION};
+use crate::source::AnalysisFrame;
+use crate::vis::VisualMode;
+use crate::{Color, ColorPalette, EnvelopeSmoother, CHANNELS, DISPLAY_CENTER, DISPLAY_SIZE};
+
+pub struct Split {
+    num_channels: usize,
+    smoothers: [[EnvelopeSmoother; CHANNELS]; 2],
+    levels: [[f32; CHANNELS]; 2], // left, right
+    right: Option<[f32; CHANNELS]>, // latest raw right bands
+    params: [Param; 1],
+}
+
+impl Split {
+    const INNER_RADIUS: f32 = 24.0;
+    const OUTER_RADIUS: f32 = 112.0;
+    const BAR_FILL: usize = 0; // params, share of each band's angle the bar covers
+    const DIVIDER_WIDTH: f32 = 1.5;
+
+    pub fn new(num_channels: usize) -> Self {
+        Self {
+            num_channels: num_channels.clamp(1, CHANNELS),
+            smoothers: core::array::from_fn(|_| core::array::from_fn(|_| EnvelopeSmoother::new(60.0, 5.0, 80.0))),
+            levels: [[0.0; CHANNELS]; 2],
+            right: None,
+            params: [Param::new("fill", "Bar width", 0.3, 1.0, 0.05, 0.72)],
+        }
+    }
+
+    // right input bands for the next update, None mirrors the left input
+    pub fn set_right(&mut self, bands: Option<&[f32]>) {
+        self.right = bands.map(|bands| {
+            let mut right = [0.0; CHANNELS];
+            let n = bands.len().min(CHANNELS);
+            right[..n].copy_from_slice(&bands[..n]);
+            right
+        });
+    }
+
+    pub fn update(&mut self, _dt: f32, energies: &[f32]) {
+        let n = self.num_channels;
+        for i in 0..n {
+            let left = energies.get(i).copied().unwrap_or(0.0);
+            let right = self.right.map_or(left, |right| right[i]);
+            self.levels[0][i] = self.smoothers[0][i].process(left.clamp(0.0, 1.0));
+            self.levels[1][i] = self.smoothers[1][i].process(right.clamp(0.0, 1.0));
+        }
+    }
+
+    pub fn render_with_palette<F>(&self, set_pixel: F, pal: &ColorPalette)
+    where
+        F: FnMut(usize, usize, Color),
+    {
+        self.draw_rows(&DISPLAY_REGION, 0..DISPLAY_SIZE, 1, set_pixel, pal);
+    }
+
+    fn draw_rows<F>(&self, region: &RenderRegion, rows: Range<usize>, step: usize, mut set_pixel: F, pal: &ColorPalette)
+    where
+        F: FnMut(usize, usize, Color),
+    {
+        let n = self.num_channels;
+        let length = Self::OUTER_RADIUS - Self::INNER_RADIUS;
+        let divider = pal.accent.scale(0.4);
+        let fill = self.params[Self::BAR_FILL].value;
+
+        for (y, columns) in region.grid(rows, step) {
+            let dy = y as f32 - DISPLAY_CENTER;
+            for x in columns {
+                let dx = x as f32 - DISPLAY_CENTER + 0.5;
+                let side = (dx >= 0.0) as usize;
+
+                let divider_cover = (Self::DIVIDER_WIDTH / 2.0 - dx.abs() + 0.5).clamp(0.0, 1.0);
+                if divider_cover > 0.0 {
+                    set_pixel(x, y, divider.scale(divider_cover));
+                    continue;
+                }
+
+                let r = sqrtf(dx * dx + dy * dy);
+                if r < Self::INNER_RADIUS - 0.5 {
+                    continue;
+                }
+                // folded angle, both halves run from the bottom (0) to the top (1)
+                let along = (FRAC_PI_2 - polar::atan2(dy, dx.abs())) / PI;
+                let slot = (along * n as f32).clamp(0.0, n as f32 - 0.001);
+                let band = slot as usize;
+                let offset = (slot - band as f32 - 0.5).abs() * 2.0; // 0 in the bar center, 1 at the gap
+                // distance to the bar's side in pixels, for the antialiased edge
+                let side_px = (fill - offset) * 0.5 * PI / n as f32 * r;
+                if side_px <= -0.5 {
+                    continue;
+                }
+
+                let level = self.levels[side][band];
+                let end = Self::INNER_RADIUS + level * length;
+                let cover = (end - r + 0.5).min(r - Self::INNER_RADIUS + 0.5).min(side_px + 0.5).clamp(0.0, 1.0);
+                if cover > 0.0 {
+                    let color = pal.band(band, n).scale(0.35 + 0.65 * level);
+                    set_pixel(x, y, color.scale(cover));
+                }
+            }
+        }
+    }
+}
+
+impl VisualMode for Split {
+    fn update(&mut self, dt: f32, frame: &AnalysisFrame) {
+        self.set_right(frame.right.as_ref().map(|right| &right[..frame.num_channels]));
+        Split::update(self, dt, frame.bands());
+    }
+
+    fn render(&self, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.render_with_palette(set_pixel, pal);
+    }
+
+    fn render_rows(&self, rows: Range<usize>, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.draw_rows(&DISPLAY_REGION, rows, 1, set_pixel, pal);
+    }
+
+    fn render_region(&self, region: &RenderRegion, step: usize, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.draw_rows(region, 0..DISPLAY_SIZE, step, set_pixel, pal);
+    }
+
+    fn stays_in_region(&self) -> bool {
+        true
+    }
+
+    fn params(&self) -> &[Param] {
+        &self.params
+    }
+
+    fn params_mut(&mut self) -> &mut [Param] {
+        &mut self.params
+    }
+}
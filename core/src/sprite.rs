@@ -0,0 +1,72 @@
+// small RGB565 images with transparency for logos, icons and menu glyphs. the data lives in
+// flash as const arrays, converted from png on the host by tools' png2sprite, so the badge
+// never decodes an image. see icons for the ones that ship.
+
+use crate::framebuffer::Framebuffer;
+use crate::Color;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpriteAlpha {
+    Opaque,
+    Mask(&'static [u8]),   // 1bpp msb first, every row starts on a new byte
+    Levels(&'static [u8]), // 4bpp high nibble first, every row starts on a new byte
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sprite {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: &'static [u16], // RGB565, row-major
+    pub alpha: SpriteAlpha,
+}
+
+impl Sprite {
+    // 0-255
+    pub fn alpha_at(&self, x: usize, y: usize) -> u8 {
+        match self.alpha {
+            SpriteAlpha::Opaque => 255,
+            SpriteAlpha::Mask(bits) => {
+                let byte = bits[y * self.width.div_ceil(8) + x / 8];
+                if byte & (0x80 >> (x % 8)) != 0 { 255 } else { 0 }
+            }
+            SpriteAlpha::Levels(nibbles) => {
+                let byte = nibbles[y * self.width.div_ceil(2) + x / 2];
+                let level = if x.is_multiple_of(2) { byte >> 4 } else { byte & 0x0f };
+                level * 17
+            }
+        }
+    }
+
+    pub fn color_at(&self, x: usize, y: usize) -> Color {
+        Color::from_rgb565(self.pixels[y * self.width + x])
+    }
+}
+
+// draws the sprite with its top left corner at (x, y), clipped to the framebuffer
+pub fn blit(fb: &mut Framebuffer, sprite: &Sprite, x: i32, y: i32) {
+    draw(fb, sprite, x, y, |color| color);
+}
+
+// for single-color glyphs drawn in white: the sprite's color is multiplied by `tint`, so one
+// icon works in every palette and at every brightness
+pub fn blit_tinted(fb: &mut Framebuffer, sprite: &Sprite, x: i32, y: i32, tint: Color) {
+    let multiply = |a: u8, b: u8| ((a as u16 * b as u16 + 127) / 255) as u8;
+    draw(fb, sprite, x, y, |c| Color::new(multiply(c.r, tint.r), multiply(c.g, tint.g), multiply(c.b, tint.b)));
+}
+
+fn draw(fb: &mut Framebuffer, sprite: &Sprite, x: i32, y: i32, shade: impl Fn(Color) -> Color) {
+    // visible part of the sprite, in sprite coordinates
+    let columns = (-x).max(0) as usize..(Framebuffer::WIDTH as i32 - x).clamp(0, sprite.width as i32) as usize;
+    let rows = (-y).max(0) as usize..(Framebuffer::HEIGHT as i32 - y).clamp(0, sprite.height as i32) as usize;
+    for sy in rows {
+        let fy = (y + sy as i32) as usize;
+        for sx in columns.clone() {
+            let fx = (x + sx as i32) as usize;
+            match sprite.alpha_at(sx, sy) {
+                0 => {}
+                255 => fb.set(fx, fy, shade(sprite.color_at(sx, sy))),
+                alpha => fb.blend(fx, fy, shade(sprite.color_at(sx, sy)), alpha),
+            }
+        }
+    }
+}
@@ -0,0 +1,186 @@
+// lock-free single producer / single consumer handoff between an audio callback (or ISR) and
+// the UI loop, hand-rolled on core atomics so it works without std or a critical section.
+// - TripleBuffer: latest-value mailbox. the writer never waits, the reader always sees the
+//   newest complete value. used for analysis frames and settings.
+// - SpscRing: bounded FIFO for streams like raw samples, pushes fail when it's full.
+// both split into one writer and one reader handle. put the shared part in a static (or leak
+// it) to hand the handles to an interrupt or another thread.
+
+use core::cell::UnsafeCell;
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+
+// set in TripleBuffer::back while the spare buffer holds a value the reader hasn't taken
+const FRESH: u8 = 0b100;
+
+pub struct TripleBuffer<T> {
+    buffers: [UnsafeCell<T>; 3],
+    back: AtomicU8, // index of the spare buffer, plus FRESH
+}
+
+// each buffer is only ever touched by the handle that currently owns its index
+unsafe impl<T: Send> Sync for TripleBuffer<T> {}
+
+impl<T: Copy> TripleBuffer<T> {
+    pub const fn new(initial: T) -> Self {
+        Self {
+            buffers: [UnsafeCell::new(initial), UnsafeCell::new(initial), UnsafeCell::new(initial)],
+            back: AtomicU8::new(1),
+        }
+    }
+
+    pub fn split(&mut self) -> (TripleWriter<'_, T>, TripleReader<'_, T>) {
+        let this: &Self = self;
+        (TripleWriter { shared: this, write: 0 }, TripleReader { shared: this, read: 2 })
+    }
+}
+
+pub struct TripleWriter<'a, T> {
+    shared: &'a TripleBuffer<T>,
+    write: u8,
+}
+
+impl<T: Copy> TripleWriter<'_, T> {
+    // publish a new value, replacing one the reader hasn't picked up yet
+    pub fn write(&mut self, value: T) {
+        // safety: `write` is owned by this handle until it is swapped into `back`
+        unsafe { *self.shared.buffers[self.write as usize].get() = value };
+        let previous = self.shared.back.swap(self.write | FRESH, Ordering::AcqRel);
+        self.write = previous & !FRESH;
+    }
+}
+
+pub struct TripleReader<'a, T> {
+    shared: &'a TripleBuffer<T>,
+    read: u8,
+}
+
+impl<T: Copy> TripleReader<'_, T> {
+    // newest published value (the initial one until the first write)
+    pub fn read(&mut self) -> &T {
+        self.take_fresh();
+        // safety: `read` is owned by this handle until it is swapped into `back`
+        unsafe { &*self.shared.buffers[self.read as usize].get() }
+    }
+
+    // only a value published since the last read
+    pub fn read_new(&mut self) -> Option<&T> {
+        self.take_fresh().then(|| unsafe { &*self.shared.buffers[self.read as usize].get() })
+    }
+
+    fn take_fresh(&mut self) -> bool {
+        if self.shared.back.load(Ordering::Relaxed) & FRESH == 0 {
+            return false;
+        }
+        let previous = self.shared.back.swap(self.read, Ordering::AcqRel);
+        self.read = previous & !FRESH;
+        true
+    }
+}
+
+pub struct SpscRing<T, const N: usize> {
+    slots: [UnsafeCell<MaybeUninit<T>>; N],
+    head: AtomicUsize, // total pushed, only the producer stores it
+    tail: AtomicUsize, // total popped, only the consumer stores it
+}
+
+// slots between tail and head belong to the consumer, the rest to the producer
+unsafe impl<T: Send, const N: usize> Sync for SpscRing<T, N> {}
+
+impl<T: Copy, const N: usize> SpscRing<T, N> {
+    // N has to be a power of two so the free-running counters can wrap
+    pub const fn new() -> Self {
+        const { assert!(N.is_power_of_two(), "SpscRing size must be a power of two") };
+        Self {
+            slots: [const { UnsafeCell::new(MaybeUninit::uninit()) }; N],
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn split(&mut self) -> (RingProducer<'_, T, N>, RingConsumer<'_, T, N>) {
+        let this: &Self = self;
+        (RingProducer { shared: this }, RingConsumer { shared: this })
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+}
+
+impl<T: Copy, const N: usize> Default for SpscRing<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct RingProducer<'a, T, const N: usize> {
+    shared: &'a SpscRing<T, N>,
+}
+
+impl<T: Copy, const N: usize> RingProducer<'_, T, N> {
+    // false when the ring is full, the value is dropped
+    pub fn push(&mut self, value: T) -> bool {
+        self.push_slice(core::slice::from_ref(&value)) == 1
+    }
+
+    // pushes as much of `values` as fits, returns how many went in
+    pub fn push_slice(&mut self, values: &[T]) -> usize {
+        let head = self.shared.head.load(Ordering::Relaxed);
+        let tail = self.shared.tail.load(Ordering::Acquire);
+        let count = values.len().min(N - head.wrapping_sub(tail));
+        for (i, &value) in values[..count].iter().enumerate() {
+            // safety: slots from head up to tail + N are free
+            unsafe { (*self.shared.slots[head.wrapping_add(i) & (N - 1)].get()).write(value) };
+        }
+        self.shared.head.store(head.wrapping_add(count), Ordering::Release);
+        count
+    }
+
+    pub fn free(&self) -> usize {
+        N - self.shared.head.load(Ordering::Relaxed).wrapping_sub(self.shared.tail.load(Ordering::Acquire))
+    }
+}
+
+pub struct RingConsumer<'a, T, const N: usize> {
+    shared: &'a SpscRing<T, N>,
+}
+
+impl<T: Copy, const N: usize> RingConsumer<'_, T, N> {
+    pub fn pop(&mut self) -> Option<T> {
+        let mut value = [MaybeUninit::uninit()];
+        if self.pop_into(&mut value) == 1 {
+            // safety: pop_into initialized it
+            Some(unsafe { value[0].assume_init() })
+        } else {
+            None
+        }
+    }
+
+    // fills `out` from the oldest values, returns how many were taken
+    pub fn pop_slice(&mut self, out: &mut [T]) -> usize {
+        // safety: T and MaybeUninit<T> share a layout, and pop_into only writes initialized values
+        let out = unsafe { &mut *(out as *mut [T] as *mut [MaybeUninit<T>]) };
+        self.pop_into(out)
+    }
+
+    fn pop_into(&mut self, out: &mut [MaybeUninit<T>]) -> usize {
+        let tail = self.shared.tail.load(Ordering::Relaxed);
+        let head = self.shared.head.load(Ordering::Acquire);
+        let count = out.len().min(head.wrapping_sub(tail));
+        for (i, o) in out[..count].iter_mut().enumerate() {
+            // safety: slots from tail up to head were written by the producer
+            *o = unsafe { *self.shared.slots[tail.wrapping_add(i) & (N - 1)].get() };
+        }
+        self.shared.tail.store(tail.wrapping_add(count), Ordering::Release);
+        count
+    }
+
+    pub fn len(&self) -> usize {
+        self.shared.head.load(Ordering::Acquire).wrapping_sub(self.shared.tail.load(Ordering::Relaxed))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
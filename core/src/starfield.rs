@@ -0,0 +1,149 @@
+// warp starfield: stars come out of the center and streak past the edge. the louder the voice
+// the faster they fly and the more of them there are, and every new star takes the color of a
+// band picked at random, weighted by how loud each band is, so the field leans toward whatever
+// part of the voice is strongest
+
+use crate::draw::line_aa;
+use crate::param::Param;
+use crate::particle::{ParticlePool, Rng};
+use crate::source::AnalysisFrame;
+use crate::vis::VisualMode;
+use crate::{Color, ColorPalette, EnvelopeSmoother, CHANNELS, DISPLAY_CENTER, DISPLAY_RADIUS};
+
+pub const MAX_STARS: usize = 256;
+
+const FOCAL: f32 = 24.0; // pixels from the center of a star at depth 1 and offset 1
+const NEAR: f32 = 0.05; // depth a star is retired at if it hasn't left the panel yet
+
+#[derive(Clone, Copy, Default)]
+struct Star {
+    x: f32,
+    y: f32,
+    z: f32,    // depth, starts at 1 and falls toward the viewer
+    last: f32, // depth a frame ago, the tail of the streak
+    band: u8,
+}
+
+impl Star {
+    fn project(&self, z: f32) -> (f32, f32) {
+        (DISPLAY_CENTER + self.x / z * FOCAL, DISPLAY_CENTER + self.y / z * FOCAL)
+    }
+}
+
+pub struct Starfield {
+    num_channels: usize,
+    stars: ParticlePool<Star, MAX_STARS>,
+    level: EnvelopeSmoother,
+    weights: [f32; CHANNELS], // band energies the next stars pick their color from
+    spawn_debt: f32,          // stars owed to the spawn rate, fractional
+    rng: Rng,
+    params: [Param; 3],
+}
+
+impl Starfield {
+    const SPEED: usize = 0; // params
+    const DENSITY: usize = 1;
+    const STREAKS: usize = 2;
+
+    const IDLE_RATE: f32 = 15.0; // stars per second in silence
+    const LOUD_RATE: f32 = 220.0; // more at full level
+    const IDLE_SPEED: f32 = 0.15; // depth per second
+    const LOUD_SPEED: f32 = 1.8;
+
+    pub fn new(num_channels: usize) -> Self {
+        Self {
+            num_channels: num_channels.clamp(1, CHANNELS),
+            stars: ParticlePool::new(),
+            level: EnvelopeSmoother::new(60.0, 40.0, 500.0),
+            weights: [0.0; CHANNELS],
+            spawn_debt: 0.0,
+            rng: Rng::new(0x2545_f491),
+            params: [
+                Param::new("speed", "Speed", 0.25, 2.0, 0.25, 1.0),
+                Param::new("density", "Density", 0.25, 2.0, 0.25, 1.0),
+                Param::new("streaks", "Streaks", 0.0, 1.0, 0.1, 1.0),
+            ],
+        }
+    }
+
+    // a band index drawn with odds in proportion to its energy, even odds in silence
+    fn pick_band(&mut self) -> u8 {
+        let n = self.num_channels;
+        let floor = 0.02; // so quiet bands still turn up now and then
+        let total: f32 = self.weights[..n].iter().map(|w| w + floor).sum();
+        let mut r = self.rng.next_f32() * total;
+        for (band, w) in self.weights[..n].iter().enumerate() {
+            r -= w + floor;
+            if r <= 0.0 {
+                return band as u8;
+            }
+        }
+        (n - 1) as u8
+    }
+
+    pub fn update(&mut self, dt: f32, energies: &[f32]) {
+        let n = self.num_channels;
+        for (w, &e) in self.weights[..n].iter_mut().zip(energies) {
+            *w = e.clamp(0.0, 1.0);
+        }
+        // rms across the bands, a couple of loud ones count for more than in a plain mean
+        let power = self.weights[..n].iter().map(|w| w * w).sum::<f32>() / n as f32;
+        let level = self.level.process(libm::sqrtf(power));
+
+        let speed = (Self::IDLE_SPEED + (Self::LOUD_SPEED - Self::IDLE_SPEED) * level) * self.params[Self::SPEED].value;
+        let edge = DISPLAY_RADIUS + 4.0;
+        self.stars.retain(|star| {
+            star.last = star.z;
+            star.z -= speed * dt * star.z.max(0.2); // slower far away, like the depth is exponential
+            let (x, y) = star.project(star.z.max(NEAR));
+            let (dx, dy) = (x - DISPLAY_CENTER, y - DISPLAY_CENTER);
+            star.z > NEAR && dx * dx + dy * dy < edge * edge
+        });
+
+        let rate = (Self::IDLE_RATE + (Self::LOUD_RATE - Self::IDLE_RATE) * level) * self.params[Self::DENSITY].value;
+        self.spawn_debt = (self.spawn_debt + rate * dt).min(MAX_STARS as f32);
+        while self.spawn_debt >= 1.0 && !self.stars.is_full() {
+            self.spawn_debt -= 1.0;
+            let angle = self.rng.next_f32() * core::f32::consts::TAU;
+            let offset = 0.15 + 0.85 * self.rng.next_f32();
+            let band = self.pick_band();
+            let z = 1.0 - 0.1 * self.rng.next_f32(); // staggered, so a burst doesn't move as one ring
+            self.stars.spawn(Star { x: offset * libm::cosf(angle), y: offset * libm::sinf(angle), z, last: z, band });
+        }
+    }
+
+    pub fn render_with_palette<F>(&self, mut set_pixel: F, pal: &ColorPalette)
+    where
+        F: FnMut(usize, usize, Color),
+    {
+        let streaks = self.params[Self::STREAKS].value;
+        for star in self.stars.iter() {
+            // fade in from the far plane so new stars don't pop
+            let brightness = ((1.0 - star.z) * 2.5).clamp(0.0, 1.0);
+            let color = pal.band(star.band as usize, self.num_channels).scale(brightness);
+            let (x1, y1) = star.project(star.z);
+            // the tail reaches back toward where the star was, up to a whole frame's travel
+            let tail = star.z + (star.last - star.z) * streaks;
+            let (x0, y0) = star.project(tail);
+            line_aa(&mut set_pixel, x0, y0, x1, y1, color);
+        }
+    }
+}
+
+impl VisualMode for Starfield {
+    fn update(&mut self, dt: f32, frame: &AnalysisFrame) {
+        Starfield::update(self, dt, frame.bands());
+    }
+
+    fn render(&self, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.render_with_palette(set_pixel, pal);
+    }
+
+    fn params(&self) -> &[Param] {
+        &self.params
+    }
+
+    fn params_mut(&mut self) -> &mut [Param] {
+        &mut self.params
+    }
+}
@@ -0,0 +1,44 @@
+// persistence backend for the settings blob. the ui only ever hands over or asks for one
+// opaque byte slice, so any storage works:
+// - simulator: a file next to the binary
+// - badge: a reserved flash sector or i2c eeprom. erase-before-write and wear leveling are
+//   the implementation's job, e.g. alternate between two sectors with a sequence number and
+//   load the newest one that decodes, so a power cut mid-save never loses both copies.
+
+#[cfg(feature = "postcard")]
+use crate::settings::Settings;
+
+// upper bound for the encoded settings, sized for one small flash page
+pub const SETTINGS_BLOB_LEN: usize = 512;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StoreError {
+    Io,       // backend failed to read or write
+    TooLarge, // blob doesn't fit the buffer or the storage area
+    Format,   // stored data doesn't decode, e.g. written by an older firmware
+}
+
+pub trait SettingsStore {
+    // copy the stored blob into `buf` and return its length, Ok(0) when nothing was saved yet
+    fn load(&mut self, buf: &mut [u8]) -> Result<usize, StoreError>;
+
+    // replace the stored blob
+    fn save(&mut self, data: &[u8]) -> Result<(), StoreError>;
+}
+
+// None when the store is empty
+#[cfg(feature = "postcard")]
+pub fn load_settings(store: &mut dyn SettingsStore) -> Result<Option<Settings>, StoreError> {
+    let mut buf = [0u8; SETTINGS_BLOB_LEN];
+    match store.load(&mut buf)? {
+        0 => Ok(None),
+        len => crate::persist::decode(&buf[..len]).map(Some).map_err(|_| StoreError::Format),
+    }
+}
+
+#[cfg(feature = "postcard")]
+pub fn save_settings(store: &mut dyn SettingsStore, settings: &Settings) -> Result<(), StoreError> {
+    let mut buf = [0u8; SETTINGS_BLOB_LEN];
+    let blob = crate::persist::encode(settings, &mut buf).map_err(|_| StoreError::TooLarge)?;
+    store.save(blob)
+}
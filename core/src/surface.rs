@@ -0,0 +1,124 @@
+// things a frame is drawn onto. the round panel is the 240x240 Framebuffer, a NeoPixel ring
+// around it is a LedRing: one row of LEDs, 0 at 12 o'clock running clockwise. both are a
+// draw::Canvas. modes that know what to do with the ring draw it themselves
+// (VisualMode::render_ring), for the rest the Visualizer carries the display's outer edge out
+// onto it. the firmware sends the ring with write_grb after every frame.
+
+use core::f32::consts::{FRAC_PI_2, TAU};
+
+use crate::draw::Canvas;
+use crate::framebuffer::Framebuffer;
+use crate::{polar, Color, DISPLAY_SIZE};
+
+pub const MAX_RING_LEDS: usize = 64;
+
+pub struct LedRing {
+    leds: [Color; MAX_RING_LEDS],
+    len: usize,
+}
+
+impl LedRing {
+    pub const BYTES_PER_LED: usize = 3;
+
+    // where the edge is sampled for modes without their own ring pattern, and how far either
+    // side of each LED's angle in pixels
+    const EDGE_RADIUS: f32 = 112.0;
+    const EDGE_SPREAD: f32 = 4.0;
+    const EDGE_SAMPLES: usize = 5;
+
+    // None for no LEDs or more than MAX_RING_LEDS
+    pub fn new(len: usize) -> Option<Self> {
+        (1..=MAX_RING_LEDS).contains(&len).then_some(Self { leds: [Color::default(); MAX_RING_LEDS], len })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn leds(&self) -> &[Color] {
+        &self.leds[..self.len]
+    }
+
+    pub fn leds_mut(&mut self) -> &mut [Color] {
+        &mut self.leds[..self.len]
+    }
+
+    // screen angle of LED `index` (polar convention, 0 at 3 o'clock, clockwise)
+    pub fn angle(&self, index: usize) -> f32 {
+        index as f32 / self.len as f32 * TAU - FRAC_PI_2
+    }
+
+    // 0-1 of the way round from 12 o'clock, clockwise
+    pub fn around(&self, index: usize) -> f32 {
+        index as f32 / self.len as f32
+    }
+
+    pub fn clear(&mut self, color: Color) {
+        self.leds_mut().fill(color);
+    }
+
+    // out-of-range LEDs are ignored
+    pub fn set(&mut self, index: usize, color: Color) {
+        if let Some(led) = self.leds_mut().get_mut(index) {
+            *led = color;
+        }
+    }
+
+    // saturating additive blend
+    pub fn add(&mut self, index: usize, color: Color) {
+        if let Some(led) = self.leds_mut().get_mut(index) {
+            *led = Color::new(led.r.saturating_add(color.r), led.g.saturating_add(color.g), led.b.saturating_add(color.b));
+        }
+    }
+
+    pub fn scale(&mut self, factor: f32) {
+        for led in self.leds_mut() {
+            *led = led.scale(factor);
+        }
+    }
+
+    // each LED takes the average of the display just inside the edge at its angle. `step` is
+    // the grid the frame was rendered on (PowerProfile::pixel_step)
+    pub fn sample_edge(&mut self, fb: &Framebuffer, step: usize) {
+        let step = step.max(1);
+        for i in 0..self.len {
+            let angle = self.angle(i);
+            let (mut r, mut g, mut b) = (0u32, 0u32, 0u32);
+            for s in 0..Self::EDGE_SAMPLES {
+                let offset = (s as f32 / (Self::EDGE_SAMPLES - 1) as f32 - 0.5) * 2.0 * Self::EDGE_SPREAD;
+                let (x, y) = polar::from_polar(angle + offset / Self::EDGE_RADIUS, Self::EDGE_RADIUS);
+                let (x, y) = ((x as usize).min(DISPLAY_SIZE - 1), (y as usize).min(DISPLAY_SIZE - 1));
+                let p = fb.get(x - x % step, y - y % step);
+                r += p.r as u32;
+                g += p.g as u32;
+                b += p.b as u32;
+            }
+            let n = Self::EDGE_SAMPLES as u32;
+            self.leds[i] = Color::new((r / n) as u8, (g / n) as u8, (b / n) as u8);
+        }
+    }
+
+    // WS2812 wire order, three bytes per LED. returns the bytes written, fewer when `out` is
+    // too short for the whole ring
+    pub fn write_grb(&self, out: &mut [u8]) -> usize {
+        let mut written = 0;
+        for (led, bytes) in self.leds().iter().zip(out.chunks_exact_mut(Self::BYTES_PER_LED)) {
+            bytes.copy_from_slice(&[led.g, led.r, led.b]);
+            written += Self::BYTES_PER_LED;
+        }
+        written
+    }
+}
+
+// the ring is one row, x is the LED. blends by coverage like the Framebuffer
+impl Canvas for LedRing {
+    fn plot(&mut self, x: usize, y: usize, color: Color, coverage: f32) {
+        if let (0, Some(led)) = (y, self.leds_mut().get_mut(x)) {
+            *led = Color::lerp(*led, color, coverage);
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use crate::font::{Font, FONT_5X7};
+use crate::framebuffer::Framebuffer;
+use crate::region::DISPLAY_REGION;
+use crate::{Color, DISPLAY_SIZE};
+
+// fixed-capacity string for formatting labels without allocating, extra text is dropped
+#[derive(Clone, Copy)]
+pub struct TextBuf<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> TextBuf<N> {
+    pub fn new() -> Self {
+        Self { bytes: [0; N], len: 0 }
+    }
+
+    pub fn as_str(&self) -> &str {
+        // only whole chars are ever copied in
+        core::str::from_utf8(&self.bytes[..self.len]).unwrap_or("")
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<const N: usize> Default for TextBuf<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> core::fmt::Write for TextBuf<N> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        for c in s.chars() {
+            let mut utf8 = [0u8; 4];
+            let encoded = c.encode_utf8(&mut utf8).as_bytes();
+            if self.len + encoded.len() > N {
+                break;
+            }
+            self.bytes[self.len..self.len + encoded.len()].copy_from_slice(encoded);
+            self.len += encoded.len();
+        }
+        Ok(())
+    }
+}
+
+// draw text with the small 5x7 font, (x, y) is the top left of the first cell
+pub fn draw_text(fb: &mut Framebuffer, x: i32, y: i32, text: &str, color: Color) {
+    draw_text_with_font(fb, &FONT_5X7, x, y, text, color);
+}
+
+pub fn draw_text_with_font(fb: &mut Framebuffer, font: &Font, x: i32, y: i32, text: &str, color: Color) {
+    let mut cx = x;
+    for c in text.chars() {
+        for (row, &bits) in font.glyph(c).iter().enumerate() {
+            for col in 0..font.width {
+                if bits & (0x80 >> col) != 0 {
+                    fb.set_i32(cx + col as i32, y + row as i32, color);
+                }
+            }
+        }
+        cx += font.advance as i32;
+    }
+}
+
+pub fn text_width(font: &Font, text: &str) -> usize {
+    let n = text.chars().count();
+    // no trailing spacing after the last glyph
+    if n == 0 { 0 } else { (n - 1) * font.advance + font.width }
+}
+
+// x that centers the text horizontally on the display
+pub fn centered_x(font: &Font, text: &str) -> i32 {
+    (DISPLAY_SIZE as i32 - text_width(font, text) as i32) / 2
+}
+
+pub fn draw_text_centered(fb: &mut Framebuffer, font: &Font, y: i32, text: &str, color: Color) {
+    draw_text_with_font(fb, font, centered_x(font, text), y, text, color);
+}
+
+// visible width for a line of text starting at row y, limited by the narrower edge of the circle
+pub fn line_width_at(font: &Font, y: i32) -> usize {
+    let top = y.clamp(0, DISPLAY_SIZE as i32 - 1) as usize;
+    let bottom = (y + font.height as i32 - 1).clamp(0, DISPLAY_SIZE as i32 - 1) as usize;
+    let (s0, e0) = DISPLAY_REGION.span(top);
+    let (s1, e1) = DISPLAY_REGION.span(bottom);
+    (e0 - s0).min(e1 - s1)
+}
+
+// whether centered text at row y stays inside the round panel
+pub fn fits_centered(font: &Font, y: i32, text: &str) -> bool {
+    text_width(font, text) <= line_width_at(font, y)
+}
+
+// characters that fit on a centered line at row y
+pub fn max_chars_at(font: &Font, y: i32) -> usize {
+    let width = line_width_at(font, y);
+    if width < font.width { 0 } else { (width - font.width) / font.advance + 1 }
+}
@@ -0,0 +1,143 @@
+// character wheel text entry: rotate the encoder to pick a character, press to accept it.
+// the wheel ends with delete and done entries, a long press cancels.
+
+pub const NAME_LEN: usize = 12;
+
+const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789 -_!";
+
+// short fixed-size ascii name (themes, presets), no allocation
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Name {
+    bytes: [u8; NAME_LEN],
+    len: u8,
+}
+
+impl Name {
+    pub fn new(text: &str) -> Self {
+        let mut name = Self::default();
+        for b in text.bytes() {
+            if !name.push(b) {
+                break;
+            }
+        }
+        name
+    }
+
+    pub fn as_str(&self) -> &str {
+        // only ascii from CHARSET (or sanitized input) ever lands in here
+        core::str::from_utf8(&self.bytes[..self.len as usize]).unwrap_or("")
+    }
+
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len as usize == NAME_LEN
+    }
+
+    // returns false if the name is full, non-printable bytes become '?'
+    pub fn push(&mut self, b: u8) -> bool {
+        if self.is_full() {
+            return false;
+        }
+        let b = if b.is_ascii_graphic() || b == b' ' { b } else { b'?' };
+        self.bytes[self.len as usize] = b;
+        self.len += 1;
+        true
+    }
+
+    pub fn pop(&mut self) -> Option<u8> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        Some(self.bytes[self.len as usize])
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WheelEntry {
+    Char(u8),
+    Delete,
+    Done,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextInputState {
+    Editing,
+    Done(Name),
+    Cancelled,
+}
+
+pub struct TextInput {
+    name: Name,
+    selected: usize,
+}
+
+impl TextInput {
+    const WHEEL_LEN: usize = CHARSET.len() + 2;
+
+    pub fn new(initial: &str) -> Self {
+        Self { name: Name::new(initial), selected: 0 }
+    }
+
+    pub fn text(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn name(&self) -> Name {
+        self.name
+    }
+
+    pub fn selected(&self) -> WheelEntry {
+        Self::entry(self.selected)
+    }
+
+    // entry `offset` positions away from the selection, for drawing the neighbours on the wheel
+    pub fn neighbour(&self, offset: i32) -> WheelEntry {
+        let len = Self::WHEEL_LEN as i32;
+        Self::entry((self.selected as i32 + offset).rem_euclid(len) as usize)
+    }
+
+    fn entry(index: usize) -> WheelEntry {
+        match index {
+            i if i < CHARSET.len() => WheelEntry::Char(CHARSET[i]),
+            i if i == CHARSET.len() => WheelEntry::Delete,
+            _ => WheelEntry::Done,
+        }
+    }
+
+    pub fn rotate(&mut self, steps: i32) {
+        let len = Self::WHEEL_LEN as i32;
+        self.selected = (self.selected as i32 + steps).rem_euclid(len) as usize;
+    }
+
+    // short press: apply the selected entry
+    pub fn press(&mut self) -> TextInputState {
+        match self.selected() {
+            WheelEntry::Char(c) => {
+                self.name.push(c);
+                // a full name can only be deleted from or confirmed, jump to done
+                if self.name.is_full() {
+                    self.selected = Self::WHEEL_LEN - 1;
+                }
+                TextInputState::Editing
+            }
+            WheelEntry::Delete => {
+                self.name.pop();
+                TextInputState::Editing
+            }
+            WheelEntry::Done => TextInputState::Done(self.name),
+        }
+    }
+
+    // long press: leave without saving
+    pub fn long_press(&mut self) -> TextInputState {
+        TextInputState::Cancelled
+    }
+}
@@ -0,0 +1,219 @@
+// short transient messages ("Preset 3", "Battery low") in a banner that follows the top edge,
+// drawn on top of everything else. the text is bent along the arc so it stays inside the round
+// panel. messages that arrive while one is showing wait their turn, and one that is waiting
+// cuts the current message short so a burst of changes doesn't take ages to play out.
+
+use core::f32::consts::{FRAC_PI_2, PI, TAU};
+use core::fmt::Write;
+
+use crate::font::{Font, FONT_8X16};
+use crate::framebuffer::Framebuffer;
+use crate::overlay::{BatteryGauge, StatusInputs, Widget};
+use crate::polar;
+use crate::text::{text_width, TextBuf};
+use crate::{palette, Color, ColorPalette, DISPLAY_CENTER};
+
+pub const TOAST_LEN: usize = 24; // characters, longer messages are cut off
+
+#[derive(Clone, Copy)]
+struct Toast {
+    text: TextBuf<TOAST_LEN>, // ascii only, so every byte is one glyph
+    seconds: f32,             // fully visible, not counting the fades
+}
+
+impl Toast {
+    fn lifetime(&self) -> f32 {
+        Toasts::FADE_IN_S + self.seconds + Toasts::FADE_OUT_S
+    }
+}
+
+pub struct Toasts {
+    queue: [Toast; Toasts::QUEUE_LEN], // queue[0] is on screen
+    len: usize,
+    age: f32, // seconds queue[0] has been showing
+    battery_zone: u8, // 0 fine, 1 low, 2 critical, so each warning fires once per discharge
+}
+
+impl Toasts {
+    pub const QUEUE_LEN: usize = 4;
+    pub const FADE_IN_S: f32 = 0.15;
+    pub const FADE_OUT_S: f32 = 0.3;
+    const MIN_SHOW_S: f32 = 0.6; // before a waiting message may cut in
+    const BATTERY_TOAST_S: f32 = 3.0;
+    const BATTERY_HYSTERESIS: f32 = 0.05;
+
+    const FONT: &'static Font = &FONT_8X16;
+    const RADIUS: f32 = 88.0; // middle of the banner
+    const PAD_Y: f32 = 3.0;
+    const PAD_X: f32 = 8.0;
+    const BACKGROUND_ALPHA: f32 = 0.85;
+
+    pub fn new() -> Self {
+        let empty = Toast { text: TextBuf::new(), seconds: 0.0 };
+        Self { queue: [empty; Self::QUEUE_LEN], len: 0, age: 0.0, battery_zone: 0 }
+    }
+
+    // show `text` for `seconds`, after whatever is already queued. the same text again only
+    // restarts its timer, and with the queue full the oldest waiting message is dropped
+    pub fn toast(&mut self, text: &str, seconds: f32) {
+        let mut toast = Toast { text: TextBuf::new(), seconds: seconds.max(0.0) };
+        for c in text.chars() {
+            let _ = toast.text.write_char(if c.is_ascii() && !c.is_ascii_control() { c } else { '?' });
+        }
+
+        if let Some(i) = self.queue[..self.len].iter().position(|t| t.text.as_str() == toast.text.as_str()) {
+            self.queue[i].seconds = toast.seconds;
+            if i == 0 {
+                self.age = self.age.min(Self::FADE_IN_S);
+            }
+            return;
+        }
+        if self.len == Self::QUEUE_LEN {
+            self.queue.copy_within(2.., 1);
+            self.len -= 1;
+        }
+        self.queue[self.len] = toast;
+        self.len += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.age = 0.0;
+    }
+
+    pub fn is_showing(&self) -> bool {
+        self.len > 0
+    }
+
+    // the message on screen
+    pub fn current(&self) -> Option<&str> {
+        (self.len > 0).then(|| self.queue[0].text.as_str())
+    }
+
+    // 0-1 over the fades
+    fn opacity(&self) -> f32 {
+        let lifetime = self.queue[0].lifetime();
+        (self.age / Self::FADE_IN_S).min((lifetime - self.age) / Self::FADE_OUT_S).clamp(0.0, 1.0)
+    }
+
+    fn check_battery(&mut self, inputs: &StatusInputs) {
+        let Some(level) = inputs.battery else {
+            self.battery_zone = 0;
+            return;
+        };
+        let zone = if level <= BatteryGauge::CRITICAL {
+            2
+        } else if level <= BatteryGauge::LOW {
+            1
+        } else {
+            0
+        };
+        if inputs.charging {
+            self.battery_zone = zone;
+            return;
+        }
+        if zone > self.battery_zone {
+            self.toast(if zone == 2 { "Battery critical" } else { "Battery low" }, Self::BATTERY_TOAST_S);
+            self.battery_zone = zone;
+        } else if zone < self.battery_zone {
+            // only rearm once the reading is clearly back above the threshold
+            let threshold = if self.battery_zone == 2 { BatteryGauge::CRITICAL } else { BatteryGauge::LOW };
+            if level > threshold + Self::BATTERY_HYSTERESIS {
+                self.battery_zone = zone;
+            }
+        }
+    }
+}
+
+impl Widget for Toasts {
+    fn update(&mut self, dt: f32, inputs: &StatusInputs) {
+        self.check_battery(inputs);
+        if self.len == 0 {
+            return;
+        }
+        self.age += dt;
+        if self.len > 1 {
+            // the next message is waiting: fade out after MIN_SHOW_S, or now if that's past
+            let shown = (self.age - Self::FADE_IN_S).max(Self::MIN_SHOW_S);
+            self.queue[0].seconds = self.queue[0].seconds.min(shown);
+        }
+        if self.age >= self.queue[0].lifetime() {
+            self.queue.copy_within(1..self.len, 0);
+            self.len -= 1;
+            self.age = 0.0;
+        }
+    }
+
+    fn render(&self, fb: &mut Framebuffer, pal: &ColorPalette) {
+        if self.len == 0 {
+            return;
+        }
+        let opacity = self.opacity();
+        if opacity <= 0.0 {
+            return;
+        }
+        let text = self.queue[0].text.as_str().as_bytes();
+        let font = Self::FONT;
+        let width = text_width(font, self.queue[0].text.as_str()) as f32;
+        let half_height = font.height as f32 / 2.0 + Self::PAD_Y;
+        let (inner, outer) = (Self::RADIUS - half_height, Self::RADIUS + half_height);
+        let half_sweep = ((width / 2.0 + Self::PAD_X) / Self::RADIUS).min(PI / 2.0);
+
+        let background = Color::lerp(palette::BLACK, pal.primary, 0.35);
+        let foreground = Color::lerp(pal.primary, palette::WHITE, 0.7);
+
+        // bounding box of the wedge around 12 o'clock
+        let x_reach = outer * libm::sinf(half_sweep) + 1.0;
+        let (x_min, x_max) = ((DISPLAY_CENTER - x_reach).max(0.0) as usize, (DISPLAY_CENTER + x_reach) as usize);
+        let y_min = (DISPLAY_CENTER - outer - 1.0).max(0.0) as usize;
+        let y_max = (DISPLAY_CENTER - inner * libm::cosf(half_sweep) + 1.0) as usize;
+
+        for y in y_min..=y_max {
+            for x in x_min..=x_max {
+                let (angle, d) = polar::to_polar(x as f32, y as f32);
+                // -PI..PI from 12 o'clock, clockwise
+                let rel = (angle + FRAC_PI_2 + PI).rem_euclid(TAU) - PI;
+                let coverage = (d - inner + 0.5).min(outer - d + 0.5).min((half_sweep - libm::fabsf(rel)) * d + 0.5);
+                if coverage <= 0.0 {
+                    continue;
+                }
+                let alpha = coverage.min(1.0) * opacity;
+                fb.blend(x, y, background, (alpha * Self::BACKGROUND_ALPHA * 255.0) as u8);
+
+                // glyph space: arc length along the banner's middle and depth below its outer edge
+                let gx = rel * Self::RADIUS + width / 2.0;
+                let gy = Self::RADIUS + font.height as f32 / 2.0 - d;
+                let ink = text_coverage(font, text, gx - 0.5, gy - 0.5);
+                if ink > 0.0 {
+                    fb.blend(x, y, foreground, (ink * alpha * 255.0) as u8);
+                }
+            }
+        }
+    }
+}
+
+impl Default for Toasts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// bilinear sample of a line of text, (0, 0) is the center of the first glyph's top left pixel
+fn text_coverage(font: &Font, text: &[u8], x: f32, y: f32) -> f32 {
+    let lit = |ix: i32, iy: i32| -> f32 {
+        if ix < 0 || iy < 0 || iy as usize >= font.height {
+            return 0.0;
+        }
+        let (i, col) = (ix as usize / font.advance, ix as usize % font.advance);
+        match text.get(i) {
+            Some(&c) if col < font.width && font.glyph(c as char)[iy as usize] & (0x80 >> col) != 0 => 1.0,
+            _ => 0.0,
+        }
+    };
+    let (x0, y0) = (libm::floorf(x), libm::floorf(y));
+    let (fx, fy) = (x - x0, y - y0);
+    let (ix, iy) = (x0 as i32, y0 as i32);
+    let top = lit(ix, iy) * (1.0 - fx) + lit(ix + 1, iy) * fx;
+    let bottom = lit(ix, iy + 1) * (1.0 - fx) + lit(ix + 1, iy + 1) * fx;
+    top * (1.0 - fy) + bottom * fy
+}
@@ -1,23 +1,224 @@
 use crate::{
     Color, ColorPalette, EnvelopeSmoother, LFO, Point2D,
-    DISPLAY_SIZE, draw_line, draw_thick_line, is_in_circle,
+    DISPLAY_SIZE, draw_line, draw_thick_line, in_display,
 };
+use crate::boot::BootAnimation;
+use crate::clock::{ClockFace, TimeOfDay};
+use crate::mirror::Mirror;
+use crate::split::Split;
+use crate::vowel::VowelField;
+use crate::param::Param;
+use crate::plosive::PlosiveGuard;
+use crate::procedural::{Aurora, Plasma};
+use crate::ripple::Ripple;
+use crate::flower::Flower;
+use crate::starfield::Starfield;
+use crate::region::{RenderRegion, DISPLAY_REGION};
+use crate::registry::{self, ModeInfo, RegisterError, UserMode, MAX_USER_MODES};
+use crate::response::ResponseCurves;
+use crate::smoothing::{BandSmoothing, EnergySmoother};
+use crate::surface::LedRing;
+use crate::framebuffer::Framebuffer;
+use crate::error::UiError;
+use crate::source::{AnalysisFrame, ChannelCount};
+use core::ops::Range;
+
 use libm::{cosf, sinf, sqrtf};
 
 const MAX_CHANNELS: usize = crate::CHANNELS;
 
-// available visualizers (one for now)
+// available visualizers
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ModeKind {
-    HarmonicLoop
+    HarmonicLoop,
+    Plasma,
+    Aurora,
+    Mirror,
+    Vowel,
+    Split,
+    Ripple,
+    Flower,
+    Starfield,
+    Clock,
+    User(u8), // registered from outside core, by its place in the registry's catalog
 }
 
 impl ModeKind {
+    pub const ALL: [ModeKind; 10] = [
+        ModeKind::HarmonicLoop,
+        ModeKind::Plasma,
+        ModeKind::Aurora,
+        ModeKind::Mirror,
+        ModeKind::Vowel,
+        ModeKind::Split,
+        ModeKind::Ripple,
+        ModeKind::Flower,
+        ModeKind::Starfield,
+        ModeKind::Clock,
+    ];
+
     pub fn name(&self) -> &'static str {
         match self {
-            ModeKind::HarmonicLoop => "Harmonic Loop"
+            ModeKind::HarmonicLoop => "Harmonic Loop",
+            ModeKind::Plasma => "Plasma",
+            ModeKind::Aurora => "Aurora",
+            ModeKind::Mirror => "Mirror",
+            ModeKind::Vowel => "Vowel",
+            ModeKind::Split => "Split",
+            ModeKind::Ripple => "Ripple",
+            ModeKind::Flower => "Flower",
+            ModeKind::Starfield => "Starfield",
+            ModeKind::Clock => "Clock",
+            ModeKind::User(i) => registry::info(*i as usize).map_or("Unknown", |info| info.name),
+        }
+    }
+
+    // short stable identifier for config files and the control protocol
+    pub fn id(&self) -> &'static str {
+        match self {
+            ModeKind::HarmonicLoop => "harmonic",
+            ModeKind::Plasma => "plasma",
+            ModeKind::Aurora => "aurora",
+            ModeKind::Mirror => "mirror",
+            ModeKind::Vowel => "vowel",
+            ModeKind::Split => "split",
+            ModeKind::Ripple => "ripple",
+            ModeKind::Flower => "flower",
+            ModeKind::Starfield => "starfield",
+            ModeKind::Clock => "clock",
+            ModeKind::User(i) => registry::info(*i as usize).map_or("unknown", |info| info.id),
+        }
+    }
+
+    // draws every visible pixel each frame instead of lines over a fading trail buffer
+    pub fn is_full_screen(&self) -> bool {
+        match self {
+            ModeKind::Plasma | ModeKind::Aurora | ModeKind::Vowel | ModeKind::Ripple => true,
+            ModeKind::User(i) => registry::info(*i as usize).is_some_and(|info| info.full_screen),
+            _ => false,
+        }
+    }
+
+    // per-mode trail decay, overriding the PostFx::Trails setting
+    pub fn trail_decay(&self) -> Option<f32> {
+        // mirror, split and the clock stay crisp, trails would blur the pulse edge, the bar
+        // ends and the digits
+        if self.is_full_screen() || matches!(self, ModeKind::Mirror | ModeKind::Split | ModeKind::Clock) {
+            Some(0.0)
+        } else {
+            None
         }
     }
+
+    pub fn from_id(id: &str) -> Option<ModeKind> {
+        Self::ALL.into_iter().find(|m| m.id() == id).or_else(|| registry::find(id))
+    }
+
+    // position in ALL, for per-mode tables. registered modes have no entry in them
+    pub fn index(&self) -> Option<usize> {
+        Self::ALL.iter().position(|m| m == self)
+    }
+
+    // every mode: the built-in ones, then the registered ones in the order they were declared
+    pub fn count() -> usize {
+        Self::ALL.len() + registry::count()
+    }
+
+    pub fn nth(n: usize) -> ModeKind {
+        match Self::ALL.get(n) {
+            Some(&mode) => mode,
+            None => ModeKind::User((n - Self::ALL.len()) as u8),
+        }
+    }
+
+    // this mode's n in nth
+    pub fn position(&self) -> usize {
+        match self {
+            ModeKind::User(i) => Self::ALL.len() + *i as usize,
+            _ => self.index().unwrap_or(0),
+        }
+    }
+
+    // `steps` modes on, wrapping around
+    pub fn step(&self, steps: i32) -> ModeKind {
+        let count = Self::count() as i32;
+        Self::nth((self.position() as i32 + steps).rem_euclid(count) as usize)
+    }
+
+    pub fn next(&self) -> ModeKind {
+        self.step(1)
+    }
+
+    pub fn prev(&self) -> ModeKind {
+        self.step(-1)
+    }
+}
+
+// one visualization: fed the analysis frame every UI frame, draws through set_pixel
+pub trait VisualMode {
+    fn update(&mut self, dt: f32, frame: &AnalysisFrame);
+    fn render(&self, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette);
+
+    // only the pixels of rows `rows`, so bands of the display can be drawn in parallel.
+    // per-pixel modes should override this to skip the other rows' work
+    fn render_rows(&self, rows: Range<usize>, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.render(
+            &mut |x, y, color| {
+                if rows.contains(&y) {
+                    set_pixel(x, y, color);
+                }
+            },
+            pal,
+        );
+    }
+
+    // only the pixels on a grid of `step` (x and y multiples of it), for the reduced
+    // resolution of PowerProfile::Saver
+    fn render_sparse(&self, step: usize, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.render_region(&DISPLAY_REGION, step, set_pixel, pal);
+    }
+
+    // only the pixels of `region` on a grid of `step`, e.g. the one wedge the kaleidoscope
+    // replicates. per-pixel modes should override this so the skipped pixels cost nothing
+    fn render_region(&self, region: &RenderRegion, step: usize, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.render(
+            &mut |x, y, color| {
+                if x.is_multiple_of(step) && y.is_multiple_of(step) && region.contains(x, y) {
+                    set_pixel(x, y, color);
+                }
+            },
+            pal,
+        );
+    }
+
+    // true when render_rows and render_region only ever hand out pixels of the rows and region
+    // they're given, e.g. by walking RenderRegion::grid. the Visualizer then skips its own
+    // per-pixel clipping, modes drawing lines and points anywhere keep the default
+    fn stays_in_region(&self) -> bool {
+        false
+    }
+
+    // adjustable knobs, at most MAX_MODE_PARAMS of them. the mode keeps the values and reads
+    // them back when it updates and renders, the menu and controllers only write them
+    fn params(&self) -> &[Param] {
+        &[]
+    }
+
+    fn params_mut(&mut self) -> &mut [Param] {
+        &mut []
+    }
+
+    // the LED ring around the display, if the mode has its own pattern for it. false leaves
+    // the ring to the Visualizer, which carries the display's edge out onto it. the ring is a
+    // draw::Canvas (one row, x is the LED), so the primitives draw on it like on the panel
+    fn render_ring(&self, _ring: &mut LedRing, _pal: &ColorPalette) -> bool {
+        false
+    }
+
+    // modes that end by themselves (the boot animation) report it here
+    fn finished(&self) -> bool {
+        false
+    }
 }
 
 // Harmonic Loop. A single closed figure where each channel adds harmonic deformation
@@ -35,12 +236,16 @@ pub struct HarmonicLoop {
     trail_index: usize,
     circular_mask: bool,
     glow: bool,
+    params: [Param; 2],
 }
 
 impl HarmonicLoop {
+    const SIZE: usize = 0; // params
+    const SPIN: usize = 1;
+
     pub fn new(num_channels: usize) -> Self {
         Self {
-            num_channels,
+            num_channels: num_channels.min(MAX_CHANNELS),
             smoothers: core::array::from_fn(|_| EnvelopeSmoother::new(60.0, 5.0, 80.0)),
             energies: [0.0; MAX_CHANNELS],
             rotation: LFO::new(0.02),
@@ -53,6 +258,7 @@ impl HarmonicLoop {
             trail_index: 0,
             circular_mask: true,
             glow: true,
+            params: [Param::new("size", "Size", 0.5, 1.5, 0.1, 1.0), Param::new("spin", "Spin", 0.0, 4.0, 0.25, 1.0)],
         }
     }
 
@@ -78,7 +284,7 @@ impl HarmonicLoop {
         }
         
         // scale based on total energy
-        let scale = 0.45 + 0.35 * self.total_energy.value();
+        let scale = (0.45 + 0.35 * self.total_energy.value()) * self.params[Self::SIZE].value;
         Point2D::new(x * scale, y * scale).rotate(rotation)
     }
 
@@ -91,7 +297,7 @@ impl HarmonicLoop {
     }
 
     pub fn update(&mut self, dt: f32, energies: &[f32]) {
-        self.rotation.tick(dt);
+        self.rotation.tick(dt * self.params[Self::SPIN].value);
         
         for lfo in &mut self.harmonic_phases[..self.num_channels] {
             lfo.tick(dt);
@@ -121,10 +327,38 @@ impl HarmonicLoop {
         self.render_with_palette(set_pixel, &ColorPalette::default());
     }
 
-    pub fn render_with_palette<F>(&self, mut set_pixel: F, pal: &ColorPalette)
+    pub fn render_with_palette<F>(&self, set_pixel: F, pal: &ColorPalette)
     where
         F: FnMut(usize, usize, Color),
     {
+        self.draw(1, 0..DISPLAY_SIZE, set_pixel, pal);
+    }
+
+    // the figure on a grid of `step`: drawn at 1/step of the resolution and spread back out, so
+    // the lines stay connected where dropping the off-grid pixels would break them up. above
+    // step 1 the caller clips to the panel. segments and spots that can't reach `rows` are
+    // skipped before they're rasterized
+    fn draw<F>(&self, step: usize, rows: Range<usize>, mut set_pixel: F, pal: &ColorPalette)
+    where
+        F: FnMut(usize, usize, Color),
+    {
+        let s = step.max(1) as i32;
+        let size = DISPLAY_SIZE as i32 / s;
+        let mask = self.circular_mask && s == 1;
+        let screen = |p: Point2D| {
+            let (x, y) = p.to_screen();
+            ((x + s / 2).div_euclid(s), (y + s / 2).div_euclid(s))
+        };
+        // low-res rows y0..=y1, widened by `pad`, against the full-res band
+        let (first, last) = (rows.start as i32, rows.end as i32 - 1);
+        let reaches = |y0: i32, y1: i32, pad: i32| (y0.max(y1) + pad) * s >= first && (y0.min(y1) - pad) * s <= last;
+        let mut set_pixel = |x: usize, y: usize, color: Color| {
+            let (x, y) = (x * s as usize, y * s as usize);
+            if rows.contains(&y) {
+                set_pixel(x, y, color);
+            }
+        };
+
         // draw faded trails using palette accent color
         for age in 1..self.trail_history.len() {
             let hist_idx = (self.trail_index + self.trail_history.len() - age) % self.trail_history.len();
@@ -135,9 +369,11 @@ impl HarmonicLoop {
             for i in 0..self.resolution {
                 let p0 = self.trail_history[hist_idx][i];
                 let p1 = self.trail_history[hist_idx][(i + 1) % self.resolution];
-                let (sx0, sy0) = p0.to_screen();
-                let (sx1, sy1) = p1.to_screen();
-                draw_line(sx0, sy0, sx1, sy1, trail_color, self.circular_mask, &mut set_pixel);
+                let (sx0, sy0) = screen(p0);
+                let (sx1, sy1) = screen(p1);
+                if reaches(sy0, sy1, 0) {
+                    draw_line(sx0, sy0, sx1, sy1, trail_color, mask, &mut set_pixel);
+                }
             }
         }
         
@@ -149,38 +385,47 @@ impl HarmonicLoop {
             
             let p0 = self.sample_point(t0, rotation);
             let p1 = self.sample_point(t1, rotation);
-            let (sx0, sy0) = p0.to_screen();
-            let (sx1, sy1) = p1.to_screen();
+            let (sx0, sy0) = screen(p0);
+            let (sx1, sy1) = screen(p1);
+            let thickness = if self.glow { (2 / s).max(1) } else { 0 };
+            if !reaches(sy0, sy1, thickness) {
+                continue;
+            }
             
             // use palette gradient around the figure
             let color = pal.sample(i as f32 / self.resolution as f32);
             let brightness = 0.7 + 0.3 * self.total_energy.value();
             
             if self.glow {
-                draw_thick_line(sx0, sy0, sx1, sy1, 2, color.scale(brightness), self.circular_mask, &mut set_pixel);
+                draw_thick_line(sx0, sy0, sx1, sy1, thickness, color.scale(brightness), mask, &mut set_pixel);
             } else {
-                draw_line(sx0, sy0, sx1, sy1, color.scale(brightness), self.circular_mask, &mut set_pixel);
+                draw_line(sx0, sy0, sx1, sy1, color.scale(brightness), mask, &mut set_pixel);
             }
         }
         
         // draw bright spots at high-energy harmonics
+        let radius = (2 / s).max(1);
+        let reach = radius as f32 + 0.5;
         for i in 0..self.num_channels {
             if self.energies[i] > 0.4 {
                 let harmonic = (i + 2) as f32;
                 let t = self.harmonic_phases[i].phase / harmonic;
                 let point = self.sample_point(t, rotation);
-                let (sx, sy) = point.to_screen();
-                let color = pal.sample(i as f32 / self.num_channels as f32);
+                let (sx, sy) = screen(point);
+                if !reaches(sy, sy, radius) {
+                    continue;
+                }
+                let color = pal.band(i, self.num_channels);
                 
-                for dy in -2..=2i32 {
-                    for dx in -2..=2i32 {
+                for dy in -radius..=radius {
+                    for dx in -radius..=radius {
                         let (px, py) = (sx + dx, sy + dy);
-                        if px >= 0 && px < DISPLAY_SIZE as i32 && py >= 0 && py < DISPLAY_SIZE as i32 {
+                        if px >= 0 && px < size && py >= 0 && py < size {
                             let (ux, uy) = (px as usize, py as usize);
-                            if !self.circular_mask || is_in_circle(ux, uy) {
+                            if !mask || in_display(ux, uy) {
                                 let dist = sqrtf((dx * dx + dy * dy) as f32);
-                                if dist <= 2.5 {
-                                    let b = (1.0 - dist / 2.5) * self.energies[i];
+                                if dist <= reach {
+                                    let b = (1.0 - dist / reach) * self.energies[i];
                                     set_pixel(ux, uy, color.scale(b));
                                 }
                             }
@@ -192,51 +437,492 @@ impl HarmonicLoop {
     }
 }
 
+impl VisualMode for HarmonicLoop {
+    fn update(&mut self, dt: f32, frame: &AnalysisFrame) {
+        HarmonicLoop::update(self, dt, frame.bands());
+    }
+
+    fn render(&self, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.render_with_palette(set_pixel, pal);
+    }
+
+    fn render_rows(&self, rows: Range<usize>, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.draw(1, rows, set_pixel, pal);
+    }
+
+    fn render_region(&self, region: &RenderRegion, step: usize, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.draw(
+            step,
+            0..DISPLAY_SIZE,
+            |x, y, color| {
+                if region.contains(x, y) {
+                    set_pixel(x, y, color);
+                }
+            },
+            pal,
+        );
+    }
+
+    // the spectrum up both sides, lowest band at the bottom
+    fn render_ring(&self, ring: &mut LedRing, pal: &ColorPalette) -> bool {
+        let n = self.num_channels.max(1);
+        for i in 0..ring.len() {
+            // 0 at the bottom, 1 at the top
+            let height = libm::fabsf(ring.around(i) - 0.5) * 2.0;
+            let band = ((height * (n - 1) as f32 + 0.5) as usize).min(n - 1);
+            let energy = self.energies[band].clamp(0.0, 1.0);
+            ring.set(i, pal.band(band, n).scale(0.1 + 0.9 * energy));
+        }
+        true
+    }
+
+    fn params(&self) -> &[Param] {
+        &self.params
+    }
+
+    fn params_mut(&mut self) -> &mut [Param] {
+        &mut self.params
+    }
+}
 
 // main visualizer mode switching
 pub struct Visualizer {
+    channels: ChannelCount,
     harmonic_loop: HarmonicLoop,
+    plasma: Plasma,
+    aurora: Aurora,
+    mirror: Mirror,
+    vowel: VowelField,
+    split: Split,
+    ripple: Ripple,
+    flower: Flower,
+    starfield: Starfield,
+    clock: ClockFace,
+    user: [Option<UserMode>; MAX_USER_MODES], // by catalog index, see registry
+    boot: Option<BootAnimation>, // plays before current_mode until finished or skipped
     current_mode: ModeKind,
-    palette: ColorPalette
+    palette: ColorPalette,
+    hue: f32,              // turns, PostChain::hue
+    drawn: ColorPalette,   // palette turned by hue, what the modes get
+    plosive_guard: PlosiveGuard,
+    smoother: EnergySmoother,
+    right_guard: PlosiveGuard, // AnalysisFrame::right gets its own, the split shows both
+    right_smoother: EnergySmoother,
+    response: ResponseCurves,
+    demo_cycle: Option<f32>, // seconds per mode when cycling
+    demo_timer: f32,
+    idle_clock: Option<f32>, // seconds without voice or input before the clock face takes over
+    idle_timer: f32,
+    idle: bool, // showing the clock face in place of current_mode
 }
 
 impl Visualizer {
-    pub fn new(num_channels: usize) -> Self {
+    pub fn new(channels: ChannelCount) -> Self {
+        let num_channels = channels.get();
         Self {
+            channels,
             harmonic_loop: HarmonicLoop::new(num_channels),
+            plasma: Plasma::new(),
+            aurora: Aurora::new(num_channels),
+            mirror: Mirror::new(),
+            vowel: VowelField::new(),
+            split: Split::new(num_channels),
+            ripple: Ripple::new(),
+            flower: Flower::new(num_channels),
+            starfield: Starfield::new(num_channels),
+            clock: ClockFace::new(),
+            user: [const { None }; MAX_USER_MODES],
+            boot: None,
             current_mode: ModeKind::HarmonicLoop,
             palette: ColorPalette::default(),
+            hue: 0.0,
+            drawn: ColorPalette::default(),
+            plosive_guard: PlosiveGuard::new(),
+            smoother: EnergySmoother::new(num_channels),
+            right_guard: PlosiveGuard::new(),
+            right_smoother: EnergySmoother::new(num_channels),
+            response: ResponseCurves::default(),
+            demo_cycle: None,
+            demo_timer: 0.0,
+            idle_clock: None,
+            idle_timer: 0.0,
+            idle: false,
         }
     }
 
-    pub fn update(&mut self, dt: f32, energies: &[f32]) {
-        match self.current_mode {
-            ModeKind::HarmonicLoop => self.harmonic_loop.update(dt, energies)
+    // update from a full analysis frame: the bands are resampled to the frame rate by the
+    // smoother, then the plosive guard softens them and the response curve shapes them, the
+    // right bands the same way. a frame with another band count than the visualizer's is
+    // refused and nothing changes
+    pub fn update_frame(&mut self, dt: f32, frame: &AnalysisFrame) -> Result<(), UiError> {
+        let channels = frame.channels()?;
+        if channels != self.channels {
+            return Err(UiError::ChannelMismatch { expected: self.channels.get(), got: channels.get() });
+        }
+        let mut smoothed = *frame;
+        smoothed.set_bands(self.smoother.process(dt, frame));
+        let n = smoothed.num_channels;
+        self.plosive_guard.apply(frame.plosive, &mut smoothed.energies[..n]);
+        let curve = self.response.get(self.displayed_mode());
+        curve.apply_all(&mut smoothed.energies[..n]);
+        if let Some(right) = &mut smoothed.right {
+            let right_frame = AnalysisFrame { energies: *right, right: None, ..*frame };
+            right[..n].copy_from_slice(self.right_smoother.process(dt, &right_frame));
+            self.right_guard.apply(frame.plosive, &mut right[..n]);
+            curve.apply_all(&mut right[..n]);
         }
+        self.update(dt, &smoothed);
+        Ok(())
+    }
+
+    // the band count the modes were built for
+    pub fn channels(&self) -> ChannelCount {
+        self.channels
+    }
+
+    // per-band attack/release applied by update_frame, to the right bands too
+    pub fn set_band_smoothing(&mut self, band: usize, smoothing: BandSmoothing) {
+        self.smoother.set_band(band, smoothing);
+        self.right_smoother.set_band(band, smoothing);
     }
 
+    // energy-to-visual curves applied by update_frame
+    pub fn set_response(&mut self, response: ResponseCurves) {
+        self.response = response;
+    }
+
+    pub fn response(&self) -> &ResponseCurves {
+        &self.response
+    }
+
+    pub fn response_mut(&mut self) -> &mut ResponseCurves {
+        &mut self.response
+    }
+
+    // a frame whose bands are already at the frame rate, update_frame is the usual entry point
+    pub fn update(&mut self, dt: f32, frame: &AnalysisFrame) {
+        if let Some(boot) = &mut self.boot {
+            boot.update(dt, frame);
+            if boot.finished() {
+                self.boot = None;
+            }
+            return;
+        }
+
+        if let Some(period) = self.demo_cycle {
+            self.demo_timer += dt;
+            if self.demo_timer >= period {
+                self.demo_timer = 0.0;
+                self.current_mode = self.stepped(1);
+            }
+        }
+
+        // the demo cycle already keeps the display busy
+        match self.idle_clock {
+            Some(after) if self.demo_cycle.is_none() => {
+                self.idle_timer = if frame.voiced { 0.0 } else { self.idle_timer + dt };
+                self.idle = self.idle_timer >= after;
+            }
+            _ => self.idle = false,
+        }
+
+        self.active_mut().update(dt, frame);
+    }
+
+    fn active(&self) -> &dyn VisualMode {
+        match &self.boot {
+            Some(boot) => boot,
+            None => self.mode(self.displayed_mode()),
+        }
+    }
+
+    fn active_mut(&mut self) -> &mut dyn VisualMode {
+        let mode = self.displayed_mode();
+        match self.boot {
+            Some(ref mut boot) => boot,
+            None => self.mode_mut(mode),
+        }
+    }
+
+    fn mode(&self, kind: ModeKind) -> &dyn VisualMode {
+        match kind {
+            ModeKind::HarmonicLoop => &self.harmonic_loop,
+            ModeKind::Plasma => &self.plasma,
+            ModeKind::Aurora => &self.aurora,
+            ModeKind::Mirror => &self.mirror,
+            ModeKind::Vowel => &self.vowel,
+            ModeKind::Split => &self.split,
+            ModeKind::Ripple => &self.ripple,
+            ModeKind::Flower => &self.flower,
+            ModeKind::Starfield => &self.starfield,
+            ModeKind::Clock => &self.clock,
+            // set_mode only selects user modes registered with this visualizer
+            ModeKind::User(i) => self.user[i as usize].as_ref().expect("user mode registered with this visualizer").get(),
+        }
+    }
+
+    fn mode_mut(&mut self, kind: ModeKind) -> &mut dyn VisualMode {
+        match kind {
+            ModeKind::HarmonicLoop => &mut self.harmonic_loop,
+            ModeKind::Plasma => &mut self.plasma,
+            ModeKind::Aurora => &mut self.aurora,
+            ModeKind::Mirror => &mut self.mirror,
+            ModeKind::Vowel => &mut self.vowel,
+            ModeKind::Split => &mut self.split,
+            ModeKind::Ripple => &mut self.ripple,
+            ModeKind::Flower => &mut self.flower,
+            ModeKind::Starfield => &mut self.starfield,
+            ModeKind::Clock => &mut self.clock,
+            ModeKind::User(i) => self.user[i as usize].as_mut().expect("user mode registered with this visualizer").get_mut(),
+        }
+    }
+
+    // adds a mode from outside core (see registry) to this visualizer, the ModeKind returned
+    // selects it. registering the same ModeInfo again replaces the instance
+    pub fn register_static(&mut self, info: &'static ModeInfo, mode: &'static mut (dyn VisualMode + Send + Sync)) -> Result<ModeKind, RegisterError> {
+        self.add_user(info, UserMode::Static(mode))
+    }
+
+    #[cfg(feature = "alloc")]
+    pub fn register(&mut self, info: &'static ModeInfo, mode: alloc::boxed::Box<dyn VisualMode + Send + Sync>) -> Result<ModeKind, RegisterError> {
+        self.add_user(info, UserMode::Boxed(mode))
+    }
+
+    fn add_user(&mut self, info: &'static ModeInfo, mode: UserMode) -> Result<ModeKind, RegisterError> {
+        let kind = registry::declare(info)?;
+        if let ModeKind::User(i) = kind {
+            self.user[i as usize] = Some(mode);
+        }
+        Ok(kind)
+    }
+
+    // knobs of the current mode, for the menu and ParamId::ModeParam
+    pub fn params(&self) -> &[Param] {
+        self.mode(self.current_mode).params()
+    }
+
+    pub fn params_mut(&mut self) -> &mut [Param] {
+        self.mode_mut(self.current_mode).params_mut()
+    }
+
+    // any mode's knobs, whether it's showing or not. none for a user mode this visualizer
+    // has no instance of
+    pub fn mode_params(&self, kind: ModeKind) -> &[Param] {
+        if !self.has_mode(kind) {
+            return &[];
+        }
+        self.mode(kind).params()
+    }
+
+    pub fn mode_params_mut(&mut self, kind: ModeKind) -> &mut [Param] {
+        if !self.has_mode(kind) {
+            return &mut [];
+        }
+        self.mode_mut(kind).params_mut()
+    }
+
+    // the built-in modes, and the user modes registered with this visualizer. the catalog is
+    // shared, so another visualizer may have registered ones this one hasn't
+    pub fn has_mode(&self, kind: ModeKind) -> bool {
+        match kind {
+            ModeKind::User(i) => matches!(self.user.get(i as usize), Some(Some(_))),
+            _ => true,
+        }
+    }
+
+    // only pixels inside the visible region ever reach set_pixel
     pub fn render<F>(&self, set_pixel: F)
     where
         F: FnMut(usize, usize, Color),
     {
-        match self.current_mode {
-            ModeKind::HarmonicLoop => self.harmonic_loop.render_with_palette(set_pixel, &self.palette)
+        self.render_rows(0..DISPLAY_SIZE, set_pixel);
+    }
+
+    // same pixels as render, restricted to rows `rows`. the bands can be drawn on separate
+    // threads and add up to the full frame
+    pub fn render_rows<F>(&self, rows: Range<usize>, mut set_pixel: F)
+    where
+        F: FnMut(usize, usize, Color),
+    {
+        let mode = self.active();
+        let direct = mode.stays_in_region();
+        if direct {
+            mode.render_rows(rows.clone(), &mut set_pixel, &self.drawn);
+        }
+        let mut clipped = |x: usize, y: usize, color: Color| {
+            if rows.contains(&y) && DISPLAY_REGION.contains(x, y) {
+                set_pixel(x, y, color);
+            }
+        };
+        if !direct {
+            mode.render_rows(rows.clone(), &mut clipped, &self.drawn);
+        }
+        self.plosive_guard.render(self.drawn.accent, &mut clipped);
+    }
+
+    // the LED ring for the frame just rendered into `fb` (on the grid of `step`). modes with
+    // their own ring pattern are dimmed to `brightness` like the display, the edge already is
+    pub fn render_ring(&self, ring: &mut LedRing, fb: &Framebuffer, step: usize, brightness: f32) {
+        ring.clear(Color::default());
+        if self.active().render_ring(ring, &self.drawn) {
+            ring.scale(brightness);
+        } else {
+            ring.sample_edge(fb, step);
         }
     }
 
+    // the pixels on a grid of `step` only, PostChain::end_frame doubles them (set_pixel_step)
+    pub fn render_sparse<F>(&self, step: usize, set_pixel: F)
+    where
+        F: FnMut(usize, usize, Color),
+    {
+        self.render_region(&DISPLAY_REGION, step, set_pixel);
+    }
+
+    // the pixels of `region` (PostChain::render_region) on a grid of `step` only. the region
+    // has to lie inside DISPLAY_REGION, like the ones PostChain hands out
+    pub fn render_region<F>(&self, region: &RenderRegion, step: usize, mut set_pixel: F)
+    where
+        F: FnMut(usize, usize, Color),
+    {
+        let step = step.max(1);
+        let mode = self.active();
+        let direct = mode.stays_in_region();
+        if direct {
+            mode.render_region(region, step, &mut set_pixel, &self.drawn);
+        }
+        let mut clipped = |x: usize, y: usize, color: Color| {
+            if x.is_multiple_of(step) && y.is_multiple_of(step) && region.contains(x, y) {
+                set_pixel(x, y, color);
+            }
+        };
+        if !direct {
+            mode.render_region(region, step, &mut clipped, &self.drawn);
+        }
+        self.plosive_guard.render(self.drawn.accent, &mut clipped);
+    }
+
+    // play the boot animation before the current mode, call once at power-on
+    pub fn start_boot(&mut self) {
+        self.boot = Some(BootAnimation::new());
+    }
+
+    // jump straight to the current mode, e.g. on the first input event
+    pub fn skip_boot(&mut self) {
+        self.boot = None;
+    }
+
+    pub fn is_booting(&self) -> bool {
+        self.boot.is_some()
+    }
+
+    pub fn set_plosive_guard(&mut self, enabled: bool) {
+        self.plosive_guard.set_enabled(enabled);
+        self.right_guard.set_enabled(enabled);
+    }
+
+    pub fn region(&self) -> &'static RenderRegion {
+        &DISPLAY_REGION
+    }
+
+    // the chosen mode, which the idle clock face doesn't change
     pub fn current_mode(&self) -> ModeKind {
         self.current_mode
     }
 
-    pub fn set_mode(&mut self, mode: ModeKind) {
+    // what is on screen after the boot animation: current_mode, or the clock while idle.
+    // trail decay and the like should follow this one
+    pub fn displayed_mode(&self) -> ModeKind {
+        if self.idle { ModeKind::Clock } else { self.current_mode }
+    }
+
+    // false (and no change) for a user mode this visualizer has no instance of
+    pub fn set_mode(&mut self, mode: ModeKind) -> bool {
+        if !self.has_mode(mode) {
+            return false;
+        }
         self.current_mode = mode;
+        self.demo_timer = 0.0;
+        self.wake();
+        true
+    }
+
+    // `steps` modes on from the current one, wrapping around and skipping the ones set_mode
+    // would refuse
+    pub fn step_mode(&mut self, steps: i32) {
+        let mode = self.stepped(steps);
+        self.set_mode(mode);
+    }
+
+    fn stepped(&self, steps: i32) -> ModeKind {
+        let mut mode = self.current_mode;
+        for _ in 0..steps.unsigned_abs() {
+            // the built-in modes are always there, so this ends
+            loop {
+                mode = mode.step(steps.signum());
+                if self.has_mode(mode) {
+                    break;
+                }
+            }
+        }
+        mode
+    }
+
+    // step through every mode, `None` stays on the current one
+    pub fn set_demo_cycle(&mut self, seconds_per_mode: Option<f32>) {
+        self.demo_cycle = seconds_per_mode;
+        self.demo_timer = 0.0;
+    }
+
+    pub fn demo_cycle(&self) -> Option<f32> {
+        self.demo_cycle
+    }
+
+    // wall clock time for the clock face, poll a TimeSource once a frame
+    pub fn set_time(&mut self, time: Option<TimeOfDay>) {
+        self.clock.set_time(time);
+    }
+
+    // switch to the clock face after this many seconds without voice activity or input,
+    // `None` never does
+    pub fn set_idle_clock(&mut self, after_seconds: Option<f32>) {
+        self.idle_clock = after_seconds;
+        self.idle_timer = 0.0;
+        self.idle = false;
+    }
+
+    pub fn idle_clock(&self) -> Option<f32> {
+        self.idle_clock
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.idle
+    }
+
+    // call on every input event. restarts the idle timer and returns whether the clock face
+    // was showing, so the event that wakes the badge can be swallowed
+    pub fn wake(&mut self) -> bool {
+        let was_idle = self.idle;
+        self.idle_timer = 0.0;
+        self.idle = false;
+        was_idle
     }
 
     pub fn set_palette(&mut self, palette: ColorPalette) {
         self.palette = palette;
+        self.drawn = palette.rotate_hue(self.hue);
     }
 
+    // the palette as set, without the hue rotation
     pub fn palette(&self) -> &ColorPalette {
         &self.palette
     }
+
+    // the modes draw with the palette turned this far (0-1), e.g. PostChain::hue
+    pub fn set_hue(&mut self, turns: f32) {
+        if turns != self.hue {
+            self.hue = turns;
+            self.drawn = self.palette.rotate_hue(turns);
+        }
+    }
 }
@@ -0,0 +1,131 @@
+// vowel mode: the display is a vowel chart (front vowels left, close vowels at the top) filled
+// with a 2D color field, lit up around the current F1/F2 estimate. the glow follows the voice
+// across the chart and fades out in silence.
+
+use core::ops::Range;
+
+use crate::formant::Formants;
+use crate::param::Param;
+use crate::region::{RenderRegion, DISPLAY_REGION};
+use crate::source::AnalysisFrame;
+use crate::vis::VisualMode;
+use crate::{Color, ColorPalette, DISPLAY_SIZE};
+
+pub struct VowelField {
+    target: Option<Formants>,
+    position: (f32, f32), // eased chart position
+    presence: f32,        // 0-1, fades in while formants are tracked
+    params: [Param; 1],
+}
+
+impl VowelField {
+    const FOLLOW_PER_S: f32 = 12.0;
+    const FADE_IN_PER_S: f32 = 6.0;
+    const FADE_OUT_PER_S: f32 = 1.5;
+    const GLOW_RADIUS: usize = 0; // params, pixels
+    const FIELD_FLOOR: f32 = 0.12; // chart stays faintly visible away from the glow
+    const MARGIN: f32 = 30.0;      // chart inset from the display edge
+
+    pub fn new() -> Self {
+        Self {
+            target: None,
+            position: (0.5, 0.5),
+            presence: 0.0,
+            params: [Param::new("glow", "Glow size", 15.0, 80.0, 5.0, 40.0)],
+        }
+    }
+
+    // latest estimate from the analysis frame, applied on the next update
+    pub fn set_formants(&mut self, formants: Option<Formants>) {
+        self.target = formants;
+    }
+
+    pub fn update(&mut self, dt: f32, energies: &[f32]) {
+        let loudness = energies.iter().copied().fold(0.0f32, f32::max);
+        match self.target {
+            Some(formants) => {
+                let (x, y) = formants.chart_position();
+                let follow = (Self::FOLLOW_PER_S * dt).min(1.0);
+                self.position.0 += (x - self.position.0) * follow;
+                self.position.1 += (y - self.position.1) * follow;
+                let goal = loudness.clamp(0.3, 1.0);
+                self.presence = (self.presence + Self::FADE_IN_PER_S * dt).min(goal);
+            }
+            None => self.presence = (self.presence - Self::FADE_OUT_PER_S * dt).max(0.0),
+        }
+    }
+
+    // field color at a chart position: palette along front/back, leaning to the accent when open
+    fn field_color(pal: &ColorPalette, x: f32, y: f32) -> Color {
+        Color::lerp(pal.sample(x), pal.accent, y * 0.5)
+    }
+
+    pub fn render_with_palette<F>(&self, set_pixel: F, pal: &ColorPalette)
+    where
+        F: FnMut(usize, usize, Color),
+    {
+        self.draw_rows(&DISPLAY_REGION, 0..DISPLAY_SIZE, 1, set_pixel, pal);
+    }
+
+    fn draw_rows<F>(&self, region: &RenderRegion, rows: Range<usize>, step: usize, mut set_pixel: F, pal: &ColorPalette)
+    where
+        F: FnMut(usize, usize, Color),
+    {
+        let span = DISPLAY_SIZE as f32 - 2.0 * Self::MARGIN;
+        let to_chart = |v: f32| ((v - Self::MARGIN) / span).clamp(0.0, 1.0);
+        let glow_x = Self::MARGIN + self.position.0 * span;
+        let glow_y = Self::MARGIN + self.position.1 * span;
+        let radius = self.params[Self::GLOW_RADIUS].value;
+        let inv_radius_sq = 1.0 / (radius * radius);
+
+        for (y, columns) in region.grid(rows, step) {
+            let chart_y = to_chart(y as f32);
+            let dy = y as f32 - glow_y;
+            for x in columns {
+                let dx = x as f32 - glow_x;
+                // rational falloff, close enough to a gaussian without an exp per pixel
+                let falloff = 1.0 / (1.0 + (dx * dx + dy * dy) * inv_radius_sq);
+                let glow = falloff * falloff * self.presence;
+                let brightness = Self::FIELD_FLOOR + (1.0 - Self::FIELD_FLOOR) * glow;
+                set_pixel(x, y, Self::field_color(pal, to_chart(x as f32), chart_y).scale(brightness));
+            }
+        }
+    }
+}
+
+impl VisualMode for VowelField {
+    fn update(&mut self, dt: f32, frame: &AnalysisFrame) {
+        self.set_formants(frame.formants);
+        VowelField::update(self, dt, frame.bands());
+    }
+
+    fn render(&self, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.render_with_palette(set_pixel, pal);
+    }
+
+    fn render_rows(&self, rows: Range<usize>, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.draw_rows(&DISPLAY_REGION, rows, 1, set_pixel, pal);
+    }
+
+    fn render_region(&self, region: &RenderRegion, step: usize, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        self.draw_rows(region, 0..DISPLAY_SIZE, step, set_pixel, pal);
+    }
+
+    fn stays_in_region(&self) -> bool {
+        true
+    }
+
+    fn params(&self) -> &[Param] {
+        &self.params
+    }
+
+    fn params_mut(&mut self) -> &mut [Param] {
+        &mut self.params
+    }
+}
+
+impl Default for VowelField {
+    fn default() -> Self {
+        Self::new()
+    }
+}
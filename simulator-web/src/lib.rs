@@ -0,0 +1,209 @@
+// the simulator in a browser: core renders into its framebuffer as on the badge, the result is
+// put on a 240x240 canvas every animation frame. input starts on the synthetic speech signal
+// and switches to the microphone once the page hands over a Microphone (WebAudio needs a user
+// gesture and a permission prompt first). see www/index.html for the JS side.
+//
+//   wasm-pack build simulator-web --target web --out-dir www/pkg
+
+#[allow(dead_code)] // full port of the gateware DSP, shared with the native simulator
+#[path = "../../simulator/src/dsp.rs"]
+mod dsp;
+#[allow(dead_code)]
+#[path = "../../simulator/src/synth.rs"]
+mod synth;
+mod source;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::{Clamped, JsCast};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, ImageData};
+
+use girlvoice_ui_core::{
+    ChannelCount, DspSettings, EnergySource, Framebuffer, MenuEffect, MenuTarget, ParamId, PostChain, Settings, StatusInputs, StatusOverlay,
+    TimeOfDay, TimeSource, Toasts, palette, VocoderConfig, Visualizer, Widget, DISPLAY_SIZE,
+};
+
+use source::{SyntheticSource, WebAudioSource};
+use synth::Signal;
+
+// longest step the animation takes, after the tab was hidden for a while
+const MAX_DT: f32 = 0.1;
+const TOAST_S: f32 = 1.5;
+
+#[wasm_bindgen]
+pub struct WebSimulator {
+    context: CanvasRenderingContext2d,
+    source: Box<dyn EnergySource>,
+    visualizer: Visualizer,
+    settings: Settings,
+    dsp: DspSettings,
+    framebuffer: Box<Framebuffer>,
+    post: Box<PostChain>,
+    status: StatusOverlay,
+    status_inputs: StatusInputs,
+    toasts: Toasts,
+    display: Vec<u32>,
+    rgba: Vec<u8>,
+    last_time: Option<f64>, // ms, from requestAnimationFrame
+    frames: u32,
+    clock: BrowserClock,
+}
+
+#[wasm_bindgen]
+impl WebSimulator {
+    #[wasm_bindgen(constructor)]
+    pub fn new(canvas: HtmlCanvasElement) -> Result<WebSimulator, JsValue> {
+        canvas.set_width(DISPLAY_SIZE as u32);
+        canvas.set_height(DISPLAY_SIZE as u32);
+        let context: CanvasRenderingContext2d =
+            canvas.get_context("2d")?.ok_or("canvas has no 2d context")?.dyn_into()?;
+
+        let (channels, vocoder) = vocoder()?;
+        let mut source: Box<dyn EnergySource> = Box::new(SyntheticSource::new(Signal::Speech, channels, &vocoder));
+        let dsp = DspSettings::default();
+        source.set_dsp_settings(&dsp);
+
+        let settings = Settings::default();
+        let mut visualizer = Visualizer::new(source.num_channels());
+        visualizer.set_palette(settings.palette);
+        visualizer.set_response(settings.response);
+        settings.apply_startup(&mut visualizer);
+        visualizer.set_idle_clock(settings.idle_clock_after());
+
+        Ok(Self {
+            context,
+            source,
+            visualizer,
+            settings,
+            dsp,
+            framebuffer: Box::new(Framebuffer::new()),
+            post: Box::new(PostChain::empty()),
+            status: StatusOverlay::new(),
+            status_inputs: StatusInputs::default(),
+            toasts: Toasts::new(),
+            display: vec![0; DISPLAY_SIZE * DISPLAY_SIZE],
+            rgba: vec![0; DISPLAY_SIZE * DISPLAY_SIZE * 4],
+            last_time: None,
+            frames: 0,
+            clock: BrowserClock,
+        })
+    }
+
+    // replaces the current source, the synthetic signal stops
+    pub fn use_microphone(&mut self, microphone: Microphone) {
+        self.source = Box::new(microphone.source);
+        self.source.set_dsp_settings(&self.dsp);
+    }
+
+    // one animation frame, `time` is the requestAnimationFrame timestamp in ms
+    pub fn frame(&mut self, time: f64) -> Result<(), JsValue> {
+        let dt = self.last_time.map_or(0.0, |last| ((time - last) / 1000.0) as f32).clamp(0.0, MAX_DT);
+        self.last_time = Some(time);
+
+        let frame = self.source.poll();
+        self.visualizer.set_time(self.clock.now());
+        self.visualizer.update_frame(dt, &frame).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        self.status_inputs.set_from_frame(&frame);
+        self.status.update(dt, &self.status_inputs);
+        self.post.update(dt, &frame);
+        self.visualizer.set_hue(self.post.hue());
+        self.toasts.update(dt, &self.status_inputs);
+
+        // the saver profile draws every other animation frame
+        let power = self.settings.power;
+        self.frames = self.frames.wrapping_add(1);
+        if power.frame_rate(2) < 2 && !self.frames.is_multiple_of(2) {
+            return Ok(());
+        }
+        power.configure(&mut self.post);
+
+        let brightness = power.brightness(self.settings.brightness);
+        let framebuffer = &mut self.framebuffer;
+        self.post.begin_frame(framebuffer, self.visualizer.displayed_mode());
+        self.visualizer.render_region(self.post.render_region(), power.pixel_step(), |x, y, color| framebuffer.add(x, y, color.scale(brightness)));
+        self.post.end_frame(framebuffer);
+        self.status.render(framebuffer, self.visualizer.palette());
+        self.toasts.render(framebuffer, self.visualizer.palette());
+        self.post.write_argb32(framebuffer, &mut self.display).map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+        for (rgba, argb) in self.rgba.chunks_exact_mut(4).zip(&self.display) {
+            let [_, r, g, b] = argb.to_be_bytes();
+            rgba.copy_from_slice(&[r, g, b, 0xff]);
+        }
+        let image = ImageData::new_with_u8_clamped_array_and_sh(Clamped(&self.rgba), DISPLAY_SIZE as u32, DISPLAY_SIZE as u32)?;
+        self.context.put_image_data(&image, 0.0, 0.0)
+    }
+
+    pub fn next_mode(&mut self) {
+        if self.visualizer.wake() {
+            return;
+        }
+        self.visualizer.step_mode(1);
+        self.settings.record(&self.visualizer);
+    }
+
+    pub fn prev_mode(&mut self) {
+        if self.visualizer.wake() {
+            return;
+        }
+        self.visualizer.step_mode(-1);
+        self.settings.record(&self.visualizer);
+    }
+
+    pub fn mode_name(&self) -> String {
+        self.visualizer.current_mode().name().to_string()
+    }
+
+    // any ParamId by its id ("mode", "palette", "brightness", "gain", "agc", "tilt", "power",
+    // "param1".."param4" for the current mode's own knobs), value 0-1. false for unknown ids
+    pub fn set_param(&mut self, id: &str, value: f32) -> bool {
+        let Some(param) = ParamId::from_id(id) else { return false };
+        let palette = *self.visualizer.palette();
+        let mut target = MenuTarget { visualizer: &mut self.visualizer, settings: &mut self.settings, dsp: &mut self.dsp };
+        if param.set(&mut target, value) == MenuEffect::DspChanged {
+            self.source.set_dsp_settings(&self.dsp);
+        }
+        if *self.visualizer.palette() != palette {
+            let name = palette::builtin_index(self.visualizer.palette()).map_or("Custom palette", |i| palette::BUILTIN[i]);
+            self.toasts.toast(name, TOAST_S);
+        }
+        true
+    }
+
+    pub fn set_pitch_trainer(&mut self, show: bool) {
+        self.status.show_pitch = show;
+    }
+
+    pub fn set_loudness_gauge(&mut self, show: bool) {
+        self.status.show_loudness = show;
+    }
+}
+
+// an open microphone, waiting to be handed to WebSimulator::use_microphone
+#[wasm_bindgen]
+pub struct Microphone {
+    source: WebAudioSource,
+}
+
+// prompts for microphone access, call it from a click handler so the AudioContext may start
+#[wasm_bindgen]
+pub async fn open_microphone() -> Result<Microphone, JsValue> {
+    let (channels, vocoder) = vocoder()?;
+    Ok(Microphone { source: WebAudioSource::open(channels, &vocoder).await? })
+}
+
+// the page's local time
+struct BrowserClock;
+
+impl TimeSource for BrowserClock {
+    fn now(&mut self) -> Option<TimeOfDay> {
+        let date = js_sys::Date::new_0();
+        TimeOfDay::new(date.get_hours() as u8, date.get_minutes() as u8, date.get_seconds() as u8)
+    }
+}
+
+// filterbank layout for both sources, the page has no config file
+fn vocoder() -> Result<(ChannelCount, VocoderConfig), JsValue> {
+    let config = VocoderConfig::default();
+    let channels = config.channels().map_err(|e| JsValue::from_str(&e.to_string()))?;
+    Ok((channels, config))
+}
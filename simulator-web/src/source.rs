@@ -0,0 +1,170 @@
+// input sources for the browser build, they feed the UI through core's EnergySource like the
+// native simulator's. WebAudio hands over blocks on the main thread, so there's no callback
+// link here, the analysis state is shared behind a RefCell.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{AudioContext, AudioProcessingEvent, MediaStream, MediaStreamAudioSourceNode, MediaStreamConstraints, ScriptProcessorNode};
+
+use girlvoice_ui_core::{AnalysisFrame, ChannelCount, DspSettings, EnergySource, VocoderConfig, WAVEFORM_LEN};
+
+use crate::dsp::{FrameHop, VocoderDSP};
+use crate::synth::{Signal, SignalGenerator};
+
+// 1024 samples is ~21 ms at 48 kHz, short enough for the bars to keep up
+const BLOCK_LEN: u32 = 1024;
+
+// the DSP plus everything an AnalysisFrame needs, samples in and frames out
+struct Analyzer {
+    dsp: VocoderDSP,
+    hop: FrameHop,
+    frame: AnalysisFrame, // the latest hop, events latched until take_frame
+    waveform: [f32; WAVEFORM_LEN],
+    waveform_pos: usize,
+}
+
+impl Analyzer {
+    fn new(channels: ChannelCount, vocoder: &VocoderConfig, sample_rate: f32) -> Self {
+        Self {
+            dsp: VocoderDSP::new(channels.get(), vocoder, sample_rate),
+            hop: FrameHop::new(channels),
+            frame: AnalysisFrame::new(channels),
+            waveform: [0.0; WAVEFORM_LEN],
+            waveform_pos: 0,
+        }
+    }
+
+    // several blocks can arrive between polls, the frame keeps the newest hop and every event
+    fn push(&mut self, samples: impl Iterator<Item = f32>) {
+        for sample in samples {
+            self.dsp.process(sample);
+            self.waveform[self.waveform_pos] = sample;
+            self.waveform_pos = (self.waveform_pos + 1) % WAVEFORM_LEN;
+            if let Some(frame) = self.hop.push(sample, sample.abs(), &mut self.dsp, None) {
+                self.frame.catch_up(frame);
+            }
+        }
+    }
+
+    fn take_frame(&mut self) -> AnalysisFrame {
+        let mut frame = self.frame;
+        for (i, o) in frame.waveform.iter_mut().enumerate() {
+            *o = self.waveform[(self.waveform_pos + i) % WAVEFORM_LEN]; // oldest sample first
+        }
+        self.frame.plosive = false;
+        self.frame.clip = false;
+        frame
+    }
+}
+
+// microphone capture through getUserMedia and a ScriptProcessorNode. deprecated, but it needs
+// no separately served worklet script and it's everywhere
+pub struct WebAudioSource {
+    analyzer: Rc<RefCell<Analyzer>>,
+    channels: ChannelCount,
+    // keep the graph alive, dropping the callback would leave the node calling into nothing
+    context: AudioContext,
+    _input: MediaStreamAudioSourceNode,
+    _processor: ScriptProcessorNode,
+    _callback: Closure<dyn FnMut(AudioProcessingEvent)>,
+}
+
+impl WebAudioSource {
+    // asks for microphone permission, fails if it's denied or there's no input
+    pub async fn open(channels: ChannelCount, vocoder: &VocoderConfig) -> Result<Self, JsValue> {
+        let window = web_sys::window().ok_or("no window")?;
+        let constraints = MediaStreamConstraints::new();
+        constraints.set_audio(&JsValue::TRUE);
+        let request = window.navigator().media_devices()?.get_user_media_with_constraints(&constraints)?;
+        let stream: MediaStream = JsFuture::from(request).await?.dyn_into()?;
+
+        let context = AudioContext::new()?;
+        let sample_rate = context.sample_rate();
+        let analyzer = Rc::new(RefCell::new(Analyzer::new(channels, vocoder, sample_rate)));
+
+        let input = context.create_media_stream_source(&stream)?;
+        let processor = context.create_script_processor_with_buffer_size_and_number_of_input_channels_and_number_of_output_channels(BLOCK_LEN, 1, 1)?;
+        let shared = analyzer.clone();
+        let callback = Closure::<dyn FnMut(AudioProcessingEvent)>::new(move |event: AudioProcessingEvent| {
+            // the browser downmixes to the node's single input channel
+            if let Ok(samples) = event.input_buffer().and_then(|buffer| buffer.get_channel_data(0)) {
+                shared.borrow_mut().push(samples.into_iter());
+            }
+        });
+        processor.set_onaudioprocess(Some(callback.as_ref().unchecked_ref()));
+        input.connect_with_audio_node(&processor)?;
+        // chrome only runs processors that are connected to the output, the node outputs silence
+        processor.connect_with_audio_node(&context.destination())?;
+
+        Ok(Self { analyzer, channels, context, _input: input, _processor: processor, _callback: callback })
+    }
+}
+
+impl Drop for WebAudioSource {
+    fn drop(&mut self) {
+        let _ = self.context.close();
+    }
+}
+
+impl EnergySource for WebAudioSource {
+    fn poll(&mut self) -> AnalysisFrame {
+        self.analyzer.borrow_mut().take_frame()
+    }
+
+    fn num_channels(&self) -> ChannelCount {
+        self.channels
+    }
+
+    fn set_dsp_settings(&mut self, settings: &DspSettings) {
+        self.analyzer.borrow_mut().dsp.set_settings(settings);
+    }
+}
+
+// built-in test signals run through the real DSP, paced by the page's clock. used until the
+// microphone is allowed, and when it isn't
+pub struct SyntheticSource {
+    generator: SignalGenerator,
+    analyzer: Analyzer,
+    channels: ChannelCount,
+    last_poll: Option<f64>, // ms
+}
+
+impl SyntheticSource {
+    pub const SAMPLE_RATE: f32 = 48000.0;
+    // don't try to catch up after the tab was in the background
+    const MAX_CATCH_UP: f64 = 0.1;
+
+    pub fn new(signal: Signal, channels: ChannelCount, vocoder: &VocoderConfig) -> Self {
+        Self {
+            generator: SignalGenerator::new(signal, Self::SAMPLE_RATE),
+            analyzer: Analyzer::new(channels, vocoder, Self::SAMPLE_RATE),
+            channels,
+            last_poll: None,
+        }
+    }
+}
+
+impl EnergySource for SyntheticSource {
+    fn poll(&mut self) -> AnalysisFrame {
+        let now = js_sys::Date::now();
+        let elapsed = self.last_poll.map_or(0.0, |last| ((now - last) / 1000.0).clamp(0.0, Self::MAX_CATCH_UP));
+        self.last_poll = Some(now);
+
+        let generator = &mut self.generator;
+        let num_samples = (elapsed * Self::SAMPLE_RATE as f64) as usize;
+        self.analyzer.push((0..num_samples).map(|_| generator.next_sample()));
+        self.analyzer.take_frame()
+    }
+
+    fn num_channels(&self) -> ChannelCount {
+        self.channels
+    }
+
+    fn set_dsp_settings(&mut self, settings: &DspSettings) {
+        self.analyzer.dsp.set_settings(settings);
+    }
+}
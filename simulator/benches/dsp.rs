@@ -0,0 +1,96 @@
+// VocoderDSP cost for one second of audio at different channel counts and filterbanks, and the
+// block filter/envelope path against the per-sample one it replaced.
+// cargo bench -p girlvoice-ui-simulator
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+// the DSP lives in the simulator binary, pull the module in directly
+#[allow(dead_code)]
+#[path = "../src/dsp.rs"]
+mod dsp;
+
+use dsp::{EnvelopeBank, FilterBank, VocoderChannel, VocoderDSP, ANALYSIS_RATE, BLOCK};
+use girlvoice_ui_core::{Filterbank, VocoderConfig};
+
+const SAMPLE_RATE: f32 = 48000.0;
+
+// a voiced sawtooth-ish tone with some vibrato, so every band has something to follow
+fn one_second() -> Vec<f32> {
+    let mut phase = 0.0f32;
+    (0..SAMPLE_RATE as usize)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE;
+            let freq = 180.0 * (1.0 + 0.02 * (t * 5.0 * std::f32::consts::TAU).sin());
+            phase = (phase + freq / SAMPLE_RATE) % 1.0;
+            0.5 * (2.0 * phase - 1.0)
+        })
+        .collect()
+}
+
+fn process_buffer(c: &mut Criterion) {
+    let samples = one_second();
+    let mut group = c.benchmark_group("process_buffer_1s");
+    group.throughput(Throughput::Elements(samples.len() as u64));
+    group.sample_size(20);
+    for filterbank in Filterbank::ALL {
+        let vocoder = VocoderConfig { filterbank, ..VocoderConfig::default() };
+        for channels in [8, 12, 16] {
+            let mut dsp = VocoderDSP::new(channels, &vocoder, SAMPLE_RATE);
+            group.bench_with_input(BenchmarkId::new(filterbank.id(), channels), &samples, |b, samples| {
+                b.iter(|| {
+                    dsp.process_buffer(black_box(samples));
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+// the channel section alone at the analysis rate: every channel per sample, or a block at a time
+// across all channels
+fn channels(c: &mut Criterion) {
+    let samples: Vec<f32> = one_second().into_iter().step_by(2).collect(); // 48k -> ANALYSIS_RATE
+    let mut group = c.benchmark_group("channels_1s");
+    group.throughput(Throughput::Elements(samples.len() as u64));
+    group.sample_size(20);
+    for count in [12, 25] {
+        let mut channels: Vec<VocoderChannel> = (0..count)
+            .map(|i| {
+                let center = 100.0 * 1.15f32.powi(i);
+                VocoderChannel::new(center * 0.965, center * 1.035, ANALYSIS_RATE)
+            })
+            .collect();
+
+        let mut levels = vec![0.0; count as usize];
+        group.bench_with_input(BenchmarkId::new("per_sample", count), &samples, |b, samples| {
+            b.iter(|| {
+                for &sample in black_box(samples) {
+                    for (level, channel) in levels.iter_mut().zip(&mut channels) {
+                        *level = channel.process(sample);
+                    }
+                }
+                black_box(&levels);
+            })
+        });
+
+        let mut filters = FilterBank::new(channels.iter().map(|ch| &ch.bandpass));
+        let mut followers = EnvelopeBank::new(channels.iter().map(|ch| &ch.envelope));
+        let mut block = vec![0.0; BLOCK * count as usize];
+        group.bench_with_input(BenchmarkId::new("block", count), &samples, |b, samples| {
+            b.iter(|| {
+                for input in black_box(samples).chunks(BLOCK) {
+                    let block = &mut block[..input.len() * count as usize];
+                    filters.process_block(input, block);
+                    followers.process_block(block);
+                }
+                black_box(&block);
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, process_buffer, channels);
+criterion_main!(benches);
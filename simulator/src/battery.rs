@@ -0,0 +1,40 @@
+// fake battery readings for the status overlay, standing in for the badge's fuel gauge ADC
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BatterySim {
+    Fixed { level: f32, charging: bool },
+    Cycle, // drains over a couple of minutes, then charges back up
+}
+
+impl BatterySim {
+    const DRAIN_S: f32 = 120.0;
+    const CHARGE_S: f32 = 30.0;
+
+    // "40" (percent), "40+" (charging) or "cycle"
+    pub fn parse(spec: &str) -> Option<Self> {
+        if spec == "cycle" {
+            return Some(BatterySim::Cycle);
+        }
+        let (number, charging) = match spec.strip_suffix('+') {
+            Some(number) => (number, true),
+            None => (spec, false),
+        };
+        let percent: f32 = number.parse().ok()?;
+        (0.0..=100.0).contains(&percent).then_some(BatterySim::Fixed { level: percent / 100.0, charging })
+    }
+
+    // (state of charge 0-1, charging) at `time` seconds into the run
+    pub fn reading(&self, time: f32) -> (f32, bool) {
+        match *self {
+            BatterySim::Fixed { level, charging } => (level, charging),
+            BatterySim::Cycle => {
+                let t = time % (Self::DRAIN_S + Self::CHARGE_S);
+                if t < Self::DRAIN_S {
+                    (1.0 - t / Self::DRAIN_S, false)
+                } else {
+                    ((t - Self::DRAIN_S) / Self::CHARGE_S, true)
+                }
+            }
+        }
+    }
+}
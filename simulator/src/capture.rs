@@ -0,0 +1,101 @@
+// S and G in the simulator: a png of the panel's picture (without the status bar, menu and
+// toasts), or a gif of it sampled at a fixed 30 fps whatever the simulator runs at, stopped
+// after MAX_GIF_S so a forgotten recording doesn't fill the disk. files go to --capture-dir,
+// named after the mode with the first free number, e.g. captures/plasma-003.png
+
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::PathBuf;
+
+use girlvoice_ui_core::DISPLAY_SIZE;
+use girlvoice_ui_tools::png;
+
+use crate::gif::GifWriter;
+
+pub const GIF_FPS: u32 = 30;
+pub const MAX_GIF_S: u32 = 20;
+
+pub struct Capture {
+    dir: PathBuf,
+    gif: Option<GifRecording>,
+}
+
+struct GifRecording {
+    writer: GifWriter<BufWriter<File>>,
+    path: PathBuf,
+    pending: Vec<u32>, // last sampled frame, written once the next sample says how long it showed
+    pending_tick: u32,
+    elapsed: f32,
+}
+
+impl Capture {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir, gif: None }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.gif.is_some()
+    }
+
+    // `pixels` is the panel, DISPLAY_SIZE square
+    pub fn screenshot(&self, name: &str, pixels: &[u32]) -> io::Result<PathBuf> {
+        let path = self.next_path(name, "png")?;
+        let mut out = BufWriter::new(File::create(&path)?);
+        png::write(&mut out, DISPLAY_SIZE, DISPLAY_SIZE, pixels)?;
+        io::Write::flush(&mut out)?;
+        Ok(path)
+    }
+
+    // `pixels` is the first frame
+    pub fn start_gif(&mut self, name: &str, pixels: &[u32]) -> io::Result<PathBuf> {
+        let path = self.next_path(name, "gif")?;
+        let writer = GifWriter::new(BufWriter::new(File::create(&path)?), DISPLAY_SIZE, DISPLAY_SIZE)?;
+        self.gif = Some(GifRecording { writer, path: path.clone(), pending: pixels.to_vec(), pending_tick: 0, elapsed: 0.0 });
+        Ok(path)
+    }
+
+    // None if nothing was recording
+    pub fn stop_gif(&mut self) -> Option<io::Result<PathBuf>> {
+        let mut gif = self.gif.take()?;
+        let last = gif.pending_tick + 1;
+        Some(gif.flush(last).and_then(|()| gif.writer.finish()).map(|_| gif.path))
+    }
+
+    // every frame shown while recording, `dt` after the previous one. Some once the recording
+    // ended, at MAX_GIF_S or on a write error
+    pub fn frame(&mut self, dt: f32, pixels: &[u32]) -> Option<io::Result<PathBuf>> {
+        let gif = self.gif.as_mut()?;
+        gif.elapsed += dt;
+        let tick = (gif.elapsed * GIF_FPS as f32) as u32;
+        if tick >= MAX_GIF_S * GIF_FPS {
+            return self.stop_gif();
+        }
+        if tick > gif.pending_tick {
+            if let Err(e) = gif.flush(tick) {
+                self.gif = None;
+                return Some(Err(e));
+            }
+            gif.pending.copy_from_slice(pixels);
+            gif.pending_tick = tick;
+        }
+        None
+    }
+
+    fn next_path(&self, name: &str, extension: &str) -> io::Result<PathBuf> {
+        std::fs::create_dir_all(&self.dir)?;
+        (1..=999)
+            .map(|n| self.dir.join(format!("{}-{:03}.{}", name, n, extension)))
+            .find(|path| !path.exists())
+            .ok_or_else(|| io::Error::other(format!("{} has 999 {} captures of {} already", self.dir.display(), extension, name)))
+    }
+}
+
+impl GifRecording {
+    // writes the pending frame, shown until `tick`. delays are whole hundredths, rounding
+    // against the running total keeps 30 fps on average (3, 3, 4, ...)
+    fn flush(&mut self, tick: u32) -> io::Result<()> {
+        let centis = |tick: u32| (tick * 100 + GIF_FPS / 2) / GIF_FPS;
+        let delay = centis(tick) - centis(self.pending_tick);
+        self.writer.frame(&self.pending, delay as u16)
+    }
+}
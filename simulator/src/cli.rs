@@ -0,0 +1,390 @@
+// command line options, parsed by hand to keep the dependency list short
+
+use std::path::PathBuf;
+
+use girlvoice_ui_core::{ModeKind, PostFx, PowerProfile, ResponseCurve, StartupPolicy, MAX_RING_LEDS};
+
+use crate::battery::BatterySim;
+use crate::clock::parse_offset;
+use crate::compare::CompareSpec;
+use crate::fidelity::Fidelity;
+use crate::panel::PanelLink;
+use crate::synth::Signal;
+use crate::upscale::{Filter, Upscaler};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SourceKind {
+    Mic,
+    Synth(Signal),
+    // the playback signal, from a capture device matching `device`. `with_mic` pairs it with
+    // the mic for the split mode
+    Output { device: Option<String>, with_mic: bool },
+    Wav(PathBuf),    // a recording played in a loop
+    Serial(PathBuf), // the badge's own analysis over its serial port
+}
+
+impl SourceKind {
+    fn parse(spec: &str) -> Result<Self, String> {
+        match spec.split_once(':') {
+            None if spec == "mic" => Ok(SourceKind::Mic),
+            None if spec == "synth" => Ok(SourceKind::Synth(Signal::Sweep)),
+            None if spec == "output" => Ok(SourceKind::Output { device: None, with_mic: false }),
+            None if spec == "mic+output" => Ok(SourceKind::Output { device: None, with_mic: true }),
+            Some(("output", device)) => Ok(SourceKind::Output { device: Some(device.to_string()), with_mic: false }),
+            Some(("mic+output", device)) => Ok(SourceKind::Output { device: Some(device.to_string()), with_mic: true }),
+            Some(("wav", path)) => Ok(SourceKind::Wav(PathBuf::from(path))),
+            Some(("serial", port)) => Ok(SourceKind::Serial(PathBuf::from(port))),
+            Some(("synth", name)) => Signal::parse(name)
+                .map(SourceKind::Synth)
+                .ok_or_else(|| format!("unknown synthetic signal '{}'", name)),
+            _ => Err(format!("unknown source '{}'", spec)),
+        }
+    }
+}
+
+// "90", "90s", "30m" or "2h", in seconds
+fn parse_duration(text: &str) -> Result<u64, String> {
+    let (number, unit) = match text.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+        Some((i, _)) => text.split_at(i),
+        None => (text, "s"),
+    };
+    let value: u64 = number.parse().map_err(|_| format!("invalid duration '{}'", text))?;
+    match unit {
+        "s" => Ok(value),
+        "m" => Ok(value * 60),
+        "h" => Ok(value * 3600),
+        _ => Err(format!("invalid duration unit in '{}'", text)),
+    }
+}
+
+const MAX_RENDER_THREADS: usize = 16;
+
+pub struct Args {
+    pub source: SourceKind,
+    pub stereo: bool,
+    pub soak_seconds: Option<u64>,
+    pub round_mask: bool,
+    pub bezel: bool,
+    pub plosive_guard: bool,
+    pub startup: Option<StartupPolicy>,
+    pub physical_dpi: Option<f32>,
+    pub post_fx: Vec<PostFx>, // empty keeps the default trails
+    pub presets: PathBuf,
+    pub config: Option<PathBuf>,
+    pub dump_config: bool,
+    pub store: PathBuf,
+    pub reset_settings: bool,
+    pub battery: Option<BatterySim>, // None: no gauge, like a USB-powered badge
+    pub boot_animation: bool,
+    pub hud: bool,
+    pub loudness: bool,
+    pub pitch: bool,
+    pub record: Option<PathBuf>,
+    pub capture_dir: PathBuf,
+    pub midi: Option<String>, // input port name (substring) or "any"
+    pub osc_port: Option<u16>,
+    pub fps: u32,
+    pub power: Option<PowerProfile>, // overrides the saved profile
+    pub idle_clock: Option<f32>, // overrides the saved timeout, 0 never
+    pub response: Vec<(Option<ModeKind>, ResponseCurve)>, // overrides the saved curves, None is the global one
+    pub utc_offset: Option<i32>, // minutes, None asks the host
+    pub panel_mhz: f32, // emulated SPI clock to the panel, 0 for instant transfers
+    pub fidelity: bool,
+    pub tearing: bool,
+    pub panel_refresh_hz: f32,
+    pub ring_leds: Option<usize>, // None: no LED ring
+    pub compare: Option<CompareSpec>,
+    pub threads: usize, // render threads, 1 keeps the scalar path
+    pub scale: usize,
+    pub filter: Filter,
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Self {
+            source: SourceKind::Mic,
+            stereo: false,
+            soak_seconds: None,
+            round_mask: false,
+            bezel: false,
+            plosive_guard: true,
+            startup: None,
+            physical_dpi: None,
+            post_fx: Vec::new(),
+            presets: PathBuf::from("presets.txt"),
+            config: None,
+            dump_config: false,
+            store: PathBuf::from("settings.bin"),
+            reset_settings: false,
+            battery: None,
+            boot_animation: true,
+            hud: false,
+            loudness: false,
+            pitch: false,
+            record: None,
+            capture_dir: PathBuf::from("captures"),
+            midi: None,
+            osc_port: None,
+            fps: 30,
+            power: None,
+            idle_clock: None,
+            response: Vec::new(),
+            utc_offset: None,
+            panel_mhz: PanelLink::DEFAULT_MHZ,
+            fidelity: false,
+            tearing: false,
+            panel_refresh_hz: Fidelity::DEFAULT_REFRESH_HZ,
+            ring_leds: None,
+            compare: None,
+            threads: std::thread::available_parallelism().map_or(1, |n| n.get().min(MAX_RENDER_THREADS)),
+            scale: 2,
+            filter: Filter::Nearest,
+        }
+    }
+}
+
+impl Args {
+    pub fn parse() -> Result<Self, String> {
+        Self::parse_from(std::env::args().skip(1))
+    }
+
+    pub fn parse_from(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Args::default();
+        while let Some(arg) = args.next() {
+            let mut value = |flag: &str| args.next().ok_or_else(|| format!("{} needs a value", flag));
+            match arg.as_str() {
+                "--source" => parsed.source = SourceKind::parse(&value("--source")?)?,
+                "--stereo" => parsed.stereo = true,
+                "--soak" => parsed.soak_seconds = Some(parse_duration(&value("--soak")?)?),
+                "--round" => parsed.round_mask = true,
+                "--bezel" => {
+                    parsed.round_mask = true;
+                    parsed.bezel = true;
+                }
+                "--no-plosive-guard" => parsed.plosive_guard = false,
+                "--no-boot" => parsed.boot_animation = false,
+                "--hud" => parsed.hud = true,
+                "--loudness" => parsed.loudness = true,
+                "--pitch" => parsed.pitch = true,
+                "--record" => parsed.record = Some(PathBuf::from(value("--record")?)),
+                "--capture-dir" => parsed.capture_dir = PathBuf::from(value("--capture-dir")?),
+                "--midi" => parsed.midi = Some(value("--midi")?),
+                "--osc" => {
+                    let text = value("--osc")?;
+                    parsed.osc_port = Some(text.parse().map_err(|_| format!("invalid udp port '{}'", text))?);
+                }
+                "--scale" => {
+                    let text = value("--scale")?;
+                    let (factor, filter) = match text.split_once(':') {
+                        None => (text.as_str(), Filter::Nearest),
+                        Some((factor, "nearest")) => (factor, Filter::Nearest),
+                        Some((factor, "bilinear")) => (factor, Filter::Bilinear),
+                        Some(_) => return Err(format!("invalid scale filter in '{}'", text)),
+                    };
+                    let scale: usize = factor.parse().map_err(|_| format!("invalid scale '{}'", text))?;
+                    if Upscaler::new(scale, filter).is_none() {
+                        return Err(format!("unsupported scale '{}' (1-4 nearest, 2 bilinear)", text));
+                    }
+                    parsed.scale = scale;
+                    parsed.filter = filter;
+                }
+                "--threads" => {
+                    let text = value("--threads")?;
+                    let threads: usize = text.parse().map_err(|_| format!("invalid thread count '{}'", text))?;
+                    if !(1..=MAX_RENDER_THREADS).contains(&threads) {
+                        return Err(format!("thread count {} out of range (1-{})", threads, MAX_RENDER_THREADS));
+                    }
+                    parsed.threads = threads;
+                }
+                "--fps" => {
+                    let text = value("--fps")?;
+                    let fps: u32 = text.parse().map_err(|_| format!("invalid frame rate '{}'", text))?;
+                    if !(1..=240).contains(&fps) {
+                        return Err(format!("frame rate {} out of range (1-240)", fps));
+                    }
+                    parsed.fps = fps;
+                }
+                "--panel-mhz" => {
+                    let text = value("--panel-mhz")?;
+                    let mhz: f32 = text.parse().map_err(|_| format!("invalid panel clock '{}'", text))?;
+                    if !(0.0..=200.0).contains(&mhz) {
+                        return Err(format!("panel clock {} out of range (0-200 MHz)", mhz));
+                    }
+                    parsed.panel_mhz = mhz;
+                }
+                "--fidelity" => parsed.fidelity = true,
+                "--tearing" => {
+                    parsed.fidelity = true;
+                    parsed.tearing = true;
+                }
+                "--panel-refresh" => {
+                    let text = value("--panel-refresh")?;
+                    let hz: f32 = text.parse().map_err(|_| format!("invalid panel refresh rate '{}'", text))?;
+                    if !(10.0..=120.0).contains(&hz) {
+                        return Err(format!("panel refresh rate {} out of range (10-120 Hz)", hz));
+                    }
+                    parsed.panel_refresh_hz = hz;
+                }
+                "--ring" => {
+                    let text = value("--ring")?;
+                    let leds: usize = text.parse().map_err(|_| format!("invalid led count '{}'", text))?;
+                    if !(1..=MAX_RING_LEDS).contains(&leds) {
+                        return Err(format!("led count {} out of range (1-{})", leds, MAX_RING_LEDS));
+                    }
+                    parsed.ring_leds = Some(leds);
+                }
+                "--compare" => {
+                    let text = value("--compare")?;
+                    parsed.compare = Some(CompareSpec::parse(&text).ok_or_else(|| format!("invalid comparison '{}'", text))?);
+                    parsed.round_mask = true;
+                }
+                "--power" => {
+                    let text = value("--power")?;
+                    let profile = PowerProfile::from_id(&text).ok_or_else(|| format!("invalid power profile '{}'", text))?;
+                    parsed.power = Some(profile);
+                }
+                "--idle-clock" => {
+                    let text = value("--idle-clock")?;
+                    let seconds: f32 = text.parse().map_err(|_| format!("invalid idle timeout '{}'", text))?;
+                    if !(0.0..=86400.0).contains(&seconds) {
+                        return Err(format!("idle timeout {} out of range (0-86400 s)", seconds));
+                    }
+                    parsed.idle_clock = Some(seconds);
+                }
+                "--response" => {
+                    let text = value("--response")?;
+                    let (mode, curve) = match text.split_once('=') {
+                        Some((id, curve)) => (Some(ModeKind::from_id(id).ok_or_else(|| format!("unknown mode '{}'", id))?), curve),
+                        None => (None, text.as_str()),
+                    };
+                    let curve = ResponseCurve::parse(curve).ok_or_else(|| format!("invalid response curve '{}'", curve))?;
+                    parsed.response.push((mode, curve));
+                }
+                "--utc-offset" => {
+                    let text = value("--utc-offset")?;
+                    parsed.utc_offset = Some(parse_offset(&text).ok_or_else(|| format!("invalid utc offset '{}'", text))?);
+                }
+                "--startup" => {
+                    let text = value("--startup")?;
+                    let policy = StartupPolicy::parse(&text).ok_or_else(|| format!("invalid startup policy '{}'", text))?;
+                    parsed.startup = Some(policy);
+                }
+                "--physical" => {
+                    let text = value("--physical")?;
+                    let dpi: f32 = text.parse().map_err(|_| format!("invalid dpi '{}'", text))?;
+                    if !(30.0..=1000.0).contains(&dpi) {
+                        return Err(format!("dpi {} out of range (30-1000)", dpi));
+                    }
+                    parsed.physical_dpi = Some(dpi);
+                }
+                "--fx" => {
+                    for spec in value("--fx")?.split(',') {
+                        parsed.post_fx.push(PostFx::parse(spec).ok_or_else(|| format!("invalid effect '{}'", spec))?);
+                    }
+                }
+                "--presets" => parsed.presets = PathBuf::from(value("--presets")?),
+                "--config" => parsed.config = Some(PathBuf::from(value("--config")?)),
+                "--dump-config" => parsed.dump_config = true,
+                "--store" => parsed.store = PathBuf::from(value("--store")?),
+                "--reset-settings" => parsed.reset_settings = true,
+                "--battery" => {
+                    let text = value("--battery")?;
+                    parsed.battery = Some(BatterySim::parse(&text).ok_or_else(|| format!("invalid battery level '{}'", text))?);
+                }
+                "-h" | "--help" => {
+                    print_usage();
+                    std::process::exit(0);
+                }
+                other => return Err(format!("unknown option '{}'", other)),
+            }
+        }
+        // each of these lays out the window its own way
+        if parsed.compare.is_some() && (parsed.physical_dpi.is_some() || parsed.ring_leds.is_some() || parsed.fidelity) {
+            return Err("--compare doesn't combine with --physical, --ring or --fidelity".into());
+        }
+        Ok(parsed)
+    }
+}
+
+pub fn print_usage() {
+    let signals: Vec<&str> = Signal::ALL.iter().map(|s| s.name()).collect();
+    let signals = format!("signals: {}", signals.join(", "));
+    let options: &[(&str, &str)] = &[
+        ("--source <spec>", "input source: mic (default), synth:<signal>, output[:<device>]"),
+        ("", "for the playback signal (loopback on windows, a capture device"),
+        ("", "matching <device> or \"monitor\" elsewhere), mic+output[:<device>]"),
+        ("", "for mic vs output in the split mode, wav:<path> to play a file"),
+        ("", "in a loop, serial:<port> for the badge's frames over usb"),
+        ("", &signals),
+        ("--stereo", "analyze the left and right inputs separately for the split"),
+        ("", "mode (the synth adds pink noise on the right)"),
+        ("--soak <duration>", "headless soak test (e.g. 90s, 30m, 2h, at least 30s), uses the synth"),
+        ("", "signal from --source or speech, exits non-zero on failure"),
+        ("", "(needs a build with --features soak)"),
+        ("--round", "mask out pixels outside the round panel"),
+        ("--bezel", "--round plus a simulated bezel ring"),
+        ("--no-plosive-guard", "don't soften the low bands on \"p\" pops"),
+        ("--no-boot", "skip the power-on animation (any key skips it too)"),
+        ("--startup <policy>", "boot into: last, demo or mode:<id>"),
+        ("--physical <dpi>", "true-size preview in a badge body for a monitor of this dpi"),
+        ("--scale <n>[:filter]", "window scale 1-4 (default 2), 2:bilinear for smoothing"),
+        ("--fps <rate>", "target frame rate (default 30, the badge's panel rate)"),
+        ("--power <profile>", "full, or saver: half the frame rate, 120x120 doubled, brightness"),
+        ("", "capped at 50% (P toggles it while running)"),
+        ("--idle-clock <secs>", "show the clock face after this long without voice or input"),
+        ("", "(default 60, 0 never)"),
+        ("--response <curve>", "energy-to-visual curve: linear, sqrt, log:<floor dB> or"),
+        ("", "power:<exponent>, or <mode>=<curve> for one mode (repeatable)"),
+        ("--utc-offset <+hh:mm>", "timezone for the clock face (default: the host's)"),
+        ("--panel-mhz <mhz>", "spi clock of the emulated panel link (default 62.5), frames"),
+        ("", "wait for the previous one to finish streaming, 0 for instant"),
+        ("--fidelity", "look like the real panel: RGB565 colors, frame rate capped by"),
+        ("", "the spi clock and the panel refresh"),
+        ("--tearing", "--fidelity plus the tearing of refreshes that overlap a transfer"),
+        ("--panel-refresh <hz>", "refresh rate of the emulated panel (default 60)"),
+        ("--ring <leds>", "a NeoPixel ring of this many LEDs around the panel (e.g. 24)"),
+        ("--compare <spec>", "a second pipeline in a circle on the right: mode:<id> for another"),
+        ("", "mode on the same frames, detector:<peak|rms|log> or"),
+        ("", "filterbank:<narrow|mel> for the current mode on a second DSP fed"),
+        ("", "the same samples. palette, curves, effects and knobs follow the"),
+        ("", "main one (implies --round)"),
+        ("--hud", "show fps, per-phase frame times and dropped frames"),
+        ("--loudness", "loudness gauge on the left edge, target range from the"),
+        ("", "[loudness] low/high (LUFS) config keys"),
+        ("--pitch", "pitch trainer on the right edge with a 10 s trace, target band"),
+        ("", "from the [pitch] low/high (Hz) config keys"),
+        ("--record <file>", "log pitch, loudness and bands per frame (.jsonl, or .csv),"),
+        ("", "or whole frames as serial telemetry (.bin), and print session"),
+        ("", "stats at exit"),
+        ("--capture-dir <dir>", "where S saves a png screenshot and G a gif recording (G again"),
+        ("", "stops it, 20 s at most) of the panel (default captures)"),
+        ("--midi <port>", "midi input (name substring, or any) mapped by the [[midi]]"),
+        ("", "config entries (needs a build with --features midi)"),
+        ("--osc <port>", "listen for OSC on this udp port: /girlvoice/<param> (mode,"),
+        ("", "palette, brightness, gain, agc, tilt, power), /girlvoice/energy"),
+        ("", "<bands> and /girlvoice/frame <blob> (a whole frame in its"),
+        ("", "telemetry layout)"),
+        ("--threads <n>", "render threads (default: one per core, up to 16), 1 for the"),
+        ("", "plain single-threaded path"),
+        ("--fx <list>", "post effects, comma separated: trails:<decay>, blur:<amount>,"),
+        ("", "scanlines:<depth>, hue:<speed> (values 0-1, default trails:0.7),"),
+        ("", "hue:sync (one hue turn per phrase of speech) and"),
+        ("", "kaleido:<folds>[:mirror] (2-12 fold symmetry)"),
+        ("--presets <file>", "preset bank (default presets.txt), F1-F8 load a slot,"),
+        ("", "shift+F1-F8 store the current look into it"),
+        ("--config <file>", "startup config (default girlvoice.toml if present),"),
+        ("", "command line options win over it"),
+        ("--dump-config", "print the effective config as toml and exit"),
+        ("--store <file>", "settings saved on exit and restored on start (default settings.bin)"),
+        ("--reset-settings", "ignore the saved settings for this run"),
+        ("--battery <level>", "fake battery for the status gauge: percent (0-100), add +"),
+        ("", "for charging, or cycle to drain and recharge in a loop"),
+        ("-h, --help", "show this help"),
+    ];
+
+    println!("usage: simulator [options]");
+    println!();
+    for (flag, help) in options {
+        println!("  {:<22}{}", flag, help);
+    }
+}
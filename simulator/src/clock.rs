@@ -0,0 +1,50 @@
+// TimeSource over the host clock, standing in for the badge's RTC
+
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use girlvoice_ui_core::{TimeOfDay, TimeSource};
+
+pub struct SystemClock {
+    offset_s: i64, // local time minus UTC
+}
+
+impl SystemClock {
+    pub fn new(offset_minutes: i32) -> Self {
+        Self { offset_s: offset_minutes as i64 * 60 }
+    }
+
+    // the host's current timezone offset from `date +%z`, UTC where there's no date command
+    pub fn local() -> Self {
+        let output = Command::new("date").arg("+%z").output().ok();
+        let offset = output.and_then(|o| parse_offset(String::from_utf8_lossy(&o.stdout).trim()));
+        Self::new(offset.unwrap_or(0))
+    }
+}
+
+impl TimeSource for SystemClock {
+    fn now(&mut self) -> Option<TimeOfDay> {
+        let utc = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs() as i64;
+        let local = (utc + self.offset_s).rem_euclid(TimeOfDay::SECONDS_PER_DAY as i64);
+        Some(TimeOfDay::from_seconds(local as u32))
+    }
+}
+
+// "+0200", "-05:30" or "+2" -> minutes east of UTC
+pub fn parse_offset(text: &str) -> Option<i32> {
+    let (sign, rest) = match text.as_bytes().first()? {
+        b'+' => (1, &text[1..]),
+        b'-' => (-1, &text[1..]),
+        _ => return None,
+    };
+    let digits: String = rest.chars().filter(|&c| c != ':').collect();
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let (hours, minutes) = match digits.len() {
+        1 | 2 => (digits.parse::<i32>().ok()?, 0),
+        4 => (digits[..2].parse::<i32>().ok()?, digits[2..].parse::<i32>().ok()?),
+        _ => return None,
+    };
+    (hours <= 14 && minutes < 60).then_some(sign * (hours * 60 + minutes))
+}
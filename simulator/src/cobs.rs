@@ -0,0 +1,36 @@
+// consistent overhead byte stuffing, the framing of the badge's serial telemetry: no 0 byte
+// inside a packet, so a 0 ends one and a reader joining mid-stream resyncs at the next
+
+// appends the encoded packet, without the closing 0
+pub fn encode(data: &[u8], out: &mut Vec<u8>) {
+    let mut code_at = out.len();
+    out.push(0);
+    for &byte in data {
+        if byte != 0 {
+            out.push(byte);
+        }
+        let run = out.len() - code_at;
+        if byte == 0 || run == 0xff {
+            out[code_at] = run as u8;
+            code_at = out.len();
+            out.push(0);
+        }
+    }
+    out[code_at] = (out.len() - code_at) as u8;
+}
+
+// None on a malformed packet
+pub fn decode(packet: &[u8], out: &mut Vec<u8>) -> Option<()> {
+    out.clear();
+    let mut rest = packet;
+    while let Some((&code, tail)) = rest.split_first() {
+        let run = (code as usize).checked_sub(1).filter(|&run| run <= tail.len())?;
+        out.extend_from_slice(&tail[..run]);
+        rest = &tail[run..];
+        // a full run of 254 isn't followed by a zero
+        if code != 0xff && !rest.is_empty() {
+            out.push(0);
+        }
+    }
+    Some(())
+}
@@ -0,0 +1,166 @@
+// --compare: a second pipeline drawn in its own circle right of the main one. mode:<id> runs
+// another mode over the main pipeline's frames. detector:<kind> and filterbank:<kind> run the
+// main mode on a second DSP inside the main source (EnergySource::set_comparison), fed the
+// same samples, with every band on that envelope detector or with that filterbank. everything
+// else follows the main pipeline every frame (palette, curves, post effects, the mode's knobs,
+// the DSP settings), so only the compared part differs on screen
+
+use girlvoice_ui_core::{
+    AnalysisFrame, ChannelCount, Detector, DspSettings, EnergySource, Filterbank, Framebuffer, ModeKind, PostChain, TimeOfDay, VocoderConfig,
+    Visualizer, CHANNELS, DISPLAY_SIZE,
+};
+
+use crate::contrib;
+use crate::render;
+use crate::upscale::Upscaler;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompareSpec {
+    Mode(ModeKind),
+    Detector(Detector),
+    Filterbank(Filterbank),
+}
+
+impl CompareSpec {
+    // "mode:<id>", "detector:<peak|rms|log>" or "filterbank:<narrow|mel>"
+    pub fn parse(text: &str) -> Option<CompareSpec> {
+        match text.split_once(':')? {
+            ("mode", id) => ModeKind::from_id(id).map(CompareSpec::Mode),
+            ("detector", id) => Detector::from_id(id).map(CompareSpec::Detector),
+            ("filterbank", id) => Filterbank::from_id(id).map(CompareSpec::Filterbank),
+            _ => None,
+        }
+    }
+
+    // detector and filterbank comparisons need the source's second DSP
+    pub fn analyzes(&self) -> bool {
+        !matches!(self, CompareSpec::Mode(_))
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            CompareSpec::Mode(mode) => mode.name().to_string(),
+            CompareSpec::Detector(detector) => format!("{} detector", detector.id()),
+            CompareSpec::Filterbank(filterbank) => format!("{} filterbank", filterbank.id()),
+        }
+    }
+}
+
+pub struct Comparison {
+    spec: CompareSpec,
+    vocoder: VocoderConfig, // the second DSP's, the main one's with the compared filterbank
+    dsp: Option<DspSettings>, // main pipeline's settings the second DSP was last set up from
+    visualizer: Visualizer,
+    framebuffer: Box<Framebuffer>,
+    post: Box<PostChain>,
+    pixels: Vec<u32>,
+    upscaler: Upscaler, // same scale as the main picture's
+    window: Vec<u32>,
+}
+
+impl Comparison {
+    pub fn new(spec: CompareSpec, channels: ChannelCount, vocoder: &VocoderConfig, upscaler: Upscaler, plosive_guard: bool) -> Self {
+        let mut visualizer = Visualizer::new(channels);
+        visualizer.set_plosive_guard(plosive_guard);
+        visualizer.set_idle_clock(None);
+        contrib::register(&mut visualizer);
+        let size = upscaler.size();
+        let mut comparison = Self {
+            spec,
+            vocoder: match spec {
+                CompareSpec::Filterbank(filterbank) => VocoderConfig { filterbank, ..*vocoder },
+                _ => *vocoder,
+            },
+            dsp: None,
+            visualizer,
+            framebuffer: Box::new(Framebuffer::new()),
+            post: Box::new(PostChain::empty()),
+            pixels: vec![0; DISPLAY_SIZE * DISPLAY_SIZE],
+            upscaler,
+            window: Vec::new(),
+        };
+        // the gap stays the color around the circles
+        comparison.window = vec![render::MASK_COLOR.to_argb32(); comparison.width() * size];
+        comparison
+    }
+
+    pub fn width(&self) -> usize {
+        2 * self.upscaler.size() + self.gap()
+    }
+
+    pub fn height(&self) -> usize {
+        self.upscaler.size()
+    }
+
+    fn gap(&self) -> usize {
+        self.upscaler.size() / 16
+    }
+
+    // everything but what is being compared follows `main`, `source` is the main one. call
+    // once a frame before update
+    pub fn sync(&mut self, main: &Visualizer, post: &PostChain, dsp: &DspSettings, source: &mut dyn EnergySource) {
+        let mode = match self.spec {
+            CompareSpec::Mode(mode) => mode,
+            CompareSpec::Detector(_) | CompareSpec::Filterbank(_) => main.displayed_mode(),
+        };
+        if self.visualizer.current_mode() != mode {
+            self.visualizer.set_mode(mode);
+        }
+        for (param, main) in self.visualizer.mode_params_mut(mode).iter_mut().zip(main.mode_params(mode)) {
+            param.value = main.value;
+        }
+        self.visualizer.set_palette(*main.palette());
+        self.visualizer.set_response(*main.response());
+
+        if self.post.slots() != post.slots() {
+            self.post.set_slots(post.slots());
+        }
+        self.post.set_pixel_step(post.pixel_step());
+
+        if self.dsp != Some(*dsp) {
+            self.set_dsp(dsp, source);
+        }
+    }
+
+    // sets up the source's second DSP from the main pipeline's settings. false if the
+    // comparison needs one and the source has no DSP of its own
+    pub fn set_dsp(&mut self, dsp: &DspSettings, source: &mut dyn EnergySource) -> bool {
+        self.dsp = Some(*dsp);
+        let settings = match self.spec {
+            CompareSpec::Mode(_) => return true,
+            CompareSpec::Detector(detector) => DspSettings { detector: [detector; CHANNELS], ..*dsp },
+            CompareSpec::Filterbank(_) => *dsp,
+        };
+        source.set_comparison(&self.vocoder, &settings)
+    }
+
+    // `frame` is the main pipeline's, used unless the comparison analyzes on its own
+    pub fn update(&mut self, dt: f32, frame: &AnalysisFrame, source: &mut dyn EnergySource, time: Option<TimeOfDay>) {
+        let frame = if self.spec.analyzes() { source.poll_comparison().unwrap_or(*frame) } else { *frame };
+        self.visualizer.set_time(time);
+        // a frame the visualizers can't take was already reported for the main one
+        let _ = self.visualizer.update_frame(dt, &frame);
+        self.post.update(dt, &frame);
+        self.visualizer.set_hue(self.post.hue());
+    }
+
+    // always round, the two pictures read as two badges
+    pub fn render(&mut self, brightness: f32, step: usize, bezel: bool) {
+        self.post.begin_frame(&mut self.framebuffer, self.visualizer.displayed_mode());
+        render::render_region_additive(&self.visualizer, &mut self.framebuffer, self.post.render_region(), brightness, step);
+        self.post.end_frame(&mut self.framebuffer);
+        render::apply_round_mask(&mut self.framebuffer, bezel);
+        self.post.write_argb32(&self.framebuffer, &mut self.pixels).expect("pixels are display sized");
+    }
+
+    // the main picture, already upscaled, on the left and this pipeline's on the right
+    pub fn compose(&mut self, main: &[u32]) -> &[u32] {
+        let (size, width, gap) = (self.upscaler.size(), self.width(), self.gap());
+        let right = self.upscaler.upscale(&self.pixels);
+        for ((row, left), right) in self.window.chunks_exact_mut(width).zip(main.chunks_exact(size)).zip(right.chunks_exact(size)) {
+            row[..size].copy_from_slice(left);
+            row[size + gap..].copy_from_slice(right);
+        }
+        &self.window
+    }
+}
@@ -0,0 +1,50 @@
+// girlvoice.toml: startup configuration, the same serde types the firmware stores with postcard.
+// every section and key is optional, missing ones keep their defaults.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use girlvoice_ui_core::{DspSettings, LoudnessTarget, PitchTarget, PostChain, PostFx, Settings, VocoderConfig};
+
+use crate::midi::{self, MidiBinding};
+
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub vocoder: VocoderConfig,
+    pub dsp: DspSettings,
+    pub settings: Settings, // first-run defaults, the settings store wins once it has data
+    pub post: Vec<PostFx>,
+    pub loudness: LoudnessTarget, // range for the --loudness gauge
+    pub pitch: PitchTarget,       // band for the --pitch trainer
+    pub midi: Vec<MidiBinding>,   // controller mapping for --midi
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            vocoder: VocoderConfig::default(),
+            dsp: DspSettings::default(),
+            settings: Settings::default(),
+            post: PostChain::default().stages().copied().collect(),
+            loudness: LoudnessTarget::default(),
+            pitch: PitchTarget::default(),
+            midi: midi::default_bindings(),
+        }
+    }
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("can't read {}: {}", path.display(), e))?;
+        let config: Config = toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        // sources and modes are sized by it, a bad count can't wait until they're built
+        config.vocoder.channels().map_err(|e| format!("{}: vocoder.num_channels: {}", path.display(), e))?;
+        Ok(config)
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("config is always representable as toml")
+    }
+}
@@ -0,0 +1,87 @@
+// modes that live outside core, added through girlvoice_ui_core::registry the way a downstream
+// crate would. Rings is the example: one concentric ring per band, lowest in the middle, each
+// as thick as its band is loud. it only uses core's public api (VisualMode, Param, the palette)
+
+use girlvoice_ui_core::registry::{self, ModeInfo};
+use girlvoice_ui_core::{AnalysisFrame, Color, ColorPalette, Param, VisualMode, Visualizer, CHANNELS, DISPLAY_CENTER, DISPLAY_RADIUS, DISPLAY_SIZE};
+
+use libm::sqrtf;
+
+pub static RINGS: ModeInfo = ModeInfo { id: "rings", name: "Rings", full_screen: false };
+
+// the ids have to resolve before the command line, the config file and the saved settings are read
+pub fn declare() {
+    registry::declare(&RINGS).expect("the catalog has room for the contrib modes");
+}
+
+// every visualizer gets its own instances
+pub fn register(visualizer: &mut Visualizer) {
+    visualizer.register(&RINGS, Box::new(Rings::new())).expect("the catalog has room for the contrib modes");
+}
+
+pub struct Rings {
+    levels: [f32; CHANNELS], // peak-held band energies
+    bands: usize,
+    params: [Param; 2],
+}
+
+impl Rings {
+    // params: how fast a ring falls back after a peak (per s), widest ring as a share of its spacing
+    const FALL: usize = 0;
+    const WIDTH: usize = 1;
+
+    const MIN_LEVEL: f32 = 0.08; // silent rings stay a hairline
+
+    pub fn new() -> Self {
+        Self {
+            levels: [0.0; CHANNELS],
+            bands: CHANNELS,
+            params: [
+                Param::new("fall", "Fall speed", 0.5, 6.0, 0.25, 2.0),
+                Param::new("width", "Ring width", 0.2, 1.0, 0.05, 0.8),
+            ],
+        }
+    }
+}
+
+impl VisualMode for Rings {
+    fn update(&mut self, dt: f32, frame: &AnalysisFrame) {
+        let bands = frame.bands();
+        self.bands = bands.len().clamp(1, CHANNELS);
+        let fall = self.params[Self::FALL].value * dt;
+        for (level, &energy) in self.levels.iter_mut().zip(bands) {
+            *level = energy.clamp(0.0, 1.0).max(*level - fall);
+        }
+    }
+
+    fn render(&self, set_pixel: &mut dyn FnMut(usize, usize, Color), pal: &ColorPalette) {
+        let spacing = DISPLAY_RADIUS / self.bands as f32;
+        let width = self.params[Self::WIDTH].value;
+        for y in 0..DISPLAY_SIZE {
+            let dy = y as f32 - DISPLAY_CENTER;
+            for x in 0..DISPLAY_SIZE {
+                let dx = x as f32 - DISPLAY_CENTER;
+                let r = sqrtf(dx * dx + dy * dy);
+                let band = (r / spacing) as usize;
+                if band >= self.bands {
+                    continue;
+                }
+                let level = self.levels[band].max(Self::MIN_LEVEL);
+                let half = 0.5 * spacing * width * level;
+                // antialiased edges, one pixel wide
+                let coverage = (half - (r - (band as f32 + 0.5) * spacing).abs() + 0.5).clamp(0.0, 1.0);
+                if coverage > 0.0 {
+                    set_pixel(x, y, pal.band(band, self.bands).scale(coverage * (0.3 + 0.7 * level)));
+                }
+            }
+        }
+    }
+
+    fn params(&self) -> &[Param] {
+        &self.params
+    }
+
+    fn params_mut(&mut self) -> &mut [Param] {
+        &mut self.params
+    }
+}
@@ -1,9 +1,14 @@
 // Rust implementation of the girlvoice Amaranth DSP pipeline:
+// - resampling from the input rate to the fixed ANALYSIS_RATE of the hardware
 // - bandpass IIR filters for each frequency band
 // - envelope followers to extract the amplitudes
 
 use std::f32::consts::PI;
 
+use girlvoice_ui_core::{
+    AnalysisFrame, BandHistory, ChannelCount, Detector, DspSettings, Filterbank, FormantTracker, Formants, VocoderConfig, CHANNELS, CLIP_LEVEL,
+};
+
 // same mel scale as girlvoice-gateware
 fn mel(freq: f32) -> f32 {
     1127.0 * (1.0 + freq / 700.0).ln()
@@ -13,6 +18,97 @@ fn mel_to_freq(m: f32) -> f32 {
     700.0 * ((m / 1127.0).exp() - 1.0)
 }
 
+// every filter, envelope and detector runs at this rate whatever the input device does, so
+// band tuning, attack/release and the agc behave the same on 44.1, 48 or 96 kHz hosts
+pub const ANALYSIS_RATE: f32 = 24000.0;
+
+// analysis samples per AnalysisFrame, ~94 frames per second like the hardware pushes them
+pub const HOP: usize = 256;
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+// rational polyphase resampler: up by `up`, windowed-sinc lowpass, down by `down`, computing
+// only the outputs that are kept. one phase of TAPS coefficients per output sample
+pub struct Resampler {
+    up: usize,
+    down: usize,
+    coeffs: Vec<f32>, // TAPS per phase, phase-major, newest input first
+    history: Vec<f32>, // last TAPS inputs, newest at `pos`
+    pos: usize,
+    phase: usize, // next output's phase, `up` or more once the newest input is used up
+}
+
+impl Resampler {
+    const TAPS: usize = 24;
+    const MAX_PHASES: u32 = 512; // rates without a small ratio are rounded to one that has
+    const CUTOFF: f32 = 0.9; // of the lower of the two nyquist frequencies
+
+    pub fn new(input_rate: f32, output_rate: f32) -> Self {
+        let (input, output) = (input_rate.round().max(1.0) as u32, output_rate.round().max(1.0) as u32);
+        let g = gcd(input, output);
+        let (mut up, mut down) = (output / g, input / g);
+        if up > Self::MAX_PHASES {
+            down = ((down as f32 * Self::MAX_PHASES as f32 / up as f32).round() as u32).max(1);
+            up = Self::MAX_PHASES;
+        }
+        let (up, down) = (up as usize, down as usize);
+
+        // prototype lowpass at the upsampled rate, gain `up` to make up for the zero stuffing
+        let len = up * Self::TAPS;
+        let cutoff = Self::CUTOFF * 0.5 / up.max(down) as f32; // cycles per upsampled sample
+        let center = (len - 1) as f32 / 2.0;
+        let prototype: Vec<f32> = (0..len)
+            .map(|n| {
+                let t = n as f32 - center;
+                let sinc = if t == 0.0 { 1.0 } else { (2.0 * PI * cutoff * t).sin() / (PI * t) * 0.5 / cutoff };
+                let w = n as f32 / (len - 1).max(1) as f32;
+                let blackman = 0.42 - 0.5 * (2.0 * PI * w).cos() + 0.08 * (4.0 * PI * w).cos();
+                2.0 * cutoff * up as f32 * sinc * blackman
+            })
+            .collect();
+        let coeffs = (0..up)
+            .flat_map(|phase| (0..Self::TAPS).map(move |k| (phase, k)))
+            .map(|(phase, k)| prototype[phase + k * up])
+            .collect();
+
+        Self { up, down, coeffs, history: vec![0.0; Self::TAPS], pos: 0, phase: 0 }
+    }
+
+    // same rate in and out, samples pass straight through
+    pub fn is_bypass(&self) -> bool {
+        self.up == 1 && self.down == 1
+    }
+
+    pub fn push(&mut self, sample: f32) {
+        self.pos = (self.pos + 1) % Self::TAPS;
+        self.history[self.pos] = sample;
+    }
+
+    // the outputs the newest input made available, call until None after every push
+    pub fn pop(&mut self) -> Option<f32> {
+        if self.phase >= self.up {
+            self.phase -= self.up;
+            return None;
+        }
+        let coeffs = &self.coeffs[self.phase * Self::TAPS..(self.phase + 1) * Self::TAPS];
+        let (newer, older) = self.history.split_at(self.pos + 1);
+        // newest first: back from pos, then wrapping around from the end
+        let output = newer.iter().rev().chain(older.iter().rev()).zip(coeffs).map(|(x, c)| x * c).sum();
+        self.phase += self.down;
+        Some(output)
+    }
+}
+
+// blocks of envelope peaks the agc can look back over, ~2.7 s
+const AGC_HISTORY: usize = 1024;
+
+// settings.agc_release in blocks of agc history
+fn agc_window(release: f32) -> usize {
+    (release * ANALYSIS_RATE / BLOCK as f32).round() as usize
+}
+
 // second-order IIR butterworth bandpass filter (girlvoice/dsp/bandpass_iir.py)
 pub struct BandpassIIR {
     // filter coefficients
@@ -26,7 +122,7 @@ pub struct BandpassIIR {
 
 
 impl BandpassIIR {
-    pub fn new(low_freq: f32, high_freq: f32, sample_rate: f32, order: u32) -> Self { // order is the filter order (1 = 2nd order, 2 = 4th order)
+    pub fn new(low_freq: f32, high_freq: f32, sample_rate: f32, _order: u32) -> Self { // order is the filter order (1 = 2nd order, 2 = 4th order)
         let nyq = sample_rate / 2.0;
         let low = low_freq / nyq;
         let high = high_freq / nyq;
@@ -85,14 +181,102 @@ impl BandpassIIR {
     }
 }
 
+// the band filters as structure-of-arrays, one lane per channel, so the update for a sample
+// runs across every channel at once and autovectorizes. all lanes see the same input, so the
+// input delay line is shared and b1 of the bandpass is always 0
+pub struct FilterBank {
+    b0: Vec<f32>,
+    b2: Vec<f32>,
+    a1: Vec<f32>,
+    a2: Vec<f32>,
+    x1: f32,
+    x2: f32,
+    y1: Vec<f32>,
+    y2: Vec<f32>
+}
+
+impl FilterBank {
+    pub fn new<'a>(filters: impl Iterator<Item = &'a BandpassIIR>) -> Self {
+        let (mut b0, mut b2, mut a1, mut a2) = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        for filter in filters {
+            b0.push(filter.b[0]);
+            b2.push(filter.b[2]);
+            a1.push(filter.a[1]);
+            a2.push(filter.a[2]);
+        }
+        let lanes = b0.len();
+        Self { b0, b2, a1, a2, x1: 0.0, x2: 0.0, y1: vec![0.0; lanes], y2: vec![0.0; lanes] }
+    }
+
+    pub fn lanes(&self) -> usize {
+        self.b0.len()
+    }
+
+    // filter `input` through every channel, output[i * lanes + c] is channel c at sample i
+    pub fn process_block(&mut self, input: &[f32], output: &mut [f32]) {
+        let lanes = self.lanes();
+        let (b0, b2, a1, a2) = (&self.b0[..lanes], &self.b2[..lanes], &self.a1[..lanes], &self.a2[..lanes]);
+        let (y1, y2) = (&mut self.y1[..lanes], &mut self.y2[..lanes]);
+        for (&x0, out) in input.iter().zip(output.chunks_exact_mut(lanes)) {
+            let x2 = self.x2;
+            for c in 0..lanes {
+                // same sum as BandpassIIR::process, without the b1 term
+                let y = b0[c] * x0 + b2[c] * x2 - a1[c] * y1[c] - a2[c] * y2[c];
+                y2[c] = y1[c];
+                y1[c] = y;
+                out[c] = y;
+            }
+            self.x2 = self.x1;
+            self.x1 = x0;
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.x1 = 0.0;
+        self.x2 = 0.0;
+        self.y1.fill(0.0);
+        self.y2.fill(0.0);
+    }
+}
+
+
+// quietest level the log detector tracks, -100 dBFS
+const LOG_FLOOR_DB: f32 = -100.0;
+
+fn to_db(amplitude: f32) -> f32 {
+    (20.0 * amplitude.log10()).max(LOG_FLOOR_DB)
+}
+
+fn from_db(db: f32) -> f32 {
+    if db <= LOG_FLOOR_DB { 0.0 } else { 10f32.powf(db / 20.0) }
+}
+
+// input sample -> the detector's domain: amplitude, square or dB
+fn detect(detector: Detector, input: f32) -> f32 {
+    match detector {
+        Detector::Peak => input.abs(),
+        Detector::Rms => input * input,
+        Detector::Log => to_db(input.abs()),
+    }
+}
+
+// a value in the detector's domain -> amplitude
+fn level(detector: Detector, value: f32) -> f32 {
+    match detector {
+        Detector::Peak => value,
+        Detector::Rms => value.sqrt(),
+        Detector::Log => from_db(value),
+    }
+}
 
 // envelope follower using exponential smoothing (girlvoice/dsp/envelope.py)
 pub struct EnvelopeFollower {
-    value: f32,
+    value: f32, // in the detector's domain: amplitude, square or dB
     attack: f32,
     release: f32,
     attack_comp: f32,
-    release_comp: f32
+    release_comp: f32,
+    detector: Detector
 }
 
 impl EnvelopeFollower {
@@ -108,30 +292,135 @@ impl EnvelopeFollower {
             attack,
             release,
             attack_comp: 1.0 - attack,
-            release_comp: 1.0 - release
+            release_comp: 1.0 - release,
+            detector: Detector::Peak
         }
     }
 
-    // process a sample
+    // the current level carries over into the new detector's domain
+    pub fn set_detector(&mut self, detector: Detector) {
+        self.value = detect(detector, self.value());
+        self.detector = detector;
+    }
+
+    // process a sample, returns the level as an amplitude whatever the detector
     pub fn process(&mut self, input: f32) -> f32 {
-        let abs_input = input.abs();
-        
-        let (coeff, comp) = if abs_input > self.value {
+        let detected = detect(self.detector, input);
+
+        let (coeff, comp) = if detected > self.value {
             (self.attack, self.attack_comp)
         } else {
             (self.release, self.release_comp)
         };
         
-        self.value = self.value * coeff + abs_input * comp;
-        self.value
+        self.value = self.value * coeff + detected * comp;
+        self.value()
     }
 
     pub fn value(&self) -> f32 {
-        self.value
+        level(self.detector, self.value)
     }
 
     pub fn reset(&mut self) {
-        self.value = 0.0;
+        self.value = match self.detector {
+            Detector::Log => LOG_FLOOR_DB,
+            Detector::Peak | Detector::Rms => 0.0,
+        };
+    }
+}
+
+// envelope followers as structure-of-arrays, the counterpart of FilterBank. the smoothing
+// recursion runs across lanes, detection and the conversion back to amplitude per lane
+pub struct EnvelopeBank {
+    value: Vec<f32>,
+    attack: Vec<f32>,
+    release: Vec<f32>,
+    attack_comp: Vec<f32>,
+    release_comp: Vec<f32>,
+    detector: Vec<Detector>
+}
+
+impl EnvelopeBank {
+    pub fn new<'a>(followers: impl Iterator<Item = &'a EnvelopeFollower>) -> Self {
+        let mut bank = Self {
+            value: Vec::new(),
+            attack: Vec::new(),
+            release: Vec::new(),
+            attack_comp: Vec::new(),
+            release_comp: Vec::new(),
+            detector: Vec::new()
+        };
+        for follower in followers {
+            bank.value.push(follower.value);
+            bank.attack.push(follower.attack);
+            bank.release.push(follower.release);
+            bank.attack_comp.push(follower.attack_comp);
+            bank.release_comp.push(follower.release_comp);
+            bank.detector.push(follower.detector);
+        }
+        bank
+    }
+
+    pub fn lanes(&self) -> usize {
+        self.value.len()
+    }
+
+    // same as EnvelopeFollower::set_detector for one lane
+    pub fn set_detector(&mut self, lane: usize, detector: Detector) {
+        self.value[lane] = detect(detector, level(self.detector[lane], self.value[lane]));
+        self.detector[lane] = detector;
+    }
+
+    // follow an interleaved block from FilterBank::process_block in place, each sample replaced
+    // by the level EnvelopeFollower::process would return
+    pub fn process_block(&mut self, block: &mut [f32]) {
+        let lanes = self.lanes();
+        // one detector for every lane is the usual case and a single contiguous pass
+        let shared = self.detector.iter().all(|&d| d == self.detector[0]).then(|| self.detector[0]);
+        match shared {
+            Some(detector) => block.iter_mut().for_each(|sample| *sample = detect(detector, *sample)),
+            None => {
+                for frame in block.chunks_exact_mut(lanes) {
+                    for (sample, &detector) in frame.iter_mut().zip(&self.detector) {
+                        *sample = detect(detector, *sample);
+                    }
+                }
+            }
+        }
+
+        let value = &mut self.value[..lanes];
+        let (attack, release) = (&self.attack[..lanes], &self.release[..lanes]);
+        let (attack_comp, release_comp) = (&self.attack_comp[..lanes], &self.release_comp[..lanes]);
+        for frame in block.chunks_exact_mut(lanes) {
+            for c in 0..lanes {
+                let rising = frame[c] > value[c];
+                let coeff = if rising { attack[c] } else { release[c] };
+                let comp = if rising { attack_comp[c] } else { release_comp[c] };
+                value[c] = value[c] * coeff + frame[c] * comp;
+                frame[c] = value[c];
+            }
+        }
+
+        match shared {
+            Some(Detector::Peak) => {}
+            Some(detector) => block.iter_mut().for_each(|sample| *sample = level(detector, *sample)),
+            None => {
+                for frame in block.chunks_exact_mut(lanes) {
+                    for (sample, &detector) in frame.iter_mut().zip(&self.detector) {
+                        *sample = level(detector, *sample);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn reset(&mut self) {
+        for (value, &detector) in self.value.iter_mut().zip(&self.detector) {
+            *value = match detector {
+                Detector::Log => LOG_FLOOR_DB,
+                Detector::Peak | Detector::Rms => 0.0,
+            };
+        }
     }
 }
 
@@ -164,94 +453,625 @@ impl VocoderChannel {
 }
 
 
+// plosive ("p" pop) detector: a sudden low-frequency burst far above the recent low-band level
+pub struct PlosiveDetector {
+    lowpass: f32,
+    lowpass_coeff: f32,
+    fast: EnvelopeFollower,
+    slow: EnvelopeFollower,
+    hold_samples: u32,
+    hold_remaining: u32
+}
+
+impl PlosiveDetector {
+    const CUTOFF_HZ: f32 = 150.0;
+    const RATIO: f32 = 4.0;   // fast envelope vs slow envelope
+    const FLOOR: f32 = 0.05;  // ignore quiet bumps
+    const HOLD_MS: f32 = 60.0;
+
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            lowpass: 0.0,
+            lowpass_coeff: 1.0 - (-2.0 * PI * Self::CUTOFF_HZ / sample_rate).exp(),
+            fast: EnvelopeFollower::new(sample_rate, 1.0, 20.0),
+            slow: EnvelopeFollower::new(sample_rate, 200.0, 200.0),
+            hold_samples: (sample_rate * Self::HOLD_MS / 1000.0) as u32,
+            hold_remaining: 0
+        }
+    }
+
+    // process a sample
+    pub fn process(&mut self, input: f32) -> bool {
+        self.lowpass += self.lowpass_coeff * (input - self.lowpass);
+        let fast = self.fast.process(self.lowpass);
+        let slow = self.slow.process(self.lowpass);
+
+        if fast > Self::FLOOR && fast > slow * Self::RATIO {
+            self.hold_remaining = self.hold_samples;
+        } else if self.hold_remaining > 0 {
+            self.hold_remaining -= 1;
+        }
+        self.is_active()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.hold_remaining > 0
+    }
+}
+
+
+// voice activity: the broadband level was above a speech threshold recently. the hold bridges
+// the gaps between syllables, so the flag doesn't flicker through a sentence
+pub struct VoiceActivityDetector {
+    level: EnvelopeFollower,
+    hold_samples: u32,
+    hold_remaining: u32
+}
+
+impl VoiceActivityDetector {
+    const THRESHOLD: f32 = 0.01; // mean absolute level, about -40 dBFS
+    const HOLD_MS: f32 = 250.0;
+
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            level: EnvelopeFollower::new(sample_rate, 5.0, 50.0),
+            hold_samples: (sample_rate * Self::HOLD_MS / 1000.0) as u32,
+            hold_remaining: 0
+        }
+    }
+
+    // process a sample
+    pub fn process(&mut self, input: f32) -> bool {
+        if self.level.process(input) > Self::THRESHOLD {
+            self.hold_remaining = self.hold_samples;
+        } else if self.hold_remaining > 0 {
+            self.hold_remaining -= 1;
+        }
+        self.is_active()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.hold_remaining > 0
+    }
+}
+
+
+// biquad in direct form I, f64 so the low K-weighting corner stays accurate
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2], // a1, a2 (a0 normalized to 1)
+    x: [f64; 2],
+    y: [f64; 2]
+}
+
+impl Biquad {
+    fn process(&mut self, input: f64) -> f64 {
+        let output = self.b[0] * input + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+                   - self.a[0] * self.y[0] - self.a[1] * self.y[1];
+        self.x = [input, self.x[0]];
+        self.y = [output, self.y[0]];
+        output
+    }
+}
+
+// loudness in LUFS-style units (ITU-R BS.1770): K-weighting (high shelf + high pass), mean
+// square over 400 ms blocks every 100 ms, then the absolute (-70) and relative (-10 LU) gates
+// over the last few seconds of blocks. a running window instead of a whole-programme integral,
+// so it tracks how loud someone is speaking right now.
+pub struct LoudnessMeter {
+    shelf: Biquad,
+    highpass: Biquad,
+    step_samples: u32,
+    step_count: u32,
+    step_sum: f64,
+    steps: [f64; 4],      // mean squares of the last four 100 ms steps (one 400 ms block)
+    blocks: Vec<f64>,     // block mean squares, oldest overwritten first
+    next_block: usize,
+    filled_steps: usize
+}
+
+impl LoudnessMeter {
+    const HISTORY_BLOCKS: usize = 50; // 5 s of blocks
+    const ABSOLUTE_GATE: f64 = -70.0;
+    const RELATIVE_GATE: f64 = -10.0;
+
+    pub fn new(sample_rate: f32) -> Self {
+        let fs = sample_rate as f64;
+
+        // stage 1: +4 dB high shelf around 1.7 kHz (head diffraction)
+        let (f0, gain_db, q) = (1681.974450955533, 3.999843853973347, 0.7071752369554196);
+        let k = (std::f64::consts::PI * f0 / fs).tan();
+        let vh = 10f64.powf(gain_db / 20.0);
+        let vb = vh.powf(0.4996667741545416);
+        let a0 = 1.0 + k / q + k * k;
+        let shelf = Biquad {
+            b: [(vh + vb * k / q + k * k) / a0, 2.0 * (k * k - vh) / a0, (vh - vb * k / q + k * k) / a0],
+            a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+            x: [0.0; 2],
+            y: [0.0; 2]
+        };
+
+        // stage 2: high pass at 38 Hz
+        let (f0, q) = (38.13547087602444, 0.5003270373238773);
+        let k = (std::f64::consts::PI * f0 / fs).tan();
+        let a0 = 1.0 + k / q + k * k;
+        let highpass = Biquad {
+            b: [1.0, -2.0, 1.0],
+            a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+            x: [0.0; 2],
+            y: [0.0; 2]
+        };
+
+        Self {
+            shelf,
+            highpass,
+            step_samples: ((fs * 0.1) as u32).max(1),
+            step_count: 0,
+            step_sum: 0.0,
+            steps: [0.0; 4],
+            blocks: vec![0.0; Self::HISTORY_BLOCKS],
+            next_block: 0,
+            filled_steps: 0
+        }
+    }
+
+    // process a sample
+    pub fn process(&mut self, sample: f32) {
+        let weighted = self.highpass.process(self.shelf.process(sample as f64));
+        self.step_sum += weighted * weighted;
+        self.step_count += 1;
+        if self.step_count < self.step_samples {
+            return;
+        }
+
+        self.steps.rotate_left(1);
+        self.steps[3] = self.step_sum / self.step_count as f64;
+        self.step_sum = 0.0;
+        self.step_count = 0;
+        self.filled_steps += 1;
+        if self.filled_steps >= self.steps.len() {
+            self.blocks[self.next_block] = self.steps.iter().sum::<f64>() / self.steps.len() as f64;
+            self.next_block = (self.next_block + 1) % self.blocks.len();
+        }
+    }
+
+    // gated loudness over the history, None until a block clears the absolute gate
+    pub fn loudness(&self) -> Option<f32> {
+        let gated_mean = |gate: f64| {
+            let (sum, count) = self.blocks.iter()
+                .filter(|&&ms| to_lufs(ms) > gate)
+                .fold((0.0, 0), |(sum, count), &ms| (sum + ms, count + 1));
+            (count > 0).then(|| sum / count as f64)
+        };
+        let ungated = gated_mean(Self::ABSOLUTE_GATE)?;
+        let relative = (to_lufs(ungated) + Self::RELATIVE_GATE).max(Self::ABSOLUTE_GATE);
+        gated_mean(relative).map(|ms| to_lufs(ms) as f32)
+    }
+}
+
+fn to_lufs(mean_square: f64) -> f64 {
+    -0.691 + 10.0 * mean_square.max(1e-12).log10()
+}
+
+
+// fundamental frequency tracker (YIN): decimated to ~12 kHz, the cumulative mean normalized
+// difference over a 20 ms window picks the first period below the threshold every 10 ms
+pub struct PitchDetector {
+    decimation: usize,
+    rate: f32, // after decimation
+    sum: f32,
+    summed: usize,
+    ring: Vec<f32>, // WINDOW + MAX_LAG decimated samples, oldest overwritten first
+    pos: usize,
+    hop_samples: usize,
+    since_hop: usize,
+    cmnd: Vec<f32>, // scratch for the normalized difference, one entry per lag
+    pitch: Option<f32>
+}
+
+impl PitchDetector {
+    const TARGET_RATE: f32 = 12000.0;
+    const MIN_HZ: f32 = 60.0;
+    const MAX_HZ: f32 = 500.0;
+    const WINDOW_S: f32 = 0.02;
+    const HOP_S: f32 = 0.01;
+    const THRESHOLD: f32 = 0.15;
+    const SILENCE_RMS: f32 = 0.01;
+
+    pub fn new(sample_rate: f32) -> Self {
+        let decimation = (sample_rate / Self::TARGET_RATE).round().max(1.0) as usize;
+        let rate = sample_rate / decimation as f32;
+        let max_lag = (rate / Self::MIN_HZ) as usize;
+        let len = (Self::WINDOW_S * rate) as usize + max_lag + 2;
+        Self {
+            decimation,
+            rate,
+            sum: 0.0,
+            summed: 0,
+            ring: vec![0.0; len],
+            pos: 0,
+            hop_samples: (Self::HOP_S * rate) as usize,
+            since_hop: 0,
+            cmnd: vec![1.0; max_lag + 2],
+            pitch: None
+        }
+    }
+
+    // process a sample
+    pub fn process(&mut self, sample: f32) {
+        self.sum += sample;
+        self.summed += 1;
+        if self.summed < self.decimation {
+            return;
+        }
+        self.ring[self.pos] = self.sum / self.decimation as f32;
+        self.pos = (self.pos + 1) % self.ring.len();
+        self.sum = 0.0;
+        self.summed = 0;
+
+        self.since_hop += 1;
+        if self.since_hop >= self.hop_samples {
+            self.since_hop = 0;
+            self.pitch = self.estimate();
+        }
+    }
+
+    // latest estimate in Hz, None while unvoiced
+    pub fn pitch(&self) -> Option<f32> {
+        self.pitch
+    }
+
+    fn estimate(&mut self) -> Option<f32> {
+        let (ring, pos) = (&self.ring, self.pos);
+        let at = |i: usize| ring[(pos + i) % ring.len()]; // oldest first
+        let window = (Self::WINDOW_S * self.rate) as usize;
+        let min_lag = (self.rate / Self::MAX_HZ) as usize;
+        let max_lag = (self.rate / Self::MIN_HZ) as usize;
+
+        let energy = (0..window).map(|i| at(i) * at(i)).sum::<f32>() / window as f32;
+        if energy < Self::SILENCE_RMS * Self::SILENCE_RMS {
+            return None;
+        }
+
+        // difference function, normalized by its running mean
+        let cmnd = &mut self.cmnd;
+        let mut running = 0.0;
+        for (lag, value) in cmnd.iter_mut().enumerate().skip(1) {
+            let diff: f32 = (0..window).map(|i| (at(i) - at(i + lag)).powi(2)).sum();
+            running += diff;
+            *value = if running > 0.0 { diff * lag as f32 / running } else { 1.0 };
+        }
+
+        let mut lag = min_lag;
+        while lag <= max_lag {
+            if cmnd[lag] < Self::THRESHOLD {
+                // walk down to the bottom of this dip
+                while lag < max_lag && cmnd[lag + 1] < cmnd[lag] {
+                    lag += 1;
+                }
+                let (l, c, r) = (cmnd[lag - 1], cmnd[lag], cmnd[lag + 1]);
+                let denom = l - 2.0 * c + r;
+                let offset = if denom.abs() > 1e-9 { (0.5 * (l - r) / denom).clamp(-0.5, 0.5) } else { 0.0 };
+                return Some(self.rate / (lag as f32 + offset));
+            }
+            lag += 1;
+        }
+        None
+    }
+}
+
+
+// analysis-rate samples per block. every stage runs over a whole block before the next one,
+// ~2.7 ms at ANALYSIS_RATE
+pub const BLOCK: usize = 64;
+
 // multi-channel vocoder (mel-spaced frequency bands)
 pub struct VocoderDSP {
-    channels: Vec<VocoderChannel>,
-    sample_rate: f32,
-    peak_values: Vec<f32>,
-    energies: Vec<f32> // smoothed output energies (0-1)
+    filters: FilterBank, // one channel per band, or the sub-bands of Filterbank::Mel
+    followers: EnvelopeBank,
+    block: Vec<f32>,  // resampled input waiting for a full block
+    levels: Vec<f32>, // filter outputs then envelope levels of a block, interleaved per channel
+    filterbank: Filterbank,
+    center_freqs: Vec<f32>, // per band
+    envelopes: Vec<f32>,    // per band, after the filterbank weighting
+    resampler: Resampler,   // input rate -> ANALYSIS_RATE
+    peaks: Box<BandHistory<AGC_HISTORY>>, // loudest envelope of every block, the agc normalizes to their rolling max
+    energies: Vec<f32>, // smoothed output energies (0-1)
+    band_gains: Vec<f32>, // trim + tilt per channel, from settings
+    plosive: PlosiveDetector,
+    plosive_seen: bool, // latched until read, so short pops inside a buffer aren't missed
+    formants: FormantTracker,
+    loudness: LoudnessMeter,
+    pitch: PitchDetector,
+    voice: VoiceActivityDetector,
+    settings: DspSettings,
+    analyzed: u64 // samples run through the analysis so far, at ANALYSIS_RATE
+}
+
+// output band centers and the filters analyzing them, a Mel band is three sub-band filters
+fn layout(num_channels: usize, vocoder: &VocoderConfig) -> (Vec<f32>, Vec<VocoderChannel>) {
+    let sample_rate = ANALYSIS_RATE;
+    let start_mel = mel(vocoder.start_freq);
+    let end_mel = mel(vocoder.end_freq);
+    let mel_step = (end_mel - start_mel) / ((num_channels - 1) as f32);
+
+    // calculate channel frequencies on mel scale
+    let center_freqs: Vec<f32> = (0..num_channels)
+        .map(|i| mel_to_freq(start_mel + mel_step * i as f32))
+        .collect();
+
+    let channels: Vec<VocoderChannel> = match vocoder.filterbank {
+        Filterbank::Narrow => {
+            // bandwidth parameter (from Stanford ECE Vocoder github)
+            let bandwidth_param = 0.035;
+            center_freqs
+                .iter()
+                .map(|&freq| {
+                    let low = freq * (1.0 - bandwidth_param);
+                    let high = freq * (1.0 + bandwidth_param);
+                    VocoderChannel::new(low, high, sample_rate)
+                })
+                .collect()
+        }
+        Filterbank::Mel => {
+            // half a band apart from half a band below the first center to half a band
+            // above the last, each a quarter band either side so they meet edge to edge
+            (0..2 * num_channels + 1)
+                .map(|j| {
+                    let m = start_mel + mel_step * (j as f32 - 1.0) / 2.0;
+                    let (low, high) = (mel_to_freq(m - mel_step / 4.0), mel_to_freq(m + mel_step / 4.0));
+                    VocoderChannel::new(low.max(1.0), high.min(sample_rate * 0.45), sample_rate)
+                })
+                .collect()
+        }
+    };
+    (center_freqs, channels)
+}
+
+// the channel table, printed once at startup rather than for every analyzer a source builds
+pub fn print_channels(num_channels: usize, vocoder: &VocoderConfig) {
+    let (center_freqs, channels) = layout(num_channels, vocoder);
+    println!("Using {} vocoder channels ({} filterbank, analyzed at {} Hz):",
+             num_channels, vocoder.filterbank.id(), ANALYSIS_RATE);
+    // the output channels, a Mel band spans its three sub-bands
+    for (i, center) in center_freqs.iter().enumerate() {
+        let (low, high) = match vocoder.filterbank {
+            Filterbank::Narrow => (&channels[i], &channels[i]),
+            Filterbank::Mel => (&channels[2 * i], &channels[2 * i + 2]),
+        };
+        println!("  Channel {}: {:.1} Hz ({:.1} - {:.1})", i, center, low.low_freq, high.high_freq);
+    }
 }
 
 impl VocoderDSP {
     // vocoder DSP
-    // - num_channels: number of frequency bands (8-16 for girlvoice)
-    // - start_freq: lowest frequency band center (Hz)
-    // - end_freq: highest frequency band center (Hz)
-    // - sample_rate: audio sample rate (Hz)
-
-    pub fn new(num_channels: usize, start_freq: f32, end_freq: f32, sample_rate: f32) -> Self {
-        let start_mel = mel(start_freq);
-        let end_mel = mel(end_freq);
-        
-        // calculate channel frequencies on mel scale
-        let channel_mels: Vec<f32> = (0..num_channels)
-            .map(|i| start_mel + (end_mel - start_mel) * (i as f32) / ((num_channels - 1) as f32))
-            .collect();
-        
-        let channel_freqs: Vec<f32> = channel_mels.iter().map(|&m| mel_to_freq(m)).collect();
-        
-        // bandwidth parameter (from Stanford ECE Vocoder github)
-        let bandwidth_param = 0.035;
-        
-        let channels: Vec<VocoderChannel> = channel_freqs
-            .iter()
-            .map(|&freq| {
-                let low = freq * (1.0 - bandwidth_param);
-                let high = freq * (1.0 + bandwidth_param);
-                VocoderChannel::new(low, high, sample_rate)
-            })
-            .collect();
+    // - num_channels: number of frequency bands (8-16 for girlvoice), checked by the caller
+    // - vocoder: center frequency range (Hz) and filterbank, its num_channels is ignored
+    // - input_rate: rate (Hz) of the samples passed to process, resampled to ANALYSIS_RATE
 
-        println!("Using {} vocoder channels:", num_channels);
-        for (i, ch) in channels.iter().enumerate() {
-            println!("  Channel {}: {:.1} Hz ({:.1} - {:.1})", 
-                     i, ch.center_freq, ch.low_freq, ch.high_freq);
-        }
+    pub fn new(num_channels: usize, vocoder: &VocoderConfig, input_rate: f32) -> Self {
+        let sample_rate = ANALYSIS_RATE;
+        let (center_freqs, channels) = layout(num_channels, vocoder);
 
         Self {
-            peak_values: vec![1.0; num_channels],
+            filters: FilterBank::new(channels.iter().map(|ch| &ch.bandpass)),
+            followers: EnvelopeBank::new(channels.iter().map(|ch| &ch.envelope)),
+            block: Vec::with_capacity(BLOCK),
+            levels: vec![0.0; BLOCK * channels.len()],
+            filterbank: vocoder.filterbank,
+            center_freqs,
+            envelopes: vec![0.0; num_channels],
+            peaks: {
+                let mut peaks = Box::new(BandHistory::new(num_channels));
+                peaks.set_window(agc_window(DspSettings::default().agc_release));
+                peaks
+            },
             energies: vec![0.0; num_channels],
-            channels,
-            sample_rate
+            band_gains: vec![1.0; num_channels],
+            resampler: Resampler::new(input_rate, sample_rate),
+            plosive: PlosiveDetector::new(sample_rate),
+            plosive_seen: false,
+            formants: FormantTracker::new(sample_rate),
+            loudness: LoudnessMeter::new(sample_rate),
+            pitch: PitchDetector::new(sample_rate),
+            voice: VoiceActivityDetector::new(sample_rate),
+            settings: DspSettings::default(),
+            analyzed: 0
         }
     }
 
-    // process a sample. returns a slice of normalized energies (0-1) for each channel
-    pub fn process(&mut self, sample: f32) -> &[f32] {
-        for (i, channel) in self.channels.iter_mut().enumerate() {
-            let envelope = channel.process(sample);
-            
-            if envelope > self.peak_values[i] {
-                self.peak_values[i] = envelope;
-            } else {
-                // slow decay
-                self.peak_values[i] *= 0.9999;
-                self.peak_values[i] = self.peak_values[i].max(0.001);
-            }
-            
-            self.energies[i] = (envelope / self.peak_values[i]).clamp(0.0, 1.0);
+    pub fn set_settings(&mut self, settings: &DspSettings) {
+        self.settings = *settings;
+        self.peaks.set_window(agc_window(settings.agc_release));
+        for (i, (gain, &center_freq)) in self.band_gains.iter_mut().zip(&self.center_freqs).enumerate() {
+            *gain = settings.band_gain(i, center_freq);
         }
-        
+        let last_band = self.envelopes.len() - 1;
+        for j in 0..self.followers.lanes() {
+            // mel sub-bands follow the band they are centered on, or the one below them
+            let band = match self.filterbank {
+                Filterbank::Narrow => j,
+                Filterbank::Mel => (j.max(1) - 1) / 2,
+            };
+            self.followers.set_detector(j, settings.detector[band.min(last_band)]);
+        }
+    }
+
+    // process a sample at the input rate. returns a slice of normalized energies (0-1) for each
+    // channel. samples are analyzed a block at a time, so the energies can trail by up to a block
+    pub fn process(&mut self, sample: f32) -> &[f32] {
+        self.push(sample);
         &self.energies
     }
 
-    // process a buffer of samples and return energies
+    // process a buffer of samples at the input rate and return energies, up to date with the
+    // whole buffer
     pub fn process_buffer(&mut self, samples: &[f32]) -> &[f32] {
         for &sample in samples {
-            self.process(sample);
+            self.push(sample);
+        }
+        if !self.block.is_empty() {
+            self.run_block();
         }
         &self.energies
     }
 
+    fn push(&mut self, sample: f32) {
+        if self.resampler.is_bypass() {
+            self.block.push(sample);
+        } else {
+            self.resampler.push(sample);
+            while let Some(resampled) = self.resampler.pop() {
+                self.block.push(resampled);
+            }
+        }
+        if self.block.len() >= BLOCK {
+            self.run_block();
+        }
+    }
+
+    // analyze the pending samples at ANALYSIS_RATE, stage by stage
+    fn run_block(&mut self) {
+        let block = &self.block[..];
+        for &sample in block {
+            self.plosive_seen |= self.plosive.process(sample);
+            self.formants.process(sample);
+            self.loudness.process(sample);
+            self.pitch.process(sample);
+            self.voice.process(sample);
+        }
+
+        let lanes = self.filters.lanes();
+        let levels = &mut self.levels[..block.len() * lanes];
+        self.filters.process_block(block, levels);
+        self.followers.process_block(levels);
+
+        // bands are independent from here on. the agc needs the loudest envelope of the block,
+        // the energy only the last one
+        let mut block_peaks = [0.0f32; CHANNELS];
+        for (i, (envelope, block_peak)) in self.envelopes.iter_mut().zip(&mut block_peaks).enumerate() {
+            for frame in levels.chunks_exact(lanes) {
+                *envelope = match self.filterbank {
+                    Filterbank::Narrow => frame[i],
+                    // sub-band 2i+1 sits on center i, its neighbours halfway to the next centers
+                    Filterbank::Mel => (0.5 * frame[2 * i] + frame[2 * i + 1] + 0.5 * frame[2 * i + 2]) / 2.0,
+                };
+                *block_peak = block_peak.max(*envelope);
+            }
+        }
+        self.peaks.push(&block_peaks);
+
+        for (i, &envelope) in self.envelopes.iter().enumerate() {
+            // noise floor and eq between envelope and normalization. the agc peak follows the
+            // untrimmed envelope, otherwise it would cancel the trim right away
+            let floor = self.settings.floor.get(i).copied().unwrap_or(0.0);
+            let trimmed = (envelope - floor).max(0.0) * self.band_gains[i];
+            let energy = if self.settings.agc {
+                trimmed / self.peaks.max(i).unwrap_or(0.0).max(0.001)
+            } else {
+                trimmed * self.settings.gain
+            };
+            self.energies[i] = self.settings.apply_gate(energy.clamp(0.0, 1.0));
+        }
+
+        self.analyzed += block.len() as u64;
+        self.block.clear();
+    }
+
     pub fn num_channels(&self) -> usize {
-        self.channels.len()
+        self.envelopes.len()
+    }
+
+    // analysis clock: samples analyzed so far at ANALYSIS_RATE
+    pub fn analyzed(&self) -> u64 {
+        self.analyzed
     }
 
     pub fn energies(&self) -> &[f32] {
         &self.energies
     }
 
+    // rate the filters and detectors run at
     pub fn sample_rate(&self) -> f32 {
-        self.sample_rate
+        ANALYSIS_RATE
+    }
+
+    // latest F1/F2 estimate, None while unvoiced
+    pub fn formants(&self) -> Option<Formants> {
+        self.formants.formants()
+    }
+
+    // gated LUFS over the last few seconds, None before anything cleared the gate
+    pub fn loudness(&self) -> Option<f32> {
+        self.loudness.loudness()
+    }
+
+    // fundamental in Hz, None while unvoiced
+    pub fn pitch(&self) -> Option<f32> {
+        self.pitch.pitch()
     }
-}
\ No newline at end of file
+
+    // someone is speaking, voiced or not
+    pub fn voice_active(&self) -> bool {
+        self.voice.is_active()
+    }
+
+    // true if a plosive was active at any point since the last call
+    pub fn take_plosive(&mut self) -> bool {
+        let seen = self.plosive_seen || self.plosive.is_active();
+        self.plosive_seen = false;
+        seen
+    }
+}
+
+// cuts the analysis into an AnalysisFrame every HOP samples of the analysis clock, whatever
+// block sizes the audio host delivers, so frames come at a steady known rate everywhere
+pub struct FrameHop {
+    frame: AnalysisFrame,
+    next: u64, // analysis clock at the end of the current hop
+    peak: f32,
+    sum_sq: f32,
+    count: u32,
+}
+
+impl FrameHop {
+    pub fn new(channels: ChannelCount) -> Self {
+        Self { frame: AnalysisFrame::new(channels), next: HOP as u64, peak: 0.0, sum_sq: 0.0, count: 0 }
+    }
+
+    // account one input sample after `dsp` (and `right`, analyzing the second input) processed
+    // it. `level` is the loudest input the sample came from. returns the new frame when this
+    // sample completed a hop, without a waveform, the sources keep their own
+    pub fn push(&mut self, sample: f32, level: f32, dsp: &mut VocoderDSP, right: Option<&VocoderDSP>) -> Option<&AnalysisFrame> {
+        self.peak = self.peak.max(level);
+        self.sum_sq += sample * sample;
+        self.count += 1;
+        if dsp.analyzed() < self.next {
+            return None;
+        }
+        self.next += HOP as u64;
+
+        let frame = &mut self.frame;
+        frame.set_bands(dsp.energies());
+        frame.right = right.map(|right| {
+            let mut bands = [0.0; CHANNELS];
+            let n = right.num_channels().min(CHANNELS);
+            bands[..n].copy_from_slice(&right.energies()[..n]);
+            bands
+        });
+        frame.peak = frame.peak * 0.9 + self.peak * 0.1; // moving avg
+        frame.rms = (self.sum_sq / self.count.max(1) as f32).sqrt();
+        frame.voiced = dsp.voice_active();
+        frame.plosive = dsp.take_plosive();
+        frame.formants = dsp.formants();
+        frame.loudness = dsp.loudness();
+        frame.pitch = dsp.pitch();
+        frame.clip = self.peak >= CLIP_LEVEL;
+        frame.time = dsp.analyzed() as f64 / ANALYSIS_RATE as f64;
+
+        self.peak = 0.0;
+        self.sum_sq = 0.0;
+        self.count = 0;
+        Some(&self.frame)
+    }
+}
@@ -0,0 +1,68 @@
+// what can go wrong setting up the simulator, worded so the message says what to try next.
+// core's UiError covers the ui itself, this adds the host side: audio devices, files, the
+// serial port and the window
+
+use std::fmt;
+use std::path::PathBuf;
+
+use girlvoice_ui_core::UiError;
+
+#[derive(Debug)]
+pub enum GirlvoiceError {
+    NoInputDevice,
+    NoOutputDevice,
+    NoMatchingDevice { wanted: String, available: Vec<String> },
+    DeviceConfig(String), // the device didn't report a usable stream config
+    UnsupportedFormat(String),
+    Stream(String), // building or starting the capture stream failed
+    Playback { path: PathBuf, reason: String }, // --source wav:<path>
+    Telemetry { path: PathBuf, reason: String }, // --source serial:<port>
+    Window(String),
+    Ui(UiError),
+}
+
+impl fmt::Display for GirlvoiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GirlvoiceError::NoInputDevice => {
+                write!(f, "no input device, plug in a microphone or run with --source synth:speech")
+            }
+            GirlvoiceError::NoOutputDevice => write!(f, "no output device to capture"),
+            GirlvoiceError::NoMatchingDevice { wanted, available } => write!(
+                f,
+                "no capture device matching '{}' for the output (available: {})",
+                wanted,
+                available.join(", ")
+            ),
+            GirlvoiceError::DeviceConfig(e) => write!(f, "the audio device has no usable config ({})", e),
+            GirlvoiceError::UnsupportedFormat(format) => {
+                write!(f, "the audio device delivers {} samples, which the simulator can't read", format)
+            }
+            GirlvoiceError::Stream(e) => {
+                write!(f, "can't start audio capture ({}), is another program holding the device?", e)
+            }
+            GirlvoiceError::Playback { path, reason } => write!(f, "can't play {} ({})", path.display(), reason),
+            GirlvoiceError::Telemetry { path, reason } => {
+                write!(f, "can't read telemetry from {} ({}), is the badge plugged in?", path.display(), reason)
+            }
+            GirlvoiceError::Window(e) => {
+                write!(f, "can't open the simulator window ({}), --soak runs without one", e)
+            }
+            GirlvoiceError::Ui(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for GirlvoiceError {}
+
+impl From<UiError> for GirlvoiceError {
+    fn from(e: UiError) -> Self {
+        GirlvoiceError::Ui(e)
+    }
+}
+
+impl From<minifb::Error> for GirlvoiceError {
+    fn from(e: minifb::Error) -> Self {
+        GirlvoiceError::Window(e.to_string())
+    }
+}
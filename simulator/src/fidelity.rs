@@ -0,0 +1,110 @@
+// --fidelity: show what the GC9A01 would rather than what the desktop can. frames leave through
+// the same RGB565 flush as on the badge and come back with the panel's bit expansion, and the
+// frame rate is capped at what the SPI link and the panel's own refresh can carry. --tearing
+// also models scan-out: the panel refreshes on its own clock with no sync to the writes, so a
+// refresh that overlaps a transfer shows the new frame above or below the point where the two
+// cross and the previous frame on the other side
+
+use std::time::{Duration, Instant};
+
+use girlvoice_ui_core::{Color, Framebuffer, PostChain, DISPLAY_SIZE, FRAMEBUFFER_LEN};
+
+use crate::panel::PanelLink;
+
+pub struct Fidelity {
+    rgb565: Vec<u16>,
+    refresh: Duration, // one panel scan, top to bottom
+    tearing: Option<ScanOut>,
+}
+
+struct ScanOut {
+    epoch: Instant,     // refreshes start at whole periods from here
+    previous: Vec<u32>, // what the panel holds before the transfer lands
+    shown: Vec<u32>,
+    frames: u32,
+    torn: u32,
+}
+
+impl Fidelity {
+    pub const DEFAULT_REFRESH_HZ: f32 = 60.0;
+
+    pub fn new(refresh_hz: f32, tearing: bool) -> Self {
+        let tearing = tearing.then(|| ScanOut {
+            epoch: Instant::now(),
+            previous: vec![0; FRAMEBUFFER_LEN],
+            shown: vec![0; FRAMEBUFFER_LEN],
+            frames: 0,
+            torn: 0,
+        });
+        Self { rgb565: vec![0; FRAMEBUFFER_LEN], refresh: Duration::from_secs_f32(1.0 / refresh_hz), tearing }
+    }
+
+    // the fastest the panel can show new frames: one per transfer, at most one per refresh
+    pub fn max_fps(&self, panel: &PanelLink) -> u32 {
+        let frame = panel.transfer_time().max(self.refresh);
+        (1.0 / frame.as_secs_f64()).floor().max(1.0) as u32
+    }
+
+    pub fn cap_fps(&self, panel: &PanelLink, fps: u32) -> u32 {
+        fps.min(self.max_fps(panel))
+    }
+
+    // the post chain's flush, through the panel's pixel format and back
+    pub fn flush(&mut self, post: &mut PostChain, fb: &Framebuffer, out: &mut [u32]) {
+        post.write_rgb565(fb, &mut self.rgb565).expect("rgb565 buffer is display sized");
+        for (o, &p) in out.iter_mut().zip(&self.rgb565) {
+            *o = Color::from_rgb565(p).to_argb32();
+        }
+    }
+
+    // what the window should show for the frame that just went out through `panel`
+    pub fn panel_view<'a>(&'a mut self, panel: &PanelLink, front: &'a [u32]) -> &'a [u32] {
+        let refresh = self.refresh;
+        let (Some(scan), Some(start)) = (&mut self.tearing, panel.transfer_start()) else {
+            return front;
+        };
+        if scan.compose(start, panel.transfer_time(), refresh, front) {
+            scan.torn += 1;
+        }
+        scan.frames += 1;
+        scan.previous.copy_from_slice(front);
+        &scan.shown
+    }
+
+    pub fn report(&self) {
+        if let Some(scan) = &self.tearing {
+            println!(
+                "Tearing: {} of {} frame(s) reached the panel mid-refresh ({:.0} Hz)",
+                scan.torn,
+                scan.frames,
+                1.0 / self.refresh.as_secs_f32()
+            );
+        }
+    }
+}
+
+impl ScanOut {
+    // the first refresh that shows any of the new frame. row y is scanned at a + y * refresh / H
+    // and written at y * transfer / H, both from the start of the transfer, so it shows the new
+    // frame once a >= y * (transfer - refresh) / H. true when the refresh mixes both frames
+    fn compose(&mut self, start: Instant, transfer: Duration, refresh: Duration, front: &[u32]) -> bool {
+        let (transfer, refresh) = (transfer.as_secs_f64(), refresh.as_secs_f64());
+        let since_epoch = start.saturating_duration_since(self.epoch).as_secs_f64();
+        // the refresh in progress when the transfer starts, 0 or negative
+        let mut a = -(since_epoch % refresh);
+        let rows = DISPLAY_SIZE as f64;
+        let is_new = |a: f64, y: usize| a >= y as f64 * (transfer - refresh) / rows;
+        while !(0..DISPLAY_SIZE).any(|y| is_new(a, y)) {
+            a += refresh;
+        }
+
+        let mut mixed = false;
+        for y in 0..DISPLAY_SIZE {
+            let row = y * DISPLAY_SIZE..(y + 1) * DISPLAY_SIZE;
+            let source = if is_new(a, y) { front } else { &self.previous };
+            mixed |= source[row.clone()] != front[row.clone()];
+            self.shown[row.clone()].copy_from_slice(&source[row]);
+        }
+        mixed
+    }
+}
@@ -0,0 +1,243 @@
+// animated gif writer for the capture hotkey. every frame gets its own 256 color table, picked
+// by median cut over the frame's colors at 5 bits per channel, so a palette fade or a hue
+// cycle doesn't band against a table fitted to its first frame
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+const BIN_BITS: u32 = 5;
+const BINS: usize = 1 << (3 * BIN_BITS);
+const TABLE_SIZE: usize = 256;
+const MAX_CODE: u16 = 4095; // 12 bit lzw codes
+
+pub struct GifWriter<W: Write> {
+    out: W,
+    width: usize,
+    height: usize,
+    quantizer: Quantizer,
+    indices: Vec<u8>,
+    data: Vec<u8>,
+}
+
+impl<W: Write> GifWriter<W> {
+    // loops forever once played
+    pub fn new(mut out: W, width: usize, height: usize) -> io::Result<Self> {
+        out.write_all(b"GIF89a")?;
+        out.write_all(&(width as u16).to_le_bytes())?;
+        out.write_all(&(height as u16).to_le_bytes())?;
+        out.write_all(&[0x70, 0, 0])?; // no global table, 8 bits of color resolution
+        out.write_all(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00")?;
+        Ok(Self { out, width, height, quantizer: Quantizer::new(), indices: Vec::new(), data: Vec::new() })
+    }
+
+    // `pixels` are argb32 (alpha ignored), row-major, shown for `delay_cs` hundredths of a second
+    pub fn frame(&mut self, pixels: &[u32], delay_cs: u16) -> io::Result<()> {
+        let table = self.quantizer.quantize(pixels, &mut self.indices);
+        self.data.clear();
+        lzw_encode(&self.indices, &mut self.data);
+
+        let delay = delay_cs.to_le_bytes();
+        self.out.write_all(&[0x21, 0xf9, 4, 0, delay[0], delay[1], 0, 0])?;
+        self.out.write_all(&[0x2c, 0, 0, 0, 0])?;
+        self.out.write_all(&(self.width as u16).to_le_bytes())?;
+        self.out.write_all(&(self.height as u16).to_le_bytes())?;
+        self.out.write_all(&[0x87])?; // local table of 2^(7+1) entries
+        for color in table {
+            self.out.write_all(color)?;
+        }
+        self.out.write_all(&[8])?; // lzw minimum code size
+        for block in self.data.chunks(255) {
+            self.out.write_all(&[block.len() as u8])?;
+            self.out.write_all(block)?;
+        }
+        self.out.write_all(&[0])
+    }
+
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.write_all(&[0x3b])?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+// pixel counts and color sums per 5-5-5 bin, reused from frame to frame
+struct Quantizer {
+    counts: Vec<u32>,
+    sums: Vec<[u32; 3]>,
+    index: Vec<u8>, // table entry of each bin
+    used: Vec<u16>, // bins with pixels, grouped box by box while splitting
+    table: [[u8; 3]; TABLE_SIZE],
+}
+
+// a run of `used` that becomes one table entry
+#[derive(Clone, Copy)]
+struct ColorBox {
+    start: usize,
+    end: usize,
+    widest: usize, // channel with the largest spread
+    spread: u16,
+}
+
+impl Quantizer {
+    fn new() -> Self {
+        Self {
+            counts: vec![0; BINS],
+            sums: vec![[0; 3]; BINS],
+            index: vec![0; BINS],
+            used: Vec::new(),
+            table: [[0; 3]; TABLE_SIZE],
+        }
+    }
+
+    fn quantize(&mut self, pixels: &[u32], indices: &mut Vec<u8>) -> &[[u8; 3]; TABLE_SIZE] {
+        self.counts.fill(0);
+        self.used.clear();
+        for &pixel in pixels {
+            let bin = bin(pixel);
+            if self.counts[bin] == 0 {
+                self.sums[bin] = [0; 3];
+                self.used.push(bin as u16);
+            }
+            self.counts[bin] += 1;
+            let [_, r, g, b] = pixel.to_be_bytes();
+            for (sum, channel) in self.sums[bin].iter_mut().zip([r, g, b]) {
+                *sum += channel as u32;
+            }
+        }
+
+        let mut boxes = vec![self.color_box(0, self.used.len())];
+        while boxes.len() < TABLE_SIZE {
+            let Some((i, _)) = boxes.iter().enumerate().filter(|(_, b)| b.spread > 0).max_by_key(|(_, b)| b.spread) else {
+                break;
+            };
+            let split = boxes[i];
+            let mid = self.split(split);
+            boxes[i] = self.color_box(split.start, mid);
+            boxes.push(self.color_box(mid, split.end));
+        }
+
+        self.table = [[0; 3]; TABLE_SIZE];
+        for (entry, b) in boxes.iter().enumerate() {
+            let mut sum = [0u64; 3];
+            let mut count = 0u64;
+            for &bin in &self.used[b.start..b.end] {
+                let bin = bin as usize;
+                self.index[bin] = entry as u8;
+                count += self.counts[bin] as u64;
+                for (s, &channel) in sum.iter_mut().zip(&self.sums[bin]) {
+                    *s += channel as u64;
+                }
+            }
+            self.table[entry] = sum.map(|s| (s / count.max(1)) as u8);
+        }
+
+        indices.clear();
+        indices.extend(pixels.iter().map(|&pixel| self.index[bin(pixel)]));
+        &self.table
+    }
+
+    fn color_box(&self, start: usize, end: usize) -> ColorBox {
+        let mut low = [u16::MAX; 3];
+        let mut high = [0u16; 3];
+        for &bin in &self.used[start..end] {
+            for channel in 0..3 {
+                let value = channel_of(bin, channel);
+                low[channel] = low[channel].min(value);
+                high[channel] = high[channel].max(value);
+            }
+        }
+        let widest = (0..3).max_by_key(|&c| high[c].saturating_sub(low[c])).unwrap_or(0);
+        ColorBox { start, end, widest, spread: high[widest].saturating_sub(low[widest]) }
+    }
+
+    // sorts the box along its widest channel and cuts it where half its pixels are on each side
+    fn split(&mut self, b: ColorBox) -> usize {
+        let bins = &mut self.used[b.start..b.end];
+        bins.sort_unstable_by_key(|&bin| channel_of(bin, b.widest));
+        let total: u64 = bins.iter().map(|&bin| self.counts[bin as usize] as u64).sum();
+        let mut below = 0;
+        let mut mid = 1;
+        for (i, &bin) in bins.iter().enumerate() {
+            below += self.counts[bin as usize] as u64;
+            if below * 2 >= total {
+                mid = i + 1;
+                break;
+            }
+        }
+        b.start + mid.clamp(1, bins.len() - 1)
+    }
+}
+
+fn bin(pixel: u32) -> usize {
+    let [_, r, g, b] = pixel.to_be_bytes();
+    let shift = 8 - BIN_BITS;
+    ((r as usize >> shift) << (2 * BIN_BITS)) | ((g as usize >> shift) << BIN_BITS) | (b as usize >> shift)
+}
+
+fn channel_of(bin: u16, channel: usize) -> u16 {
+    (bin >> ((2 - channel) as u32 * BIN_BITS)) & ((1 << BIN_BITS) - 1)
+}
+
+// variable width lzw over 8-bit indices, codes packed lsb-first. the table starts over with a
+// clear code once it reaches 12 bits
+fn lzw_encode(indices: &[u8], out: &mut Vec<u8>) {
+    const CLEAR: u16 = 256;
+    const END: u16 = 257;
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut acc = 0u32;
+    let mut count = 0u32;
+    let mut width = 9;
+    let mut next = END; // last code in use
+    let mut emit = |code: u16, width: u32| {
+        acc |= (code as u32) << count;
+        count += width;
+        while count >= 8 {
+            out.push(acc as u8);
+            acc >>= 8;
+            count -= 8;
+        }
+    };
+
+    emit(CLEAR, width);
+    let Some((&first, rest)) = indices.split_first() else {
+        emit(END, width);
+        out.push(acc as u8);
+        return;
+    };
+    let mut prefix = first as u16;
+    for &index in rest {
+        if let Some(&code) = table.get(&(prefix, index)) {
+            prefix = code;
+            continue;
+        }
+        emit(prefix, width);
+        let key = (prefix, index);
+        prefix = index as u16;
+        // the decoder adds an entry per code it reads and widens at the same points
+        next += 1;
+        if next == 1 << width {
+            width += 1;
+        }
+        if next == MAX_CODE {
+            emit(CLEAR, width);
+            table.clear();
+            width = 9;
+            next = END;
+        } else {
+            table.insert(key, next);
+        }
+    }
+    emit(prefix, width);
+    next += 1;
+    if next == 1 << width {
+        width += 1;
+    }
+    if next == MAX_CODE {
+        emit(CLEAR, width);
+        width = 9;
+    }
+    emit(END, width);
+    if count > 0 {
+        out.push(acc as u8);
+    }
+}
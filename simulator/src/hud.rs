@@ -0,0 +1,127 @@
+// frame pacing and the --hud performance overlay. the pacer sleeps to a fixed frame deadline
+// itself instead of relying on minifb's set_target_fps, so the render rate is known and missed
+// deadlines can be counted.
+
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+use girlvoice_ui_core::draw::rounded_rect;
+use girlvoice_ui_core::{palette, Color, Framebuffer, TextBuf, FONT_5X7, draw_text_with_font};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    Poll,   // source poll, mostly waiting on the DSP lock
+    Update, // visualizer and overlays
+    Render, // framebuffer drawing and post effects
+    Blit,   // panel swap (waiting out the last transfer), scaling, handing over to the window
+}
+
+impl Phase {
+    const COUNT: usize = 4;
+
+    fn label(&self) -> &'static str {
+        match self {
+            Phase::Poll => "poll",
+            Phase::Update => "upd",
+            Phase::Render => "rend",
+            Phase::Blit => "blit",
+        }
+    }
+}
+
+pub struct Pacer {
+    period: Duration,
+    deadline: Instant,
+}
+
+impl Pacer {
+    pub fn new(fps: u32) -> Self {
+        Self { period: Duration::from_secs_f64(1.0 / fps as f64), deadline: Instant::now() }
+    }
+
+    // takes effect from the next deadline on
+    pub fn set_fps(&mut self, fps: u32) {
+        self.period = Duration::from_secs_f64(1.0 / fps.max(1) as f64);
+    }
+
+    // sleep until the next frame is due, returns how many deadlines were missed since the last one
+    pub fn wait(&mut self) -> u32 {
+        self.deadline += self.period;
+        let now = Instant::now();
+        if now < self.deadline {
+            std::thread::sleep(self.deadline - now);
+            return 0;
+        }
+        // late: skip the missed slots instead of rushing to catch up
+        let missed = ((now - self.deadline).as_secs_f64() / self.period.as_secs_f64()) as u32;
+        self.deadline += self.period * missed;
+        missed
+    }
+}
+
+const HUD_BACKGROUND: Color = Color::new(0x10, 0x10, 0x18);
+
+pub struct Hud {
+    phase_ms: [f32; Phase::COUNT], // smoothed per-phase time
+    frame_ms: f32,
+    fps: f32,
+    frames: u32,
+    window_start: Instant,
+    dropped: u64,
+}
+
+impl Hud {
+    const SMOOTHING: f32 = 0.1;
+
+    pub fn new() -> Self {
+        Self {
+            phase_ms: [0.0; Phase::COUNT],
+            frame_ms: 0.0,
+            fps: 0.0,
+            frames: 0,
+            window_start: Instant::now(),
+            dropped: 0,
+        }
+    }
+
+    pub fn record(&mut self, phase: Phase, elapsed: Duration) {
+        let ms = &mut self.phase_ms[phase as usize];
+        *ms += (elapsed.as_secs_f32() * 1000.0 - *ms) * Self::SMOOTHING;
+    }
+
+    // call once per frame with the frame's start, fps is counted over one second windows
+    pub fn frame_done(&mut self, frame_start: Instant, dropped: u32) {
+        let now = Instant::now();
+        self.frame_ms += ((now - frame_start).as_secs_f32() * 1000.0 - self.frame_ms) * Self::SMOOTHING;
+        self.dropped += dropped as u64;
+        self.frames += 1;
+        let window = (now - self.window_start).as_secs_f32();
+        if window >= 1.0 {
+            self.fps = self.frames as f32 / window;
+            self.frames = 0;
+            self.window_start = now;
+        }
+    }
+
+    pub fn draw(&self, fb: &mut Framebuffer) {
+        let line_height = FONT_5X7.height as i32 + 3;
+        let (x, y) = (54, 52);
+        rounded_rect(fb, x as f32 - 4.0, y as f32 - 4.0, 140.0, (line_height * 4 + 5) as f32, 3.0, HUD_BACKGROUND);
+
+        let mut line = TextBuf::<32>::new();
+        let _ = write!(line, "{:5.1} fps  {} dropped", self.fps, self.dropped);
+        draw_text_with_font(fb, &FONT_5X7, x, y, line.as_str(), palette::WHITE);
+        line.clear();
+        let _ = write!(line, "frame {:6.2} ms", self.frame_ms);
+        draw_text_with_font(fb, &FONT_5X7, x, y + line_height, line.as_str(), palette::WHITE);
+
+        for (row, phases) in [[Phase::Poll, Phase::Update], [Phase::Render, Phase::Blit]].iter().enumerate() {
+            line.clear();
+            for phase in phases {
+                let _ = write!(line, "{:<5}{:5.2} ", phase.label(), self.phase_ms[*phase as usize]);
+            }
+            let y = y + line_height * (row as i32 + 2);
+            draw_text_with_font(fb, &FONT_5X7, x, y, line.as_str(), palette::WHITE.scale(0.8));
+        }
+    }
+}
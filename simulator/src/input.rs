@@ -0,0 +1,54 @@
+// keyboard and mouse -> core InputEvents, standing in for the badge's buttons and encoder.
+// button keys go through the same PressDetector as the hardware, so holding space for
+// 600 ms is a long press just like on the badge.
+//   space / enter        select (encoder push)
+//   backspace            back button
+//   arrows, [ ], wheel   encoder
+
+use std::time::Instant;
+
+use minifb::{Key, KeyRepeat, Window};
+
+use girlvoice_ui_core::{Button, InputEvent, PressDetector};
+
+pub struct KeyboardInput {
+    select: PressDetector,
+    back: PressDetector,
+    scroll: f32, // wheel movement not yet worth a detent
+    start: Instant,
+}
+
+impl KeyboardInput {
+    // wheel units per encoder detent, one notch on most mice
+    const SCROLL_PER_STEP: f32 = 1.0;
+
+    pub fn new() -> Self {
+        Self { select: PressDetector::new(), back: PressDetector::new(), scroll: 0.0, start: Instant::now() }
+    }
+
+    // appends this frame's events to `events`
+    pub fn poll(&mut self, window: &Window, events: &mut Vec<InputEvent>) {
+        let now_ms = self.start.elapsed().as_millis() as u32;
+
+        let select = window.is_key_down(Key::Space) || window.is_key_down(Key::Enter);
+        events.extend(self.select.update(Button::Select, select, now_ms));
+        events.extend(self.back.update(Button::Back, window.is_key_down(Key::Backspace), now_ms));
+
+        let mut steps = 0;
+        for key in [Key::Right, Key::Up, Key::RightBracket] {
+            steps += window.is_key_pressed(key, KeyRepeat::Yes) as i32;
+        }
+        for key in [Key::Left, Key::Down, Key::LeftBracket] {
+            steps -= window.is_key_pressed(key, KeyRepeat::Yes) as i32;
+        }
+        if let Some((_, dy)) = window.get_scroll_wheel() {
+            self.scroll += dy / Self::SCROLL_PER_STEP;
+            let whole = self.scroll.trunc();
+            self.scroll -= whole;
+            steps += whole as i32;
+        }
+        if steps != 0 {
+            events.push(InputEvent::Encoder(steps));
+        }
+    }
+}
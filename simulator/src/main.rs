@@ -1,208 +1,666 @@
+#[allow(dead_code)] // full port of the gateware DSP, not every knob is used by the simulator yet
 mod dsp;
-
-use std::sync::{Arc, Mutex};
+mod battery;
+mod capture;
+mod cli;
+mod cobs;
+mod compare;
+mod contrib;
+mod clock;
+mod input;
+mod midi;
+mod osc;
+mod config;
+mod error;
+mod fidelity;
+mod gif;
+mod panel;
+mod hud;
+mod present;
+mod presets;
+mod record;
+mod render;
+mod ring;
+mod soak;
+mod source;
+mod store;
+mod synth;
+mod upscale;
+mod wav;
+
+use std::path::Path;
 use std::time::Instant; // for shader time, would be replaced by timer on MCU
 
-use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-
-use minifb::{Key, Window, WindowOptions, Scale};
-
-use dsp::VocoderDSP;
-
+use minifb::{Key, KeyRepeat, Window, WindowOptions, Scale};
+
+use capture::Capture;
+use cli::{Args, SourceKind};
+use compare::Comparison;
+use clock::SystemClock;
+use config::Config;
+use error::GirlvoiceError;
+use fidelity::Fidelity;
+use hud::{Hud, Pacer, Phase};
+use input::KeyboardInput;
+use midi::MidiController;
+use osc::OscServer;
+use panel::PanelLink;
+use present::PhysicalPreview;
+use record::SessionRecorder;
+use ring::RingView;
+use source::{MicSource, PairedSource, SerialSource, SyntheticSource, WavSource};
+use store::FileStore;
+use synth::Signal;
+use upscale::Upscaler;
+
+use girlvoice_ui_core::store::{load_settings, save_settings};
 use girlvoice_ui_core::{
-    Visualizer, Color, ColorPalette, palette, DISPLAY_SIZE
+    AnalysisFrame, ChannelCount, Visualizer, LedRing, TimeSource, Calibrator, Color, DoubleBuffer, EnergySource, Framebuffer, InputHandler, Menu, MenuEffect, MenuTarget, UiAction, PostChain, Preset, PresetBank, StatusInputs,
+    StatusOverlay, Toasts, Widget,
+    palette, VocoderConfig, DISPLAY_SIZE
 };
 
-const SCALE: usize = 2;
+const DEFAULT_CONFIG: &str = "girlvoice.toml";
+const BRIGHTNESS_STEP: f32 = 0.1;
+const TOAST_S: f32 = 1.5;
+const PRESET_KEYS: [Key; 8] = [Key::F1, Key::F2, Key::F3, Key::F4, Key::F5, Key::F6, Key::F7, Key::F8];
 
-// shared between DSP and main UI thread
-struct SharedState {
-    energies: Vec<f32>,
-    peak_level: f32,
-}
+#[cfg(feature = "soak")]
+#[global_allocator]
+static ALLOCATOR: soak::CountingAlloc = soak::CountingAlloc;
 
-impl SharedState {
-    fn new(num_channels: usize) -> Self {
-        Self {
-            energies: vec![0.0; num_channels],
-            peak_level: 0.0,
-        }
+fn main() {
+    // before anything parses a mode id, the flags included
+    contrib::declare();
+    let args = Args::parse().unwrap_or_else(|e| {
+        eprintln!("error: {}\n", e);
+        cli::print_usage();
+        std::process::exit(1);
+    });
+
+    // a missing default config is fine, an explicitly named one has to exist
+    let config = match &args.config {
+        Some(path) => Config::load(path),
+        None if Path::new(DEFAULT_CONFIG).exists() => Config::load(Path::new(DEFAULT_CONFIG)),
+        None => Ok(Config::default()),
+    }
+    .unwrap_or_else(|e| {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    });
+    if args.dump_config {
+        print!("{}", config.to_toml());
+        return;
     }
-}
 
-fn main() {
     println!("### Girlvoice Vocoder UI Simulator");
     println!();
 
-    // simulator UI
-    let window_size = DISPLAY_SIZE * SCALE;
-    
-    let num_channels = 12;
-    let start_freq = 100.0;
-    let end_freq = 3000.0;
-
-    // audio init
-    let host = cpal::default_host();
-    let device = host.default_input_device().expect("No input device available");
-    println!("Using input device: {}", device.name().unwrap());
+    let channels = config.vocoder.channels().expect("channel count checked when the config was loaded");
 
-    let config = device.default_input_config().expect("No input config available");
-    println!("Audio config: {:?}", config);
+    if let Some(seconds) = args.soak_seconds {
+        let signal = match args.source {
+            SourceKind::Synth(signal) => signal,
+            SourceKind::Mic | SourceKind::Output { .. } | SourceKind::Wav(_) | SourceKind::Serial(_) => Signal::Speech,
+        };
+        let soak_config = soak::SoakConfig { signal, seconds, channels, vocoder: config.vocoder, dsp: config.dsp };
+        std::process::exit(if soak::run(&soak_config) { 0 } else { 1 });
+    }
 
-    let sample_rate = config.sample_rate() as f32;
-    let channels = config.channels() as usize;
+    let mut source = open_source(&args, channels, &config.vocoder);
+    dsp::print_channels(channels.get(), &config.vocoder);
+    let mut dsp = config.dsp;
+    source.set_dsp_settings(&dsp);
 
-    let shared = Arc::new(Mutex::new(SharedState::new(num_channels)));
-    let shared_audio = Arc::clone(&shared);
+    let midi_map = midi::build_map(&config.midi).unwrap_or_else(|e| {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    });
+    let mut midi = args.midi.as_deref().map(|port| {
+        MidiController::open(port).unwrap_or_else(|e| {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        })
+    });
 
-    let analyzer = Arc::new(Mutex::new(VocoderDSP::new(
-        num_channels, start_freq, end_freq, sample_rate,
-    )));
-    let analyzer_audio = Arc::clone(&analyzer);
+    let mut osc = args.osc_port.map(|port| {
+        let server = OscServer::bind(port).unwrap_or_else(|e| {
+            eprintln!("error: can't listen for osc on udp port {}: {}", port, e);
+            std::process::exit(1);
+        });
+        println!("Listening for OSC on udp port {}", port);
+        server
+    });
 
-    // from fft example
-    let audio_callback = move |data: &[f32], _: &cpal::InputCallbackInfo| {
-        let mut analyzer = analyzer_audio.lock().unwrap();
-        let mut shared = shared_audio.lock().unwrap();
-        
-        let mut peak = 0.0f32;
-        for frame in data.chunks(channels) {
-            let sample = if channels > 1 {
-                frame.iter().sum::<f32>() / channels as f32
-            } else {
-                frame[0]
-            };
-            peak = peak.max(sample.abs());
-            analyzer.process(sample);
+    // simulator UI
+    let preview = args.physical_dpi.map(PhysicalPreview::new);
+    let mut upscaler = Upscaler::new(args.scale, args.filter).expect("scale checked by the argument parser");
+    let picture_size = preview.as_ref().map_or(upscaler.size(), |p| p.window_size());
+    let mut ring = args.ring_leds.and_then(LedRing::new);
+    let mut ring_view = ring.as_ref().map(|ring| RingView::new(picture_size, ring.len()));
+    let window_size = ring_view.as_ref().map_or(picture_size, |v| v.size());
+    let mut comparison = args.compare.map(|spec| {
+        println!("Comparing with {} on the right", spec.label());
+        let upscaler = Upscaler::new(args.scale, args.filter).expect("scale checked by the argument parser");
+        let mut comparison = Comparison::new(spec, channels, &config.vocoder, upscaler, args.plosive_guard);
+        if !comparison.set_dsp(&dsp, source.as_mut()) {
+            eprintln!("error: comparing the {} needs a source with its own DSP, serial:<port> has none", spec.label());
+            std::process::exit(1);
         }
-        
-        shared.energies.copy_from_slice(analyzer.energies());
-        shared.peak_level = shared.peak_level * 0.9 + peak * 0.1;
-    };
-
-    let stream = match config.sample_format() {
-        cpal::SampleFormat::F32 => device.build_input_stream(
-            &config.into(),
-            audio_callback,
-            |err| eprintln!("Audio error: {}", err),
-            None
-        ).unwrap(),
-        cpal::SampleFormat::I16 => {
-            let analyzer_audio = Arc::clone(&analyzer);
-            let shared_audio = Arc::clone(&shared);
-            device.build_input_stream(
-                &config.into(),
-                move |data: &[i16], _: &cpal::InputCallbackInfo| {
-                    let mut analyzer = analyzer_audio.lock().unwrap();
-                    let mut shared = shared_audio.lock().unwrap();
-                    let mut peak = 0.0f32;
-                    for frame in data.chunks(channels) {
-                        let sample = if channels > 1 {
-                            frame.iter().map(|&s| s as f32 / 32768.0).sum::<f32>() / channels as f32
-                        } else {
-                            frame[0] as f32 / 32768.0
-                        };
-                        peak = peak.max(sample.abs());
-                        analyzer.process(sample);
-                    }
-                    shared.energies.copy_from_slice(analyzer.energies());
-                    shared.peak_level = shared.peak_level * 0.9 + peak * 0.1; // moving avg
-                },
-                |err| eprintln!("Audio error: {}", err),
-                None
-            ).unwrap()
-        },
-        format => panic!("Unsupported sample format: {:?}", format)
-    };
-
-    stream.play().expect("Audio stream failed");
-    println!("Audio stream started\n");
+        comparison
+    });
+    let (window_width, window_height) = comparison.as_ref().map_or((window_size, window_size), |c| (c.width(), c.height()));
+    if preview.is_none() {
+        println!("Window scale: {}x {}", args.scale, args.filter.name());
+    }
+    // only the physical preview composes into its own buffer
+    let mut preview_buffer = if preview.is_some() { vec![0u32; picture_size * picture_size] } else { Vec::new() };
 
-    
     let mut window = Window::new(
-        "Girlvoice Visualizer - ESC to exit",
-        window_size,
-        window_size,
+        "Girlvoice Visualizer - space: next mode (hold: menu), arrows: brightness, C: calibrate, P: power saver, S: screenshot, G: gif, ESC to exit",
+        window_width,
+        window_height,
         WindowOptions { scale: Scale::X1, ..Default::default() }
     )
     .unwrap_or_else(|e| {
-        panic!("{}", e);
+        eprintln!("error: {}", GirlvoiceError::from(e));
+        std::process::exit(1);
     });
 
-    window.set_target_fps(30);
+    // paced by Pacer below, not by minifb
+    window.set_target_fps(0);
 
-    let mut visualizer = Visualizer::new(num_channels);
-    let mut framebuffer = vec![0u32; DISPLAY_SIZE * DISPLAY_SIZE];
+    let mut visualizer = Visualizer::new(source.num_channels());
+    visualizer.set_plosive_guard(args.plosive_guard);
+    contrib::register(&mut visualizer);
 
-    let mut last_frame = Instant::now();
+    let mut framebuffer = Box::new(Framebuffer::new());
+    let mut post = Box::new(PostChain::empty());
+    let post_fx = if args.post_fx.is_empty() { &config.post } else { &args.post_fx };
+    for &fx in post_fx {
+        if !post.push(fx) {
+            eprintln!("warning: too many post effects, ignoring {}", fx);
+        }
+    }
+    // the same front/back flow as the panel on the badge
+    let [mut front, mut back] = [(); 2].map(|_| vec![0u32; DISPLAY_SIZE * DISPLAY_SIZE]);
+    let mut display = DoubleBuffer::new(&mut front, &mut back).expect("buffers are display sized");
+    let mut panel = PanelLink::new(args.panel_mhz);
+    if !panel.transfer_time().is_zero() {
+        println!("Panel link: {} MHz, {:.1} ms per frame", args.panel_mhz, panel.transfer_time().as_secs_f32() * 1000.0);
+    }
+    let mut fidelity = args.fidelity.then(|| Fidelity::new(args.panel_refresh_hz, args.tearing));
+    if let Some(fidelity) = &fidelity {
+        println!("Hardware fidelity: RGB565, up to {} fps", fidelity.max_fps(&panel));
+    }
+
+    let mut bank = if args.presets.exists() {
+        presets::load(&args.presets).unwrap_or_else(|e| {
+            eprintln!("warning: {}, starting with an empty bank", e);
+            PresetBank::new()
+        })
+    } else {
+        PresetBank::new()
+    };
+    println!("Preset bank {}: {} slot(s) in use", args.presets.display(), bank.iter().count());
+
+    // saved settings win over the config file, like flash over factory defaults on the badge
+    let mut store = FileStore::new(args.store.clone());
+    let stored = if args.reset_settings { Ok(None) } else { load_settings(&mut store) };
+    let mut settings = match stored {
+        Ok(Some(settings)) => {
+            println!("Restored settings from {}", args.store.display());
+            settings
+        }
+        Ok(None) => config.settings,
+        Err(e) => {
+            eprintln!("warning: can't restore settings from {} ({:?}), using defaults", args.store.display(), e);
+            config.settings
+        }
+    };
+    if let Some(policy) = args.startup {
+        settings.startup = policy;
+    }
+    if let Some(power) = args.power {
+        settings.power = power;
+    }
+    if let Some(seconds) = args.idle_clock {
+        settings.idle_clock = seconds;
+    }
+    for &(mode, curve) in &args.response {
+        match mode {
+            Some(mode) => settings.response.set_mode(mode, Some(curve)),
+            None => settings.response.global = curve,
+        }
+    }
+    if let Some(preset) = settings.last_preset.and_then(|slot| bank.select(slot)) {
+        dsp = preset.apply(&mut visualizer, &mut post);
+    }
+    // the calibration belongs to the mic, it wins over what a preset or the config says
+    if let Some(calibration) = &settings.calibration {
+        calibration.apply(&mut dsp);
+    }
+    source.set_dsp_settings(&dsp);
+    visualizer.set_palette(settings.palette);
+    visualizer.set_response(settings.response);
+    settings.apply_startup(&mut visualizer);
+    visualizer.set_idle_clock(settings.idle_clock_after());
+    let mut clock = args.utc_offset.map_or_else(SystemClock::local, SystemClock::new);
+    if args.boot_animation {
+        visualizer.start_boot();
+    }
+
+    let mut keyboard = KeyboardInput::new();
+    let mut input_handler = InputHandler::new();
+    let mut menu = Menu::new();
+    let mut events = Vec::new();
+    let mut status = StatusOverlay::new();
+    let mut toasts = Toasts::new();
+    let mut shown_palette = *visualizer.palette(); // to announce palette changes from any control
+    status.show_loudness = args.loudness;
+    status.loudness.target = config.loudness;
+    status.show_pitch = args.pitch;
+    status.pitch.target = config.pitch;
+    let mut status_inputs = StatusInputs::default();
+    let mut calibrator = Calibrator::new(source.num_channels());
+
+    let mut recorder = args.record.as_ref().map(|path| {
+        SessionRecorder::create(path, source.num_channels().get(), config.pitch, config.loudness).unwrap_or_else(|e| {
+            eprintln!("error: can't record to {}: {}", path.display(), e);
+            std::process::exit(1);
+        })
+    });
+
+    let mut capture = Capture::new(args.capture_dir.clone());
+    let mut captured = vec![0u32; DISPLAY_SIZE * DISPLAY_SIZE]; // the picture without the overlays
+
+    let renderer = render::ParallelRenderer::new(args.threads);
+    let mut hud = Hud::new();
+    let mut pacer = Pacer::new(args.fps);
+    let mut dropped = 0;
+    let mut frame_error = None; // last update_frame failure, so it's only reported when it changes
+    let mut power = None; // profile the pacer and flush are set up for
 
+    let start_time = Instant::now();
+    let mut last_frame = start_time;
 
     while window.is_open() && !window.is_key_down(Key::Escape) {
         let now = Instant::now();
         let dt = (now - last_frame).as_secs_f32();
         last_frame = now;
-       
-        let energies = {
-            let shared = shared.lock().unwrap();
-            shared.energies.clone()
-        };
 
-        // run main shader
-        visualizer.update(dt, &energies);
-
-        // fade buffer for trails
-        let fade = 0.7;
-        for pixel in framebuffer.iter_mut() {
-            let r = ((*pixel >> 16) & 0xFF) as f32 * fade;
-            let g = ((*pixel >> 8) & 0xFF) as f32 * fade;
-            let b = (*pixel & 0xFF) as f32 * fade;
-            *pixel = 0xFF000000 | ((r as u32) << 16) | ((g as u32) << 8) | (b as u32);
-        }
-
-        let vis_brightness = 1.0;
-        visualizer.render(|x, y, color| {
-            if x < DISPLAY_SIZE && y < DISPLAY_SIZE {
-                let idx = y * DISPLAY_SIZE + x;
-                let dimmed = color.scale(vis_brightness);
-                let existing = framebuffer[idx];
-                let er = ((existing >> 16) & 0xFF) as u32;
-                let eg = ((existing >> 8) & 0xFF) as u32;
-                let eb = (existing & 0xFF) as u32;
-                let nr = (er + dimmed.r as u32).min(255);
-                let ng = (eg + dimmed.g as u32).min(255);
-                let nb = (eb + dimmed.b as u32).min(255);
-                framebuffer[idx] = 0xFF000000 | (nr << 16) | (ng << 8) | nb;
+        events.clear();
+        keyboard.poll(&window, &mut events);
+        for &event in &events {
+            // the first input only skips the boot animation
+            if visualizer.is_booting() {
+                visualizer.skip_boot();
+                continue;
             }
-        });
+            // and the first one after going idle only brings the mode back
+            if visualizer.wake() {
+                continue;
+            }
+            let action = input_handler.handle(event);
+            if menu.is_open() || action == UiAction::OpenMenu {
+                let mut target = MenuTarget { visualizer: &mut visualizer, settings: &mut settings, dsp: &mut dsp };
+                if menu.handle(action, &mut target) == MenuEffect::DspChanged {
+                    source.set_dsp_settings(&dsp);
+                }
+                input_handler.set_menu_open(menu.is_open());
+                continue;
+            }
+            match action {
+                UiAction::NextMode | UiAction::PrevMode if visualizer.demo_cycle().is_some() => {
+                    // any manual mode change ends the demo
+                    visualizer.set_demo_cycle(None);
+                }
+                UiAction::NextMode => visualizer.step_mode(1),
+                UiAction::PrevMode => visualizer.step_mode(-1),
+                UiAction::Brightness(steps) => {
+                    settings.adjust_brightness(steps as f32 * BRIGHTNESS_STEP);
+                    println!("Brightness: {:.0}%", settings.brightness * 100.0);
+                }
+                UiAction::OpenMenu | UiAction::MenuStep(_) | UiAction::MenuSelect | UiAction::MenuBack | UiAction::None => {}
+            }
+            if settings.last_mode != visualizer.current_mode() {
+                settings.record(&visualizer);
+                println!("Mode: {}", visualizer.current_mode().name());
+            }
+        }
 
-        draw_level_meters(&mut framebuffer, &energies);
-
-        // scale up screen
-        let scaled_framebuffer: Vec<u32> = if SCALE > 1 {
-            let mut scaled = vec![0u32; window_size * window_size];
-            for y in 0..DISPLAY_SIZE {
-                for x in 0..DISPLAY_SIZE {
-                    let color = framebuffer[y * DISPLAY_SIZE + x];
-                    for sy in 0..SCALE {
-                        for sx in 0..SCALE {
-                            scaled[(y * SCALE + sy) * window_size + (x * SCALE + sx)] = color;
-                        }
+        let shift = window.is_key_down(Key::LeftShift) || window.is_key_down(Key::RightShift);
+        for (slot, &key) in PRESET_KEYS.iter().enumerate() {
+            if !window.is_key_pressed(key, KeyRepeat::No) {
+                continue;
+            }
+            if shift {
+                let name = format!("SLOT {}", slot + 1);
+                bank.set(slot, Preset::capture(&name, &visualizer, &post, &dsp));
+                match presets::save(&args.presets, &bank) {
+                    Ok(()) => {
+                        println!("Stored preset {} in {}", slot + 1, args.presets.display());
+                        toasts.toast(&format!("Saved to preset {}", slot + 1), TOAST_S);
                     }
+                    Err(e) => eprintln!("warning: {}", e),
+                }
+            } else if let Some(preset) = bank.select(slot) {
+                dsp = preset.apply(&mut visualizer, &mut post);
+                if let Some(calibration) = &settings.calibration {
+                    calibration.apply(&mut dsp);
+                }
+                source.set_dsp_settings(&dsp);
+                settings.record(&visualizer);
+                settings.last_preset = Some(slot);
+                println!("Preset {}: {}", slot + 1, preset.name.as_str());
+                toasts.toast(&format!("Preset {}", slot + 1), TOAST_S);
+                // the preset's palette comes with it, no second toast for that
+                shown_palette = *visualizer.palette();
+            } else {
+                println!("Preset slot {} is empty", slot + 1);
+            }
+        }
+
+        if window.is_key_pressed(Key::P, KeyRepeat::No) {
+            settings.power = settings.power.next();
+        }
+        if power != Some(settings.power) {
+            settings.power.configure(&mut post);
+            let fps = settings.power.frame_rate(args.fps);
+            pacer.set_fps(fidelity.as_ref().map_or(fps, |f| f.cap_fps(&panel, fps)));
+            if power.is_some() || settings.power != Default::default() {
+                println!("Power: {}", settings.power.name());
+            }
+            power = Some(settings.power);
+        }
+
+        if window.is_key_pressed(Key::C, KeyRepeat::No) {
+            if calibrator.is_running() {
+                calibrator.cancel();
+                source.set_dsp_settings(&dsp);
+                println!("Calibration cancelled");
+            } else {
+                calibrator.start();
+                source.set_dsp_settings(&Calibrator::measuring_settings(&dsp));
+                println!(
+                    "Calibrating: stay quiet for {:.0} s, then speak normally for {:.0} s (C cancels)",
+                    Calibrator::SILENCE_S,
+                    Calibrator::SPEECH_S
+                );
+            }
+        }
+
+        if let Some(midi) = &mut midi {
+            midi.poll(|key, value| {
+                let mut target = MenuTarget { visualizer: &mut visualizer, settings: &mut settings, dsp: &mut dsp };
+                if midi_map.apply(key, value, &mut target) == Some(MenuEffect::DspChanged) {
+                    source.set_dsp_settings(&dsp);
+                }
+            });
+        }
+
+        if let Some(osc) = &mut osc {
+            osc.poll(|param, value| {
+                let mut target = MenuTarget { visualizer: &mut visualizer, settings: &mut settings, dsp: &mut dsp };
+                if param.set(&mut target, value) == MenuEffect::DspChanged {
+                    source.set_dsp_settings(&dsp);
                 }
+            });
+        }
+
+        if *visualizer.palette() != shown_palette {
+            shown_palette = *visualizer.palette();
+            let name = match palette::builtin_index(&shown_palette) {
+                Some(i) => palette::BUILTIN[i],
+                None => settings.theme.as_ref().filter(|theme| theme.palette == shown_palette).map_or("Custom palette", |theme| theme.name.as_str()),
+            };
+            toasts.toast(name, TOAST_S);
+        }
+
+        if menu.update(dt) == MenuEffect::Closed {
+            input_handler.set_menu_open(false);
+        }
+
+        let phase_start = Instant::now();
+        let mut frame = source.poll();
+        if let Some(external) = osc.as_ref().and_then(|osc| osc.frame()) {
+            // a relayed frame replaces the source's completely, its clock isn't ours either
+            frame = AnalysisFrame { time: 0.0, ..*external };
+        } else if let Some(bands) = osc.as_ref().and_then(|osc| osc.energies()) {
+            // external analysis replaces the source's bands, without its clock
+            frame.set_bands(bands);
+            frame.time = 0.0;
+        }
+        let energies = frame.bands();
+        hud.record(Phase::Poll, phase_start.elapsed());
+        match calibrator.update(dt, &frame) {
+            Some(Ok(calibration)) => {
+                calibration.apply(&mut dsp);
+                source.set_dsp_settings(&dsp);
+                settings.calibration = Some(calibration);
+                settings.record(&visualizer);
+                match save_settings(&mut store, &settings) {
+                    Ok(()) => println!("Calibration saved to {}", args.store.display()),
+                    Err(e) => eprintln!("warning: can't save the calibration to {} ({:?})", args.store.display(), e),
+                }
+            }
+            Some(Err(e)) => {
+                source.set_dsp_settings(&dsp);
+                println!("Calibration failed ({:?}), speak a little louder or closer to the mic and try again", e);
             }
-            scaled
+            None => {}
+        }
+        if let Some(recorder) = &mut recorder {
+            recorder.record((now - start_time).as_secs_f64(), dt, &frame);
+        }
+
+        // run main shader
+        let phase_start = Instant::now();
+        let time = clock.now();
+        visualizer.set_time(time);
+        // a relayed frame can carry another band count, it's skipped. said once, not every frame
+        let update = visualizer.update_frame(dt, &frame);
+        if let Err(e) = update
+            && frame_error != Some(e)
+        {
+            eprintln!("warning: {}, skipping those frames", e);
+        }
+        frame_error = update.err();
+        post.update(dt, &frame);
+        visualizer.set_hue(post.hue());
+        if let Some(comparison) = &mut comparison {
+            comparison.sync(&visualizer, &post, &dsp, source.as_mut());
+            comparison.update(dt, &frame, source.as_mut(), time);
+        }
+
+        status_inputs.set_from_frame(&frame);
+        if let Some(battery) = &args.battery {
+            let (level, charging) = battery.reading((now - start_time).as_secs_f32());
+            status_inputs.battery = Some(level);
+            status_inputs.charging = charging;
+        }
+        status.update(dt, &status_inputs);
+        toasts.update(dt, &status_inputs);
+        hud.record(Phase::Update, phase_start.elapsed());
+
+        let phase_start = Instant::now();
+        let brightness = settings.power.brightness(settings.brightness);
+        let step = settings.power.pixel_step();
+        if step > 1 || post.kaleidoscope().is_some() {
+            post.begin_frame(&mut framebuffer, visualizer.displayed_mode());
+            render::render_region_additive(&visualizer, &mut framebuffer, post.render_region(), brightness, step);
+        } else if renderer.threads() > 1 {
+            renderer.render(&visualizer, &post, &mut framebuffer, brightness);
         } else {
-            framebuffer.clone()
+            post.begin_frame(&mut framebuffer, visualizer.displayed_mode());
+            render::render_additive(&visualizer, &mut framebuffer, brightness);
+        }
+        post.end_frame(&mut framebuffer);
+        // before the overlays, so the ring carries the picture and not the menu
+        if let Some(ring) = &mut ring {
+            visualizer.render_ring(ring, &framebuffer, step, brightness);
+        }
+        // screenshots and gifs get the picture, not the status bar, menu and toasts
+        let screenshot = window.is_key_pressed(Key::S, KeyRepeat::No);
+        let gif_key = window.is_key_pressed(Key::G, KeyRepeat::No);
+        if screenshot || gif_key || capture.is_recording() {
+            post.write_argb32(&framebuffer, &mut captured).expect("capture buffer is display sized");
+        }
+        status.render(&mut framebuffer, visualizer.palette());
+        calibrator.render(&mut framebuffer, visualizer.palette());
+        menu.render(&mut framebuffer, &visualizer, &settings, &dsp);
+        toasts.render(&mut framebuffer, visualizer.palette());
+        if args.round_mask {
+            render::apply_round_mask(&mut framebuffer, args.bezel);
+        }
+
+        if let Some(comparison) = &mut comparison {
+            comparison.render(brightness, step, args.bezel);
+        }
+
+        draw_level_meters(&mut framebuffer, energies);
+        if args.hud {
+            hud.draw(&mut framebuffer);
+        }
+        match &mut fidelity {
+            Some(fidelity) => fidelity.flush(&mut post, &framebuffer, display.back_mut()),
+            None => post.write_argb32(&framebuffer, display.back_mut()).expect("back buffer is display sized"),
+        }
+        hud.record(Phase::Render, phase_start.elapsed());
+
+        let phase_start = Instant::now();
+        panel.present(&mut display);
+
+        let shown = match &mut fidelity {
+            Some(fidelity) => fidelity.panel_view(&panel, display.front()),
+            None => display.front(),
         };
+        if screenshot {
+            match capture.screenshot(visualizer.displayed_mode().id(), &captured) {
+                Ok(path) => {
+                    println!("Screenshot: {}", path.display());
+                    toasts.toast("Screenshot saved", TOAST_S);
+                }
+                Err(e) => eprintln!("warning: can't save the screenshot: {}", e),
+            }
+        }
+        let gif_done = if gif_key {
+            if capture.is_recording() {
+                capture.stop_gif()
+            } else {
+                match capture.start_gif(visualizer.displayed_mode().id(), &captured) {
+                    Ok(path) => println!("Recording {} (G stops, {} s at most)", path.display(), capture::MAX_GIF_S),
+                    Err(e) => eprintln!("warning: can't record a gif: {}", e),
+                }
+                None
+            }
+        } else {
+            capture.frame(dt, &captured)
+        };
+        match gif_done {
+            Some(Ok(path)) => {
+                println!("Saved {}", path.display());
+                toasts.toast("GIF saved", TOAST_S);
+            }
+            Some(Err(e)) => eprintln!("warning: gif recording failed: {}", e),
+            None => {}
+        }
+        let picture = match &preview {
+            Some(preview) => {
+                preview.compose(shown, &mut preview_buffer);
+                &preview_buffer
+            }
+            None => upscaler.upscale(shown),
+        };
+        let window_buffer = match (&mut ring_view, &ring, &mut comparison) {
+            (Some(view), Some(ring), _) => view.compose(picture, ring),
+            (_, _, Some(comparison)) => comparison.compose(picture),
+            _ => picture,
+        };
+        if let Err(e) = window.update_with_buffer(window_buffer, window_width, window_height) {
+            eprintln!("error: {}", GirlvoiceError::from(e));
+            break;
+        }
+        hud.record(Phase::Blit, phase_start.elapsed());
+
+        hud.frame_done(now, dropped);
+        dropped = pacer.wait();
+    }
+
+    if let Some(recorder) = recorder {
+        recorder.finish();
+    }
+    // a recording still running at exit is kept
+    match capture.stop_gif() {
+        Some(Ok(path)) => println!("Saved {}", path.display()),
+        Some(Err(e)) => eprintln!("warning: gif recording failed: {}", e),
+        None => {}
+    }
+    panel.report();
+    if let Some(fidelity) = &fidelity {
+        fidelity.report();
+    }
+
+    settings.record(&visualizer);
+    if let Err(e) = save_settings(&mut store, &settings) {
+        eprintln!("warning: can't save settings to {} ({:?})", args.store.display(), e);
+    }
+}
 
-        window
-            .update_with_buffer(&scaled_framebuffer, window_size, window_size)
-            .unwrap();
+// the --source input
+fn open_source(args: &Args, channels: ChannelCount, vocoder: &VocoderConfig) -> Box<dyn EnergySource> {
+    match &args.source {
+        SourceKind::Mic => or_synthetic(MicSource::new(channels, vocoder, args.stereo), channels, vocoder, args.stereo),
+        &SourceKind::Synth(signal) => synthetic(signal, channels, vocoder, args.stereo),
+        SourceKind::Output { device, with_mic } => {
+            let output = or_synthetic(MicSource::output(device.as_deref(), channels, vocoder), channels, vocoder, false);
+            if *with_mic {
+                if args.stereo {
+                    eprintln!("warning: --stereo is ignored with mic+output, the output takes the right side");
+                }
+                let mic = or_synthetic(MicSource::new(channels, vocoder, false), channels, vocoder, false);
+                println!("Mic on the left, output on the right, the split mode shows both");
+                Box::new(PairedSource::new(mic, output))
+            } else {
+                output
+            }
+        }
+        // a named file or port has to be there, like an explicitly named config
+        SourceKind::Wav(path) => Box::new(WavSource::open(path, channels, vocoder, args.stereo).unwrap_or_else(|e| exit_with(e))),
+        SourceKind::Serial(port) => Box::new(SerialSource::open(port, channels).unwrap_or_else(|e| exit_with(e))),
     }
 }
 
+fn exit_with(e: GirlvoiceError) -> ! {
+    eprintln!("error: {}", e);
+    std::process::exit(1);
+}
 
-fn draw_level_meters(framebuffer: &mut [u32], energies: &[f32]) {
+// a device that won't open shouldn't stop the simulator, the synthetic voice stands in for it
+fn or_synthetic(
+    source: Result<MicSource, GirlvoiceError>,
+    channels: ChannelCount,
+    vocoder: &VocoderConfig,
+    stereo: bool,
+) -> Box<dyn EnergySource> {
+    match source {
+        Ok(source) => Box::new(source),
+        Err(e) => {
+            eprintln!("warning: {}", e);
+            eprintln!("warning: falling back to the synthetic speech signal");
+            synthetic(Signal::Speech, channels, vocoder, stereo)
+        }
+    }
+}
+
+fn synthetic(signal: Signal, channels: ChannelCount, vocoder: &VocoderConfig, stereo: bool) -> Box<dyn EnergySource> {
+    println!("Using synthetic source: {}{}", signal.name(), if stereo { ", pink noise on the right" } else { "" });
+    Box::new(SyntheticSource::new(signal, channels, vocoder, stereo))
+}
+
+const METER_BACKGROUND: Color = Color::new(0x20, 0x20, 0x20);
+
+fn draw_level_meters(framebuffer: &mut Framebuffer, energies: &[f32]) {
     let meter_width = 4;
     let meter_height = 40;
     let spacing = 2;
@@ -214,10 +672,7 @@ fn draw_level_meters(framebuffer: &mut [u32], energies: &[f32]) {
         
         for dy in 0..meter_height {
             for dx in 0..meter_width {
-                let (px, py) = (x + dx, y + dy);
-                if px < DISPLAY_SIZE && py < DISPLAY_SIZE {
-                    framebuffer[py * DISPLAY_SIZE + px] = 0xFF202020;
-                }
+                framebuffer.set(x + dx, y + dy, METER_BACKGROUND);
             }
         }
         
@@ -225,10 +680,7 @@ fn draw_level_meters(framebuffer: &mut [u32], energies: &[f32]) {
         let color = palette::rainbow(i as f32 / energies.len() as f32);
         for dy in 0..level_height {
             for dx in 0..meter_width {
-                let (px, py) = (x + dx, y + meter_height - 1 - dy);
-                if px < DISPLAY_SIZE && py < DISPLAY_SIZE {
-                    framebuffer[py * DISPLAY_SIZE + px] = color.to_argb32();
-                }
+                framebuffer.set(x + dx, y + meter_height - 1 - dy, color);
             }
         }
     }
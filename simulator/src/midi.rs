@@ -0,0 +1,143 @@
+// --midi: a knob box or DAW drives the visualizer. CCs and note-ons (any channel) are looked
+// up in a ParamMap and applied through ParamId, the same path the badge's serial protocol
+// takes. the midir backend is behind the `midi` cargo feature, without it --midi reports that
+// the build has no MIDI support.
+
+use serde::{Deserialize, Serialize};
+
+use girlvoice_ui_core::{ParamBinding, ParamId, ParamMap};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MidiKey {
+    Cc(u8),
+    Note(u8),
+}
+
+// one [[midi]] entry in girlvoice.toml: either `cc` or `note`, plus the parameter (a ParamId,
+// or a mode's own param id like "speed"). notes send `value` when given, otherwise their velocity
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MidiBinding {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cc: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<u8>,
+    pub param: ParamId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<f32>,
+}
+
+// CC 20 up for the knobs in ParamId::ALL order (28-31 are the current mode's own params),
+// notes from C1 (36) up pick a mode each
+pub fn default_bindings() -> Vec<MidiBinding> {
+    let cc = |cc, param| MidiBinding { cc: Some(cc), note: None, param, value: None };
+    let mut bindings: Vec<MidiBinding> =
+        ParamId::ALL.iter().enumerate().map(|(i, &param)| cc(20 + i as u8, param)).collect();
+    for index in 0..ParamId::Mode.steps().unwrap_or(0) {
+        bindings.push(MidiBinding {
+            cc: None,
+            note: Some(36 + index as u8),
+            param: ParamId::Mode,
+            value: Some(ParamId::Mode.step_value(index)),
+        });
+    }
+    bindings
+}
+
+pub fn build_map(bindings: &[MidiBinding]) -> Result<ParamMap<MidiKey>, String> {
+    let mut map = ParamMap::new();
+    for b in bindings {
+        let key = match (b.cc, b.note) {
+            (Some(cc), None) if cc < 128 => MidiKey::Cc(cc),
+            (None, Some(note)) if note < 128 => MidiKey::Note(note),
+            _ => return Err(format!("midi binding for {} needs one cc or note (0-127)", b.param.id())),
+        };
+        if !map.bind(key, ParamBinding { param: b.param, value: b.value }) {
+            return Err("too many midi bindings".to_string());
+        }
+    }
+    Ok(map)
+}
+
+#[cfg(feature = "midi")]
+pub use backend::MidiController;
+
+#[cfg(feature = "midi")]
+mod backend {
+    use midir::{Ignore, MidiInput, MidiInputConnection};
+
+    use girlvoice_ui_core::{RingConsumer, RingProducer, SpscRing};
+
+    use super::MidiKey;
+
+    const QUEUE_LEN: usize = 256;
+
+    type Queue = RingProducer<'static, [u8; 3], QUEUE_LEN>;
+
+    pub struct MidiController {
+        messages: RingConsumer<'static, [u8; 3], QUEUE_LEN>,
+        _connection: MidiInputConnection<Queue>, // keep alive, dropping disconnects
+    }
+
+    impl MidiController {
+        // first input port whose name contains `port` (case-insensitive), "any" takes the first one
+        pub fn open(port: &str) -> Result<Self, String> {
+            let mut input = MidiInput::new("girlvoice-ui").map_err(|e| e.to_string())?;
+            input.ignore(Ignore::All);
+            let ports = input.ports();
+            let names: Vec<String> = ports.iter().map(|p| input.port_name(p).unwrap_or_default()).collect();
+            let wanted = port.to_lowercase();
+            let index = names
+                .iter()
+                .position(|name| wanted == "any" || name.to_lowercase().contains(&wanted))
+                .ok_or_else(|| format!("no midi input matching '{}' (available: {})", port, names.join(", ")))?;
+            println!("Using midi input: {}", names[index]);
+
+            let queue = Box::leak(Box::new(SpscRing::new()));
+            let (producer, messages) = queue.split();
+            let connection = input
+                .connect(&ports[index], "girlvoice-ui", |_, message, queue: &mut Queue| {
+                    // channel messages only, at most three bytes
+                    if let [status, rest @ ..] = message
+                        && rest.len() <= 2
+                    {
+                        let mut bytes = [*status, 0, 0];
+                        bytes[1..1 + rest.len()].copy_from_slice(rest);
+                        queue.push(bytes);
+                    }
+                }, producer)
+                .map_err(|e| e.to_string())?;
+            Ok(Self { messages, _connection: connection })
+        }
+
+        // hand every CC and note-on since the last poll to `handle`, values scaled to 0-1
+        pub fn poll(&mut self, mut handle: impl FnMut(MidiKey, f32)) {
+            while let Some(message) = self.messages.pop() {
+                if let Some((key, value)) = decode(message) {
+                    handle(key, value);
+                }
+            }
+        }
+    }
+
+    fn decode([status, data1, data2]: [u8; 3]) -> Option<(MidiKey, f32)> {
+        let value = data2.min(127) as f32 / 127.0;
+        match status & 0xf0 {
+            0xb0 => Some((MidiKey::Cc(data1 & 0x7f), value)),
+            0x90 if data2 > 0 => Some((MidiKey::Note(data1 & 0x7f), value)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(not(feature = "midi"))]
+pub struct MidiController;
+
+#[cfg(not(feature = "midi"))]
+impl MidiController {
+    pub fn open(_port: &str) -> Result<Self, String> {
+        Err("this build has no midi support, rebuild with --features midi".to_string())
+    }
+
+    pub fn poll(&mut self, _handle: impl FnMut(MidiKey, f32)) {}
+}
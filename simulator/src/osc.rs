@@ -0,0 +1,201 @@
+// --osc: Open Sound Control over UDP for VJ software and TouchOSC. decoded by hand, only what
+// remote control needs (messages and bundles; int, float, string, bool, double and blob
+// arguments).
+// - /girlvoice/<param> <value>: any ParamId (mode, palette, brightness, gain, agc, tilt, power,
+//   startup, param1-param4) or the current mode's own param ids (/girlvoice/speed). floats are
+//   normalized 0-1, ints pick a step of the stepped ones, strings name a mode id, palette, power
+//   profile or startup policy (last, demo, mode:<id>). param1-param4 are whatever the current
+//   mode declares, in order
+// - /girlvoice/energy <f> <f> ...: band energies from an external analyzer, they replace the
+//   source's bands for as long as they keep arriving
+// - /girlvoice/frame <blob>: a whole AnalysisFrame in its telemetry layout, e.g. relayed from
+//   a badge. replaces the source's frame the same way
+
+use std::io;
+use std::net::UdpSocket;
+use std::time::{Duration, Instant};
+
+use girlvoice_ui_core::{palette, AnalysisFrame, ModeKind, ParamId, PowerProfile, StartupPolicy, CHANNELS};
+
+const PREFIX: &str = "/girlvoice/";
+const MAX_ARGS: usize = 32;
+// external energies and frames go stale after this, the source takes over again
+const ENERGY_TIMEOUT: Duration = Duration::from_millis(500);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Arg<'a> {
+    Int(i32),
+    Float(f32),
+    Str(&'a str),
+    Bool(bool),
+    Blob(&'a [u8]),
+}
+
+pub struct OscServer {
+    socket: UdpSocket,
+    buffer: Vec<u8>,
+    energies: [f32; CHANNELS],
+    num_energies: usize,
+    energies_at: Option<Instant>,
+    frame: Option<(AnalysisFrame, Instant)>,
+}
+
+impl OscServer {
+    pub fn bind(port: u16) -> io::Result<Self> {
+        let socket = UdpSocket::bind(("0.0.0.0", port))?;
+        socket.set_nonblocking(true)?;
+        Ok(Self { socket, buffer: vec![0; 65536], energies: [0.0; CHANNELS], num_energies: 0, energies_at: None, frame: None })
+    }
+
+    // drain pending packets, parameter changes go to `apply`
+    pub fn poll(&mut self, mut apply: impl FnMut(ParamId, f32)) {
+        loop {
+            let len = match self.socket.recv_from(&mut self.buffer) {
+                Ok((len, _)) => len,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    eprintln!("warning: osc receive failed: {}", e);
+                    break;
+                }
+            };
+            let (energies, num_energies, frame) = (&mut self.energies, &mut self.num_energies, &mut self.frame);
+            let mut got_energies = false;
+            let parsed = parse_packet(&self.buffer[..len], &mut |address, args| {
+                let Some(name) = address.strip_prefix(PREFIX) else { return };
+                if name == "frame" {
+                    match args.first() {
+                        Some(Arg::Blob(bytes)) => match AnalysisFrame::from_bytes(bytes) {
+                            Ok(decoded) => *frame = Some((decoded, Instant::now())),
+                            Err(e) => eprintln!("warning: ignoring osc frame: {}", e),
+                        },
+                        _ => eprintln!("warning: /girlvoice/frame needs a blob argument"),
+                    }
+                } else if name == "energy" {
+                    let mut n = 0;
+                    for (out, arg) in energies.iter_mut().zip(args) {
+                        *out = number(arg).unwrap_or(0.0).clamp(0.0, 1.0);
+                        n += 1;
+                    }
+                    *num_energies = n;
+                    got_energies = true;
+                } else if let Some(param) = ParamId::from_id(name)
+                    && let Some(value) = args.first().and_then(|arg| param_value(param, arg))
+                {
+                    apply(param, value);
+                }
+            });
+            if parsed.is_none() {
+                eprintln!("warning: ignoring malformed osc packet ({} bytes)", len);
+            }
+            if got_energies {
+                self.energies_at = Some(Instant::now());
+            }
+        }
+    }
+
+    // external band energies, while they're fresh
+    pub fn energies(&self) -> Option<&[f32]> {
+        let fresh = self.energies_at.is_some_and(|at| at.elapsed() < ENERGY_TIMEOUT);
+        fresh.then(|| &self.energies[..self.num_energies])
+    }
+
+    // latest external frame, while it's fresh
+    pub fn frame(&self) -> Option<&AnalysisFrame> {
+        self.frame.as_ref().filter(|(_, at)| at.elapsed() < ENERGY_TIMEOUT).map(|(frame, _)| frame)
+    }
+}
+
+fn number(arg: &Arg) -> Option<f32> {
+    match *arg {
+        Arg::Float(v) => Some(v),
+        Arg::Int(v) => Some(v as f32),
+        Arg::Bool(v) => Some(v as u8 as f32),
+        Arg::Str(_) | Arg::Blob(_) => None,
+    }
+}
+
+// normalized value for a parameter from one argument
+fn param_value(param: ParamId, arg: &Arg) -> Option<f32> {
+    match (*arg, param.steps()) {
+        (Arg::Float(v), _) => Some(v),
+        (Arg::Bool(v), _) => Some(v as u8 as f32),
+        (Arg::Int(i), Some(_)) => Some(param.step_value(i.max(0) as usize)),
+        (Arg::Int(i), None) => Some(i as f32),
+        (Arg::Str(s), _) => {
+            let index = match param {
+                ParamId::Mode => ModeKind::from_id(s).map(|m| m.position()),
+                ParamId::Palette => palette::BUILTIN.iter().position(|name| name.eq_ignore_ascii_case(s)),
+                ParamId::Power => PowerProfile::ALL.iter().position(|p| p.id() == s),
+                ParamId::Startup => StartupPolicy::parse(s).map(|p| p.position()),
+                _ => None,
+            }?;
+            Some(param.step_value(index))
+        }
+        (Arg::Blob(_), _) => None,
+    }
+}
+
+// calls `handle` for every message in a packet (bundles are unpacked), None if it's malformed
+fn parse_packet<'a>(packet: &'a [u8], handle: &mut dyn FnMut(&'a str, &[Arg<'a>])) -> Option<()> {
+    if let Some(mut rest) = packet.strip_prefix(b"#bundle\0") {
+        rest = rest.get(8..)?; // time tag, everything applies immediately
+        while !rest.is_empty() {
+            let size = i32::from_be_bytes(rest.get(..4)?.try_into().ok()?);
+            let size = usize::try_from(size).ok()?;
+            parse_packet(rest.get(4..4 + size)?, handle)?;
+            rest = &rest[4 + size..];
+        }
+        return Some(());
+    }
+
+    let (address, mut rest) = read_str(packet)?;
+    let mut args = [Arg::Int(0); MAX_ARGS];
+    let mut count = 0;
+    // messages without a type tag string are allowed, they just have no arguments
+    if let Some((tags, after)) = read_str(rest).filter(|(tags, _)| tags.starts_with(',')) {
+        rest = after;
+        for tag in tags[1..].bytes() {
+            let arg = match tag {
+                b'i' => Arg::Int(i32::from_be_bytes(take::<4>(&mut rest)?)),
+                b'f' => Arg::Float(f32::from_be_bytes(take::<4>(&mut rest)?)),
+                b'd' => Arg::Float(f64::from_be_bytes(take::<8>(&mut rest)?) as f32),
+                b'h' => Arg::Int(i64::from_be_bytes(take::<8>(&mut rest)?) as i32),
+                b's' | b'S' => {
+                    let (s, after) = read_str(rest)?;
+                    rest = after;
+                    Arg::Str(s)
+                }
+                b'b' => {
+                    let size = usize::try_from(i32::from_be_bytes(take::<4>(&mut rest)?)).ok()?;
+                    let blob = rest.get(..size)?;
+                    rest = rest.get((size + 3) & !3..)?;
+                    Arg::Blob(blob)
+                }
+                b'T' => Arg::Bool(true),
+                b'F' => Arg::Bool(false),
+                b'N' | b'I' => continue,
+                _ => return None, // arrays, midi, ...: nothing here sends those
+            };
+            if count < MAX_ARGS {
+                args[count] = arg;
+                count += 1;
+            }
+        }
+    }
+    handle(address, &args[..count]);
+    Some(())
+}
+
+// nul-terminated string padded to four bytes, returns it and what follows
+fn read_str(bytes: &[u8]) -> Option<(&str, &[u8])> {
+    let end = bytes.iter().position(|&b| b == 0)?;
+    let text = std::str::from_utf8(&bytes[..end]).ok()?;
+    let padded = (end + 4) & !3;
+    Some((text, bytes.get(padded..)?))
+}
+
+fn take<const N: usize>(bytes: &mut &[u8]) -> Option<[u8; N]> {
+    let (head, tail) = bytes.split_first_chunk::<N>()?;
+    *bytes = tail;
+    Some(*head)
+}
@@ -0,0 +1,65 @@
+// stand-in for the SPI link to the GC9A01: a swapped-in front buffer takes as long to stream as
+// 240x240 RGB565 at the link's clock would. the UI loop goes through the same DoubleBuffer flow
+// as the firmware, so a frame that can't be swapped yet costs a visible wait here too
+
+use std::time::{Duration, Instant};
+
+use girlvoice_ui_core::{DoubleBuffer, FRAMEBUFFER_LEN};
+
+pub struct PanelLink {
+    transfer: Duration,     // one full frame
+    busy_until: Option<Instant>,
+    waits: u32,             // swaps that found the previous transfer still running
+    longest_wait: Duration,
+}
+
+impl PanelLink {
+    pub const DEFAULT_MHZ: f32 = 62.5;
+    const BITS_PER_PIXEL: f32 = 16.0;
+
+    // 0 MHz streams instantly
+    pub fn new(mhz: f32) -> Self {
+        let transfer = if mhz > 0.0 {
+            Duration::from_secs_f32(FRAMEBUFFER_LEN as f32 * Self::BITS_PER_PIXEL / (mhz * 1e6))
+        } else {
+            Duration::ZERO
+        };
+        Self { transfer, busy_until: None, waits: 0, longest_wait: Duration::ZERO }
+    }
+
+    pub fn transfer_time(&self) -> Duration {
+        self.transfer
+    }
+
+    // when the latest frame started streaming, None before the first
+    pub fn transfer_start(&self) -> Option<Instant> {
+        self.busy_until.map(|until| until - self.transfer)
+    }
+
+    // swaps the flushed back buffer in and starts streaming it, after waiting out the previous
+    // transfer like the firmware's DMA-complete wait
+    pub fn present<T>(&mut self, buffers: &mut DoubleBuffer<T>) {
+        if let Some(until) = self.busy_until.take() {
+            let now = Instant::now();
+            if until > now {
+                let wait = until - now;
+                std::thread::sleep(wait);
+                self.waits += 1;
+                self.longest_wait = self.longest_wait.max(wait);
+            }
+            buffers.transfer_done();
+        }
+        buffers.swap().expect("the previous transfer finished above");
+        self.busy_until = Some(Instant::now() + self.transfer);
+    }
+
+    pub fn report(&self) {
+        if self.waits > 0 {
+            println!(
+                "Panel: {} frame(s) waited on the previous transfer, longest {:.1} ms",
+                self.waits,
+                self.longest_wait.as_secs_f32() * 1000.0
+            );
+        }
+    }
+}
@@ -0,0 +1,121 @@
+// true-size preview: draws the panel at its physical 1.8" diameter for a given monitor DPI,
+// inside a rendered badge body, so text and widgets can be judged at the real size
+
+use girlvoice_ui_core::{Color, DISPLAY_SIZE};
+
+use crate::render::bezel_shade;
+
+const DISPLAY_DIAMETER_IN: f32 = 1.8;
+const BEZEL_IN: f32 = 0.12;  // black ring around the glass
+const BODY_IN: f32 = 0.3;    // badge body around the bezel
+const MARGIN_IN: f32 = 0.15; // desk around the badge
+
+const BODY_COLOR: Color = Color::new(58, 30, 52);
+const DESK_COLOR: Color = Color::new(24, 24, 28);
+
+pub struct PhysicalPreview {
+    window_size: usize,
+    px_per_inch: f32,
+    // source pixel range [start, end) for every output row/column inside the panel
+    src_ranges: Vec<(usize, usize)>,
+    // everything but the panel itself is static, drawn once
+    background: Vec<u32>,
+}
+
+impl PhysicalPreview {
+    pub fn new(dpi: f32) -> Self {
+        let px_per_inch = dpi;
+        let total_in = DISPLAY_DIAMETER_IN + 2.0 * (BEZEL_IN + BODY_IN + MARGIN_IN);
+        let window_size = (total_in * px_per_inch).ceil() as usize;
+        let center = window_size as f32 / 2.0;
+
+        let panel_r = DISPLAY_DIAMETER_IN / 2.0 * px_per_inch;
+        let bezel_r = panel_r + BEZEL_IN * px_per_inch;
+        let body_r = bezel_r + BODY_IN * px_per_inch;
+
+        let mut background = vec![0u32; window_size * window_size];
+        for y in 0..window_size {
+            for x in 0..window_size {
+                let (dx, dy) = (x as f32 - center, y as f32 - center);
+                let r = (dx * dx + dy * dy).sqrt();
+                let color = if r < panel_r {
+                    Color::default()
+                } else if r < bezel_r {
+                    bezel_shade(dx, dy, (r - panel_r) / (bezel_r - panel_r))
+                } else if r < body_r {
+                    // soft rim so the body reads as a rounded object
+                    let rim = 1.0 - ((r - bezel_r) / (body_r - bezel_r) - 0.5).abs() * 0.6;
+                    BODY_COLOR.scale(rim)
+                } else {
+                    DESK_COLOR
+                };
+                background[y * window_size + x] = color.to_argb32();
+            }
+        }
+
+        // output pixel -> footprint in display pixels, for box-filtered downscaling
+        let panel_px = DISPLAY_DIAMETER_IN * px_per_inch;
+        let origin = center - panel_px / 2.0;
+        let scale = DISPLAY_SIZE as f32 / panel_px;
+        let src_ranges = (0..window_size)
+            .map(|o| {
+                let start = ((o as f32 - origin) * scale).floor().clamp(0.0, (DISPLAY_SIZE - 1) as f32) as usize;
+                // nearest when enlarging, average the footprint when shrinking
+                let end = if scale < 1.0 {
+                    start + 1
+                } else {
+                    ((o as f32 + 1.0 - origin) * scale).ceil().clamp(0.0, DISPLAY_SIZE as f32) as usize
+                };
+                (start, end.clamp(start + 1, DISPLAY_SIZE))
+            })
+            .collect();
+
+        println!(
+            "Physical preview at {:.0} dpi: panel {:.0} px across ({:.2}x)",
+            dpi, panel_px, 1.0 / scale
+        );
+
+        Self { window_size, px_per_inch, src_ranges, background }
+    }
+
+    pub fn window_size(&self) -> usize {
+        self.window_size
+    }
+
+    pub fn compose(&self, display: &[u32], out: &mut [u32]) {
+        out.copy_from_slice(&self.background);
+
+        let center = self.window_size as f32 / 2.0;
+        let panel_r = DISPLAY_DIAMETER_IN / 2.0 * self.px_per_inch;
+        let panel_r_sq = panel_r * panel_r;
+
+        for y in 0..self.window_size {
+            let dy = y as f32 + 0.5 - center;
+            if dy * dy > panel_r_sq {
+                continue;
+            }
+            let (sy0, sy1) = self.src_ranges[y];
+            for x in 0..self.window_size {
+                let dx = x as f32 + 0.5 - center;
+                if dx * dx + dy * dy > panel_r_sq {
+                    continue;
+                }
+                let (sx0, sx1) = self.src_ranges[x];
+                out[y * self.window_size + x] = box_average(display, sx0, sx1, sy0, sy1);
+            }
+        }
+    }
+}
+
+fn box_average(display: &[u32], x0: usize, x1: usize, y0: usize, y1: usize) -> u32 {
+    let (mut r, mut g, mut b) = (0u32, 0u32, 0u32);
+    for y in y0..y1 {
+        for &p in &display[y * DISPLAY_SIZE + x0..y * DISPLAY_SIZE + x1] {
+            r += (p >> 16) & 0xFF;
+            g += (p >> 8) & 0xFF;
+            b += p & 0xFF;
+        }
+    }
+    let n = ((x1 - x0) * (y1 - y0)).max(1) as u32;
+    0xFF000000 | ((r / n) << 16) | ((g / n) << 8) | (b / n)
+}
@@ -0,0 +1,147 @@
+// preset bank file: one [slot N] section per preset with key = value lines, easy to edit by hand
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use girlvoice_ui_core::{Color, Detector, ModeKind, Name, PostFx, Preset, PresetBank, CHANNELS, MAX_POST_STAGES, PRESET_SLOTS};
+
+fn color_hex(c: Color) -> String {
+    format!("{:02x}{:02x}{:02x}", c.r, c.g, c.b)
+}
+
+fn parse_color(text: &str) -> Result<Color, String> {
+    let value = u32::from_str_radix(text, 16).ok().filter(|_| text.len() == 6);
+    let value = value.ok_or_else(|| format!("invalid color '{}'", text))?;
+    Ok(Color::new((value >> 16) as u8, (value >> 8) as u8, value as u8))
+}
+
+fn parse_f32(key: &str, text: &str) -> Result<f32, String> {
+    text.parse().map_err(|_| format!("invalid {} '{}'", key, text))
+}
+
+pub fn save(path: &Path, bank: &PresetBank) -> Result<(), String> {
+    let mut out = String::from("# girlvoice preset bank, slots 1-8 map to F1-F8\n");
+    for (slot, preset) in bank.iter() {
+        let post: Vec<String> = preset.post.iter().flatten().map(|fx| fx.to_string()).collect();
+        let palette: Vec<String> = preset.palette.colors.iter().map(|&c| color_hex(c)).collect();
+        let _ = writeln!(out);
+        let _ = writeln!(out, "[slot {}]", slot + 1);
+        let _ = writeln!(out, "name = {}", preset.name.as_str());
+        let _ = writeln!(out, "mode = {}", preset.mode.id());
+        let _ = writeln!(out, "palette = {}", palette.join(" "));
+        let _ = writeln!(out, "primary = {}", color_hex(preset.palette.primary));
+        let _ = writeln!(out, "secondary = {}", color_hex(preset.palette.secondary));
+        let _ = writeln!(out, "accent = {}", color_hex(preset.palette.accent));
+        let _ = writeln!(out, "post = {}", post.join(","));
+        let _ = writeln!(out, "agc = {}", if preset.dsp.agc { "on" } else { "off" });
+        let _ = writeln!(out, "agc_release = {}", preset.dsp.agc_release);
+        let _ = writeln!(out, "gain = {}", preset.dsp.gain);
+        let _ = writeln!(out, "gate = {}", preset.dsp.gate);
+        let _ = writeln!(out, "tilt = {}", preset.dsp.tilt);
+        let trim: Vec<String> = preset.dsp.trim.iter().map(|db| db.to_string()).collect();
+        let _ = writeln!(out, "trim = {}", trim.join(" "));
+        let detector: Vec<&str> = preset.dsp.detector.iter().map(|d| d.id()).collect();
+        let _ = writeln!(out, "detector = {}", detector.join(" "));
+    }
+    std::fs::write(path, out).map_err(|e| format!("can't write {}: {}", path.display(), e))
+}
+
+pub fn load(path: &Path) -> Result<PresetBank, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("can't read {}: {}", path.display(), e))?;
+    parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn parse(text: &str) -> Result<PresetBank, String> {
+    let mut bank = PresetBank::new();
+    let mut current: Option<(usize, Preset)> = None;
+
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let at_line = |e: String| format!("line {}: {}", n + 1, e);
+
+        if let Some(header) = line.strip_prefix("[slot ").and_then(|l| l.strip_suffix(']')) {
+            if let Some((slot, preset)) = current.take() {
+                bank.set(slot, preset);
+            }
+            let slot: usize = header.trim().parse().map_err(|_| at_line(format!("invalid slot '{}'", header)))?;
+            if !(1..=PRESET_SLOTS).contains(&slot) {
+                return Err(at_line(format!("slot {} out of range (1-{})", slot, PRESET_SLOTS)));
+            }
+            current = Some((slot - 1, Preset::default()));
+            continue;
+        }
+
+        let (key, value) = line.split_once('=').ok_or_else(|| at_line(format!("expected key = value, got '{}'", line)))?;
+        let (key, value) = (key.trim(), value.trim());
+        let (_, preset) = current.as_mut().ok_or_else(|| at_line("key outside of a [slot N] section".into()))?;
+        apply_key(preset, key, value).map_err(at_line)?;
+    }
+    if let Some((slot, preset)) = current {
+        bank.set(slot, preset);
+    }
+    Ok(bank)
+}
+
+fn apply_key(preset: &mut Preset, key: &str, value: &str) -> Result<(), String> {
+    match key {
+        "name" => preset.name = Name::new(value),
+        "mode" => preset.mode = ModeKind::from_id(value).ok_or_else(|| format!("unknown mode '{}'", value))?,
+        "palette" => {
+            let colors: Vec<&str> = value.split_whitespace().collect();
+            if colors.len() != preset.palette.colors.len() {
+                return Err(format!("palette needs {} colors, got {}", preset.palette.colors.len(), colors.len()));
+            }
+            for (c, text) in preset.palette.colors.iter_mut().zip(colors) {
+                *c = parse_color(text)?;
+            }
+        }
+        "primary" => preset.palette.primary = parse_color(value)?,
+        "secondary" => preset.palette.secondary = parse_color(value)?,
+        "accent" => preset.palette.accent = parse_color(value)?,
+        "post" => {
+            preset.post = [None; MAX_POST_STAGES];
+            for (i, spec) in value.split(',').map(str::trim).filter(|s| !s.is_empty()).enumerate() {
+                let slot = preset.post.get_mut(i).ok_or_else(|| format!("more than {} post effects", MAX_POST_STAGES))?;
+                *slot = Some(PostFx::parse(spec).ok_or_else(|| format!("invalid effect '{}'", spec))?);
+            }
+        }
+        "agc" => {
+            preset.dsp.agc = match value {
+                "on" => true,
+                "off" => false,
+                _ => return Err(format!("agc must be on or off, got '{}'", value)),
+            }
+        }
+        "agc_release" => preset.dsp.agc_release = parse_f32(key, value)?,
+        "gain" => preset.dsp.gain = parse_f32(key, value)?,
+        "gate" => preset.dsp.gate = parse_f32(key, value)?.clamp(0.0, 1.0),
+        "tilt" => preset.dsp.tilt = parse_f32(key, value)?,
+        "trim" => {
+            // dB per band from the lowest, missing bands stay at 0
+            let values: Vec<&str> = value.split_whitespace().collect();
+            if values.len() > preset.dsp.trim.len() {
+                return Err(format!("trim takes at most {} values, got {}", preset.dsp.trim.len(), values.len()));
+            }
+            preset.dsp.trim = [0.0; CHANNELS];
+            for (db, text) in preset.dsp.trim.iter_mut().zip(values) {
+                *db = parse_f32(key, text)?;
+            }
+        }
+        "detector" => {
+            // peak, rms or log per band from the lowest, missing bands stay on peak
+            let values: Vec<&str> = value.split_whitespace().collect();
+            if values.len() > preset.dsp.detector.len() {
+                return Err(format!("detector takes at most {} values, got {}", preset.dsp.detector.len(), values.len()));
+            }
+            preset.dsp.detector = [Detector::Peak; CHANNELS];
+            for (detector, text) in preset.dsp.detector.iter_mut().zip(values) {
+                *detector = Detector::from_id(text).ok_or_else(|| format!("invalid detector '{}'", text))?;
+            }
+        }
+        _ => return Err(format!("unknown key '{}'", key)),
+    }
+    Ok(())
+}
@@ -0,0 +1,166 @@
+// --record: per-frame pitch, loudness and band energies, written as JSON lines (or CSV when the
+// file ends in .csv, or whole frames in the badge's serial telemetry framing for .bin), plus a
+// short summary printed at exit for tracking voice training over time
+
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::cobs;
+
+use girlvoice_ui_core::{AnalysisFrame, LoudnessTarget, LoudnessZone, PitchTarget, PitchZone};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    Jsonl,
+    Csv,
+    Telemetry, // AnalysisFrame::to_bytes, COBS encoded and 0 terminated like SerialSource reads
+}
+
+pub struct SessionRecorder {
+    out: BufWriter<File>,
+    path: PathBuf,
+    format: Format,
+    pitch_target: PitchTarget,
+    loudness_target: LoudnessTarget,
+    line: String, // reused for every frame
+    packet: Vec<u8>,
+    frames: u64,
+    duration: f64,
+    pitches: Vec<f32>, // every voiced frame, for the median
+    voiced_s: f64,
+    pitch_in_band_s: f64,
+    loudness_in_range_s: f64,
+    error: Option<io::Error>, // first write error, recording stops there
+}
+
+impl SessionRecorder {
+    pub fn create(path: &Path, num_channels: usize, pitch_target: PitchTarget, loudness_target: LoudnessTarget) -> io::Result<Self> {
+        let format = match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => Format::Csv,
+            Some(ext) if ext.eq_ignore_ascii_case("bin") => Format::Telemetry,
+            _ => Format::Jsonl,
+        };
+        let mut out = BufWriter::new(File::create(path)?);
+        if format == Format::Csv {
+            let bands: Vec<String> = (0..num_channels).map(|i| format!(",band{}", i)).collect();
+            writeln!(out, "time,pitch,loudness{}", bands.concat())?;
+        }
+        Ok(Self {
+            out,
+            path: path.to_path_buf(),
+            format,
+            pitch_target,
+            loudness_target,
+            line: String::new(),
+            packet: Vec::with_capacity(AnalysisFrame::ENCODED_LEN + 8),
+            frames: 0,
+            duration: 0.0,
+            pitches: Vec::new(),
+            voiced_s: 0.0,
+            pitch_in_band_s: 0.0,
+            loudness_in_range_s: 0.0,
+            error: None,
+        })
+    }
+
+    // one UI frame, `time` in seconds since the session started
+    pub fn record(&mut self, time: f64, dt: f32, frame: &AnalysisFrame) {
+        self.frames += 1;
+        self.duration = time;
+        if let Some(pitch) = frame.pitch {
+            let dt = dt as f64;
+            self.pitches.push(pitch);
+            self.voiced_s += dt;
+            if self.pitch_target.zone(pitch) == PitchZone::InBand {
+                self.pitch_in_band_s += dt;
+            }
+            if frame.loudness.is_some_and(|lufs| self.loudness_target.zone(lufs) == LoudnessZone::Good) {
+                self.loudness_in_range_s += dt;
+            }
+        }
+
+        if self.error.is_some() {
+            return;
+        }
+        self.line.clear();
+        self.packet.clear();
+        let _ = match self.format {
+            Format::Jsonl => write_json(&mut self.line, time, frame),
+            Format::Csv => write_csv(&mut self.line, time, frame),
+            Format::Telemetry => {
+                cobs::encode(&frame.to_bytes(), &mut self.packet);
+                self.packet.push(0);
+                Ok(())
+            }
+        };
+        if let Err(e) = self.out.write_all(self.line.as_bytes()).and_then(|()| self.out.write_all(&self.packet)) {
+            eprintln!("warning: recording to {} stopped: {}", self.path.display(), e);
+            self.error = Some(e);
+        }
+    }
+
+    // flush and print the session summary
+    pub fn finish(mut self) {
+        if self.error.is_none()
+            && let Err(e) = self.out.flush()
+        {
+            eprintln!("warning: can't finish {}: {}", self.path.display(), e);
+        }
+
+        let share = |seconds: f64| if self.voiced_s > 0.0 { 100.0 * seconds / self.voiced_s } else { 0.0 };
+        println!("Session recorded to {}: {} frame(s), {:.1} s", self.path.display(), self.frames, self.duration);
+        println!("  voiced:            {:.1} s", self.voiced_s);
+        match median(&mut self.pitches) {
+            Some(pitch) => println!("  median pitch:      {:.1} Hz", pitch),
+            None => println!("  median pitch:      -"),
+        }
+        println!(
+            "  pitch in band:     {:.0}% of voiced time ({:.0}-{:.0} Hz)",
+            share(self.pitch_in_band_s),
+            self.pitch_target.low,
+            self.pitch_target.high
+        );
+        println!(
+            "  loudness in range: {:.0}% of voiced time ({:.0} to {:.0} LUFS)",
+            share(self.loudness_in_range_s),
+            self.loudness_target.low,
+            self.loudness_target.high
+        );
+    }
+}
+
+fn median(values: &mut [f32]) -> Option<f32> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable_by(f32::total_cmp);
+    let mid = values.len() / 2;
+    Some(if values.len().is_multiple_of(2) { (values[mid - 1] + values[mid]) / 2.0 } else { values[mid] })
+}
+
+fn write_json(line: &mut String, time: f64, frame: &AnalysisFrame) -> std::fmt::Result {
+    let optional = |value: Option<f32>| value.map_or_else(|| "null".to_string(), |v| format!("{:.1}", v));
+    write!(line, "{{\"time\":{:.3},\"pitch\":{},\"loudness\":{},\"bands\":[", time, optional(frame.pitch), optional(frame.loudness))?;
+    for (i, energy) in frame.bands().iter().enumerate() {
+        write!(line, "{}{:.3}", if i > 0 { "," } else { "" }, energy)?;
+    }
+    writeln!(line, "]}}")
+}
+
+fn write_csv(line: &mut String, time: f64, frame: &AnalysisFrame) -> std::fmt::Result {
+    write!(line, "{:.3},", time)?;
+    if let Some(pitch) = frame.pitch {
+        write!(line, "{:.1}", pitch)?;
+    }
+    line.push(',');
+    if let Some(loudness) = frame.loudness {
+        write!(line, "{:.1}", loudness)?;
+    }
+    for energy in frame.bands() {
+        write!(line, ",{:.3}", energy)?;
+    }
+    line.push('\n');
+    Ok(())
+}
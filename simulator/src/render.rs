@@ -0,0 +1,129 @@
+// framebuffer passes shared by the window loop and the headless soak run
+
+use girlvoice_ui_core::{Color, Framebuffer, PostChain, RenderRegion, Visualizer, DISPLAY_REGION, DISPLAY_SIZE};
+
+// run the visualizer with additive blending onto the existing contents
+pub fn render_additive(visualizer: &Visualizer, framebuffer: &mut Framebuffer, brightness: f32) {
+    visualizer.render(|x, y, color| framebuffer.add(x, y, color.scale(brightness)));
+}
+
+// only the pixels of `region` (the kaleidoscope's wedge) on a grid of `step` (PowerProfile::Saver,
+// PostChain::end_frame doubles them)
+pub fn render_region_additive(visualizer: &Visualizer, framebuffer: &mut Framebuffer, region: &RenderRegion, brightness: f32, step: usize) {
+    visualizer.render_region(region, step, |x, y, color| framebuffer.add(x, y, color.scale(brightness)));
+}
+
+// std-only fast path for PostChain::begin_frame + render_additive: row bands on scoped threads.
+// each band is copied out as packed 0x00rrggbb (faded on the way through a table built from
+// Color::scale), blended with one saturating add per pixel instead of three, and copied back.
+// the output is bit-identical to the scalar pair, saturating adds of unsigned values end up the
+// same in any order
+pub struct ParallelRenderer {
+    threads: usize,
+}
+
+impl ParallelRenderer {
+    pub fn new(threads: usize) -> Self {
+        Self { threads: threads.clamp(1, DISPLAY_SIZE) }
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    pub fn render(&self, visualizer: &Visualizer, post: &PostChain, framebuffer: &mut Framebuffer, brightness: f32) {
+        let fade = fade_table(post.trail_decay(visualizer.displayed_mode()));
+        let rows_per_band = DISPLAY_SIZE.div_ceil(self.threads);
+        let fade = &fade;
+
+        std::thread::scope(|scope| {
+            for (band, pixels) in framebuffer.pixels_mut().chunks_mut(rows_per_band * Framebuffer::WIDTH).enumerate() {
+                let first_row = band * rows_per_band;
+                scope.spawn(move || {
+                    let mut packed: Vec<u32> = pixels.iter().map(|&p| pack(p)).collect();
+                    for (i, row) in packed.chunks_mut(Framebuffer::WIDTH).enumerate() {
+                        let (x_start, x_end) = DISPLAY_REGION.span(first_row + i);
+                        for p in &mut row[x_start..x_end] {
+                            let c = unpack(*p);
+                            *p = pack(Color::new(fade[c.r as usize], fade[c.g as usize], fade[c.b as usize]));
+                        }
+                    }
+
+                    let rows = first_row..first_row + pixels.len() / Framebuffer::WIDTH;
+                    visualizer.render_rows(rows, |x, y, color| {
+                        let p = &mut packed[(y - first_row) * Framebuffer::WIDTH + x];
+                        *p = add_packed(*p, pack(color.scale(brightness)));
+                    });
+
+                    for (p, &c) in pixels.iter_mut().zip(&packed) {
+                        *p = unpack(c);
+                    }
+                });
+            }
+        });
+    }
+}
+
+fn pack(c: Color) -> u32 {
+    ((c.r as u32) << 16) | ((c.g as u32) << 8) | c.b as u32
+}
+
+fn unpack(p: u32) -> Color {
+    Color::new((p >> 16) as u8, (p >> 8) as u8, p as u8)
+}
+
+// per-channel saturating add of two packed pixels. the low seven bits of each byte add without
+// reaching the next byte, the top bit and its carry out (a majority of a, b and the low sum) are
+// worked out on their own, and bytes that carried out are filled with 0xff
+fn add_packed(a: u32, b: u32) -> u32 {
+    const HIGH: u32 = 0x0080_8080;
+    let low = (a & !HIGH) + (b & !HIGH);
+    let sum = low ^ ((a ^ b) & HIGH);
+    let carries = ((a & b) | ((a | b) & low)) & HIGH;
+    sum | ((carries >> 7) * 0xff)
+}
+
+// channel value -> faded value, matching what begin_frame does per pixel
+fn fade_table(decay: f32) -> [u8; 256] {
+    core::array::from_fn(|v| {
+        if decay <= 0.0 {
+            0
+        } else if decay < 1.0 {
+            Color::new(v as u8, 0, 0).scale(decay).r
+        } else {
+            v as u8
+        }
+    })
+}
+
+pub const MASK_COLOR: Color = Color::new(8, 8, 8);
+const BEZEL_WIDTH: f32 = 9.0;
+const BEZEL_DARK: Color = Color::new(20, 20, 24);
+const BEZEL_LIGHT: Color = Color::new(110, 110, 120);
+
+// darken everything the round panel can't show, optionally with a shaded bezel ring around it
+pub fn apply_round_mask(framebuffer: &mut Framebuffer, bezel: bool) {
+    let center = DISPLAY_SIZE as f32 / 2.0;
+    for (y, x_start, x_end) in DISPLAY_REGION.rows() {
+        for x in (0..x_start).chain(x_end..DISPLAY_SIZE) {
+            let (dx, dy) = (x as f32 - center, y as f32 - center);
+            let dist = (dx * dx + dy * dy).sqrt() - center;
+
+            let color = if bezel && dist < BEZEL_WIDTH {
+                bezel_shade(dx, dy, dist / BEZEL_WIDTH)
+            } else {
+                MASK_COLOR
+            };
+            framebuffer.set(x, y, color);
+        }
+    }
+}
+
+// bezel ring color at offset (dx, dy) from the panel center, `depth` 0 at the glass edge to 1 outside
+pub fn bezel_shade(dx: f32, dy: f32, depth: f32) -> Color {
+    let r = (dx * dx + dy * dy).sqrt().max(1.0);
+    // lit from the top left, darker towards the outer edge
+    let light = 0.5 - 0.5 * (dx + dy) / (2.0f32.sqrt() * r);
+    let edge = 1.0 - depth.clamp(0.0, 1.0);
+    Color::lerp(BEZEL_DARK, BEZEL_LIGHT, light * (0.4 + 0.6 * edge))
+}
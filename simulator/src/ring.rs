@@ -0,0 +1,72 @@
+// --ring: the NeoPixel ring drawn around the window, one soft dot per LED on a dark band. the
+// LEDs sit just outside the window's picture of the panel, at the same angles as LedRing gives
+// them, so edge-following patterns line up with the display
+
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use girlvoice_ui_core::{Color, LedRing};
+
+const BAND_COLOR: Color = Color::new(14, 12, 16);
+const MARGIN: f32 = 0.12;  // of the panel picture's size, on each side
+const DOT_RADIUS: f32 = 0.3; // lit core of a dot, of the margin
+const GLOW_RADIUS: f32 = 0.5;
+
+pub struct RingView {
+    inner_size: usize,
+    margin: usize,
+    size: usize,
+    // (pixel, led, weight) for every pixel a dot reaches, weights 0-1
+    spots: Vec<(usize, usize, f32)>,
+    background: Vec<u32>,
+    buffer: Vec<u32>,
+}
+
+impl RingView {
+    pub fn new(inner_size: usize, leds: usize) -> Self {
+        let margin = (inner_size as f32 * MARGIN).round() as usize;
+        let size = inner_size + 2 * margin;
+        let center = size as f32 / 2.0;
+        let radius = inner_size as f32 / 2.0 + margin as f32 / 2.0;
+        let (dot, glow) = (margin as f32 * DOT_RADIUS, margin as f32 * GLOW_RADIUS);
+        let probe = LedRing::new(leds).expect("led count is checked by the command line parser");
+
+        let background = vec![BAND_COLOR.to_argb32(); size * size];
+        let mut spots = Vec::new();
+        for y in 0..size {
+            for x in 0..size {
+                let (dx, dy) = (x as f32 + 0.5 - center, y as f32 + 0.5 - center);
+                // nearest LED by angle, the dots never overlap
+                let angle = dy.atan2(dx);
+                let led = ((angle + FRAC_PI_2).rem_euclid(TAU) / TAU * leds as f32).round() as usize % leds;
+                let led_angle = probe.angle(led);
+                let (lx, ly) = (center + radius * led_angle.cos(), center + radius * led_angle.sin());
+                let d = ((x as f32 + 0.5 - lx).powi(2) + (y as f32 + 0.5 - ly).powi(2)).sqrt();
+                if d < glow {
+                    let core = (dot - d + 0.5).clamp(0.0, 1.0);
+                    let halo = (1.0 - (d - dot).max(0.0) / (glow - dot)).powi(2) * 0.5;
+                    let weight = core.max(halo);
+                    spots.push((y * size + x, led, weight));
+                }
+            }
+        }
+        Self { inner_size, margin, size, spots, buffer: background.clone(), background }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    // `inner` is the window picture without the ring, inner_size pixels square
+    pub fn compose(&mut self, inner: &[u32], ring: &LedRing) -> &[u32] {
+        self.buffer.copy_from_slice(&self.background);
+        for (row, line) in inner.chunks_exact(self.inner_size).enumerate() {
+            let start = (row + self.margin) * self.size + self.margin;
+            self.buffer[start..start + self.inner_size].copy_from_slice(line);
+        }
+        let leds = ring.leds();
+        for &(pixel, led, weight) in &self.spots {
+            self.buffer[pixel] = Color::lerp(BAND_COLOR, leds[led], weight).to_argb32();
+        }
+        &self.buffer
+    }
+}
@@ -0,0 +1,241 @@
+// headless soak run: hours of generated audio through source -> DSP -> visualizer -> framebuffer
+// as fast as the machine allows, failing on allocation growth, NaN/denormal energies,
+// normalization drift or frame times creeping up. run with --release for realistic timings.
+// the allocation counting needs the `soak` cargo feature, without it --soak reports that the
+// build has no soak support.
+
+#[cfg(feature = "soak")]
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicIsize, AtomicUsize, Ordering};
+use std::time::Instant;
+
+use girlvoice_ui_core::{ChannelCount, DspSettings, EnergySource, Framebuffer, ModeKind, PostChain, TimeOfDay, VocoderConfig, Visualizer};
+
+use crate::contrib;
+use crate::dsp;
+use crate::render;
+use crate::source::SyntheticSource;
+use crate::synth::Signal;
+
+// global allocator wrapper so the soak run can see every allocation
+#[cfg(feature = "soak")]
+pub struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static LIVE_BYTES: AtomicIsize = AtomicIsize::new(0);
+
+#[cfg(feature = "soak")]
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        LIVE_BYTES.fetch_add(layout.size() as isize, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE_BYTES.fetch_sub(layout.size() as isize, Ordering::Relaxed);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        LIVE_BYTES.fetch_add(new_size as isize - layout.size() as isize, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+const FPS: usize = 30;
+// long runs use minute windows, shorter ones split the run so there's at least one window
+// to check against the baseline
+const WINDOW_SECONDS: usize = 60;
+const MIN_WINDOW_SECONDS: usize = 10;
+// first window lets the peak normalization settle, the second one is the baseline
+const WARMUP_WINDOWS: usize = 1;
+const MIN_WINDOWS: usize = WARMUP_WINDOWS + 2;
+
+const MAX_ENERGY_DRIFT: f32 = 0.15;   // relative change of mean energy vs baseline window
+const MAX_FRAME_TIME_GROWTH: f32 = 2.0; // median frame time vs baseline window
+const FRAME_TIME_SLACK_US: f32 = 200.0; // ignore growth below this, scheduler noise
+
+struct WindowStats {
+    mean_energy: f32,
+    median_frame_us: f32,
+}
+
+pub struct SoakConfig {
+    pub signal: Signal,
+    pub seconds: u64,
+    pub channels: ChannelCount,
+    pub vocoder: VocoderConfig,
+    pub dsp: DspSettings,
+}
+
+// returns true when every check passed
+pub fn run(config: &SoakConfig) -> bool {
+    if !cfg!(feature = "soak") {
+        println!("FAIL: this build can't count allocations, rebuild with --features soak");
+        return false;
+    }
+    let seconds = config.seconds as usize;
+    let window_seconds = (seconds / MIN_WINDOWS).min(WINDOW_SECONDS);
+    if window_seconds < MIN_WINDOW_SECONDS {
+        println!("FAIL: a {}s soak is too short to check, it needs at least {}s", seconds, MIN_WINDOWS * MIN_WINDOW_SECONDS);
+        return false;
+    }
+    let windows = seconds.div_ceil(window_seconds);
+    let frames_per_window = FPS * window_seconds;
+    println!(
+        "Soak: {} windows of {}s with '{}' ({} channels)",
+        windows, window_seconds, config.signal.name(), config.channels.get()
+    );
+    dsp::print_channels(config.channels.get(), &config.vocoder);
+
+    let mut source = SyntheticSource::new(config.signal, config.channels, &config.vocoder, false);
+    source.set_dsp_settings(&config.dsp);
+    let mut visualizer = Visualizer::new(config.channels);
+    contrib::register(&mut visualizer);
+    let mut framebuffer = Box::new(Framebuffer::new());
+    let post = PostChain::default();
+    let mut frame_times = vec![0.0f32; frames_per_window];
+    let mut stats: Vec<WindowStats> = Vec::with_capacity(windows);
+
+    let samples_per_frame = SyntheticSource::SAMPLE_RATE as usize / FPS;
+    let dt = 1.0 / FPS as f32;
+
+    let mut failures = 0usize;
+    let mut bad_energies = 0usize;
+    let mut steady_allocations = 0usize;
+    let mut baseline_live_bytes = 0isize;
+
+    for window in 0..windows {
+        let allocs_before = ALLOCATIONS.load(Ordering::Relaxed);
+        let mut energy_sum = 0.0f64;
+
+        for frame_time in frame_times.iter_mut() {
+            let start = Instant::now();
+
+            source.advance(samples_per_frame);
+            let frame = source.snapshot();
+            let energies = frame.bands();
+            visualizer.update_frame(dt, &frame).expect("source built with the visualizer's channels");
+            post.begin_frame(&mut framebuffer, visualizer.displayed_mode());
+            render::render_additive(&visualizer, &mut framebuffer, 1.0);
+
+            *frame_time = start.elapsed().as_secs_f32() * 1e6;
+
+            for &e in energies {
+                if !e.is_finite() || e.is_subnormal() || !(0.0..=1.0).contains(&e) {
+                    bad_energies += 1;
+                }
+                energy_sum += e as f64;
+            }
+        }
+
+        let allocs = ALLOCATIONS.load(Ordering::Relaxed) - allocs_before;
+        if window >= WARMUP_WINDOWS {
+            steady_allocations += allocs;
+        }
+        if window == WARMUP_WINDOWS {
+            baseline_live_bytes = LIVE_BYTES.load(Ordering::Relaxed);
+        }
+
+        frame_times.sort_unstable_by(f32::total_cmp);
+        let current = WindowStats {
+            mean_energy: (energy_sum / (frames_per_window * config.channels.get()) as f64) as f32,
+            median_frame_us: frame_times[frames_per_window / 2],
+        };
+        println!(
+            "  [{:>6} s] mean energy {:.4}  median frame {:>7.1} us  p99 {:>7.1} us  allocs {}",
+            (window + 1) * window_seconds,
+            current.mean_energy,
+            current.median_frame_us,
+            frame_times[frames_per_window * 99 / 100],
+            allocs,
+        );
+
+        if window > WARMUP_WINDOWS {
+            let baseline = &stats[WARMUP_WINDOWS];
+            let drift = (current.mean_energy - baseline.mean_energy).abs() / baseline.mean_energy.max(1e-6);
+            if drift > MAX_ENERGY_DRIFT {
+                println!("  FAIL: normalization drifted {:.1}% from baseline", drift * 100.0);
+                failures += 1;
+            }
+            let limit = (baseline.median_frame_us * MAX_FRAME_TIME_GROWTH).max(baseline.median_frame_us + FRAME_TIME_SLACK_US);
+            if current.median_frame_us > limit {
+                println!(
+                    "  FAIL: median frame time {:.1} us exceeds {:.1} us",
+                    current.median_frame_us, limit
+                );
+                failures += 1;
+            }
+        }
+        stats.push(current);
+    }
+
+    let live_growth = LIVE_BYTES.load(Ordering::Relaxed) - baseline_live_bytes;
+    println!();
+    println!("Steady-state allocations: {}", steady_allocations);
+    println!("Live heap growth: {} bytes", live_growth);
+    println!("Invalid energies (NaN/inf/denormal/out of range): {}", bad_energies);
+
+    if steady_allocations > 0 || live_growth > 0 {
+        println!("FAIL: allocations in the steady-state frame loop");
+        failures += 1;
+    }
+    if bad_energies > 0 {
+        println!("FAIL: invalid band energies");
+        failures += 1;
+    }
+
+    // after the allocation checks, spawning render threads allocates
+    let mismatches = check_parallel_render(config);
+    println!("Parallel render mismatches vs scalar: {} frame(s)", mismatches);
+    if mismatches > 0 {
+        println!("FAIL: the multi-threaded renderer isn't bit-identical");
+        failures += 1;
+    }
+
+    if failures == 0 {
+        println!("Soak passed");
+    } else {
+        println!("Soak failed with {} problem(s)", failures);
+    }
+    failures == 0
+}
+
+const PARITY_FRAMES: usize = 120; // per mode
+const PARITY_THREADS: usize = 4;
+
+// renders every mode both ways from the same starting buffer, returns frames that differ
+fn check_parallel_render(config: &SoakConfig) -> usize {
+    let mut source = SyntheticSource::new(config.signal, config.channels, &config.vocoder, false);
+    source.set_dsp_settings(&config.dsp);
+    let mut visualizer = Visualizer::new(config.channels);
+    contrib::register(&mut visualizer);
+    let renderer = render::ParallelRenderer::new(PARITY_THREADS);
+    let post = PostChain::default();
+    let mut scalar = Box::new(Framebuffer::new());
+    let mut parallel = Box::new(Framebuffer::new());
+    let samples_per_frame = SyntheticSource::SAMPLE_RATE as usize / FPS;
+
+    // the first mode's frames start with the boot animation
+    visualizer.start_boot();
+    visualizer.set_time(TimeOfDay::new(10, 9, 30));
+    let mut mismatches = 0;
+    for mode in (0..ModeKind::count()).map(ModeKind::nth) {
+        visualizer.set_mode(mode);
+        for _ in 0..PARITY_FRAMES {
+            source.advance(samples_per_frame);
+            visualizer.update_frame(1.0 / FPS as f32, &source.snapshot()).expect("source built with the visualizer's channels");
+            post.begin_frame(&mut scalar, mode);
+            render::render_additive(&visualizer, &mut scalar, 0.9);
+            renderer.render(&visualizer, &post, &mut parallel, 0.9);
+            if scalar.pixels() != parallel.pixels() {
+                mismatches += 1;
+                // keep comparing from a common state
+                parallel.pixels_mut().copy_from_slice(scalar.pixels());
+            }
+        }
+    }
+    mismatches
+}
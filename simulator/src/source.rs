@@ -0,0 +1,713 @@
+// input sources for the simulator, all feeding the UI through core's EnergySource
+
+use std::fs::File;
+use std::io::{ErrorKind, Read};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Instant;
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SampleFormat, SizedSample, I24, U24};
+
+use girlvoice_ui_core::{
+    AnalysisFrame, ChannelCount, DspSettings, EnergySource, RingConsumer, RingProducer, SpscRing, TripleBuffer, TripleReader, TripleWriter,
+    VocoderConfig, WAVEFORM_LEN,
+};
+
+use crate::cobs;
+use crate::dsp::{FrameHop, VocoderDSP};
+use crate::error::GirlvoiceError;
+use crate::synth::{Signal, SignalGenerator};
+use crate::wav::Wav;
+
+// ring buffer of the most recent samples, unrolled into AnalysisFrame::waveform on poll
+struct WaveformRing {
+    samples: [f32; WAVEFORM_LEN],
+    pos: usize,
+}
+
+impl WaveformRing {
+    fn new() -> Self {
+        Self { samples: [0.0; WAVEFORM_LEN], pos: 0 }
+    }
+
+    fn push(&mut self, sample: f32) {
+        self.samples[self.pos] = sample;
+        self.pos = (self.pos + 1) % WAVEFORM_LEN;
+    }
+
+    // oldest sample first
+    fn copy_to(&self, out: &mut [f32; WAVEFORM_LEN]) {
+        for (i, o) in out.iter_mut().enumerate() {
+            *o = self.samples[(self.pos + i) % WAVEFORM_LEN];
+        }
+    }
+}
+
+// the --compare analysis: the source's samples again through a second DSP, with other
+// settings or another filterbank, framed on hops like the main one
+struct SecondDsp {
+    vocoder: VocoderConfig,
+    analyzer: VocoderDSP,
+    hop: FrameHop,
+    frame: AnalysisFrame, // the latest hop, events latched until the next poll
+}
+
+impl SecondDsp {
+    // builds `slot` the first time and whenever the filterbank layout changes
+    fn configure(slot: &mut Option<SecondDsp>, channels: ChannelCount, vocoder: &VocoderConfig, settings: &DspSettings, sample_rate: f32) {
+        if slot.as_ref().is_none_or(|second| second.vocoder != *vocoder) {
+            *slot = Some(SecondDsp {
+                vocoder: *vocoder,
+                analyzer: VocoderDSP::new(channels.get(), vocoder, sample_rate),
+                hop: FrameHop::new(channels),
+                frame: AnalysisFrame::new(channels),
+            });
+        }
+        if let Some(second) = slot {
+            second.analyzer.set_settings(settings);
+        }
+    }
+
+    fn process(&mut self, sample: f32) {
+        self.analyzer.process(sample);
+        if let Some(frame) = self.hop.push(sample, sample.abs(), &mut self.analyzer, None) {
+            self.frame.catch_up(frame);
+        }
+    }
+
+    fn poll(&mut self, waveform: &WaveformRing) -> AnalysisFrame {
+        let mut frame = self.frame;
+        waveform.copy_to(&mut frame.waveform);
+        self.frame.plosive = false;
+        self.frame.clip = false;
+        frame
+    }
+}
+
+// lock-free channels between the audio callback and the UI thread (same ones the firmware's
+// ISR uses), so the callback never blocks on the UI
+const SAMPLE_RING_LEN: usize = 8192; // ~170 ms at 48 kHz, the UI drains it every frame
+const FRAME_RING_LEN: usize = 64; // ~680 ms of hops
+
+struct CallbackLink {
+    frames: RingProducer<'static, AnalysisFrame, FRAME_RING_LEN>,
+    samples: RingProducer<'static, f32, SAMPLE_RING_LEN>,
+    settings: TripleReader<'static, DspSettings>,
+}
+
+struct MicLink {
+    frames: RingConsumer<'static, AnalysisFrame, FRAME_RING_LEN>,
+    samples: RingConsumer<'static, f32, SAMPLE_RING_LEN>,
+    settings: TripleWriter<'static, DspSettings>,
+}
+
+// the shared halves live for the rest of the run, the simulator opens one mic at startup
+fn link() -> (CallbackLink, MicLink) {
+    let frames = Box::leak(Box::new(SpscRing::new()));
+    let samples = Box::leak(Box::new(SpscRing::new()));
+    let settings = Box::leak(Box::new(TripleBuffer::new(DspSettings::default())));
+    let (frame_tx, frame_rx) = frames.split();
+    let (sample_tx, sample_rx) = samples.split();
+    let (settings_tx, settings_rx) = settings.split();
+    (
+        CallbackLink { frames: frame_tx, samples: sample_tx, settings: settings_rx },
+        MicLink { frames: frame_rx, samples: sample_rx, settings: settings_tx },
+    )
+}
+
+// live capture through cpal: the microphone, or the output signal for --source output
+pub struct MicSource {
+    link: MicLink,
+    frame: AnalysisFrame, // the latest hop, events latched until poll hands them over
+    waveform: WaveformRing,
+    channels: ChannelCount,
+    sample_rate: f32,
+    second: Option<SecondDsp>, // on the UI thread, from the samples the callback passes on
+    _stream: cpal::Stream, // keep alive, dropping stops capture
+}
+
+impl MicSource {
+    // `stereo` analyzes the first two input channels separately instead of their mix
+    pub fn new(channels: ChannelCount, vocoder: &VocoderConfig, stereo: bool) -> Result<Self, GirlvoiceError> {
+        let host = cpal::default_host();
+        let device = host.default_input_device().ok_or(GirlvoiceError::NoInputDevice)?;
+        println!("Using input device: {}", device_name(&device));
+
+        let config = device.default_input_config().map_err(|e| GirlvoiceError::DeviceConfig(e.to_string()))?;
+        Self::open(&device, config, channels, vocoder, stereo)
+    }
+
+    // what the system plays, e.g. the vocoded output. windows captures the default output
+    // through WASAPI loopback. elsewhere the output has to show up as a capture device:
+    // pulseaudio/pipewire "Monitor of ..." sources (found by default), or a loopback driver
+    // like BlackHole on macOS, picked by a substring of its name
+    pub fn output(device: Option<&str>, channels: ChannelCount, vocoder: &VocoderConfig) -> Result<Self, GirlvoiceError> {
+        let host = cpal::default_host();
+        if device.is_none() && cfg!(windows) {
+            let device = host.default_output_device().ok_or(GirlvoiceError::NoOutputDevice)?;
+            println!("Using output loopback: {}", device_name(&device));
+            let config = device.default_output_config().map_err(|e| GirlvoiceError::DeviceConfig(e.to_string()))?;
+            return Self::open(&device, config, channels, vocoder, false);
+        }
+
+        let wanted = device.unwrap_or("monitor").to_lowercase();
+        let devices: Vec<cpal::Device> = host.input_devices().map_err(|e| GirlvoiceError::DeviceConfig(e.to_string()))?.collect();
+        let names: Vec<String> = devices.iter().map(device_name).collect();
+        let Some(index) = names.iter().position(|name| name.to_lowercase().contains(&wanted)) else {
+            return Err(GirlvoiceError::NoMatchingDevice { wanted, available: names });
+        };
+        println!("Using output capture device: {}", names[index]);
+        let config = devices[index].default_input_config().map_err(|e| GirlvoiceError::DeviceConfig(e.to_string()))?;
+        Self::open(&devices[index], config, channels, vocoder, false)
+    }
+
+    fn open(
+        device: &cpal::Device,
+        config: cpal::SupportedStreamConfig,
+        channels: ChannelCount,
+        vocoder: &VocoderConfig,
+        stereo: bool,
+    ) -> Result<Self, GirlvoiceError> {
+        println!("Audio config: {:?}", config);
+
+        let sample_rate = config.sample_rate() as f32;
+        let (callback_link, link) = link();
+        let right = match (stereo, config.channels()) {
+            (false, _) => None,
+            (true, 1) => {
+                eprintln!("warning: the input device is mono, --stereo shows it on both sides");
+                None
+            }
+            (true, _) => Some(VocoderDSP::new(channels.get(), vocoder, sample_rate)),
+        };
+        let capture = Capture {
+            analyzer: VocoderDSP::new(channels.get(), vocoder, sample_rate),
+            right,
+            hop: FrameHop::new(channels),
+            channels: config.channels() as usize,
+            link: callback_link,
+        };
+
+        // every format cpal delivers goes through the same callback, only the conversion differs
+        let stream_config = config.config();
+        let stream = match config.sample_format() {
+            SampleFormat::I8 => build_stream::<i8>(device, &stream_config, capture),
+            SampleFormat::I16 => build_stream::<i16>(device, &stream_config, capture),
+            SampleFormat::I24 => build_stream::<I24>(device, &stream_config, capture),
+            SampleFormat::I32 => build_stream::<i32>(device, &stream_config, capture),
+            SampleFormat::I64 => build_stream::<i64>(device, &stream_config, capture),
+            SampleFormat::U8 => build_stream::<u8>(device, &stream_config, capture),
+            SampleFormat::U16 => build_stream::<u16>(device, &stream_config, capture),
+            SampleFormat::U24 => build_stream::<U24>(device, &stream_config, capture),
+            SampleFormat::U32 => build_stream::<u32>(device, &stream_config, capture),
+            SampleFormat::U64 => build_stream::<u64>(device, &stream_config, capture),
+            SampleFormat::F32 => build_stream::<f32>(device, &stream_config, capture),
+            SampleFormat::F64 => build_stream::<f64>(device, &stream_config, capture),
+            format => Err(GirlvoiceError::UnsupportedFormat(format.to_string())),
+        }?;
+
+        stream.play().map_err(|e| GirlvoiceError::Stream(e.to_string()))?;
+        println!("Audio stream started\n");
+
+        Ok(Self {
+            link,
+            frame: AnalysisFrame::new(channels),
+            waveform: WaveformRing::new(),
+            channels,
+            sample_rate,
+            second: None,
+            _stream: stream,
+        })
+    }
+}
+
+fn device_name(device: &cpal::Device) -> String {
+    device.description().map(|d| d.name().to_string()).unwrap_or_default()
+}
+
+// what the audio callback owns. samples come in as f32 whatever the device delivers,
+// downmixed to mono. with a `right` analyzer the first channel goes to `analyzer` and the
+// second to `right`. a frame goes out every hop, however many hops the device's buffer size
+// makes per callback
+struct Capture {
+    analyzer: VocoderDSP,
+    right: Option<VocoderDSP>,
+    hop: FrameHop,
+    channels: usize, // interleaved in the device's buffers
+    link: CallbackLink,
+}
+
+impl Capture {
+    // unsigned formats sit around half their range, to_sample centers them on 0
+    fn process<T>(&mut self, data: &[T])
+    where
+        T: SizedSample,
+        f32: FromSample<T>,
+    {
+        if let Some(settings) = self.link.settings.read_new() {
+            self.analyzer.set_settings(settings);
+            if let Some(right) = &mut self.right {
+                right.set_settings(settings);
+            }
+        }
+
+        for chunk in data.chunks(self.channels) {
+            let (sample, level) = match &mut self.right {
+                Some(right) => {
+                    let (first, second) = (chunk[0].to_sample::<f32>(), chunk[1].to_sample::<f32>());
+                    right.process(second);
+                    (first, first.abs().max(second.abs()))
+                }
+                None => {
+                    let sample = chunk.iter().map(|&s| s.to_sample::<f32>()).sum::<f32>() / chunk.len() as f32;
+                    (sample, sample.abs())
+                }
+            };
+            self.analyzer.process(sample);
+            self.link.samples.push(sample); // dropped while the UI is stalled
+            if let Some(frame) = self.hop.push(sample, level, &mut self.analyzer, self.right.as_ref()) {
+                self.link.frames.push(*frame); // likewise
+            }
+        }
+    }
+}
+
+fn build_stream<T>(device: &cpal::Device, config: &cpal::StreamConfig, mut capture: Capture) -> Result<cpal::Stream, GirlvoiceError>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| capture.process(data),
+            |err| eprintln!("Audio error: {}", err),
+            None,
+        )
+        .map_err(|e| GirlvoiceError::Stream(e.to_string()))
+}
+
+impl EnergySource for MicSource {
+    fn poll(&mut self) -> AnalysisFrame {
+        let mut latest = self.frame;
+        self.poll_frames(&mut |frame| latest.catch_up(frame));
+        self.waveform.copy_to(&mut latest.waveform); // newer than the last hop's
+        latest
+    }
+
+    fn poll_frames(&mut self, on_frame: &mut dyn FnMut(&AnalysisFrame)) {
+        let mut chunk = [0.0f32; 256];
+        loop {
+            let n = self.link.samples.pop_slice(&mut chunk);
+            for &sample in &chunk[..n] {
+                self.waveform.push(sample);
+                if let Some(second) = &mut self.second {
+                    second.process(sample);
+                }
+            }
+            if n < chunk.len() {
+                break;
+            }
+        }
+
+        // only the newest frame gets the waveform, the ring has no per-hop history
+        let mut last = None;
+        while let Some(frame) = self.link.frames.pop() {
+            if let Some(previous) = last.replace(frame) {
+                on_frame(&previous);
+            }
+        }
+        if let Some(mut frame) = last {
+            self.waveform.copy_to(&mut frame.waveform);
+            on_frame(&frame);
+            self.frame = AnalysisFrame { plosive: false, clip: false, ..frame };
+        }
+    }
+
+    fn num_channels(&self) -> ChannelCount {
+        self.channels
+    }
+
+    fn set_dsp_settings(&mut self, settings: &DspSettings) {
+        self.link.settings.write(*settings);
+    }
+
+    fn set_comparison(&mut self, vocoder: &VocoderConfig, settings: &DspSettings) -> bool {
+        SecondDsp::configure(&mut self.second, self.channels, vocoder, settings, self.sample_rate);
+        true
+    }
+
+    fn poll_comparison(&mut self) -> Option<AnalysisFrame> {
+        Some(self.second.as_mut()?.poll(&self.waveform))
+    }
+}
+
+// samples due since the last poll for the sources that generate their own, paced by
+// wall-clock time
+struct Pace {
+    last_poll: Instant,
+}
+
+impl Pace {
+    // don't try to catch up after a stall (debugger, window drag)
+    const MAX_CATCH_UP: f32 = 0.1;
+
+    fn new() -> Self {
+        Self { last_poll: Instant::now() }
+    }
+
+    fn due(&mut self, sample_rate: f32) -> usize {
+        let now = Instant::now();
+        let elapsed = (now - self.last_poll).as_secs_f32().min(Self::MAX_CATCH_UP);
+        self.last_poll = now;
+        (elapsed * sample_rate) as usize
+    }
+}
+
+// built-in test signals run through the real DSP, paced by wall-clock time
+pub struct SyntheticSource {
+    generator: SignalGenerator,
+    analyzer: VocoderDSP,
+    ambient: Option<(SignalGenerator, VocoderDSP)>, // stand-in for a second mic
+    second: Option<SecondDsp>,
+    hop: FrameHop,
+    frame: AnalysisFrame, // the latest hop, events latched until the next advance
+    channels: ChannelCount,
+    waveform: WaveformRing,
+    pace: Pace,
+}
+
+impl SyntheticSource {
+    pub const SAMPLE_RATE: f32 = 48000.0;
+    const AMBIENT_LEVEL: f32 = 0.25;
+
+    // `stereo` adds quiet pink noise as the right input, like an ambient mic in a noisy room
+    pub fn new(signal: Signal, channels: ChannelCount, vocoder: &VocoderConfig, stereo: bool) -> Self {
+        let ambient = stereo.then(|| {
+            (
+                SignalGenerator::new(Signal::PinkNoise, Self::SAMPLE_RATE),
+                VocoderDSP::new(channels.get(), vocoder, Self::SAMPLE_RATE),
+            )
+        });
+        Self {
+            generator: SignalGenerator::new(signal, Self::SAMPLE_RATE),
+            analyzer: VocoderDSP::new(channels.get(), vocoder, Self::SAMPLE_RATE),
+            ambient,
+            second: None,
+            hop: FrameHop::new(channels),
+            frame: AnalysisFrame::new(channels),
+            channels,
+            waveform: WaveformRing::new(),
+            pace: Pace::new(),
+        }
+    }
+
+    // run a fixed number of samples, independent of wall-clock time
+    pub fn advance(&mut self, num_samples: usize) {
+        self.run(num_samples, &mut |_| {});
+    }
+
+    fn run(&mut self, num_samples: usize, on_frame: &mut dyn FnMut(&AnalysisFrame)) {
+        self.frame.plosive = false;
+        self.frame.clip = false;
+        for _ in 0..num_samples {
+            let sample = self.generator.next_sample();
+            self.analyzer.process(sample);
+            self.waveform.push(sample);
+            if let Some(second) = &mut self.second {
+                second.process(sample);
+            }
+            let mut right = None;
+            if let Some((generator, analyzer)) = &mut self.ambient {
+                analyzer.process(generator.next_sample() * Self::AMBIENT_LEVEL);
+                right = Some(&*analyzer);
+            }
+            if let Some(frame) = self.hop.push(sample, sample.abs(), &mut self.analyzer, right) {
+                self.frame.catch_up(frame);
+                let mut frame = *frame;
+                self.waveform.copy_to(&mut frame.waveform);
+                on_frame(&frame);
+            }
+        }
+    }
+
+    // latest frame without advancing time
+    pub fn snapshot(&self) -> AnalysisFrame {
+        let mut frame = self.frame;
+        self.waveform.copy_to(&mut frame.waveform);
+        frame
+    }
+}
+
+impl EnergySource for SyntheticSource {
+    fn poll(&mut self) -> AnalysisFrame {
+        let num_samples = self.pace.due(Self::SAMPLE_RATE);
+        self.advance(num_samples);
+        self.snapshot()
+    }
+
+    fn poll_frames(&mut self, on_frame: &mut dyn FnMut(&AnalysisFrame)) {
+        let num_samples = self.pace.due(Self::SAMPLE_RATE);
+        self.run(num_samples, on_frame);
+    }
+
+    fn num_channels(&self) -> ChannelCount {
+        self.channels
+    }
+
+    fn set_dsp_settings(&mut self, settings: &DspSettings) {
+        self.analyzer.set_settings(settings);
+        if let Some((_, analyzer)) = &mut self.ambient {
+            analyzer.set_settings(settings);
+        }
+    }
+
+    fn set_comparison(&mut self, vocoder: &VocoderConfig, settings: &DspSettings) -> bool {
+        SecondDsp::configure(&mut self.second, self.channels, vocoder, settings, Self::SAMPLE_RATE);
+        true
+    }
+
+    fn poll_comparison(&mut self) -> Option<AnalysisFrame> {
+        Some(self.second.as_mut()?.poll(&self.waveform))
+    }
+}
+
+// a wav file played through the real DSP in a loop, paced by wall-clock time. channels are
+// mixed down, or with `stereo` the first two analyzed separately like the mic's
+pub struct WavSource {
+    wav: Wav,
+    position: usize, // next sample frame
+    analyzer: VocoderDSP,
+    right: Option<VocoderDSP>,
+    second: Option<SecondDsp>,
+    hop: FrameHop,
+    frame: AnalysisFrame, // the latest hop, events latched until the next poll
+    channels: ChannelCount,
+    waveform: WaveformRing,
+    pace: Pace,
+}
+
+impl WavSource {
+    pub fn open(path: &Path, channels: ChannelCount, vocoder: &VocoderConfig, stereo: bool) -> Result<Self, GirlvoiceError> {
+        let wav = Wav::read(path).map_err(|reason| GirlvoiceError::Playback { path: path.to_path_buf(), reason })?;
+        if wav.frames() == 0 {
+            return Err(GirlvoiceError::Playback { path: path.to_path_buf(), reason: "no samples".to_string() });
+        }
+        println!(
+            "Playing {}: {:.1}s, {} channel(s) at {} Hz, looped",
+            path.display(), wav.seconds(), wav.channels, wav.sample_rate
+        );
+        let right = match (stereo, wav.channels) {
+            (false, _) => None,
+            (true, 1) => {
+                eprintln!("warning: {} is mono, --stereo shows it on both sides", path.display());
+                None
+            }
+            (true, _) => Some(VocoderDSP::new(channels.get(), vocoder, wav.sample_rate)),
+        };
+        Ok(Self {
+            analyzer: VocoderDSP::new(channels.get(), vocoder, wav.sample_rate),
+            right,
+            second: None,
+            wav,
+            position: 0,
+            hop: FrameHop::new(channels),
+            frame: AnalysisFrame::new(channels),
+            channels,
+            waveform: WaveformRing::new(),
+            pace: Pace::new(),
+        })
+    }
+
+    fn run(&mut self, num_samples: usize, on_frame: &mut dyn FnMut(&AnalysisFrame)) {
+        self.frame.plosive = false;
+        self.frame.clip = false;
+        let width = self.wav.channels;
+        for _ in 0..num_samples {
+            let chunk = &self.wav.samples[self.position * width..(self.position + 1) * width];
+            self.position = (self.position + 1) % self.wav.frames();
+            let (sample, level) = match &mut self.right {
+                Some(right) => {
+                    right.process(chunk[1]);
+                    (chunk[0], chunk[0].abs().max(chunk[1].abs()))
+                }
+                None => {
+                    let sample = chunk.iter().sum::<f32>() / width as f32;
+                    (sample, sample.abs())
+                }
+            };
+            self.analyzer.process(sample);
+            self.waveform.push(sample);
+            if let Some(second) = &mut self.second {
+                second.process(sample);
+            }
+            if let Some(frame) = self.hop.push(sample, level, &mut self.analyzer, self.right.as_ref()) {
+                self.frame.catch_up(frame);
+                let mut frame = *frame;
+                self.waveform.copy_to(&mut frame.waveform);
+                on_frame(&frame);
+            }
+        }
+    }
+}
+
+impl EnergySource for WavSource {
+    fn poll(&mut self) -> AnalysisFrame {
+        let num_samples = self.pace.due(self.wav.sample_rate);
+        self.run(num_samples, &mut |_| {});
+        let mut frame = self.frame;
+        self.waveform.copy_to(&mut frame.waveform);
+        frame
+    }
+
+    fn poll_frames(&mut self, on_frame: &mut dyn FnMut(&AnalysisFrame)) {
+        let num_samples = self.pace.due(self.wav.sample_rate);
+        self.run(num_samples, on_frame);
+    }
+
+    fn num_channels(&self) -> ChannelCount {
+        self.channels
+    }
+
+    fn set_dsp_settings(&mut self, settings: &DspSettings) {
+        self.analyzer.set_settings(settings);
+        if let Some(right) = &mut self.right {
+            right.set_settings(settings);
+        }
+    }
+
+    fn set_comparison(&mut self, vocoder: &VocoderConfig, settings: &DspSettings) -> bool {
+        SecondDsp::configure(&mut self.second, self.channels, vocoder, settings, self.wav.sample_rate);
+        true
+    }
+
+    fn poll_comparison(&mut self) -> Option<AnalysisFrame> {
+        Some(self.second.as_mut()?.poll(&self.waveform))
+    }
+}
+
+// analysis frames from the badge over its usb serial port, in AnalysisFrame::to_bytes layout,
+// each COBS encoded and ended by a 0 byte. the port is read like a file: the badge's usb
+// serial ignores the line rate, a real uart has to be set up beforehand (stty). the DSP runs
+// on the badge, so set_dsp_settings has nothing to tune here
+pub struct SerialSource {
+    frames: Receiver<AnalysisFrame>,
+    frame: AnalysisFrame, // the latest, events latched until poll hands them over
+    channels: ChannelCount,
+}
+
+impl SerialSource {
+    pub fn open(path: &Path, channels: ChannelCount) -> Result<Self, GirlvoiceError> {
+        let port = File::open(path).map_err(|e| GirlvoiceError::Telemetry { path: path.to_path_buf(), reason: e.to_string() })?;
+        println!("Reading telemetry from {}", path.display());
+        let (tx, rx) = mpsc::channel();
+        let name = path.display().to_string();
+        thread::spawn(move || read_telemetry(port, &name, |frame| tx.send(frame).is_ok()));
+        Ok(Self { frames: rx, frame: AnalysisFrame::new(channels), channels })
+    }
+}
+
+// until the port closes or `send` says nobody is listening
+fn read_telemetry(mut port: File, name: &str, mut send: impl FnMut(AnalysisFrame) -> bool) {
+    let mut buf = [0u8; 1024];
+    let mut packet = Vec::with_capacity(AnalysisFrame::ENCODED_LEN + 8);
+    let mut decoded = Vec::with_capacity(AnalysisFrame::ENCODED_LEN);
+    let mut synced = false; // opened mid-stream, the bytes before the first 0 are a partial packet
+    let mut warned = false;
+    loop {
+        let n = match port.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => {
+                eprintln!("warning: telemetry from {} stopped ({})", name, e);
+                return;
+            }
+        };
+        for &byte in &buf[..n] {
+            if byte != 0 {
+                packet.push(byte);
+                continue;
+            }
+            let frame = match cobs::decode(&packet, &mut decoded) {
+                Some(()) => AnalysisFrame::from_bytes(&decoded).map_err(|e| e.to_string()),
+                None => Err("bad framing".to_string()),
+            };
+            packet.clear();
+            match frame {
+                _ if !synced => synced = true,
+                Ok(frame) => {
+                    if !send(frame) {
+                        return;
+                    }
+                }
+                Err(e) if !warned => {
+                    eprintln!("warning: dropping telemetry frames from {} ({})", name, e);
+                    warned = true;
+                }
+                Err(_) => {}
+            }
+        }
+    }
+    eprintln!("warning: telemetry from {} ended", name);
+}
+
+impl EnergySource for SerialSource {
+    fn poll(&mut self) -> AnalysisFrame {
+        let mut latest = self.frame;
+        self.poll_frames(&mut |frame| latest.catch_up(frame));
+        latest
+    }
+
+    fn poll_frames(&mut self, on_frame: &mut dyn FnMut(&AnalysisFrame)) {
+        while let Ok(frame) = self.frames.try_recv() {
+            on_frame(&frame);
+            self.frame = AnalysisFrame { plosive: false, clip: false, ..frame };
+        }
+    }
+
+    fn num_channels(&self) -> ChannelCount {
+        self.channels
+    }
+}
+
+// two sources side by side: `left` is the main one, `right` only adds its bands as
+// AnalysisFrame::right, so the split mode shows e.g. the mic input next to the vocoded output
+pub struct PairedSource {
+    left: Box<dyn EnergySource>,
+    right: Box<dyn EnergySource>,
+}
+
+impl PairedSource {
+    pub fn new(left: Box<dyn EnergySource>, right: Box<dyn EnergySource>) -> Self {
+        Self { left, right }
+    }
+}
+
+impl EnergySource for PairedSource {
+    fn poll(&mut self) -> AnalysisFrame {
+        let mut frame = self.left.poll();
+        frame.right = Some(self.right.poll().energies);
+        frame
+    }
+
+    fn num_channels(&self) -> ChannelCount {
+        self.left.num_channels()
+    }
+
+    fn set_dsp_settings(&mut self, settings: &DspSettings) {
+        self.left.set_dsp_settings(settings);
+        self.right.set_dsp_settings(settings);
+    }
+
+    // the comparison is of the main (left) input
+    fn set_comparison(&mut self, vocoder: &VocoderConfig, settings: &DspSettings) -> bool {
+        self.left.set_comparison(vocoder, settings)
+    }
+
+    fn poll_comparison(&mut self) -> Option<AnalysisFrame> {
+        self.left.poll_comparison()
+    }
+}
@@ -0,0 +1,38 @@
+// file-backed SettingsStore, the simulator's stand-in for the badge's flash sector
+
+use std::path::PathBuf;
+
+use girlvoice_ui_core::{SettingsStore, StoreError, SETTINGS_BLOB_LEN};
+
+pub struct FileStore {
+    path: PathBuf,
+}
+
+impl FileStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl SettingsStore for FileStore {
+    fn load(&mut self, buf: &mut [u8]) -> Result<usize, StoreError> {
+        let data = match std::fs::read(&self.path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(_) => return Err(StoreError::Io),
+        };
+        let out = buf.get_mut(..data.len()).ok_or(StoreError::TooLarge)?;
+        out.copy_from_slice(&data);
+        Ok(data.len())
+    }
+
+    // write a temp file and rename it over the old one, so a crash never leaves half a blob
+    fn save(&mut self, data: &[u8]) -> Result<(), StoreError> {
+        if data.len() > SETTINGS_BLOB_LEN {
+            return Err(StoreError::TooLarge);
+        }
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, data).map_err(|_| StoreError::Io)?;
+        std::fs::rename(&tmp, &self.path).map_err(|_| StoreError::Io)
+    }
+}
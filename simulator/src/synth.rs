@@ -0,0 +1,146 @@
+// synthetic test signals, so visual modes can be developed without audio hardware
+
+use std::f32::consts::TAU;
+
+use girlvoice_ui_core::Rng;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Signal {
+    Sweep,     // log sine sweep across the vocoder range
+    PinkNoise, // equal energy per octave, lights every band evenly
+    Speech,    // voiced harmonic bursts with syllable-like timing
+    Metronome, // short clicks at 120 bpm, good for checking attack/release
+}
+
+impl Signal {
+    pub const ALL: [Signal; 4] = [Signal::Sweep, Signal::PinkNoise, Signal::Speech, Signal::Metronome];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Signal::Sweep => "sweep",
+            Signal::PinkNoise => "pink",
+            Signal::Speech => "speech",
+            Signal::Metronome => "metronome",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Signal> {
+        Self::ALL.into_iter().find(|s| s.name() == name)
+    }
+}
+
+const SWEEP_SECONDS: f32 = 4.0;
+const SWEEP_LOW: f32 = 80.0;
+const SWEEP_HIGH: f32 = 4000.0;
+const METRONOME_BPM: f32 = 120.0;
+// (F1, F2) per syllable: the original neutral hump, then roughly "ah", "ee", "oo", "eh"
+const VOWELS: [(f32, f32); 5] = [(500.0, 1800.0), (800.0, 1200.0), (300.0, 2300.0), (320.0, 850.0), (550.0, 1900.0)];
+
+pub struct SignalGenerator {
+    signal: Signal,
+    sample_rate: f32,
+    sample_index: u64,
+    phase: f32,
+    rng: Rng,
+    pink: [f32; 7], // Paul Kellet's pink filter state
+    // speech burst state
+    burst_remaining: u32,
+    gap_remaining: u32,
+    pitch: f32,
+    vowel: (f32, f32), // formant centers of the current syllable
+}
+
+impl SignalGenerator {
+    pub fn new(signal: Signal, sample_rate: f32) -> Self {
+        Self {
+            signal,
+            sample_rate,
+            sample_index: 0,
+            phase: 0.0,
+            rng: Rng::new(0x9E37_79B9),
+            pink: [0.0; 7],
+            burst_remaining: 0,
+            gap_remaining: 0,
+            pitch: 180.0,
+            vowel: VOWELS[0],
+        }
+    }
+
+    pub fn next_sample(&mut self) -> f32 {
+        let index = self.sample_index;
+        self.sample_index += 1;
+
+        match self.signal {
+            Signal::Sweep => {
+                // wrap in integer samples so long runs don't lose f32 precision
+                let period = (SWEEP_SECONDS * self.sample_rate) as u64;
+                let pos = (index % period) as f32 / period as f32;
+                let freq = SWEEP_LOW * (SWEEP_HIGH / SWEEP_LOW).powf(pos);
+                self.advance_phase(freq);
+                0.5 * self.phase.sin()
+            }
+            Signal::PinkNoise => 0.3 * self.pink_sample(),
+            Signal::Speech => self.speech_sample(),
+            Signal::Metronome => {
+                let beat = (60.0 / METRONOME_BPM * self.sample_rate) as u64;
+                let since_beat = (index % beat) as f32 / self.sample_rate;
+                if since_beat < 0.03 {
+                    let env = 1.0 - since_beat / 0.03;
+                    0.8 * env * env * (TAU * 1000.0 * since_beat).sin()
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+
+    fn advance_phase(&mut self, freq: f32) {
+        self.phase += TAU * freq / self.sample_rate;
+        if self.phase > TAU {
+            self.phase -= TAU;
+        }
+    }
+
+    fn pink_sample(&mut self) -> f32 {
+        let white = self.rng.next_f32() * 2.0 - 1.0;
+        let p = &mut self.pink;
+        p[0] = 0.99886 * p[0] + white * 0.0555179;
+        p[1] = 0.99332 * p[1] + white * 0.0750759;
+        p[2] = 0.96900 * p[2] + white * 0.153852;
+        p[3] = 0.86650 * p[3] + white * 0.3104856;
+        p[4] = 0.55000 * p[4] + white * 0.5329522;
+        p[5] = -0.7616 * p[5] - white * 0.0168980;
+        let out = p[0] + p[1] + p[2] + p[3] + p[4] + p[5] + p[6] + white * 0.5362;
+        p[6] = white * 0.115926;
+        out * 0.11
+    }
+
+    fn speech_sample(&mut self) -> f32 {
+        if self.burst_remaining == 0 && self.gap_remaining == 0 {
+            // new syllable: 120-320 ms voiced, 60-260 ms pause, pitch wanders around 180 Hz
+            let r = self.rng.next_u32();
+            self.burst_remaining = ((0.12 + (r % 200) as f32 / 1000.0) * self.sample_rate) as u32;
+            self.gap_remaining = ((0.06 + ((r >> 8) % 200) as f32 / 1000.0) * self.sample_rate) as u32;
+            self.pitch = 150.0 + ((r >> 16) % 90) as f32;
+            self.vowel = VOWELS[(r >> 24) as usize % VOWELS.len()];
+        }
+
+        if self.burst_remaining > 0 {
+            self.burst_remaining -= 1;
+            self.advance_phase(self.pitch);
+
+            // harmonic stack with two formant humps, placed per syllable
+            let mut out = 0.0;
+            for h in 1..=16 {
+                let freq = self.pitch * h as f32;
+                let f1 = 1.0 / (1.0 + ((freq - self.vowel.0) / 250.0).powi(2));
+                let f2 = 0.6 / (1.0 + ((freq - self.vowel.1) / 400.0).powi(2));
+                out += (f1 + f2 + 0.05) * (self.phase * h as f32).sin();
+            }
+            0.2 * out + 0.01 * (self.rng.next_f32() * 2.0 - 1.0)
+        } else {
+            self.gap_remaining -= 1;
+            0.005 * (self.rng.next_f32() * 2.0 - 1.0)
+        }
+    }
+}
@@ -0,0 +1,96 @@
+// display -> window upscaling with a buffer allocated once up front. integer math only:
+// nearest for 1x-4x, bilinear for 2x (each output pixel mixes its four nearest source
+// pixels 9:3:3:1, edges clamped).
+
+use girlvoice_ui_core::DISPLAY_SIZE;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Filter {
+    Nearest,
+    Bilinear,
+}
+
+impl Filter {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Filter::Nearest => "nearest",
+            Filter::Bilinear => "bilinear",
+        }
+    }
+}
+
+pub struct Upscaler {
+    scale: usize,
+    filter: Filter,
+    buffer: Vec<u32>,
+}
+
+impl Upscaler {
+    pub const MAX_SCALE: usize = 4;
+
+    // None for combinations that aren't supported (bilinear is 2x only)
+    pub fn new(scale: usize, filter: Filter) -> Option<Self> {
+        let supported = match filter {
+            Filter::Nearest => (1..=Self::MAX_SCALE).contains(&scale),
+            Filter::Bilinear => scale == 2,
+        };
+        let len = if scale > 1 { (DISPLAY_SIZE * scale).pow(2) } else { 0 };
+        supported.then(|| Self { scale, filter, buffer: vec![0; len] })
+    }
+
+    // window width and height in pixels
+    pub fn size(&self) -> usize {
+        DISPLAY_SIZE * self.scale
+    }
+
+    // `display` is DISPLAY_SIZE x DISPLAY_SIZE ARGB32, 1x hands it back untouched
+    pub fn upscale<'a>(&'a mut self, display: &'a [u32]) -> &'a [u32] {
+        match (self.scale, self.filter) {
+            (1, _) => return display,
+            (_, Filter::Nearest) => self.nearest(display),
+            (_, Filter::Bilinear) => self.bilinear_2x(display),
+        }
+        &self.buffer
+    }
+
+    fn nearest(&mut self, display: &[u32]) {
+        let scale = self.scale;
+        let width = self.size();
+        for (y, src_row) in display.chunks_exact(DISPLAY_SIZE).enumerate() {
+            let first = y * scale * width;
+            let (row, rest) = self.buffer[first..first + scale * width].split_at_mut(width);
+            for (out, &color) in row.chunks_exact_mut(scale).zip(src_row) {
+                out.fill(color);
+            }
+            for copy in rest.chunks_exact_mut(width) {
+                copy.copy_from_slice(row);
+            }
+        }
+    }
+
+    fn bilinear_2x(&mut self, display: &[u32]) {
+        let width = self.size();
+        let last = DISPLAY_SIZE - 1;
+        for oy in 0..width {
+            // output pixel centers sit a quarter pixel before or after a source center
+            let y = oy / 2;
+            let y2 = if oy % 2 == 0 { y.saturating_sub(1) } else { (y + 1).min(last) };
+            let (row, row2) = (&display[y * DISPLAY_SIZE..][..DISPLAY_SIZE], &display[y2 * DISPLAY_SIZE..][..DISPLAY_SIZE]);
+            let out = &mut self.buffer[oy * width..][..width];
+            for (ox, o) in out.iter_mut().enumerate() {
+                let x = ox / 2;
+                let x2 = if ox % 2 == 0 { x.saturating_sub(1) } else { (x + 1).min(last) };
+                *o = mix_9331(row[x], row[x2], row2[x], row2[x2]);
+            }
+        }
+    }
+}
+
+// (9a + 3b + 3c + d) / 16 per channel, rounded, alpha forced opaque
+fn mix_9331(a: u32, b: u32, c: u32, d: u32) -> u32 {
+    let channel = |shift: u32| {
+        let v = |p: u32| (p >> shift) & 0xff;
+        ((9 * v(a) + 3 * v(b) + 3 * v(c) + v(d) + 8) / 16) << shift
+    };
+    0xff00_0000 | channel(16) | channel(8) | channel(0)
+}
@@ -0,0 +1,102 @@
+// just enough wav to play recordings through the DSP: integer pcm (8, 16, 24 or 32 bit),
+// 32 or 64 bit float, plain or WAVE_FORMAT_EXTENSIBLE. the whole file is read up front
+
+use std::fs;
+use std::path::Path;
+
+const PCM: u16 = 1;
+const FLOAT: u16 = 3;
+const EXTENSIBLE: u16 = 0xfffe;
+
+pub struct Wav {
+    pub sample_rate: f32,
+    pub channels: usize,
+    pub samples: Vec<f32>, // interleaved, -1 - 1
+}
+
+impl Wav {
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let bytes = fs::read(path).map_err(|e| e.to_string())?;
+        Self::parse(&bytes)
+    }
+
+    fn parse(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < 12 || &bytes[..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+            return Err("not a RIFF/WAVE file".to_string());
+        }
+        let mut format = None;
+        let mut data = None;
+        let mut rest = &bytes[12..];
+        while rest.len() >= 8 {
+            let (id, len) = (&rest[..4], u32::from_le_bytes([rest[4], rest[5], rest[6], rest[7]]) as usize);
+            let body = &rest[8..(8 + len).min(rest.len())];
+            match id {
+                b"fmt " => format = Some(Format::parse(body)?),
+                b"data" => data = Some(body),
+                _ => {}
+            }
+            // chunks are padded to an even length
+            rest = rest.get(8 + len + (len & 1)..).unwrap_or(&[]);
+        }
+        let format = format.ok_or("no fmt chunk")?;
+        let data = data.ok_or("no data chunk")?;
+
+        let width = format.bits as usize / 8;
+        let convert: fn(&[u8]) -> f32 = match (format.kind, format.bits) {
+            (PCM, 8) => |b| (b[0] as f32 - 128.0) / 128.0,
+            (PCM, 16) => |b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0,
+            (PCM, 24) => |b| i32::from_le_bytes([0, b[0], b[1], b[2]]) as f32 / 2_147_483_648.0,
+            (PCM, 32) => |b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2_147_483_648.0,
+            (FLOAT, 32) => |b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+            (FLOAT, 64) => |b| f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]) as f32,
+            (kind, bits) => return Err(format!("unsupported sample format (format {}, {} bits)", kind, bits)),
+        };
+        Ok(Self {
+            sample_rate: format.sample_rate as f32,
+            channels: format.channels as usize,
+            samples: data.chunks_exact(width).map(convert).collect(),
+        })
+    }
+
+    pub fn frames(&self) -> usize {
+        self.samples.len() / self.channels
+    }
+
+    pub fn seconds(&self) -> f32 {
+        self.frames() as f32 / self.sample_rate
+    }
+}
+
+struct Format {
+    kind: u16,
+    channels: u16,
+    sample_rate: u32,
+    bits: u16,
+}
+
+impl Format {
+    fn parse(body: &[u8]) -> Result<Self, String> {
+        if body.len() < 16 {
+            return Err("fmt chunk too short".to_string());
+        }
+        let u16_at = |at: usize| u16::from_le_bytes([body[at], body[at + 1]]);
+        let mut kind = u16_at(0);
+        // the extensible header names the real format in the first two bytes of its guid
+        if kind == EXTENSIBLE {
+            if body.len() < 26 {
+                return Err("extensible fmt chunk too short".to_string());
+            }
+            kind = u16_at(24);
+        }
+        let format = Self {
+            kind,
+            channels: u16_at(2),
+            sample_rate: u32::from_le_bytes([body[4], body[5], body[6], body[7]]),
+            bits: u16_at(14),
+        };
+        if format.channels == 0 || format.sample_rate == 0 {
+            return Err("no channels or no sample rate".to_string());
+        }
+        Ok(format)
+    }
+}
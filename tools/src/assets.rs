@@ -0,0 +1,56 @@
+// the whole asset directory in one go, for core's build script: every *.png becomes a Sprite
+// const in sprites.rs, every *.gpl / *.pal a ColorPalette const in palettes.rs, both written
+// to the output directory for include!()
+
+use std::path::{Path, PathBuf};
+
+use crate::palette::{parse_gpl, parse_jasc, Palette};
+use crate::sprite::{to_rust, AlphaDepth};
+use crate::{const_name, png};
+
+pub struct Converted {
+    pub inputs: Vec<PathBuf>, // for cargo:rerun-if-changed
+    pub sprites: usize,
+    pub palettes: usize,
+}
+
+pub fn convert_dir(assets: &Path, out_dir: &Path) -> Result<Converted, String> {
+    let mut inputs: Vec<PathBuf> = std::fs::read_dir(assets)
+        .map_err(|e| format!("can't list {}: {}", assets.display(), e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file())
+        .collect();
+    inputs.sort(); // stable output whatever order the filesystem lists them in
+
+    let mut sprites = String::new();
+    let mut palettes = String::new();
+    let mut converted = Converted { inputs: Vec::new(), sprites: 0, palettes: 0 };
+    for path in inputs {
+        let extension = path.extension().map(|e| e.to_ascii_lowercase());
+        let file = path.file_name().map(|f| f.to_string_lossy().into_owned()).unwrap_or_default();
+        let fail = |e: String| format!("{}: {}", path.display(), e);
+        match extension.as_ref().and_then(|e| e.to_str()) {
+            Some("png") => {
+                let bytes = std::fs::read(&path).map_err(|e| fail(e.to_string()))?;
+                let image = png::decode(&bytes).map_err(fail)?;
+                sprites.push_str(&to_rust(&image, &const_name(&path), AlphaDepth::detect(&image), &file));
+                converted.sprites += 1;
+            }
+            Some(kind @ ("gpl" | "pal")) => {
+                let text = std::fs::read_to_string(&path).map_err(|e| fail(e.to_string()))?;
+                let entries = if kind == "gpl" { parse_gpl(&text) } else { parse_jasc(&text) }.map_err(fail)?;
+                let palette = Palette::from_entries(&entries).map_err(fail)?;
+                palettes.push_str(&palette.to_rust(&const_name(&path), &file));
+                converted.palettes += 1;
+            }
+            _ => continue, // readmes, editor project files
+        }
+        converted.inputs.push(path);
+    }
+
+    for (name, source) in [("sprites.rs", sprites), ("palettes.rs", palettes)] {
+        let path = out_dir.join(name);
+        std::fs::write(&path, source).map_err(|e| format!("can't write {}: {}", path.display(), e))?;
+    }
+    Ok(converted)
+}
@@ -0,0 +1,61 @@
+// png2sprite <file.png> [NAME] [--alpha none|1|4] [-o out.rs]
+//
+// prints (or writes) a core Sprite const for the image, for sprites kept outside core/assets
+// (whose pngs core's build script converts by itself). the alpha depth defaults to the
+// smallest one that keeps the image's transparency, the name to the file stem in caps
+
+use std::path::PathBuf;
+
+use girlvoice_ui_tools::{const_name, png};
+use girlvoice_ui_tools::sprite::{to_rust, AlphaDepth};
+
+const USAGE: &str = "usage: png2sprite <file.png> [NAME] [--alpha none|1|4] [-o out.rs]";
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("error: {}\n\n{}", e, USAGE);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<(), String> {
+    let mut input: Option<PathBuf> = None;
+    let mut name: Option<String> = None;
+    let mut depth: Option<AlphaDepth> = None;
+    let mut output: Option<PathBuf> = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--alpha" => {
+                let value = args.next().ok_or("--alpha needs a value")?;
+                depth = Some(AlphaDepth::from_arg(&value).ok_or_else(|| format!("unknown alpha depth {}", value))?);
+            }
+            "-o" => output = Some(args.next().ok_or("-o needs a path")?.into()),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            _ if arg.starts_with('-') => return Err(format!("unknown option {}", arg)),
+            _ if input.is_none() => input = Some(arg.into()),
+            _ if name.is_none() => name = Some(arg),
+            _ => return Err(format!("unexpected argument {}", arg)),
+        }
+    }
+
+    let input = input.ok_or("no input file")?;
+    let bytes = std::fs::read(&input).map_err(|e| format!("can't read {}: {}", input.display(), e))?;
+    let image = png::decode(&bytes).map_err(|e| format!("{}: {}", input.display(), e))?;
+    let name = name.unwrap_or_else(|| const_name(&input));
+    let depth = depth.unwrap_or_else(|| AlphaDepth::detect(&image));
+
+    let source = input.file_name().map_or_else(|| input.display().to_string(), |f| f.to_string_lossy().into_owned());
+    let rust = to_rust(&image, &name, depth, &source);
+    match output {
+        Some(path) => std::fs::write(&path, rust).map_err(|e| format!("can't write {}: {}", path.display(), e)),
+        None => {
+            print!("{}", rust);
+            Ok(())
+        }
+    }
+}
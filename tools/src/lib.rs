@@ -0,0 +1,19 @@
+// host-side asset tools: images and palettes are decoded here and come out as Rust source for
+// core's Sprite and ColorPalette consts, so nothing on the badge ever parses a file format.
+// core's build script runs them over core/assets, png2sprite does single images by hand. the
+// simulator saves its screenshots through png::write
+
+use std::path::Path;
+
+pub mod assets;
+pub mod palette;
+pub mod png;
+pub mod sprite;
+pub mod zlib;
+
+// "battery-low.png" -> BATTERY_LOW
+pub fn const_name(path: &Path) -> String {
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let name: String = stem.chars().map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' }).collect();
+    if name.starts_with(|c: char| c.is_ascii_digit()) || name.is_empty() { format!("ASSET_{}", name) } else { name }
+}
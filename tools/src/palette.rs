@@ -0,0 +1,118 @@
+// GIMP (.gpl) and JASC/Paint Shop Pro (.pal) palette files -> core ColorPalette const.
+//
+// the colors in the file are gradient stops, resampled to the 16 palette colors like
+// palette::ramp does. gpl entries named primary, secondary or accent fill those roles instead;
+// a .pal file can't name its colors, so with 19 entries the last three are the roles. missing
+// roles come from the first, last and middle stop.
+
+use std::fmt::Write;
+
+pub type Rgb = [u8; 3];
+
+pub struct Entry {
+    pub color: Rgb,
+    pub name: String,
+}
+
+pub struct Palette {
+    pub colors: [Rgb; 16],
+    pub primary: Rgb,
+    pub secondary: Rgb,
+    pub accent: Rgb,
+}
+
+const ROLES: [&str; 3] = ["primary", "secondary", "accent"];
+
+pub fn parse_gpl(text: &str) -> Result<Vec<Entry>, String> {
+    let mut lines = text.lines().enumerate();
+    if lines.next().map(|(_, l)| l.trim()) != Some("GIMP Palette") {
+        return Err("not a GIMP palette (first line isn't \"GIMP Palette\")".into());
+    }
+    let mut entries = Vec::new();
+    for (number, line) in lines {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with("Name:") || line.starts_with("Columns:") {
+            continue;
+        }
+        let mut words = line.split_whitespace();
+        let mut channel = || -> Result<u8, String> {
+            let word = words.next().ok_or_else(|| format!("line {}: expected \"R G B [name]\"", number + 1))?;
+            word.parse().map_err(|_| format!("line {}: {} isn't a 0-255 channel value", number + 1, word))
+        };
+        let color = [channel()?, channel()?, channel()?];
+        entries.push(Entry { color, name: words.collect::<Vec<_>>().join(" ") });
+    }
+    Ok(entries)
+}
+
+pub fn parse_jasc(text: &str) -> Result<Vec<Entry>, String> {
+    let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+    if lines.next() != Some("JASC-PAL") {
+        return Err("not a JASC palette (first line isn't \"JASC-PAL\")".into());
+    }
+    lines.next(); // version, always 0100
+    let count: usize = lines.next().and_then(|l| l.parse().ok()).ok_or("JASC palette without a color count")?;
+    let mut entries = Vec::new();
+    for line in lines.take(count) {
+        let channels: Vec<u8> = line.split_whitespace().map(|w| w.parse()).collect::<Result<_, _>>().map_err(|_| format!("bad color line \"{}\"", line))?;
+        let [r, g, b] = channels[..] else { return Err(format!("bad color line \"{}\"", line)) };
+        entries.push(Entry { color: [r, g, b], name: String::new() });
+    }
+    if entries.len() != count {
+        return Err(format!("JASC palette promises {} colors, has {}", count, entries.len()));
+    }
+    if count == 19 {
+        for (entry, role) in entries[16..].iter_mut().zip(ROLES) {
+            entry.name = role.into();
+        }
+    }
+    Ok(entries)
+}
+
+impl Palette {
+    pub fn from_entries(entries: &[Entry]) -> Result<Self, String> {
+        let role = |role: &str| entries.iter().find(|e| e.name.eq_ignore_ascii_case(role)).map(|e| e.color);
+        let stops: Vec<Rgb> =
+            entries.iter().filter(|e| !ROLES.iter().any(|r| e.name.eq_ignore_ascii_case(r))).map(|e| e.color).collect();
+        let (&first, &last) = stops.first().zip(stops.last()).ok_or("palette has no gradient colors")?;
+
+        let colors = std::array::from_fn(|i| {
+            if stops.len() == 1 {
+                return first;
+            }
+            let pos = i as f32 / 15.0 * (stops.len() - 1) as f32;
+            let idx = (pos as usize).min(stops.len() - 2);
+            lerp(stops[idx], stops[idx + 1], pos - idx as f32)
+        });
+        Ok(Self {
+            colors,
+            primary: role("primary").unwrap_or(first),
+            secondary: role("secondary").unwrap_or(last),
+            accent: role("accent").unwrap_or(stops[stops.len() / 2]),
+        })
+    }
+
+    // the generated source names Color and ColorPalette unqualified
+    pub fn to_rust(&self, name: &str, source: &str) -> String {
+        let color = |c: Rgb| format!("Color::new({}, {}, {})", c[0], c[1], c[2]);
+        let mut out = String::new();
+        let _ = writeln!(out, "// generated from {}, don't edit", source);
+        let _ = writeln!(out, "pub const {}: ColorPalette = ColorPalette {{", name);
+        out.push_str("    colors: [\n");
+        for row in self.colors.chunks(4) {
+            let row: Vec<String> = row.iter().map(|&c| color(c)).collect();
+            let _ = writeln!(out, "        {},", row.join(", "));
+        }
+        out.push_str("    ],\n");
+        let _ = writeln!(out, "    primary: {},", color(self.primary));
+        let _ = writeln!(out, "    secondary: {},", color(self.secondary));
+        let _ = writeln!(out, "    accent: {},", color(self.accent));
+        out.push_str("};\n");
+        out
+    }
+}
+
+// same rounding as core's Color::lerp
+fn lerp(a: Rgb, b: Rgb, t: f32) -> Rgb {
+    std::array::from_fn(|i| (a[i] as f32 * (1.0 - t) + b[i] as f32 * t) as u8)
+}
@@ -0,0 +1,247 @@
+// just enough png for hand-drawn assets and the simulator's screenshots. decode reads every
+// color type at 8 bits (16 is cut to 8, palette and gray also at 1/2/4), tRNS transparency, no
+// interlacing, and checks the chunk crcs and the zlib checksum. write saves 8-bit rgb with every
+// row sub-filtered, see zlib::compress for why that's enough
+
+use std::io::{self, Write};
+
+use crate::zlib;
+
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub rgba: Vec<[u8; 4]>, // row-major, straight alpha
+}
+
+impl Image {
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        self.rgba[y * self.width + x]
+    }
+}
+
+const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+pub fn decode(bytes: &[u8]) -> Result<Image, String> {
+    let mut rest = bytes.strip_prefix(SIGNATURE).ok_or("not a png file")?;
+    let mut header = None;
+    let mut palette: Vec<[u8; 4]> = Vec::new();
+    let mut transparent: Option<[u16; 3]> = None; // tRNS key color for gray and rgb images
+    let mut compressed = Vec::new();
+
+    while rest.len() >= 12 {
+        let len = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+        let kind = &rest[4..8];
+        let data = rest.get(8..8 + len).ok_or("truncated png chunk")?;
+        let crc = rest.get(8 + len..12 + len).ok_or("truncated png chunk")?;
+        if u32::from_be_bytes(crc.try_into().unwrap()) != zlib::crc32(&[kind, data]) {
+            return Err(format!("crc mismatch in the {} chunk, the png is corrupt", String::from_utf8_lossy(kind)));
+        }
+        rest = &rest[12 + len..];
+        match kind {
+            b"IHDR" => header = Some(Header::parse(data)?),
+            b"PLTE" => palette = data.chunks_exact(3).map(|c| [c[0], c[1], c[2], 0xff]).collect(),
+            b"tRNS" => match header.as_ref().map(|h| h.color) {
+                Some(3) => {
+                    for (entry, &alpha) in palette.iter_mut().zip(data) {
+                        entry[3] = alpha;
+                    }
+                }
+                Some(0) if data.len() >= 2 => {
+                    let gray = u16::from_be_bytes([data[0], data[1]]);
+                    transparent = Some([gray; 3]);
+                }
+                Some(2) if data.len() >= 6 => {
+                    let channel = |i: usize| u16::from_be_bytes([data[i], data[i + 1]]);
+                    transparent = Some([channel(0), channel(2), channel(4)]);
+                }
+                _ => {}
+            },
+            b"IDAT" => compressed.extend_from_slice(data),
+            b"IEND" => break,
+            _ => {} // ancillary chunks (gamma, text, ...) don't matter for sprites
+        }
+    }
+
+    let header = header.ok_or("png without IHDR")?;
+    let raw = zlib::decompress(&compressed).map_err(|e| format!("{} in png", e))?;
+    let rows = header.unfilter(&raw)?;
+
+    let mut rgba = Vec::with_capacity(header.width * header.height);
+    for row in rows.chunks_exact(header.stride()) {
+        for x in 0..header.width {
+            let sample = |channel: usize| header.sample(row, x, channel);
+            let pixel = match header.color {
+                0 => {
+                    let g = sample(0);
+                    [g, g, g, 0xff]
+                }
+                2 => [sample(0), sample(1), sample(2), 0xff],
+                3 => *palette.get(sample(0) as usize).ok_or("png palette index out of range")?,
+                4 => {
+                    let g = sample(0);
+                    [g, g, g, sample(1)]
+                }
+                _ => [sample(0), sample(1), sample(2), sample(3)],
+            };
+            let keyed = transparent.is_some_and(|key| {
+                let channels = if header.color == 0 { 1 } else { 3 };
+                (0..channels).all(|c| header.raw_sample(row, x, c) == key[c])
+            });
+            rgba.push(if keyed { [pixel[0], pixel[1], pixel[2], 0] } else { pixel });
+        }
+    }
+    Ok(Image { width: header.width, height: header.height, rgba })
+}
+
+// `pixels` are argb32 (alpha ignored), row-major
+pub fn write(out: &mut impl Write, width: usize, height: usize, pixels: &[u32]) -> io::Result<()> {
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    header.extend_from_slice(&[8, 2, 0, 0, 0]); // 8 bits, rgb, deflate, adaptive filters, no interlacing
+
+    out.write_all(SIGNATURE)?;
+    write_chunk(out, b"IHDR", &header)?;
+    write_chunk(out, b"IDAT", &zlib::compress(&filter_rows(width, pixels)))?;
+    write_chunk(out, b"IEND", &[])
+}
+
+fn write_chunk(out: &mut impl Write, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+    out.write_all(&zlib::crc32(&[kind, data]).to_be_bytes())
+}
+
+// each byte minus the same channel of the pixel to its left
+fn filter_rows(width: usize, pixels: &[u32]) -> Vec<u8> {
+    let mut raw = Vec::with_capacity(pixels.len() * 3 + pixels.len() / width.max(1));
+    for row in pixels.chunks(width) {
+        raw.push(1); // sub
+        let mut left = [0u8; 3];
+        for &pixel in row {
+            let [_, r, g, b] = pixel.to_be_bytes();
+            for (channel, prev) in [r, g, b].into_iter().zip(&mut left) {
+                raw.push(channel.wrapping_sub(*prev));
+                *prev = channel;
+            }
+        }
+    }
+    raw
+}
+
+struct Header {
+    width: usize,
+    height: usize,
+    depth: usize,
+    color: u8,
+}
+
+impl Header {
+    fn parse(data: &[u8]) -> Result<Self, String> {
+        if data.len() < 13 {
+            return Err("short IHDR".into());
+        }
+        let width = u32::from_be_bytes(data[0..4].try_into().unwrap()) as usize;
+        let height = u32::from_be_bytes(data[4..8].try_into().unwrap()) as usize;
+        let (depth, color, interlace) = (data[8] as usize, data[9], data[12]);
+        let depth_ok = match color {
+            0 => matches!(depth, 1 | 2 | 4 | 8 | 16),
+            3 => matches!(depth, 1 | 2 | 4 | 8),
+            2 | 4 | 6 => matches!(depth, 8 | 16),
+            _ => false,
+        };
+        if !depth_ok {
+            return Err(format!("unsupported png format (color type {}, {} bits)", color, depth));
+        }
+        if interlace != 0 {
+            return Err("interlaced png, save it without interlacing".into());
+        }
+        if width == 0 || height == 0 {
+            return Err("empty png".into());
+        }
+        Ok(Self { width, height, depth, color })
+    }
+
+    fn channels(&self) -> usize {
+        match self.color {
+            2 => 3,
+            4 => 2,
+            6 => 4,
+            _ => 1,
+        }
+    }
+
+    fn stride(&self) -> usize {
+        (self.width * self.channels() * self.depth).div_ceil(8)
+    }
+
+    // undoes the per-row filters, returns the rows without their filter bytes
+    fn unfilter(&self, raw: &[u8]) -> Result<Vec<u8>, String> {
+        let stride = self.stride();
+        let step = (self.channels() * self.depth).div_ceil(8); // bytes per pixel, at least 1
+        if raw.len() < (stride + 1) * self.height {
+            return Err("png image data too short".into());
+        }
+        let mut out = vec![0u8; stride * self.height];
+        for y in 0..self.height {
+            let filter = raw[y * (stride + 1)];
+            let line = &raw[y * (stride + 1) + 1..(y + 1) * (stride + 1)];
+            let (done, current) = out.split_at_mut(y * stride);
+            let previous = if y > 0 { &done[(y - 1) * stride..] } else { &[][..] };
+            let current = &mut current[..stride];
+            for i in 0..stride {
+                let a = if i >= step { current[i - step] as i16 } else { 0 };
+                let b = previous.get(i).copied().unwrap_or(0) as i16;
+                let c = if i >= step { previous.get(i - step).copied().unwrap_or(0) as i16 } else { 0 };
+                let predicted = match filter {
+                    0 => 0,
+                    1 => a,
+                    2 => b,
+                    3 => (a + b) / 2,
+                    4 => paeth(a, b, c),
+                    _ => return Err(format!("unknown png filter {}", filter)),
+                };
+                current[i] = line[i].wrapping_add(predicted as u8);
+            }
+        }
+        Ok(out)
+    }
+
+    // sample at its stored depth
+    fn raw_sample(&self, row: &[u8], x: usize, channel: usize) -> u16 {
+        let index = x * self.channels() + channel;
+        match self.depth {
+            16 => u16::from_be_bytes([row[index * 2], row[index * 2 + 1]]),
+            8 => row[index] as u16,
+            depth => {
+                let bit = index * depth;
+                let shift = 8 - depth - bit % 8;
+                ((row[bit / 8] >> shift) & ((1 << depth) - 1) as u8) as u16
+            }
+        }
+    }
+
+    // sample scaled to 8 bits, palette indices stay indices
+    fn sample(&self, row: &[u8], x: usize, channel: usize) -> u8 {
+        let value = self.raw_sample(row, x, channel);
+        match self.depth {
+            16 => (value >> 8) as u8,
+            8 => value as u8,
+            _ if self.color == 3 => value as u8,
+            depth => (value * 255 / ((1 << depth) - 1)) as u8,
+        }
+    }
+}
+
+fn paeth(a: i16, b: i16, c: i16) -> i16 {
+    let p = a + b - c;
+    let (pa, pb, pc) = ((p - a).abs(), (p - b).abs(), (p - c).abs());
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
@@ -0,0 +1,112 @@
+// png -> core Sprite const. the generated source names Sprite and SpriteAlpha unqualified, the
+// module it ends up in imports them from girlvoice_ui_core::sprite
+
+use std::fmt::Write;
+
+use crate::png::Image;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlphaDepth {
+    None, // fully opaque
+    Bit1, // cut-out mask
+    Bit4, // 16 levels, enough for antialiased edges
+}
+
+impl AlphaDepth {
+    pub fn from_arg(arg: &str) -> Option<Self> {
+        match arg {
+            "none" | "0" => Some(Self::None),
+            "1" => Some(Self::Bit1),
+            "4" => Some(Self::Bit4),
+            _ => None,
+        }
+    }
+
+    // the smallest depth that keeps the image's alpha
+    pub fn detect(image: &Image) -> Self {
+        let alphas = || image.rgba.iter().map(|p| p[3]);
+        if alphas().all(|a| a == 0xff) {
+            Self::None
+        } else if alphas().all(|a| a == 0 || a == 0xff) {
+            Self::Bit1
+        } else {
+            Self::Bit4
+        }
+    }
+}
+
+pub fn rgb565(pixel: [u8; 4]) -> u16 {
+    ((pixel[0] as u16 >> 3) << 11) | ((pixel[1] as u16 >> 2) << 5) | (pixel[2] as u16 >> 3)
+}
+
+// 1bpp msb first, every row starts on a new byte
+pub fn mask_bytes(image: &Image) -> Vec<u8> {
+    let mut out = Vec::new();
+    for y in 0..image.height {
+        for chunk in (0..image.width).collect::<Vec<_>>().chunks(8) {
+            let mut byte = 0u8;
+            for (bit, &x) in chunk.iter().enumerate() {
+                if image.pixel(x, y)[3] >= 0x80 {
+                    byte |= 0x80 >> bit;
+                }
+            }
+            out.push(byte);
+        }
+    }
+    out
+}
+
+// 4bpp high nibble first, every row starts on a new byte
+pub fn level_bytes(image: &Image) -> Vec<u8> {
+    let mut out = Vec::new();
+    for y in 0..image.height {
+        for pair in (0..image.width).collect::<Vec<_>>().chunks(2) {
+            let level = |x: usize| (image.pixel(x, y)[3] as u16 * 15 + 127) / 255;
+            let high = level(pair[0]) as u8;
+            let low = pair.get(1).map_or(0, |&x| level(x) as u8);
+            out.push(high << 4 | low);
+        }
+    }
+    out
+}
+
+// `name` is the const's name, `source` only goes into the header comment
+pub fn to_rust(image: &Image, name: &str, depth: AlphaDepth, source: &str) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "// generated by png2sprite from {}, don't edit", source);
+    let _ = writeln!(out, "pub const {}: Sprite = Sprite {{", name);
+    let _ = writeln!(out, "    width: {},", image.width);
+    let _ = writeln!(out, "    height: {},", image.height);
+
+    // fully transparent pixels are zeroed, their color never shows
+    let pixels: Vec<u16> = image
+        .rgba
+        .iter()
+        .map(|&p| if depth != AlphaDepth::None && p[3] == 0 { 0 } else { rgb565(p) })
+        .collect();
+    out.push_str("    pixels: &[\n");
+    for row in pixels.chunks(image.width.clamp(1, 12)) {
+        let words: Vec<String> = row.iter().map(|p| format!("0x{:04x}", p)).collect();
+        let _ = writeln!(out, "        {},", words.join(", "));
+    }
+    out.push_str("    ],\n");
+
+    let bytes = match depth {
+        AlphaDepth::None => None,
+        AlphaDepth::Bit1 => Some(("Mask", mask_bytes(image))),
+        AlphaDepth::Bit4 => Some(("Levels", level_bytes(image))),
+    };
+    match bytes {
+        None => out.push_str("    alpha: SpriteAlpha::Opaque,\n"),
+        Some((variant, bytes)) => {
+            let _ = writeln!(out, "    alpha: SpriteAlpha::{}(&[", variant);
+            for row in bytes.chunks(16) {
+                let hex: Vec<String> = row.iter().map(|b| format!("0x{:02x}", b)).collect();
+                let _ = writeln!(out, "        {},", hex.join(", "));
+            }
+            out.push_str("    ]),\n");
+        }
+    }
+    out.push_str("};\n");
+    out
+}
@@ -0,0 +1,284 @@
+// zlib streams and the checksums around them, shared by the png decoder here and the
+// simulator's screenshot writer. compress only emits fixed huffman codes with distance-1 runs,
+// which is most of the way there for frames that are mostly black or flat color once png's sub
+// filter has turned them into runs of zeros. decompress reads any deflate stream
+
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let mut bits = BitWriter::default();
+    bits.out.extend_from_slice(&[0x78, 0x01]);
+    bits.put(1, 1); // final block
+    bits.put(1, 2); // fixed huffman codes
+
+    let mut i = 0;
+    while i < data.len() {
+        let run = if i > 0 { data[i..].iter().take(MAX_MATCH).take_while(|&&b| b == data[i - 1]).count() } else { 0 };
+        if run >= MIN_MATCH {
+            put_length(&mut bits, run);
+            bits.put(0, 5); // distance 1 is distance code 0, no extra bits
+            i += run;
+        } else {
+            put_literal(&mut bits, data[i] as u16);
+            i += 1;
+        }
+    }
+    put_literal(&mut bits, 256); // end of block
+
+    bits.flush();
+    bits.out.extend_from_slice(&adler32(data).to_be_bytes());
+    bits.out
+}
+
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>, String> {
+    if data.len() < 6 || data[0] & 0x0f != 8 || !u16::from_be_bytes([data[0], data[1]]).is_multiple_of(31) {
+        return Err("bad zlib header".into());
+    }
+    if data[1] & 0x20 != 0 {
+        return Err("zlib preset dictionaries aren't supported".into());
+    }
+    let mut bits = BitReader { data: &data[2..], pos: 0, bit: 0 };
+    let out = inflate(&mut bits)?;
+    bits.align();
+    let trailer = data.get(2 + bits.pos..2 + bits.pos + 4).ok_or("zlib stream without its checksum")?;
+    if u32::from_be_bytes(trailer.try_into().unwrap()) != adler32(&out) {
+        return Err("zlib checksum mismatch, the data is corrupt".into());
+    }
+    Ok(out)
+}
+
+pub fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+// over the parts in order, as if they were one slice
+pub fn crc32(parts: &[&[u8]]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in parts.iter().copied().flatten() {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+// deflate's length codes 257-285: shortest length of each and its extra bits
+const LENGTH_BASE: [u16; 29] =
+    [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145,
+    8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+// order the code length code lengths are stored in
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+fn put_length(bits: &mut BitWriter, length: usize) {
+    let code = LENGTH_BASE.iter().rposition(|&base| base as usize <= length).expect("length is at least 3");
+    put_literal(bits, 257 + code as u16);
+    bits.put(length as u32 - LENGTH_BASE[code] as u32, LENGTH_EXTRA[code] as u32);
+}
+
+// a literal/length symbol in the fixed code, which is sent most significant bit first
+fn put_literal(bits: &mut BitWriter, symbol: u16) {
+    let (code, len) = match symbol {
+        0..=143 => (0x30 + symbol, 8),
+        144..=255 => (0x190 + symbol - 144, 9),
+        256..=279 => (symbol - 256, 7),
+        _ => (0xc0 + symbol - 280, 8),
+    };
+    bits.put((code as u32).reverse_bits() >> (32 - len), len);
+}
+
+// lsb-first bit writer
+#[derive(Default)]
+struct BitWriter {
+    out: Vec<u8>,
+    acc: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    fn put(&mut self, value: u32, count: u32) {
+        self.acc |= value << self.count;
+        self.count += count;
+        while self.count >= 8 {
+            self.out.push(self.acc as u8);
+            self.acc >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    fn flush(&mut self) {
+        if self.count > 0 {
+            self.out.push(self.acc as u8);
+        }
+        self.acc = 0;
+        self.count = 0;
+    }
+}
+
+// lsb-first bit reader over a deflate stream
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bit: u32,
+}
+
+impl BitReader<'_> {
+    fn take(&mut self, count: u32) -> Result<u32, String> {
+        let mut value = 0;
+        for i in 0..count {
+            let byte = *self.data.get(self.pos).ok_or("truncated deflate stream")?;
+            value |= ((byte >> self.bit) as u32 & 1) << i;
+            self.bit += 1;
+            if self.bit == 8 {
+                self.bit = 0;
+                self.pos += 1;
+            }
+        }
+        Ok(value)
+    }
+
+    fn align(&mut self) {
+        if self.bit > 0 {
+            self.bit = 0;
+            self.pos += 1;
+        }
+    }
+}
+
+// canonical huffman code from its code lengths, decoded one bit at a time
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0u16; 16];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut offsets = [0usize; 16];
+        for len in 1..15 {
+            offsets[len + 1] = offsets[len] + counts[len] as usize;
+        }
+        let mut symbols = vec![0u16; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize]] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+        Self { counts, symbols }
+    }
+
+    fn decode(&self, bits: &mut BitReader) -> Result<u16, String> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for len in 1..16 {
+            code |= bits.take(1)? as i32;
+            let count = self.counts[len] as i32;
+            if code - count < first {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err("bad huffman code in deflate stream".into())
+    }
+}
+
+fn inflate(bits: &mut BitReader) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    loop {
+        let last = bits.take(1)? == 1;
+        match bits.take(2)? {
+            0 => {
+                bits.align();
+                let header = bits.data.get(bits.pos..bits.pos + 4).ok_or("truncated stored block")?;
+                let len = u16::from_le_bytes([header[0], header[1]]) as usize;
+                let block = bits.data.get(bits.pos + 4..bits.pos + 4 + len).ok_or("truncated stored block")?;
+                out.extend_from_slice(block);
+                bits.pos += 4 + len;
+            }
+            1 => {
+                let mut lengths = [0u8; 288];
+                lengths[..144].fill(8);
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                lengths[280..].fill(8);
+                inflate_block(bits, &mut out, &Huffman::new(&lengths), &Huffman::new(&[5; 30]))?;
+            }
+            2 => {
+                let literals = bits.take(5)? as usize + 257;
+                let distances = bits.take(5)? as usize + 1;
+                let code_lengths = bits.take(4)? as usize + 4;
+                let mut order_lengths = [0u8; 19];
+                for &i in &CODE_LENGTH_ORDER[..code_lengths] {
+                    order_lengths[i] = bits.take(3)? as u8;
+                }
+                let code_length_code = Huffman::new(&order_lengths);
+
+                let mut lengths = vec![0u8; literals + distances];
+                let mut i = 0;
+                while i < lengths.len() {
+                    let (value, repeat) = match code_length_code.decode(bits)? {
+                        symbol @ 0..=15 => (symbol as u8, 1),
+                        16 => (*lengths[..i].last().ok_or("length repeat without a previous length")?, 3 + bits.take(2)?),
+                        17 => (0, 3 + bits.take(3)?),
+                        _ => (0, 11 + bits.take(7)?),
+                    };
+                    for _ in 0..repeat {
+                        *lengths.get_mut(i).ok_or("too many code lengths")? = value;
+                        i += 1;
+                    }
+                }
+                let (literal_lengths, distance_lengths) = lengths.split_at(literals);
+                inflate_block(bits, &mut out, &Huffman::new(literal_lengths), &Huffman::new(distance_lengths))?;
+            }
+            _ => return Err("invalid deflate block type".into()),
+        }
+        if last {
+            return Ok(out);
+        }
+    }
+}
+
+fn inflate_block(bits: &mut BitReader, out: &mut Vec<u8>, literals: &Huffman, distances: &Huffman) -> Result<(), String> {
+    loop {
+        let symbol = literals.decode(bits)? as usize;
+        match symbol {
+            0..=255 => out.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let code = symbol - 257;
+                let base = *LENGTH_BASE.get(code).ok_or("bad length code")? as usize;
+                let len = base + bits.take(LENGTH_EXTRA[code] as u32)? as usize;
+                let code = distances.decode(bits)? as usize;
+                let base = *DIST_BASE.get(code).ok_or("bad distance code")? as usize;
+                let dist = base + bits.take(DIST_EXTRA[code] as u32)? as usize;
+                if dist > out.len() {
+                    return Err("deflate distance before the start".into());
+                }
+                let start = out.len() - dist;
+                for i in 0..len {
+                    out.push(out[start + i]);
+                }
+            }
+        }
+    }
+}